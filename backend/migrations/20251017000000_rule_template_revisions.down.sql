@@ -0,0 +1,10 @@
+-- 撤销规则模版版本历史
+
+ALTER TABLE games
+    DROP COLUMN template_revision,
+    DROP COLUMN template_id;
+
+DROP TABLE IF EXISTS rule_template_revisions;
+
+ALTER TABLE rule_templates
+    DROP COLUMN current_revision;
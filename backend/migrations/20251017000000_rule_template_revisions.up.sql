@@ -0,0 +1,25 @@
+-- migrations/20251017000000_rule_template_revisions.up.sql
+-- 规则模版版本历史，以及游戏创建时使用的模版版本
+
+-- 1. 模版当前版本号
+ALTER TABLE rule_templates
+    ADD COLUMN current_revision INT NOT NULL DEFAULT 1 COMMENT '当前规则配置版本号' AFTER rules_config;
+
+-- 2. 规则模版版本表
+CREATE TABLE IF NOT EXISTS rule_template_revisions (
+    template_id VARCHAR(36) NOT NULL COMMENT '所属模版ID',
+    revision INT NOT NULL COMMENT '版本号(从1开始递增)',
+    rules_config JSON NOT NULL COMMENT '该版本的完整规则配置(JSON格式)',
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP COMMENT '版本创建时间',
+    PRIMARY KEY (template_id, revision),
+    FOREIGN KEY (template_id) REFERENCES rule_templates(id) ON DELETE CASCADE
+) COMMENT '规则模版版本表';
+
+-- 3. 为已有模版补录首个版本
+INSERT INTO rule_template_revisions (template_id, revision, rules_config, created_at)
+SELECT id, 1, rules_config, updated_at FROM rule_templates;
+
+-- 4. 游戏记录创建时使用的模版及版本（模版删除后保留记录）
+ALTER TABLE games
+    ADD COLUMN template_id VARCHAR(36) NULL COMMENT '创建游戏时使用的规则模版ID' AFTER rules_config,
+    ADD COLUMN template_revision INT NULL COMMENT '创建游戏时使用的规则模版版本号' AFTER template_id;
@@ -0,0 +1,8 @@
+-- 撤销密码哈希存储的列定义
+-- 注意：已转换为哈希的密码无法还原为明文，回滚后需重新设置导演密码与演员密码
+
+ALTER TABLE actors
+    MODIFY COLUMN password VARCHAR(255) NOT NULL COMMENT '演员密码(1-40位字母数字)';
+
+ALTER TABLE games
+    MODIFY COLUMN director_password VARCHAR(255) NOT NULL COMMENT '导演密码(1-40字符)';
@@ -0,0 +1,9 @@
+-- migrations/20251018000000_hash_game_passwords.up.sql
+-- 导演密码与演员密码改为bcrypt哈希存储
+-- 已有的明文密码由服务启动时的系统初始化统一转换为哈希（见 GameCredentials::hash_plaintext_passwords）
+
+ALTER TABLE games
+    MODIFY COLUMN director_password VARCHAR(255) NOT NULL COMMENT '导演密码(bcrypt哈希)';
+
+ALTER TABLE actors
+    MODIFY COLUMN password VARCHAR(255) NOT NULL COMMENT '演员密码(bcrypt哈希)';
@@ -0,0 +1,5 @@
+-- 回滚游戏会话令牌版本号
+
+ALTER TABLE actors DROP COLUMN session_version;
+
+ALTER TABLE games DROP COLUMN director_session_version;
@@ -0,0 +1,8 @@
+-- migrations/20251019000000_game_session_versions.up.sql
+-- 游戏会话令牌版本号：版本号递增后，此前签发的令牌全部失效
+
+ALTER TABLE games
+    ADD COLUMN director_session_version INT NOT NULL DEFAULT 0 COMMENT '导演会话版本号(修改导演密码时递增)' AFTER director_password;
+
+ALTER TABLE actors
+    ADD COLUMN session_version INT NOT NULL DEFAULT 0 COMMENT '演员会话版本号(导演重置演员时递增)' AFTER password;
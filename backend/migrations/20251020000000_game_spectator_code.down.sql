@@ -0,0 +1,3 @@
+-- 回滚观战码
+
+ALTER TABLE games DROP COLUMN spectator_session_version, DROP COLUMN spectator_code;
@@ -0,0 +1,5 @@
+-- 观战码：设置后观众须凭观战码获取观战令牌，修改观战码时递增会话版本号以吊销此前签发的观战令牌
+
+ALTER TABLE games
+    ADD COLUMN spectator_code VARCHAR(255) NULL COMMENT '观战码哈希(为空时无需观战码)' AFTER director_session_version,
+    ADD COLUMN spectator_session_version INT NOT NULL DEFAULT 0 COMMENT '观众会话版本号(修改观战码时递增)' AFTER spectator_code;
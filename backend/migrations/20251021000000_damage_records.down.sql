@@ -0,0 +1,3 @@
+-- 回滚伤害记录
+
+DROP TABLE IF EXISTS damage_records;
@@ -0,0 +1,18 @@
+-- 伤害记录：记录玩家受到的每次实际伤害，用于赛后统计伤害输出与承受
+
+CREATE TABLE IF NOT EXISTS damage_records (
+    id VARCHAR(36) PRIMARY KEY COMMENT '伤害记录唯一标识符(UUID)',
+    game_id VARCHAR(36) NOT NULL COMMENT '所属游戏ID',
+    attacker_id VARCHAR(36) NULL COMMENT '造成伤害的玩家ID（可为空，表示非玩家伤害）',
+    victim_id VARCHAR(36) NOT NULL COMMENT '受到伤害的玩家ID',
+    damage INT NOT NULL COMMENT '实际损失的生命值',
+    cause VARCHAR(50) NOT NULL COMMENT '伤害来源（如：攻击、溅射、流血、遥控地雷）',
+    weapon VARCHAR(50) NULL COMMENT '使用的武器/道具',
+    damage_time TIMESTAMP DEFAULT CURRENT_TIMESTAMP COMMENT '伤害时间',
+    FOREIGN KEY (game_id) REFERENCES games(id) ON DELETE CASCADE,
+    FOREIGN KEY (attacker_id) REFERENCES actors(id) ON DELETE CASCADE,
+    FOREIGN KEY (victim_id) REFERENCES actors(id) ON DELETE CASCADE,
+
+    INDEX idx_damage_records_game_id (game_id),
+    INDEX idx_damage_records_damage_time (damage_time)
+) COMMENT '伤害记录表';
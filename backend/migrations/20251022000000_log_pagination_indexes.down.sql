@@ -0,0 +1,4 @@
+-- 回滚日志分页索引
+
+DROP INDEX idx_game_logs_game_time_id ON game_logs;
+DROP INDEX idx_kill_records_game_time_id ON kill_records;
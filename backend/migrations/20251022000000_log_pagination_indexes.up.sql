@@ -0,0 +1,4 @@
+-- 日志分页索引：消息记录与击杀记录按（时间戳，ID）进行游标分页
+
+CREATE INDEX idx_game_logs_game_time_id ON game_logs (game_id, timestamp, id);
+CREATE INDEX idx_kill_records_game_time_id ON kill_records (game_id, kill_time, id);
@@ -3,5 +3,8 @@ pub mod middleware;
 pub mod service;
 
 pub use jwt::JwtManager;
-pub use middleware::{jwt_auth_middleware, super_admin_middleware};
+pub use middleware::{
+    actor_session_middleware, director_session_middleware, game_session_middleware,
+    jwt_auth_middleware, super_admin_middleware,
+};
 pub use service::AuthService;
@@ -1,6 +1,8 @@
 use crate::admin::models::JwtClaims;
 use crate::errors::AuthError;
+use crate::game::models::{GameSessionClaims, GameSessionRole};
 use chrono::{Duration, Utc};
+use jsonwebtoken::errors::ErrorKind;
 use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation, decode, encode};
 
 #[derive(Clone)]
@@ -8,17 +10,29 @@ pub struct JwtManager {
     encoding_key: EncodingKey,
     decoding_key: DecodingKey,
     pub expiration_hours: u64,
+    /// 游戏会话令牌有效期（分钟）
+    pub game_token_expiration_minutes: u64,
 }
 
+/// 游戏会话令牌默认有效期（分钟）
+pub const DEFAULT_GAME_TOKEN_EXPIRATION_MINUTES: u64 = 120;
+
 impl JwtManager {
     pub fn new(secret: &str, expiration_hours: u64) -> Self {
         Self {
             encoding_key: EncodingKey::from_secret(secret.as_ref()),
             decoding_key: DecodingKey::from_secret(secret.as_ref()),
             expiration_hours,
+            game_token_expiration_minutes: DEFAULT_GAME_TOKEN_EXPIRATION_MINUTES,
         }
     }
 
+    /// 设置游戏会话令牌有效期（分钟）
+    pub fn with_game_token_expiration(mut self, minutes: u64) -> Self {
+        self.game_token_expiration_minutes = minutes;
+        self
+    }
+
     pub fn generate_token(
         &self,
         user_id: &str,
@@ -54,4 +68,71 @@ impl JwtManager {
             Err(_) => Err(AuthError::InvalidToken),
         }
     }
+
+    /// 签发游戏会话令牌
+    pub fn generate_game_token(
+        &self,
+        game_id: &str,
+        role: GameSessionRole,
+        subject: &str,
+        session_version: i32,
+    ) -> Result<String, AuthError> {
+        let now = Utc::now();
+        let exp = now + Duration::minutes(self.game_token_expiration_minutes as i64);
+
+        let claims = GameSessionClaims {
+            sub: subject.to_string(),
+            game_id: game_id.to_string(),
+            role,
+            ver: session_version,
+            exp: exp.timestamp() as usize,
+            iat: now.timestamp() as usize,
+        };
+
+        encode(&Header::default(), &claims, &self.encoding_key).map_err(AuthError::from)
+    }
+
+    /// 校验游戏会话令牌的签名与有效期（会话版本号由调用方核对）
+    pub fn validate_game_token(&self, token: &str) -> Result<GameSessionClaims, AuthError> {
+        let validation = Validation::new(Algorithm::HS256);
+
+        match decode::<GameSessionClaims>(token, &self.decoding_key, &validation) {
+            Ok(token_data) => Ok(token_data.claims),
+            Err(err) if matches!(err.kind(), ErrorKind::ExpiredSignature) => {
+                Err(AuthError::TokenExpired)
+            }
+            Err(_) => Err(AuthError::InvalidToken),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_game_token_is_scoped_and_distinct_from_admin_token() {
+        let jwt_manager = JwtManager::new("test_secret_key", 24).with_game_token_expiration(30);
+
+        let token = jwt_manager
+            .generate_game_token("game-1", GameSessionRole::Actor, "actor-1", 3)
+            .unwrap();
+        let claims = jwt_manager.validate_game_token(&token).unwrap();
+        assert_eq!(claims.game_id, "game-1");
+        assert_eq!(claims.role, GameSessionRole::Actor);
+        assert_eq!(claims.actor_id(), Some("actor-1"));
+        assert_eq!(claims.ver, 3);
+        assert_eq!(claims.exp - claims.iat, 30 * 60);
+
+        // 管理员令牌与游戏会话令牌不可互换
+        assert!(jwt_manager.validate_token(&token).is_err());
+        let admin_token = jwt_manager
+            .generate_token("admin-1", "admin", true)
+            .unwrap();
+        assert!(jwt_manager.validate_game_token(&admin_token).is_err());
+
+        // 其他密钥签发的令牌无效
+        let other_manager = JwtManager::new("other_secret", 24);
+        assert!(other_manager.validate_game_token(&token).is_err());
+    }
 }
@@ -1,13 +1,22 @@
 use axum::{
-    extract::{Request, State},
+    extract::{Path, Query, Request, State},
     http::header::AUTHORIZATION,
     middleware::Next,
     response::Response,
 };
+use serde::Deserialize;
+use std::collections::HashMap;
 
 use crate::admin::models::JwtClaims;
 use crate::auth::service::AuthService;
 use crate::errors::AuthError;
+use crate::game::models::{GameSessionClaims, GameSessionRole};
+
+/// 游戏会话令牌查询参数（WebSocket握手无法设置请求头时使用）
+#[derive(Debug, Deserialize)]
+pub struct GameSessionTokenQuery {
+    pub token: Option<String>,
+}
 
 /// JWT 认证中间件
 pub async fn jwt_auth_middleware(
@@ -50,6 +59,74 @@ pub async fn super_admin_middleware(req: Request, next: Next) -> Result<Response
     Ok(next.run(req).await)
 }
 
+/// 游戏会话认证中间件
+/// 令牌优先取自`Authorization`头，缺省时取`token`查询参数；
+/// 校验通过且令牌所属游戏与路径中的`game_id`一致时，将会话声明注入请求扩展
+pub async fn game_session_middleware(
+    State(auth_service): State<AuthService>,
+    Path(params): Path<HashMap<String, String>>,
+    Query(query): Query<GameSessionTokenQuery>,
+    mut req: Request,
+    next: Next,
+) -> Result<Response, AuthError> {
+    let token = req
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|header| header.to_str().ok())
+        .and_then(|header| header.strip_prefix("Bearer "))
+        .map(str::to_string)
+        .or(query.token)
+        .ok_or(AuthError::InvalidToken)?;
+
+    let claims = match auth_service.validate_game_token(&token).await {
+        Ok(claims) => claims,
+        Err(crate::errors::ServiceError::Auth(auth_err)) => return Err(auth_err),
+        Err(_) => return Err(AuthError::InvalidToken),
+    };
+
+    if params.get("game_id") != Some(&claims.game_id) {
+        return Err(AuthError::InsufficientPermissions);
+    }
+
+    req.extensions_mut().insert(claims);
+    Ok(next.run(req).await)
+}
+
+/// 导演会话权限中间件
+pub async fn director_session_middleware(req: Request, next: Next) -> Result<Response, AuthError> {
+    let claims = req
+        .extensions()
+        .get::<GameSessionClaims>()
+        .ok_or(AuthError::InvalidToken)?;
+
+    if claims.role != GameSessionRole::Director {
+        return Err(AuthError::InsufficientPermissions);
+    }
+
+    Ok(next.run(req).await)
+}
+
+/// 演员会话权限中间件
+/// 路径中的`player_id`必须是令牌所属的演员
+pub async fn actor_session_middleware(
+    Path(params): Path<HashMap<String, String>>,
+    req: Request,
+    next: Next,
+) -> Result<Response, AuthError> {
+    let claims = req
+        .extensions()
+        .get::<GameSessionClaims>()
+        .ok_or(AuthError::InvalidToken)?;
+
+    if claims.actor_id().is_none()
+        || claims.actor_id() != params.get("player_id").map(String::as_str)
+    {
+        return Err(AuthError::InsufficientPermissions);
+    }
+
+    Ok(next.run(req).await)
+}
+
 // 提取认证头的辅助函数（用于可选认证场景）
 // pub fn extract_auth_header(req: &Request) -> Option<String> {
 //     req.headers()
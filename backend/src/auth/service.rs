@@ -2,6 +2,7 @@ use crate::admin::models::{AdminUser, JwtClaims, LoginRequest, LoginResponse};
 use crate::auth::jwt::JwtManager;
 use crate::database::DatabasePool;
 use crate::errors::{AuthError, ServiceError};
+use crate::game::models::{GameSessionClaims, GameSessionRole};
 
 #[derive(Clone)]
 pub struct AuthService {
@@ -56,6 +57,79 @@ impl AuthService {
         Ok(claims)
     }
 
+    /// 为通过身份验证的导演、演员或观众签发游戏会话令牌，返回令牌及有效期（秒）
+    ///
+    /// 演员令牌的`subject`为演员ID，导演与观众令牌为游戏ID。
+    pub async fn issue_game_token(
+        &self,
+        game_id: &str,
+        role: GameSessionRole,
+        subject: &str,
+    ) -> Result<(String, u64), ServiceError> {
+        let session_version = self
+            .find_session_version(game_id, role, subject)
+            .await?
+            .ok_or(AuthError::InvalidCredentials)?;
+
+        let token =
+            self.jwt_manager
+                .generate_game_token(game_id, role, subject, session_version)?;
+
+        Ok((token, self.jwt_manager.game_token_expiration_minutes * 60))
+    }
+
+    /// 校验游戏会话令牌
+    ///
+    /// 演员被删除、导演重置演员、修改导演密码或观战码后，会话版本号变化，此前签发的令牌随即失效。
+    pub async fn validate_game_token(
+        &self,
+        token: &str,
+    ) -> Result<GameSessionClaims, ServiceError> {
+        let claims = self.jwt_manager.validate_game_token(token)?;
+
+        let session_version = self
+            .find_session_version(&claims.game_id, claims.role, &claims.sub)
+            .await?
+            .ok_or(AuthError::TokenRevoked)?;
+        if session_version != claims.ver {
+            return Err(AuthError::TokenRevoked.into());
+        }
+
+        Ok(claims)
+    }
+
+    async fn find_session_version(
+        &self,
+        game_id: &str,
+        role: GameSessionRole,
+        subject: &str,
+    ) -> Result<Option<i32>, sqlx::Error> {
+        let row: Option<(i32,)> = match role {
+            GameSessionRole::Actor => {
+                sqlx::query_as("SELECT session_version FROM actors WHERE id = ? AND game_id = ?")
+                    .bind(subject)
+                    .bind(game_id)
+                    .fetch_optional(&self.pool)
+                    .await?
+            }
+            GameSessionRole::Director if subject == game_id => {
+                sqlx::query_as("SELECT director_session_version FROM games WHERE id = ?")
+                    .bind(game_id)
+                    .fetch_optional(&self.pool)
+                    .await?
+            }
+            GameSessionRole::Spectator if subject == game_id => {
+                sqlx::query_as("SELECT spectator_session_version FROM games WHERE id = ?")
+                    .bind(game_id)
+                    .fetch_optional(&self.pool)
+                    .await?
+            }
+            GameSessionRole::Director | GameSessionRole::Spectator => None,
+        };
+
+        Ok(row.map(|(session_version,)| session_version))
+    }
+
     async fn find_by_username(&self, username: &str) -> Result<Option<AdminUser>, sqlx::Error> {
         sqlx::query_as::<_, AdminUser>(
             r#"
@@ -6,9 +6,14 @@ pub struct AppConfig {
     pub database_url: String,
     pub jwt_secret: String,
     pub jwt_expiration_hours: u64,
+    pub game_token_expiration_minutes: u64,
     pub bcrypt_cost: u32,
     pub server_port: u16,
     pub api_prefix: String,
+    pub ws_outbound_queue_capacity: usize,
+    pub ws_ping_interval_seconds: u64,
+    pub ws_idle_timeout_seconds: u64,
+    pub archive_import_max_mb: usize,
 }
 
 impl AppConfig {
@@ -26,6 +31,11 @@ impl AppConfig {
             .parse()
             .map_err(|_| "JWT_EXPIRATION_HOURS must be a valid number".to_string())?;
 
+        let game_token_expiration_minutes = env::var("GAME_TOKEN_EXPIRATION_MINUTES")
+            .unwrap_or_else(|_| "120".to_string())
+            .parse()
+            .map_err(|_| "GAME_TOKEN_EXPIRATION_MINUTES must be a valid number".to_string())?;
+
         let bcrypt_cost = env::var("BCRYPT_COST")
             .unwrap_or_else(|_| "12".to_string())
             .parse()
@@ -38,13 +48,44 @@ impl AppConfig {
 
         let api_prefix = env::var("API_PREFIX").unwrap_or_else(|_| "/royale-arena".to_string());
 
+        let ws_outbound_queue_capacity = env::var("WS_OUTBOUND_QUEUE_CAPACITY")
+            .unwrap_or_else(|_| "256".to_string())
+            .parse()
+            .map_err(|_| "WS_OUTBOUND_QUEUE_CAPACITY must be a valid number".to_string())?;
+
+        let ws_ping_interval_seconds = env::var("WS_PING_INTERVAL_SECONDS")
+            .unwrap_or_else(|_| "30".to_string())
+            .parse()
+            .ok()
+            .filter(|seconds| *seconds > 0)
+            .ok_or_else(|| "WS_PING_INTERVAL_SECONDS must be a positive number".to_string())?;
+
+        let ws_idle_timeout_seconds = env::var("WS_IDLE_TIMEOUT_SECONDS")
+            .unwrap_or_else(|_| "90".to_string())
+            .parse()
+            .ok()
+            .filter(|seconds| *seconds > 0)
+            .ok_or_else(|| "WS_IDLE_TIMEOUT_SECONDS must be a positive number".to_string())?;
+
+        let archive_import_max_mb = env::var("ARCHIVE_IMPORT_MAX_MB")
+            .unwrap_or_else(|_| "64".to_string())
+            .parse()
+            .ok()
+            .filter(|megabytes| *megabytes > 0)
+            .ok_or_else(|| "ARCHIVE_IMPORT_MAX_MB must be a positive number".to_string())?;
+
         Ok(Self {
             database_url,
             jwt_secret,
             jwt_expiration_hours,
+            game_token_expiration_minutes,
             bcrypt_cost,
             server_port,
             api_prefix,
+            ws_outbound_queue_capacity,
+            ws_ping_interval_seconds,
+            ws_idle_timeout_seconds,
+            archive_import_max_mb,
         })
     }
 }
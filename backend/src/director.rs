@@ -6,8 +6,9 @@ pub mod service;
 // 导出主要的公共接口
 pub use errors::DirectorError;
 pub use handlers::{
-    batch_add_players, batch_delete_players, edit_game, get_players, list_save_files, manual_save,
-    update_game_status,
+    batch_add_players, batch_delete_players, edit_game, get_action_journal, get_connection_metrics,
+    get_players, list_save_files, manual_save, replay_game_state, reset_player_password,
+    rewind_game, update_game_status, update_spectator_code,
 };
 pub use service::DirectorService;
 
@@ -6,6 +6,8 @@ use axum::{
 use serde_json::json;
 use thiserror::Error;
 
+use crate::game::rules_validator::RulesValidationReport;
+
 /// 导演模块错误类型
 #[derive(Debug, Error)]
 #[allow(dead_code)]
@@ -51,6 +53,9 @@ pub enum DirectorError {
 
     #[error("Validation error: {message}")]
     ValidationError { message: String },
+
+    #[error("Invalid rules config: {}", .report.summary())]
+    RulesValidationFailed { report: RulesValidationReport },
 }
 
 impl IntoResponse for DirectorError {
@@ -72,25 +77,34 @@ impl IntoResponse for DirectorError {
                 (StatusCode::INTERNAL_SERVER_ERROR, self.to_string())
             }
             DirectorError::ValidationError { .. } => (StatusCode::BAD_REQUEST, self.to_string()),
-            DirectorError::DatabaseError(_) => (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "Database error occurred".to_string(),
-            ),
+            DirectorError::RulesValidationFailed { .. } => {
+                (StatusCode::BAD_REQUEST, self.to_string())
+            }
+            DirectorError::DatabaseError(_) => {
+                crate::metrics::metrics().record_db_error("director");
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Database error occurred".to_string(),
+                )
+            }
             DirectorError::UuidError(_) => (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 "UUID generation error".to_string(),
             ),
         };
 
-        let body = Json(json!({
+        let mut body = json!({
             "success": false,
             "error": {
                 "message": error_message,
                 "details": self.to_string()
             }
-        }));
+        });
+        if let DirectorError::RulesValidationFailed { report } = &self {
+            body["error"]["issues"] = json!(report.issues);
+        }
 
-        (status, body).into_response()
+        (status, Json(body)).into_response()
     }
 }
 
@@ -2,7 +2,6 @@ use axum::{
     extract::{Path, Query, State},
     response::Json,
 };
-use serde::Deserialize;
 use serde_json::json;
 
 use super::errors::DirectorError;
@@ -10,22 +9,15 @@ use super::models::*;
 use crate::game::models::GameStatus;
 use crate::routes::AppState;
 
-/// 导演密码查询参数
-#[derive(Debug, Deserialize)]
-pub struct DirectorPasswordQuery {
-    pub password: String,
-}
-
 /// 批量添加演员 (导演接口)
 pub async fn batch_add_players(
     State(state): State<AppState>,
     Path(game_id): Path<String>,
-    Query(query): Query<DirectorPasswordQuery>,
     Json(request): Json<BatchAddPlayersRequest>,
 ) -> Result<Json<serde_json::Value>, DirectorError> {
     let response = state
         .director_service
-        .batch_add_players(&game_id, &query.password, request)
+        .batch_add_players(&game_id, request)
         .await?;
 
     Ok(Json(json!({
@@ -38,12 +30,8 @@ pub async fn batch_add_players(
 pub async fn get_players(
     State(state): State<AppState>,
     Path(game_id): Path<String>,
-    Query(query): Query<DirectorPasswordQuery>,
 ) -> Result<Json<serde_json::Value>, DirectorError> {
-    let players = state
-        .director_service
-        .get_players(&game_id, &query.password)
-        .await?;
+    let players = state.director_service.get_players(&game_id).await?;
 
     let response = PlayersListResponse { players };
 
@@ -57,12 +45,11 @@ pub async fn get_players(
 pub async fn batch_delete_players(
     State(state): State<AppState>,
     Path(game_id): Path<String>,
-    Query(query): Query<DirectorPasswordQuery>,
     Json(request): Json<BatchDeletePlayersRequest>,
 ) -> Result<Json<serde_json::Value>, DirectorError> {
     let response = state
         .director_service
-        .batch_delete_players(&game_id, &query.password, request)
+        .batch_delete_players(&game_id, request)
         .await?;
 
     Ok(Json(json!({
@@ -71,18 +58,57 @@ pub async fn batch_delete_players(
     })))
 }
 
-/// 导演更新游戏状态
-pub async fn update_game_status(
+/// 重置演员密码 (导演接口)
+/// 吊销该演员已签发的游戏会话令牌，并断开其WebSocket连接
+pub async fn reset_player_password(
+    State(state): State<AppState>,
+    Path((game_id, player_id)): Path<(String, String)>,
+    Json(request): Json<ResetPlayerPasswordRequest>,
+) -> Result<Json<serde_json::Value>, DirectorError> {
+    let player = state
+        .director_service
+        .reset_player_password(&game_id, &player_id, request)
+        .await?;
+
+    state
+        .global_connection_manager
+        .disconnect_player_connections(&game_id, &player_id, "导演已重置你的密码，请重新登录。")
+        .await;
+
+    Ok(Json(json!({
+        "success": true,
+        "data": player
+    })))
+}
+
+/// 设置或取消观战码
+/// 修改后断开现有观众连接，观众需使用新的观战码重新获取令牌
+pub async fn update_spectator_code(
     State(state): State<AppState>,
     Path(game_id): Path<String>,
-    Json(request): Json<UpdateGameStatusRequest>,
+    Json(request): Json<UpdateSpectatorCodeRequest>,
 ) -> Result<Json<serde_json::Value>, DirectorError> {
-    // 验证导演密码
     state
         .director_service
-        .verify_director_password(&game_id, &request.password)
+        .update_spectator_code(&game_id, request)
         .await?;
 
+    state
+        .global_connection_manager
+        .disconnect_spectator_connections(&game_id, "观战码已变更，请重新验证。")
+        .await;
+
+    Ok(Json(json!({
+        "success": true
+    })))
+}
+
+/// 导演更新游戏状态
+pub async fn update_game_status(
+    State(state): State<AppState>,
+    Path(game_id): Path<String>,
+    Json(request): Json<UpdateGameStatusRequest>,
+) -> Result<Json<serde_json::Value>, DirectorError> {
     // 获取当前游戏状态
     let game = state
         .game_service
@@ -96,7 +122,10 @@ pub async fn update_game_status(
     let result: Result<UpdateGameStatusResponse, DirectorError> = match request.status {
         GameStatus::Running => match game.status {
             GameStatus::Waiting => {
-                state.director_service.start_game(&state, &game_id).await?;
+                state
+                    .director_service
+                    .start_game(&state, &game_id, request.rng_seed)
+                    .await?;
                 Ok(UpdateGameStatusResponse {
                     success: true,
                     message: "Game started successfully".to_string(),
@@ -177,12 +206,8 @@ pub async fn update_game_status(
 pub async fn manual_save(
     State(state): State<AppState>,
     Path(game_id): Path<String>,
-    Json(request): Json<ManualSaveRequest>,
 ) -> Result<Json<serde_json::Value>, DirectorError> {
-    let save_file_name = state
-        .director_service
-        .manual_save(&state, &game_id, &request.password)
-        .await?;
+    let save_file_name = state.director_service.manual_save(&state, &game_id).await?;
 
     let response = ManualSaveResponse {
         success: true,
@@ -193,16 +218,32 @@ pub async fn manual_save(
     Ok(Json(json!(response)))
 }
 
+/// 回退游戏到指定存档 (导演接口)
+pub async fn rewind_game(
+    State(state): State<AppState>,
+    Path(game_id): Path<String>,
+    Json(request): Json<RewindGameRequest>,
+) -> Result<Json<serde_json::Value>, DirectorError> {
+    let response = state
+        .director_service
+        .rewind_game(&state, &game_id, &request.save_file_name)
+        .await?;
+
+    Ok(Json(json!({
+        "success": true,
+        "data": response
+    })))
+}
+
 /// 导演编辑游戏
 pub async fn edit_game(
     State(state): State<AppState>,
     Path(game_id): Path<String>,
-    Query(query): Query<DirectorPasswordQuery>,
     Json(request): Json<DirectorEditGameRequest>,
 ) -> Result<Json<serde_json::Value>, DirectorError> {
     let game = state
         .director_service
-        .edit_game(&state, &game_id, &query.password, request)
+        .edit_game(&state, &game_id, request)
         .await?;
 
     Ok(Json(json!({
@@ -215,11 +256,10 @@ pub async fn edit_game(
 pub async fn list_save_files(
     State(state): State<AppState>,
     Path(game_id): Path<String>,
-    Query(query): Query<DirectorPasswordQuery>,
 ) -> Result<Json<serde_json::Value>, DirectorError> {
     let save_files = state
         .director_service
-        .list_save_files(&state, &game_id, &query.password)
+        .list_save_files(&state, &game_id)
         .await?;
 
     let response = ListSaveFilesResponse {
@@ -230,17 +270,51 @@ pub async fn list_save_files(
     Ok(Json(json!(response)))
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_director_password_query_deserialization() {
-        // 这个测试可以验证查询参数的反序列化是否正常工作
-        let test_password = "test123";
-        let query = DirectorPasswordQuery {
-            password: test_password.to_string(),
-        };
-        assert_eq!(query.password, test_password);
-    }
+/// 查询WebSocket连接统计接口
+pub async fn get_connection_metrics(
+    State(state): State<AppState>,
+    Path(game_id): Path<String>,
+) -> Result<Json<serde_json::Value>, DirectorError> {
+    let metrics = state
+        .global_connection_manager
+        .connection_metrics(&game_id)
+        .await;
+
+    Ok(Json(json!({
+        "success": true,
+        "data": metrics
+    })))
+}
+
+/// 查询行动日志接口
+pub async fn get_action_journal(
+    State(state): State<AppState>,
+    Path(game_id): Path<String>,
+) -> Result<Json<serde_json::Value>, DirectorError> {
+    let entries = state.director_service.get_action_journal(&game_id).await?;
+
+    Ok(Json(json!({
+        "success": true,
+        "data": entries
+    })))
+}
+
+/// 重放行动日志接口
+pub async fn replay_game_state(
+    State(state): State<AppState>,
+    Path(game_id): Path<String>,
+    Query(query): Query<ReplayGameStateQuery>,
+) -> Result<Json<serde_json::Value>, DirectorError> {
+    let game_state = state
+        .director_service
+        .replay_game_state(&game_id, &query.save_file_name, query.until_sequence)
+        .await?;
+
+    Ok(Json(json!({
+        "success": true,
+        "data": {
+            "journal_sequence": game_state.journal_sequence,
+            "game_state": game_state
+        }
+    })))
 }
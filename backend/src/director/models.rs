@@ -1,4 +1,5 @@
 use crate::game::models::{GameStatus, SaveFileInfo};
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 
@@ -25,18 +26,32 @@ pub struct CreatePlayerRequest {
     pub team_id: Option<i32>, // 可选的队伍ID，默认为0
 }
 
+/// 重置演员密码请求
+#[derive(Debug, Deserialize)]
+pub struct ResetPlayerPasswordRequest {
+    /// 新的演员密码
+    pub password: String,
+}
+
+/// 设置观战码请求
+#[derive(Debug, Deserialize)]
+pub struct UpdateSpectatorCodeRequest {
+    /// 新的观战码，为空时取消观战码（观众无需观战码即可观战）
+    #[serde(default)]
+    pub code: Option<String>,
+}
+
 /// 批量删除演员请求
 #[derive(Debug, Deserialize)]
 pub struct BatchDeletePlayersRequest {
     pub player_ids: Vec<String>,
 }
 
-/// 演员信息
+/// 演员信息（密码以哈希存储，不对外输出）
 #[derive(Debug, Serialize, FromRow)]
 pub struct PlayerInfo {
     pub id: String,
     pub name: String,
-    pub password: String,
     pub game_id: String,
     pub team_id: i32,
 }
@@ -73,20 +88,14 @@ pub struct DeleteSuccessInfo {
 /// 导演更新游戏状态请求
 #[derive(Debug, Deserialize)]
 pub struct UpdateGameStatusRequest {
-    /// 导演密码
-    pub password: String,
     /// 目标游戏状态
     pub status: GameStatus,
     /// 存档文件名（可选，用于恢复游戏时指定从哪个存档恢复）
     #[serde(skip_serializing_if = "Option::is_none")]
     pub save_file_name: Option<String>,
-}
-
-/// 手动存盘请求
-#[derive(Debug, Deserialize)]
-pub struct ManualSaveRequest {
-    /// 导演密码
-    pub password: String,
+    /// 随机数种子（可选，仅在开始游戏时生效，未提供时随机生成）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rng_seed: Option<u64>,
 }
 
 /// 手动存盘响应
@@ -109,6 +118,36 @@ pub struct ListSaveFilesResponse {
     pub data: Vec<SaveFileInfo>,
 }
 
+/// 行动日志重放查询参数
+#[derive(Debug, Deserialize)]
+pub struct ReplayGameStateQuery {
+    /// 作为重放起点的存档文件名
+    pub save_file_name: String,
+    /// 重放到的日志序号（含），为空时重放全部日志
+    pub until_sequence: Option<u64>,
+}
+
+/// 回退游戏请求
+#[derive(Debug, Deserialize)]
+pub struct RewindGameRequest {
+    /// 回退到的存档文件名
+    pub save_file_name: String,
+}
+
+/// 回退游戏结果
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RewindGameResponse {
+    /// 回退到的存档文件名
+    pub save_file_name: String,
+    /// 存档的保存时间，晚于该时间的日志、击杀与伤害记录已被删除
+    pub save_time: DateTime<Utc>,
+    /// 回退前自动保存的当前状态存档（内存中没有游戏状态时为空）
+    pub backup_save_file_name: Option<String>,
+    pub deleted_logs: u64,
+    pub deleted_kill_records: u64,
+    pub deleted_damage_records: u64,
+}
+
 /// 导演更新游戏状态响应
 #[derive(Debug, Serialize, Deserialize)]
 pub struct UpdateGameStatusResponse {
@@ -154,6 +193,45 @@ impl DirectorEditGameRequest {
     }
 }
 
+/// 验证演员密码格式：1-40位，仅包含字母和数字
+fn validate_player_password(password: &str) -> Result<(), String> {
+    if password.is_empty() || password.len() > 40 {
+        return Err("密码长度必须在1-40位字符之间".to_string());
+    }
+
+    if !password.chars().all(|c| c.is_alphanumeric()) {
+        return Err("密码只能包含字母和数字".to_string());
+    }
+
+    Ok(())
+}
+
+impl ResetPlayerPasswordRequest {
+    /// 验证重置演员密码请求的数据
+    pub fn validate(&self) -> Result<(), String> {
+        validate_player_password(&self.password)
+    }
+}
+
+impl UpdateSpectatorCodeRequest {
+    /// 验证设置观战码请求的数据
+    pub fn validate(&self) -> Result<(), String> {
+        let Some(code) = &self.code else {
+            return Ok(());
+        };
+
+        if code.is_empty() || code.len() > 40 {
+            return Err("观战码长度必须在1-40位字符之间".to_string());
+        }
+
+        if !code.chars().all(|c| c.is_alphanumeric()) {
+            return Err("观战码只能包含字母和数字".to_string());
+        }
+
+        Ok(())
+    }
+}
+
 impl CreatePlayerRequest {
     /// 验证创建演员请求的数据
     pub fn validate(&self) -> Result<(), String> {
@@ -167,14 +245,7 @@ impl CreatePlayerRequest {
         }
 
         // 验证密码格式
-        if self.password.is_empty() || self.password.len() > 40 {
-            return Err("密码长度必须在1-40位字符之间".to_string());
-        }
-
-        // 验证密码只包含字母和数字
-        if !self.password.chars().all(|c| c.is_alphanumeric()) {
-            return Err("密码只能包含字母和数字".to_string());
-        }
+        validate_player_password(&self.password)?;
 
         // 验证队伍ID
         if let Some(team_id) = self.team_id {
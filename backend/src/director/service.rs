@@ -1,6 +1,14 @@
 use crate::director::{DirectorError, models::*};
-use crate::game::models::{GameWithPlayerCounts, SaveFileInfo};
+use crate::game::action_journal::{ActionJournal, JournalEntry};
+use crate::game::archive::is_valid_save_file_name;
+use crate::game::credentials::GameCredentials;
+use crate::game::global_game_state_manager::GlobalGameStateManager;
+use crate::game::log_service::GameLogService;
+use crate::game::models::{GameStatus, GameWithPlayerCounts, SaveFileInfo};
+use crate::game::rules_validator::RulesValidator;
 use crate::routes::AppState;
+use crate::websocket::models::GameState;
+use chrono::{DateTime, Utc};
 use sqlx::{MySqlPool, Row};
 use uuid::Uuid;
 
@@ -8,15 +16,16 @@ use uuid::Uuid;
 #[derive(Clone)]
 pub struct DirectorService {
     pub pool: MySqlPool,
+    bcrypt_cost: u32,
 }
 
 impl DirectorService {
-    pub fn new(pool: MySqlPool) -> Self {
-        Self { pool }
+    pub fn new(pool: MySqlPool, bcrypt_cost: u32) -> Self {
+        Self { pool, bcrypt_cost }
     }
 
-    /// 获取导演明文密码
-    async fn get_director_password(&self, game_id: &str) -> Result<String, DirectorError> {
+    /// 获取导演密码哈希
+    async fn get_director_password_hash(&self, game_id: &str) -> Result<String, DirectorError> {
         let row = sqlx::query("SELECT director_password FROM games WHERE id = ?")
             .bind(game_id)
             .fetch_optional(&self.pool)
@@ -31,20 +40,6 @@ impl DirectorService {
         }
     }
 
-    /// 验证导演密码
-    pub async fn verify_director_password(
-        &self,
-        game_id: &str,
-        password: &str,
-    ) -> Result<(), DirectorError> {
-        let director_password = self.get_director_password(game_id).await?;
-        if director_password == password {
-            Ok(())
-        } else {
-            Err(DirectorError::InvalidDirectorPassword)
-        }
-    }
-
     /// 检查游戏状态是否允许删除演员
     async fn check_game_status_for_deletion(&self, game_id: &str) -> Result<(), DirectorError> {
         let row = sqlx::query("SELECT status FROM games WHERE id = ?")
@@ -91,6 +86,11 @@ impl DirectorService {
         team_id: i32,
     ) -> Result<PlayerInfo, DirectorError> {
         let player_id = Uuid::new_v4().to_string();
+        let password_hash = GameCredentials::hash_password_blocking(password, self.bcrypt_cost)
+            .await
+            .map_err(|e| DirectorError::OtherError {
+                message: format!("Failed to hash password: {}", e),
+            })?;
 
         sqlx::query(
             "INSERT INTO actors (id, game_id, name, password, team_id, created_at, updated_at) 
@@ -99,7 +99,7 @@ impl DirectorService {
         .bind(&player_id)
         .bind(game_id)
         .bind(player_name)
-        .bind(password)
+        .bind(&password_hash)
         .bind(team_id)
         .execute(&self.pool)
         .await?;
@@ -107,7 +107,6 @@ impl DirectorService {
         Ok(PlayerInfo {
             id: player_id,
             name: player_name.to_string(),
-            password: password.to_string(),
             game_id: game_id.to_string(),
             team_id,
         })
@@ -117,18 +116,19 @@ impl DirectorService {
     pub async fn batch_add_players(
         &self,
         game_id: &str,
-        password: &str,
         request: BatchAddPlayersRequest,
     ) -> Result<BatchOperationResponse<PlayerInfo>, DirectorError> {
-        // 验证导演密码
-        self.verify_director_password(game_id, password).await?;
-
-        // 获取导演密码用于后续验证
-        let director_password = self.get_director_password(game_id).await?;
+        // 获取导演密码哈希（游戏不存在时返回错误）
+        let director_password_hash = self.get_director_password_hash(game_id).await?;
 
         // 首先检查所有演员密码是否与导演密码相同
         for player_request in &request.players {
-            if player_request.password == director_password {
+            if GameCredentials::verify_password_blocking(
+                &player_request.password,
+                &director_password_hash,
+            )
+            .await
+            {
                 // 如果发现任何演员密码与导演密码相同，拒绝所有演员的加入
                 let mut failed = Vec::new();
                 for player_request in request.players {
@@ -211,16 +211,9 @@ impl DirectorService {
     }
 
     /// 获取演员列表
-    pub async fn get_players(
-        &self,
-        game_id: &str,
-        password: &str,
-    ) -> Result<Vec<PlayerInfo>, DirectorError> {
-        // 验证导演密码
-        self.verify_director_password(game_id, password).await?;
-
+    pub async fn get_players(&self, game_id: &str) -> Result<Vec<PlayerInfo>, DirectorError> {
         let players = sqlx::query_as::<_, PlayerInfo>(
-            "SELECT id, game_id, name, password, team_id 
+            "SELECT id, game_id, name, team_id 
              FROM actors 
              WHERE game_id = ? 
              ORDER BY created_at ASC",
@@ -235,7 +228,7 @@ impl DirectorService {
     /// 获取演员信息（内部方法）
     async fn get_player_by_id(&self, player_id: &str) -> Result<PlayerInfo, DirectorError> {
         let player = sqlx::query_as::<_, PlayerInfo>(
-            "SELECT id, game_id, name, password, team_id FROM actors WHERE id = ?",
+            "SELECT id, game_id, name, team_id FROM actors WHERE id = ?",
         )
         .bind(player_id)
         .fetch_optional(&self.pool)
@@ -273,12 +266,8 @@ impl DirectorService {
     pub async fn batch_delete_players(
         &self,
         game_id: &str,
-        password: &str,
         request: BatchDeletePlayersRequest,
     ) -> Result<BatchOperationResponse<DeleteSuccessInfo>, DirectorError> {
-        // 验证导演密码
-        self.verify_director_password(game_id, password).await?;
-
         // 检查游戏状态
         self.check_game_status_for_deletion(game_id).await?;
 
@@ -330,11 +319,100 @@ impl DirectorService {
         Ok(BatchOperationResponse { success, failed })
     }
 
+    /// 重置演员密码
+    ///
+    /// 同时递增演员会话版本号，吊销该演员此前签发的游戏会话令牌。
+    pub async fn reset_player_password(
+        &self,
+        game_id: &str,
+        player_id: &str,
+        request: ResetPlayerPasswordRequest,
+    ) -> Result<PlayerInfo, DirectorError> {
+        request
+            .validate()
+            .map_err(|message| DirectorError::ValidationError { message })?;
+
+        let player = self.get_player_by_id(player_id).await?;
+        if player.game_id != game_id {
+            return Err(DirectorError::PlayerNotFound {
+                id: player_id.to_string(),
+            });
+        }
+
+        let director_password_hash = self.get_director_password_hash(game_id).await?;
+        if GameCredentials::verify_password_blocking(&request.password, &director_password_hash)
+            .await
+        {
+            return Err(DirectorError::ValidationError {
+                message: "演员密码不能与导演密码相同".to_string(),
+            });
+        }
+
+        let password_hash =
+            GameCredentials::hash_password_blocking(&request.password, self.bcrypt_cost)
+                .await
+                .map_err(|e| DirectorError::OtherError {
+                    message: format!("Failed to hash password: {}", e),
+                })?;
+        sqlx::query(
+            "UPDATE actors SET password = ?, session_version = session_version + 1, updated_at = CURRENT_TIMESTAMP WHERE id = ?",
+        )
+        .bind(&password_hash)
+        .bind(player_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(player)
+    }
+
+    /// 设置或取消观战码
+    ///
+    /// 观战码以哈希存储，修改后递增观众会话版本号，此前签发的观战令牌随即失效。
+    pub async fn update_spectator_code(
+        &self,
+        game_id: &str,
+        request: UpdateSpectatorCodeRequest,
+    ) -> Result<(), DirectorError> {
+        request
+            .validate()
+            .map_err(|message| DirectorError::ValidationError { message })?;
+
+        let director_password_hash = self.get_director_password_hash(game_id).await?;
+        let code_hash = match &request.code {
+            Some(code) => {
+                if GameCredentials::verify_password_blocking(code, &director_password_hash).await {
+                    return Err(DirectorError::ValidationError {
+                        message: "观战码不能与导演密码相同".to_string(),
+                    });
+                }
+                Some(
+                    GameCredentials::hash_password_blocking(code, self.bcrypt_cost)
+                        .await
+                        .map_err(|e| DirectorError::OtherError {
+                            message: format!("Failed to hash spectator code: {}", e),
+                        })?,
+                )
+            }
+            None => None,
+        };
+
+        sqlx::query(
+            "UPDATE games SET spectator_code = ?, spectator_session_version = spectator_session_version + 1, updated_at = CURRENT_TIMESTAMP WHERE id = ?",
+        )
+        .bind(&code_hash)
+        .bind(game_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
     /// 开始游戏（等待中 → 进行中）
     pub async fn start_game(
         &self,
         app_state: &AppState,
         game_id: &str,
+        rng_seed: Option<u64>,
     ) -> Result<(), DirectorError> {
         // 更新数据库中游戏状态为 "running"
         let result = sqlx::query!(
@@ -359,12 +437,21 @@ impl DirectorService {
             })?;
         app_state
             .game_state_manager
-            .create_game_state(game_id, game.rules_config)
+            .create_game_state(game_id, game.rules_config, rng_seed)
             .await
             .map_err(|e| DirectorError::OtherError {
                 message: format!("Failed to create game state: {}", e),
             })?;
 
+        // 启动昼夜自动调度（规则未启用时不启动）
+        app_state
+            .game_phase_scheduler
+            .start(app_state, game_id)
+            .await;
+
+        // 启动定时自动存档（规则中存档间隔为0时不启动）
+        app_state.game_state_manager.start_autosave(game_id).await;
+
         Ok(())
     }
 
@@ -387,6 +474,10 @@ impl DirectorService {
             return Err(DirectorError::GameNotFound);
         }
 
+        // 停止昼夜自动调度与定时自动存档
+        app_state.game_phase_scheduler.stop(game_id);
+        app_state.game_state_manager.stop_autosave(game_id);
+
         // 断开所有WebSocket连接
         app_state
             .global_connection_manager
@@ -424,6 +515,7 @@ impl DirectorService {
         }
 
         // 移除内存中的游戏状态，使游戏可以重新开始
+        app_state.game_phase_scheduler.stop(game_id);
         app_state.game_state_manager.remove_game_state(game_id);
 
         // 清理该游戏的连接管理器，确保后续连接能够重新建立
@@ -449,6 +541,18 @@ impl DirectorService {
                 message: format!("Failed to clear kill records: {}", e),
             })?;
 
+        app_state
+            .game_log_service
+            .delete_damage_records_after_timestamp(game_id, None)
+            .await
+            .map_err(|e| DirectorError::OtherError {
+                message: format!("Failed to clear damage records: {}", e),
+            })?;
+
+        ActionJournal::delete(game_id).map_err(|e| DirectorError::OtherError {
+            message: format!("Failed to clear action journal: {}", e),
+        })?;
+
         Ok(())
     }
 
@@ -472,6 +576,9 @@ impl DirectorService {
             return Err(DirectorError::GameNotFound);
         }
 
+        // 停止昼夜自动调度
+        app_state.game_phase_scheduler.stop(game_id);
+
         // 断开所有连接（调用全局连接管理器的实现）
         app_state
             .global_connection_manager
@@ -537,6 +644,14 @@ impl DirectorService {
         // 获取恢复的游戏状态的保存时间
         if let Ok(game_state) = app_state.game_state_manager.get_game_state(game_id).await {
             let game_state_guard = game_state.read().await;
+
+            // 丢弃存档之后的行动日志，使日志与恢复后的时间线一致
+            if let Err(e) =
+                ActionJournal::truncate_after(game_id, game_state_guard.journal_sequence)
+            {
+                eprintln!("Failed to truncate action journal: {}", e);
+            }
+
             if let Some(save_time) = game_state_guard.save_time {
                 // 删除晚于保存时间的日志记录
                 let _ = app_state
@@ -549,22 +664,194 @@ impl DirectorService {
                     .game_log_service
                     .delete_kill_records_after_timestamp(game_id, Some(save_time))
                     .await;
+
+                // 删除晚于保存时间的伤害记录
+                let _ = app_state
+                    .game_log_service
+                    .delete_damage_records_after_timestamp(game_id, Some(save_time))
+                    .await;
             }
         }
 
+        // 恢复昼夜自动调度（规则未启用时不启动）
+        app_state
+            .game_phase_scheduler
+            .start(app_state, game_id)
+            .await;
+
+        // 恢复定时自动存档（规则中存档间隔为0时不启动）
+        app_state.game_state_manager.start_autosave(game_id).await;
+
         Ok(())
     }
 
+    /// 回退进行中的游戏到指定存档
+    ///
+    /// 作为一次完整操作执行：标记游戏正在回退并等待进行中的行动写完记录、停止自动调度与存档、
+    /// 通知并断开所有连接、保存回退前的状态、加载存档并截断行动日志，在同一事务中删除晚于
+    /// 存档保存时间的日志、击杀与伤害记录并写入对所有人可见的回退记录。
+    /// 回退期间拒绝新的行动与连接；无论回退是否成功，最后都会恢复调度与自动存档。
+    pub async fn rewind_game(
+        &self,
+        app_state: &AppState,
+        game_id: &str,
+        save_file_name: &str,
+    ) -> Result<RewindGameResponse, DirectorError> {
+        let game = app_state
+            .game_service
+            .get_game_by_id(game_id)
+            .await
+            .map_err(|e| DirectorError::OtherError {
+                message: format!("Failed to get game: {}", e),
+            })?;
+        if game.status != GameStatus::Running {
+            return Err(DirectorError::InvalidGameStateTransition);
+        }
+
+        // 先校验存档，存档无效时不影响进行中的游戏
+        if !is_valid_save_file_name(save_file_name) {
+            return Err(DirectorError::ValidationError {
+                message: format!("无效的存档文件名 {}", save_file_name),
+            });
+        }
+        let snapshot = GlobalGameStateManager::read_game_state_from_disk(game_id, save_file_name)
+            .map_err(|e| DirectorError::ValidationError {
+            message: format!("无法读取存档 {}: {}", save_file_name, e),
+        })?;
+        let save_time = snapshot
+            .save_time
+            .ok_or_else(|| DirectorError::ValidationError {
+                message: format!("存档 {} 缺少保存时间，无法回退", save_file_name),
+            })?;
+
+        // 标记游戏正在回退：等待进行中的行动与调度事件写完记录，回退完成前拒绝新的行动与连接
+        let rewinding = app_state.game_state_manager.begin_rewind(game_id).await;
+
+        // 停止昼夜自动调度与定时自动存档，避免回退过程中修改游戏状态
+        app_state.game_phase_scheduler.stop(game_id);
+        app_state.game_state_manager.stop_autosave(game_id);
+
+        let result = self
+            .rewind_to_save(
+                app_state,
+                game_id,
+                save_file_name,
+                snapshot.journal_sequence,
+                save_time,
+            )
+            .await;
+        drop(rewinding);
+
+        // 无论回退是否成功都恢复昼夜自动调度与定时自动存档
+        app_state
+            .game_phase_scheduler
+            .start(app_state, game_id)
+            .await;
+        app_state.game_state_manager.start_autosave(game_id).await;
+
+        result
+    }
+
+    /// 回退的主体步骤，调用方负责停止与恢复自动调度和存档
+    async fn rewind_to_save(
+        &self,
+        app_state: &AppState,
+        game_id: &str,
+        save_file_name: &str,
+        journal_sequence: u64,
+        save_time: DateTime<Utc>,
+    ) -> Result<RewindGameResponse, DirectorError> {
+        // 通知并断开所有连接，同时丢弃连接管理器中回退前的消息缓冲，客户端重连后获取回退后的完整状态
+        let notice = format!(
+            "导演已将游戏回退到 {} 的存档，连接已被服务器关闭，请重新连接。",
+            save_time.format("%Y-%m-%d %H:%M:%S")
+        );
+        app_state
+            .global_connection_manager
+            .disconnect_game_connections(game_id, &notice)
+            .await;
+        app_state
+            .global_connection_manager
+            .remove_game_manager(game_id.to_string())
+            .await;
+
+        // 保存回退前的状态，误操作时可再次回退到该存档
+        let backup_save_file_name = match app_state
+            .game_state_manager
+            .save_game_state_to_disk(game_id)
+            .await
+        {
+            Ok(file_name) => Some(file_name),
+            Err(e) => {
+                tracing::warn!(game_id, "Failed to save game state before rewind: {}", e);
+                None
+            }
+        };
+
+        // 丢弃存档之后的数据库记录，回退记录写在删除之后，保留在回退后的时间线中；
+        // 事务在存档加载与行动日志截断成功后才提交，任一步骤失败时数据库记录保持不变
+        let mut tx = self.pool.begin().await?;
+        let (deleted_logs, deleted_kill_records, deleted_damage_records) =
+            GameLogService::delete_records_after_timestamp_in(&mut tx, game_id, save_time)
+                .await
+                .map_err(|e| DirectorError::OtherError {
+                    message: format!("Failed to delete game records: {}", e),
+                })?;
+        let audit_message = format!(
+            "导演将游戏回退到存档 {}（保存于 {}），删除了 {} 条日志、{} 条击杀记录",
+            save_file_name,
+            save_time.format("%Y-%m-%d %H:%M:%S"),
+            deleted_logs,
+            deleted_kill_records
+        );
+        GameLogService::create_system_log_in(&mut tx, game_id, &audit_message, Utc::now())
+            .await
+            .map_err(|e| DirectorError::OtherError {
+                message: format!("Failed to record rewind: {}", e),
+            })?;
+
+        app_state
+            .game_state_manager
+            .load_game_state_from_disk_with_name(game_id, save_file_name)
+            .await
+            .map_err(|e| DirectorError::OtherError {
+                message: format!("Failed to load game state from disk: {}", e),
+            })?;
+
+        // 丢弃存档之后的行动日志，使其与回退后的时间线一致
+        ActionJournal::truncate_after(game_id, journal_sequence).map_err(|e| {
+            DirectorError::OtherError {
+                message: format!("Failed to truncate action journal: {}", e),
+            }
+        })?;
+        tx.commit().await?;
+
+        tracing::info!(
+            game_id,
+            save_file_name,
+            ?backup_save_file_name,
+            deleted_logs,
+            deleted_kill_records,
+            deleted_damage_records,
+            "Game rewound"
+        );
+
+        Ok(RewindGameResponse {
+            save_file_name: save_file_name.to_string(),
+            save_time,
+            backup_save_file_name,
+            deleted_logs,
+            deleted_kill_records,
+            deleted_damage_records,
+        })
+    }
+
     /// 手动存盘操作
     pub async fn manual_save(
         &self,
         app_state: &AppState,
         game_id: &str,
-        password: &str,
     ) -> Result<String, DirectorError> {
-        // 验证导演密码
-        self.verify_director_password(game_id, password).await?;
-
         // 执行存盘操作
         let save_file_name = app_state
             .game_state_manager
@@ -582,18 +869,22 @@ impl DirectorService {
         &self,
         app_state: &AppState,
         game_id: &str,
-        password: &str,
         request: DirectorEditGameRequest,
     ) -> Result<GameWithPlayerCounts, DirectorError> {
-        // 1. 验证导演密码
-        self.verify_director_password(game_id, password).await?;
-
-        // 2. 验证请求参数
+        // 1. 验证请求参数
         request
             .validate()
             .map_err(|e| DirectorError::ValidationError { message: e })?;
 
-        // 3. 执行字段更新操作
+        // 校验规则配置内容
+        if let Some(ref rules_config) = request.rules_config {
+            let report = RulesValidator::validate(rules_config);
+            if !report.valid {
+                return Err(DirectorError::RulesValidationFailed { report });
+            }
+        }
+
+        // 2. 执行字段更新操作
         if let Some(ref name) = request.name {
             sqlx::query("UPDATE games SET name = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?")
                 .bind(name)
@@ -629,10 +920,10 @@ impl DirectorService {
             .await?;
         }
 
-        // 4. 查询并返回更新后的游戏信息
+        // 3. 查询并返回更新后的游戏信息
         let game = app_state
             .game_service
-            .get_game_by_id_with_player_counts(game_id, true)
+            .get_game_by_id_with_player_counts(game_id)
             .await
             .map_err(|e| DirectorError::OtherError {
                 message: format!("Failed to get game: {}", e),
@@ -641,16 +932,48 @@ impl DirectorService {
         Ok(game)
     }
 
+    /// 查询行动日志
+    pub async fn get_action_journal(
+        &self,
+        game_id: &str,
+    ) -> Result<Vec<JournalEntry>, DirectorError> {
+        ActionJournal::read_entries(game_id).map_err(|e| DirectorError::OtherError {
+            message: format!("Failed to read action journal: {}", e),
+        })
+    }
+
+    /// 基于存档快照重放行动日志，重建指定序号时的游戏状态
+    pub async fn replay_game_state(
+        &self,
+        game_id: &str,
+        save_file_name: &str,
+        until_sequence: Option<u64>,
+    ) -> Result<GameState, DirectorError> {
+        let snapshot = GlobalGameStateManager::read_game_state_from_disk(game_id, save_file_name)
+            .map_err(|e| DirectorError::OtherError {
+            message: format!("Failed to load game state from disk: {}", e),
+        })?;
+
+        if until_sequence.is_some_and(|sequence| sequence < snapshot.journal_sequence) {
+            return Err(DirectorError::ValidationError {
+                message: format!("重放目标序号不能早于存档序号 {}", snapshot.journal_sequence),
+            });
+        }
+
+        let entries =
+            ActionJournal::read_entries(game_id).map_err(|e| DirectorError::OtherError {
+                message: format!("Failed to read action journal: {}", e),
+            })?;
+
+        Ok(ActionJournal::replay(snapshot, &entries, until_sequence))
+    }
+
     /// 查询存档文件列表
     pub async fn list_save_files(
         &self,
         app_state: &AppState,
         game_id: &str,
-        password: &str,
     ) -> Result<Vec<SaveFileInfo>, DirectorError> {
-        // 验证导演密码
-        self.verify_director_password(game_id, password).await?;
-
         // 获取存档文件列表
         let save_files = app_state
             .game_state_manager
@@ -21,6 +21,9 @@ pub enum AuthError {
     #[error("Invalid token")]
     InvalidToken,
 
+    #[error("Token revoked")]
+    TokenRevoked,
+
     #[error("Insufficient permissions")]
     InsufficientPermissions,
 
@@ -78,6 +81,7 @@ impl IntoResponse for AuthError {
             AuthError::UserNotFound => (StatusCode::NOT_FOUND, "用户不存在"),
             AuthError::TokenExpired => (StatusCode::UNAUTHORIZED, "认证令牌已过期"),
             AuthError::InvalidToken => (StatusCode::UNAUTHORIZED, "认证令牌无效"),
+            AuthError::TokenRevoked => (StatusCode::UNAUTHORIZED, "认证令牌已失效，请重新验证身份"),
             AuthError::InsufficientPermissions => (StatusCode::FORBIDDEN, "权限不足"),
             AuthError::JwtError(_) => (StatusCode::UNAUTHORIZED, "认证令牌处理失败"),
             AuthError::BcryptError(_) => (StatusCode::INTERNAL_SERVER_ERROR, "密码处理失败"),
@@ -95,7 +99,10 @@ impl IntoResponse for AuthError {
 impl IntoResponse for ServiceError {
     fn into_response(self) -> Response {
         let (status, message) = match self {
-            ServiceError::Database(_) => (StatusCode::INTERNAL_SERVER_ERROR, "数据库操作失败"),
+            ServiceError::Database(_) => {
+                crate::metrics::metrics().record_db_error("service");
+                (StatusCode::INTERNAL_SERVER_ERROR, "数据库操作失败")
+            }
             ServiceError::Auth(auth_err) => return auth_err.into_response(),
             ServiceError::Validation(ref msg) => (StatusCode::BAD_REQUEST, msg.as_str()),
             ServiceError::UserAlreadyExists => (StatusCode::CONFLICT, "用户已存在"),
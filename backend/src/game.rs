@@ -1,10 +1,17 @@
+pub mod action_journal;
+pub mod archive;
+pub mod credentials;
 pub mod errors;
+pub mod game_rng;
 pub mod game_rule_engine;
 pub mod global_game_state_manager;
 pub mod handlers;
+pub mod log_query;
 pub mod log_service;
 pub mod models;
+pub mod rules_validator;
 pub mod service;
+pub mod stats;
 pub mod system_initializer;
 
 pub use handlers::*;
@@ -0,0 +1,264 @@
+//! 行动日志（事件溯源）
+//! 每个被接受的玩家行动、导演行动与系统调度事件在修改GameState之前追加到
+//! `game_states/{game_id}/journal.jsonl`，可在任意存档快照上按序重放以重建中间状态。
+//!
+//! 行动处理中依赖当前时间的判断（夜晚行动时间、搜索冷却）在应用条目时统一使用条目记录的时间，
+//! 随机判定则从条目记录的随机数状态继续，因此重放结果是确定的。
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use tracing::error;
+
+use crate::websocket::actions::director_action_scheduler::{
+    DirectorActionParams, DirectorActionScheduler,
+};
+use crate::websocket::actions::player_action_scheduler::{ActionParams, PlayerActionScheduler};
+use crate::websocket::models::{ActionResults, GameState};
+
+/// 行动发起者
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "role", rename_all = "snake_case")]
+pub enum JournalActor {
+    /// 玩家
+    Player { player_id: String },
+    /// 导演
+    Director,
+    /// 系统（昼夜自动调度等）
+    System,
+}
+
+/// 行动日志条目
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    /// 游戏内递增序号（从1开始）
+    pub sequence: u64,
+    /// 记录时间
+    pub timestamp: DateTime<Utc>,
+    /// 行动发起者
+    pub actor: JournalActor,
+    /// 行动类型
+    pub action: String,
+    /// 原始行动参数
+    pub params: JsonValue,
+    /// 行动开始前的随机数生成器状态（重放时据此恢复随机序列）
+    pub rng_seed: Option<u64>,
+}
+
+impl JournalEntry {
+    /// 为游戏状态分配下一个序号并创建日志条目
+    pub fn new(
+        game_state: &mut GameState,
+        actor: JournalActor,
+        action: &str,
+        params: JsonValue,
+    ) -> Self {
+        game_state.journal_sequence += 1;
+
+        Self {
+            sequence: game_state.journal_sequence,
+            timestamp: Utc::now(),
+            actor,
+            action: action.to_string(),
+            params,
+            rng_seed: Some(game_state.rng.state()),
+        }
+    }
+
+    /// 将日志条目应用到游戏状态（行动判定使用条目记录的时间）
+    pub fn apply(&self, game_state: &mut GameState) -> Result<ActionResults, String> {
+        if self.sequence > game_state.journal_sequence {
+            game_state.journal_sequence = self.sequence;
+        }
+
+        if let Some(rng_seed) = self.rng_seed {
+            game_state.rng.set_state(rng_seed);
+        }
+
+        game_state.action_time = Some(self.timestamp);
+        let result = self.dispatch(game_state);
+        game_state.action_time = None;
+        result
+    }
+
+    /// 按行动发起者分发到对应的调度器
+    fn dispatch(&self, game_state: &mut GameState) -> Result<ActionResults, String> {
+        match &self.actor {
+            JournalActor::Player { player_id } => {
+                let action_params = ActionParams::from_json(&self.params)?;
+                PlayerActionScheduler::dispatch(game_state, player_id, &self.action, action_params)
+            }
+            JournalActor::Director => {
+                let action_params = DirectorActionParams::from_json(&self.params)?;
+                DirectorActionScheduler::dispatch(game_state, &self.action, action_params)
+            }
+            JournalActor::System => match self.action.as_str() {
+                "schedule_next_night" => Ok(game_state
+                    .schedule_next_night(self.timestamp)
+                    .unwrap_or(ActionResults { results: vec![] })),
+                "scheduled_night_start" => Ok(game_state.handle_scheduled_night_start()),
+                "scheduled_night_end" => game_state.handle_scheduled_night_end(),
+                _ => Err(format!("Unknown system action: {}", self.action)),
+            },
+        }
+    }
+}
+
+/// 行动日志存储
+pub struct ActionJournal;
+
+impl ActionJournal {
+    /// 获取指定游戏的行动日志文件路径
+    pub fn journal_path(game_id: &str) -> String {
+        format!("game_states/{}/journal.jsonl", game_id)
+    }
+
+    /// 为游戏状态分配序号、创建日志条目并追加到行动日志
+    ///
+    /// 写入在阻塞线程池中进行，不占用异步运行时的工作线程。
+    /// 写入失败时撤回已分配的序号并返回错误，调用方不得再应用该行动，
+    /// 否则游戏状态会包含日志中不存在的修改而无法重放。
+    pub async fn record(
+        game_state: &mut GameState,
+        actor: JournalActor,
+        action: &str,
+        params: JsonValue,
+    ) -> Result<JournalEntry, String> {
+        let entry = JournalEntry::new(game_state, actor, action, params);
+        if let Err(e) = Self::append_blocking(&game_state.game_id, &entry).await {
+            game_state.journal_sequence -= 1;
+            error!(
+                game_id = %game_state.game_id,
+                sequence = entry.sequence,
+                action,
+                "Failed to append action journal: {}",
+                e
+            );
+            return Err(format!("Failed to write action journal: {}", e));
+        }
+        Ok(entry)
+    }
+
+    /// 追加日志条目
+    pub fn append(game_id: &str, entry: &JournalEntry) -> Result<(), String> {
+        let file_path = Self::journal_path(game_id);
+        if let Some(parent) = Path::new(&file_path).parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
+        }
+
+        let line = serde_json::to_string(entry)
+            .map_err(|e| format!("Failed to serialize journal entry: {}", e))?;
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&file_path)
+            .map_err(|e| format!("Failed to open journal: {}", e))?;
+        writeln!(file, "{}", line).map_err(|e| format!("Failed to write journal: {}", e))?;
+
+        Ok(())
+    }
+
+    /// 在阻塞线程池中追加日志条目
+    pub async fn append_blocking(game_id: &str, entry: &JournalEntry) -> Result<(), String> {
+        let game_id = game_id.to_string();
+        let entry = entry.clone();
+        tokio::task::spawn_blocking(move || Self::append(&game_id, &entry))
+            .await
+            .map_err(|e| format!("Journal write task failed: {}", e))?
+    }
+
+    /// 读取指定游戏的全部日志条目（按序号排列）
+    pub fn read_entries(game_id: &str) -> Result<Vec<JournalEntry>, String> {
+        let file_path = Self::journal_path(game_id);
+        if !Path::new(&file_path).exists() {
+            return Ok(vec![]);
+        }
+
+        let content =
+            fs::read_to_string(&file_path).map_err(|e| format!("Failed to read journal: {}", e))?;
+
+        let mut entries = Vec::new();
+        for (index, line) in content.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let entry: JournalEntry = serde_json::from_str(line)
+                .map_err(|e| format!("Failed to parse journal line {}: {}", index + 1, e))?;
+            entries.push(entry);
+        }
+        entries.sort_by_key(|entry| entry.sequence);
+
+        Ok(entries)
+    }
+
+    /// 丢弃序号大于指定值的日志条目（从旧存档恢复时，之后的行动已不属于当前时间线）
+    pub fn truncate_after(game_id: &str, sequence: u64) -> Result<(), String> {
+        let file_path = Self::journal_path(game_id);
+        if !Path::new(&file_path).exists() {
+            return Ok(());
+        }
+
+        let entries = Self::read_entries(game_id)?;
+        let kept: Vec<JournalEntry> = entries
+            .into_iter()
+            .filter(|entry| entry.sequence <= sequence)
+            .collect();
+        Self::write_entries(game_id, &kept)
+    }
+
+    /// 以给定条目覆盖指定游戏的行动日志
+    pub fn write_entries(game_id: &str, entries: &[JournalEntry]) -> Result<(), String> {
+        let file_path = Self::journal_path(game_id);
+        if let Some(parent) = Path::new(&file_path).parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
+        }
+
+        let mut content = String::new();
+        for entry in entries {
+            let line = serde_json::to_string(entry)
+                .map_err(|e| format!("Failed to serialize journal entry: {}", e))?;
+            content.push_str(&line);
+            content.push('\n');
+        }
+
+        fs::write(&file_path, content).map_err(|e| format!("Failed to write journal: {}", e))
+    }
+
+    /// 删除指定游戏的行动日志
+    pub fn delete(game_id: &str) -> Result<(), String> {
+        let file_path = Self::journal_path(game_id);
+        if Path::new(&file_path).exists() {
+            fs::remove_file(&file_path).map_err(|e| format!("Failed to delete journal: {}", e))?;
+        }
+        Ok(())
+    }
+
+    /// 在快照上重放日志，重建指定序号（含）时的游戏状态
+    ///
+    /// 仅应用序号大于快照`journal_sequence`的条目；`until_sequence`为空时重放全部条目。
+    pub fn replay(
+        snapshot: GameState,
+        entries: &[JournalEntry],
+        until_sequence: Option<u64>,
+    ) -> GameState {
+        let mut game_state = snapshot;
+        let start_sequence = game_state.journal_sequence;
+
+        for entry in entries {
+            if entry.sequence <= start_sequence {
+                continue;
+            }
+            if until_sequence.is_some_and(|until| entry.sequence > until) {
+                break;
+            }
+            // 与实时处理一致：行动处理失败时游戏状态保持不变，继续重放后续条目
+            let _ = entry.apply(&mut game_state);
+        }
+
+        game_state
+    }
+}
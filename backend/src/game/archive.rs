@@ -0,0 +1,233 @@
+//! 游戏归档
+//! 自描述的JSON文件格式，将一场已结束游戏的元数据、规则配置、演员名单、日志、击杀与伤害记录、
+//! 行动日志以及全部存档快照打包，用于在清理旧游戏前保留历史或迁移到其他服务器
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use std::collections::HashSet;
+use std::fs;
+use uuid::Uuid;
+
+use super::action_journal::{ActionJournal, JournalEntry};
+use super::global_game_state_manager::GlobalGameStateManager;
+use super::models::{DamageRecord, GameStatus, KillRecord, MessageRecord, SaveKind};
+
+/// 归档格式标识
+pub const ARCHIVE_FORMAT: &str = "royale-arena/game-archive";
+
+/// 当前归档格式版本
+pub const ARCHIVE_FORMAT_VERSION: u32 = 1;
+
+/// 游戏归档
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameArchive {
+    /// 格式标识，固定为`royale-arena/game-archive`
+    pub format: String,
+    /// 格式版本
+    pub format_version: u32,
+    /// 导出时间
+    pub exported_at: DateTime<Utc>,
+    /// 游戏元数据与规则配置
+    pub game: ArchivedGame,
+    /// 演员名单（不含密码）
+    pub players: Vec<ArchivedPlayer>,
+    /// 按时间排列的全部消息记录
+    pub logs: Vec<MessageRecord>,
+    pub kill_records: Vec<KillRecord>,
+    #[serde(default)]
+    pub damage_records: Vec<DamageRecord>,
+    /// 行动日志条目
+    #[serde(default)]
+    pub journal: Vec<JournalEntry>,
+    /// 存档快照
+    #[serde(default)]
+    pub saves: Vec<ArchivedSave>,
+}
+
+/// 归档中的游戏信息（不含导演密码与观战码）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedGame {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub max_players: i32,
+    /// 导出时的游戏状态（导入后统一为已结束）
+    pub status: GameStatus,
+    pub rules_config: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// 归档中的演员
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct ArchivedPlayer {
+    pub id: String,
+    pub name: String,
+    pub team_id: i32,
+}
+
+/// 归档中的存档快照
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedSave {
+    pub file_name: String,
+    pub kind: SaveKind,
+    pub created_at: Option<DateTime<Utc>>,
+    /// 存档文件内容（原样保留，不按当前版本的游戏状态结构解析）
+    pub state: serde_json::Value,
+}
+
+/// 只有已结束的游戏（包括已隐藏、已删除的游戏）可以导出归档
+pub fn is_archivable_status(status: &GameStatus) -> bool {
+    matches!(
+        status,
+        GameStatus::Ended | GameStatus::Hidden | GameStatus::Deleted
+    )
+}
+
+/// 存档文件名是否可安全写入游戏存档目录
+pub fn is_valid_save_file_name(file_name: &str) -> bool {
+    file_name.ends_with(".json")
+        && file_name.len() > ".json".len()
+        && !file_name.starts_with('.')
+        && !file_name.contains(['/', '\\'])
+        && !file_name.contains("..")
+}
+
+impl GameArchive {
+    /// 以当前格式版本创建归档
+    pub fn new(game: ArchivedGame, players: Vec<ArchivedPlayer>) -> Self {
+        Self {
+            format: ARCHIVE_FORMAT.to_string(),
+            format_version: ARCHIVE_FORMAT_VERSION,
+            exported_at: Utc::now(),
+            game,
+            players,
+            logs: Vec::new(),
+            kill_records: Vec::new(),
+            damage_records: Vec::new(),
+            journal: Vec::new(),
+            saves: Vec::new(),
+        }
+    }
+
+    /// 校验归档：格式、游戏信息、名单与记录之间的引用关系以及存档文件名
+    ///
+    /// 游戏ID与演员ID必须是UUID：游戏ID用作存档目录名，演员ID会写入数据库并被存档快照引用。
+    pub fn validate(&self) -> Result<(), String> {
+        if self.format != ARCHIVE_FORMAT {
+            return Err(format!("不是游戏归档（应为 {}）", ARCHIVE_FORMAT));
+        }
+        if self.format_version == 0 || self.format_version > ARCHIVE_FORMAT_VERSION {
+            return Err(format!(
+                "不支持的归档格式版本 {}（支持 1 至 {}）",
+                self.format_version, ARCHIVE_FORMAT_VERSION
+            ));
+        }
+
+        let game_id = self.game.id.as_str();
+        if Uuid::parse_str(game_id).is_err() {
+            return Err(format!("游戏ID {} 不是有效的UUID", game_id));
+        }
+        if self.game.name.trim().is_empty() || self.game.name.chars().count() > 100 {
+            return Err("游戏名称不能为空且不能超过100个字符".to_string());
+        }
+
+        let mut player_ids = HashSet::new();
+        for player in &self.players {
+            if Uuid::parse_str(&player.id).is_err() {
+                return Err(format!("演员ID {} 不是有效的UUID", player.id));
+            }
+            if !player_ids.insert(player.id.as_str()) {
+                return Err(format!("演员 {} 在归档中重复", player.id));
+            }
+        }
+        let check_player = |player_id: &str, record: &str| {
+            if player_ids.contains(player_id) {
+                Ok(())
+            } else {
+                Err(format!("{} 引用了不在名单中的演员 {}", record, player_id))
+            }
+        };
+        let check_game = |record_game_id: &str, record: &str| {
+            if record_game_id == game_id {
+                Ok(())
+            } else {
+                Err(format!("{} 不属于游戏 {}", record, game_id))
+            }
+        };
+
+        let mut record_ids = HashSet::new();
+        for log in &self.logs {
+            let record = format!("日志 {}", log.id);
+            check_game(&log.game_id, &record)?;
+            if let Some(player_id) = &log.player_id {
+                check_player(player_id, &record)?;
+            }
+            if !record_ids.insert(log.id.as_str()) {
+                return Err(format!("{} 在归档中重复", record));
+            }
+        }
+
+        record_ids.clear();
+        for kill in &self.kill_records {
+            let record = format!("击杀记录 {}", kill.id);
+            check_game(&kill.game_id, &record)?;
+            if let Some(killer_id) = &kill.killer_id {
+                check_player(killer_id, &record)?;
+            }
+            check_player(&kill.victim_id, &record)?;
+            if !record_ids.insert(kill.id.as_str()) {
+                return Err(format!("{} 在归档中重复", record));
+            }
+        }
+
+        record_ids.clear();
+        for damage in &self.damage_records {
+            let record = format!("伤害记录 {}", damage.id);
+            check_game(&damage.game_id, &record)?;
+            if let Some(attacker_id) = &damage.attacker_id {
+                check_player(attacker_id, &record)?;
+            }
+            check_player(&damage.victim_id, &record)?;
+            if !record_ids.insert(damage.id.as_str()) {
+                return Err(format!("{} 在归档中重复", record));
+            }
+        }
+
+        let mut file_names = HashSet::new();
+        for save in &self.saves {
+            if !is_valid_save_file_name(&save.file_name) {
+                return Err(format!("无效的存档文件名 {}", save.file_name));
+            }
+            if !file_names.insert(save.file_name.as_str()) {
+                return Err(format!("存档文件 {} 在归档中重复", save.file_name));
+            }
+        }
+
+        Ok(())
+    }
+    /// 写入归档中的行动日志与存档文件，须在`validate`通过之后调用
+    pub fn write_files(&self) -> Result<(), String> {
+        if !self.journal.is_empty() {
+            ActionJournal::write_entries(&self.game.id, &self.journal)?;
+        }
+        for save in &self.saves {
+            GlobalGameStateManager::write_save_file_json(
+                &self.game.id,
+                &save.file_name,
+                &save.state,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// 删除`write_files`写入的文件，目录为空时一并删除；用于导入失败时清理
+    pub fn remove_files(&self) {
+        let _ = ActionJournal::delete(&self.game.id);
+        for save in &self.saves {
+            let _ = fs::remove_file(format!("game_states/{}/{}", self.game.id, save.file_name));
+        }
+        let _ = fs::remove_dir(format!("game_states/{}", self.game.id));
+    }
+}
@@ -0,0 +1,150 @@
+//! 游戏凭据
+//! 导演密码与演员密码以bcrypt哈希存储，所有凭据的哈希与校验都经过此处
+
+use sqlx::MySqlPool;
+
+/// 通过名称与密码识别出的演员
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActorIdentity {
+    pub id: String,
+    pub name: String,
+}
+
+/// 游戏凭据工具
+pub struct GameCredentials;
+
+impl GameCredentials {
+    /// 生成密码哈希
+    pub fn hash_password(password: &str, bcrypt_cost: u32) -> Result<String, bcrypt::BcryptError> {
+        bcrypt::hash(password, bcrypt_cost)
+    }
+
+    /// 校验密码与哈希是否匹配（哈希格式无效时视为不匹配）
+    pub fn verify_password(password: &str, password_hash: &str) -> bool {
+        bcrypt::verify(password, password_hash).unwrap_or(false)
+    }
+
+    /// 判断存储的值是否已是bcrypt哈希
+    pub fn is_password_hash(value: &str) -> bool {
+        value.len() == 60
+            && ["$2a$", "$2b$", "$2x$", "$2y$"]
+                .iter()
+                .any(|prefix| value.starts_with(prefix))
+    }
+
+    /// 在阻塞线程池中生成密码哈希，避免bcrypt计算占用异步运行时的工作线程
+    pub async fn hash_password_blocking(
+        password: &str,
+        bcrypt_cost: u32,
+    ) -> Result<String, bcrypt::BcryptError> {
+        let password = password.to_string();
+        tokio::task::spawn_blocking(move || Self::hash_password(&password, bcrypt_cost))
+            .await
+            .map_err(|e| bcrypt::BcryptError::Io(std::io::Error::other(e)))?
+    }
+
+    /// 在阻塞线程池中校验密码，避免bcrypt计算占用异步运行时的工作线程
+    pub async fn verify_password_blocking(password: &str, password_hash: &str) -> bool {
+        let password = password.to_string();
+        let password_hash = password_hash.to_string();
+        tokio::task::spawn_blocking(move || Self::verify_password(&password, &password_hash))
+            .await
+            .unwrap_or(false)
+    }
+
+    /// 按名称查找游戏中的演员并校验其密码
+    ///
+    /// 每次验证只校验该演员的一个哈希，耗时与游戏中的演员数量无关。
+    pub async fn find_actor(
+        pool: &MySqlPool,
+        game_id: &str,
+        actor_name: &str,
+        password: &str,
+    ) -> Result<Option<ActorIdentity>, sqlx::Error> {
+        let actor: Option<(String, String, String)> = sqlx::query_as(
+            "SELECT id, name, password FROM actors WHERE game_id = ? AND name = ? LIMIT 1",
+        )
+        .bind(game_id)
+        .bind(actor_name)
+        .fetch_optional(pool)
+        .await?;
+
+        let Some((id, name, password_hash)) = actor else {
+            return Ok(None);
+        };
+        if Self::verify_password_blocking(password, &password_hash).await {
+            Ok(Some(ActorIdentity { id, name }))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// 将数据库中仍为明文的导演密码与演员密码替换为哈希
+    ///
+    /// 用于升级前创建的游戏，返回更新的记录数。已是哈希的记录保持不变，可重复执行。
+    pub async fn hash_plaintext_passwords(
+        pool: &MySqlPool,
+        bcrypt_cost: u32,
+    ) -> Result<u64, Box<dyn std::error::Error>> {
+        let mut updated = 0;
+
+        let games: Vec<(String, String)> =
+            sqlx::query_as("SELECT id, director_password FROM games")
+                .fetch_all(pool)
+                .await?;
+        for (game_id, password) in games {
+            if Self::is_password_hash(&password) {
+                continue;
+            }
+            sqlx::query("UPDATE games SET director_password = ? WHERE id = ?")
+                .bind(Self::hash_password_blocking(&password, bcrypt_cost).await?)
+                .bind(&game_id)
+                .execute(pool)
+                .await?;
+            updated += 1;
+        }
+
+        let actors: Vec<(String, String)> = sqlx::query_as("SELECT id, password FROM actors")
+            .fetch_all(pool)
+            .await?;
+        for (actor_id, password) in actors {
+            if Self::is_password_hash(&password) {
+                continue;
+            }
+            sqlx::query("UPDATE actors SET password = ? WHERE id = ?")
+                .bind(Self::hash_password_blocking(&password, bcrypt_cost).await?)
+                .bind(&actor_id)
+                .execute(pool)
+                .await?;
+            updated += 1;
+        }
+
+        Ok(updated)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash_and_verify_password() {
+        let password_hash = GameCredentials::hash_password("abc123", 4).unwrap();
+        assert!(GameCredentials::is_password_hash(&password_hash));
+        assert!(GameCredentials::verify_password("abc123", &password_hash));
+        assert!(!GameCredentials::verify_password("abc124", &password_hash));
+
+        // 明文不会被当作哈希，也不会与自身匹配
+        assert!(!GameCredentials::is_password_hash("abc123"));
+        assert!(!GameCredentials::verify_password("abc123", "abc123"));
+    }
+
+    #[tokio::test]
+    async fn test_hash_and_verify_password_blocking() {
+        let password_hash = GameCredentials::hash_password_blocking("abc123", 4)
+            .await
+            .unwrap();
+        assert!(GameCredentials::verify_password_blocking("abc123", &password_hash).await);
+        assert!(!GameCredentials::verify_password_blocking("abc124", &password_hash).await);
+    }
+}
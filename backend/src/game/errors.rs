@@ -50,7 +50,10 @@ impl IntoResponse for GameError {
             GameError::GameIdExists => (StatusCode::CONFLICT, "游戏ID已存在"),
             GameError::InvalidGameState => (StatusCode::BAD_REQUEST, "游戏状态不允许此操作"),
             GameError::ValidationError(ref msg) => (StatusCode::BAD_REQUEST, msg.as_str()),
-            GameError::DatabaseError(_) => (StatusCode::INTERNAL_SERVER_ERROR, "数据库操作失败"),
+            GameError::DatabaseError(_) => {
+                crate::metrics::metrics().record_db_error("game");
+                (StatusCode::INTERNAL_SERVER_ERROR, "数据库操作失败")
+            }
             GameError::OtherError(ref msg) => (StatusCode::BAD_REQUEST, msg.as_str()),
         };
 
@@ -0,0 +1,66 @@
+//! 游戏随机数生成器
+//! 由GameState持有并随存档一同序列化，保证相同种子与相同行动序列得到相同结果
+
+use rand::RngCore;
+use rand::rand_core::impls;
+use serde::{Deserialize, Serialize};
+
+/// 可设置种子、可序列化的游戏随机数生成器（SplitMix64）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameRng {
+    /// 初始种子
+    seed: u64,
+    /// 当前内部状态
+    state: u64,
+}
+
+impl GameRng {
+    /// 使用指定种子创建随机数生成器
+    pub fn from_seed(seed: u64) -> Self {
+        Self { seed, state: seed }
+    }
+
+    /// 使用系统随机种子创建随机数生成器
+    pub fn from_entropy() -> Self {
+        Self::from_seed(rand::random())
+    }
+
+    /// 获取初始种子
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// 获取当前内部状态（可用于在任意时刻恢复随机序列）
+    pub fn state(&self) -> u64 {
+        self.state
+    }
+
+    /// 将内部状态恢复到指定值
+    pub fn set_state(&mut self, state: u64) {
+        self.state = state;
+    }
+}
+
+impl Default for GameRng {
+    fn default() -> Self {
+        Self::from_entropy()
+    }
+}
+
+impl RngCore for GameRng {
+    fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    fn fill_bytes(&mut self, dst: &mut [u8]) {
+        impls::fill_bytes_via_next(self, dst)
+    }
+}
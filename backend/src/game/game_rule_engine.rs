@@ -1,6 +1,7 @@
 //! 游戏规则引擎
 //! 负责解析和管理游戏规则配置，确保前后端规则一致性
 
+use chrono::{DateTime, Duration, Local, NaiveTime, TimeZone, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use uuid::Uuid;
@@ -107,9 +108,11 @@ pub struct GameRuleEngine {
     pub action_costs: ActionCosts,
     pub rest_mode: RestModeConfig,
     pub items_config: ItemsConfig,
-    #[allow(dead_code)]
-    pub teammate_behavior: TeammateBehavior, // TODO: 实现队友行为规则
+    pub teammate_behavior: TeammateBehavior,
     pub death_item_disposition: DeathItemDisposition,
+    pub game_flow: GameFlowConfig,
+    pub spectator: SpectatorConfig,
+    pub autosave: AutosaveConfig,
 }
 
 /// 地图配置结构体
@@ -117,6 +120,60 @@ pub struct GameRuleEngine {
 pub struct MapConfig {
     pub places: Vec<String>,
     pub safe_places: Vec<String>,
+    /// 地点之间的双向连接（可选），未配置时任意地点之间均可移动
+    #[serde(default)]
+    pub edges: Vec<MapEdge>,
+}
+
+/// 地点连接
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MapEdge {
+    pub from: String,
+    pub to: String,
+    /// 沿该连接移动的体力消耗（可选），未设置时使用`action_costs.move`
+    #[serde(default)]
+    pub cost: Option<i32>,
+}
+
+impl MapConfig {
+    /// 是否配置了地点连接
+    pub fn has_edges(&self) -> bool {
+        !self.edges.is_empty()
+    }
+
+    /// 查找两个地点之间的连接（不区分方向）
+    pub fn find_edge(&self, from: &str, to: &str) -> Option<&MapEdge> {
+        self.edges.iter().find(|edge| {
+            (edge.from == from && edge.to == to) || (edge.from == to && edge.to == from)
+        })
+    }
+
+    /// 获取与指定地点相连的所有地点（按配置顺序，未配置连接时为其他所有地点）
+    pub fn neighbors(&self, place_name: &str) -> Vec<String> {
+        if !self.has_edges() {
+            return self
+                .places
+                .iter()
+                .filter(|name| name.as_str() != place_name)
+                .cloned()
+                .collect();
+        }
+
+        let mut neighbors: Vec<String> = Vec::new();
+        for edge in &self.edges {
+            let neighbor = if edge.from == place_name {
+                &edge.to
+            } else if edge.to == place_name {
+                &edge.from
+            } else {
+                continue;
+            };
+            if neighbor != place_name && !neighbors.contains(neighbor) {
+                neighbors.push(neighbor.clone());
+            }
+        }
+        neighbors
+    }
 }
 
 /// 玩家配置结构体
@@ -158,7 +215,130 @@ pub struct RestModeConfig {
 /// 队友行为配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TeammateBehavior {
-    pub mode: i32, // 0: 无队友伤害免疫, 1: 有队友伤害免疫
+    pub mode: i32, // 位压缩存储：1 禁止队友伤害，2 禁止搜索到队友，4 允许观看队友状态，8 允许赠送队友物品
+}
+
+impl TeammateBehavior {
+    /// 禁止队友伤害
+    pub const NO_TEAMMATE_DAMAGE: i32 = 1;
+    /// 禁止搜索到队友
+    pub const NO_TEAMMATE_SEARCH: i32 = 2;
+    /// 允许观看队友状态
+    pub const VIEW_TEAMMATE_STATUS: i32 = 4;
+    /// 允许赠送队友物品
+    pub const GIFT_TEAMMATE_ITEMS: i32 = 8;
+
+    /// 检查是否启用了指定的队友行为规则位
+    pub fn has(&self, flag: i32) -> bool {
+        self.mode & flag != 0
+    }
+}
+
+/// 游戏流程配置（昼夜自动调度）
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct GameFlowConfig {
+    /// 白天时长(秒)
+    #[serde(default)]
+    pub day_duration: i64,
+    /// 夜晚时长(秒)
+    #[serde(default)]
+    pub night_duration: i64,
+    /// 首个夜晚的开始时刻（服务器本地时间，格式 HH:MM），未设置时不启用自动调度
+    #[serde(default)]
+    pub night_start_time: Option<String>,
+    /// 夜晚结束时是否自动执行夜晚结算
+    #[serde(default)]
+    pub auto_settlement: bool,
+}
+
+/// 观战配置
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct SpectatorConfig {
+    /// 观战画面的延迟时长(秒)
+    #[serde(default)]
+    pub delay_seconds: u64,
+}
+
+/// 自动存档配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AutosaveConfig {
+    /// 自动存档间隔(秒)，为0时不定时存档
+    #[serde(default = "AutosaveConfig::default_interval_seconds")]
+    pub interval_seconds: u64,
+    /// 保留的自动存档数量，更早的自动存档会被删除
+    #[serde(default = "AutosaveConfig::default_keep_saves")]
+    pub keep_saves: usize,
+}
+
+impl AutosaveConfig {
+    fn default_interval_seconds() -> u64 {
+        300
+    }
+
+    fn default_keep_saves() -> usize {
+        10
+    }
+}
+
+impl Default for AutosaveConfig {
+    fn default() -> Self {
+        Self {
+            interval_seconds: Self::default_interval_seconds(),
+            keep_saves: Self::default_keep_saves(),
+        }
+    }
+}
+
+impl GameFlowConfig {
+    /// 是否启用了服务器时钟驱动的昼夜自动调度
+    pub fn is_scheduled(&self) -> bool {
+        self.night_start_time.is_some() && self.night_duration > 0 && self.day_duration >= 0
+    }
+
+    /// 解析首个夜晚的开始时刻
+    fn parse_night_start_time(&self) -> Option<NaiveTime> {
+        self.night_start_time
+            .as_deref()
+            .and_then(|time| NaiveTime::parse_from_str(time, "%H:%M").ok())
+    }
+
+    /// 计算当前时刻所处或之后最近的夜晚时间窗口
+    ///
+    /// 若已有上一个夜晚的结束时间，则按“白天时长 + 夜晚时长”的周期顺延；
+    /// 否则以配置的本地开始时刻锚定首个夜晚。已错过的夜晚会被跳过。
+    pub fn next_night_window(
+        &self,
+        now: DateTime<Utc>,
+        previous_night_end: Option<DateTime<Utc>>,
+    ) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+        if !self.is_scheduled() {
+            return None;
+        }
+
+        let night = Duration::seconds(self.night_duration);
+        let (mut start, step) = match previous_night_end {
+            Some(end) => (
+                end + Duration::seconds(self.day_duration),
+                Duration::seconds(self.night_duration + self.day_duration),
+            ),
+            None => {
+                let start_time = self.parse_night_start_time()?;
+                // 从前一天开始查找，以覆盖跨越午夜的夜晚
+                let yesterday = now.with_timezone(&Local).date_naive() - Duration::days(1);
+                let start = Local
+                    .from_local_datetime(&yesterday.and_time(start_time))
+                    .earliest()?
+                    .with_timezone(&Utc);
+                (start, Duration::days(1))
+            }
+        };
+
+        while start + night <= now {
+            start += step;
+        }
+
+        Some((start, start + night))
+    }
 }
 
 /// 死亡物品处置规则
@@ -394,6 +574,27 @@ impl GameRuleEngine {
                 .to_string(),
         };
 
+        // 解析游戏流程配置（可选）
+        let game_flow = match rules_value.get("game_flow") {
+            Some(value) => serde_json::from_value(value.clone())
+                .map_err(|e| format!("Failed to parse game flow config: {}", e))?,
+            None => GameFlowConfig::default(),
+        };
+
+        // 解析观战配置（可选）
+        let spectator = match rules_value.get("spectator") {
+            Some(value) => serde_json::from_value(value.clone())
+                .map_err(|e| format!("Failed to parse spectator config: {}", e))?,
+            None => SpectatorConfig::default(),
+        };
+
+        // 解析自动存档配置（可选）
+        let autosave = match rules_value.get("autosave") {
+            Some(value) => serde_json::from_value(value.clone())
+                .map_err(|e| format!("Failed to parse autosave config: {}", e))?,
+            None => AutosaveConfig::default(),
+        };
+
         Ok(Self {
             map_config,
             player_config,
@@ -402,6 +603,9 @@ impl GameRuleEngine {
             items_config,
             teammate_behavior,
             death_item_disposition,
+            game_flow,
+            spectator,
+            autosave,
         })
     }
     /// 获取搜索冷却时间
@@ -8,9 +8,14 @@ use sqlx::MySqlPool;
 use std::fs;
 use std::path::Path;
 use std::sync::Arc;
-use tokio::sync::RwLock;
-
-use crate::game::models::{GameStatus, SaveFileInfo};
+use std::time::{Duration, Instant};
+use tokio::sync::{OwnedRwLockReadGuard, OwnedRwLockWriteGuard, RwLock};
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+use crate::game::game_rng::GameRng;
+use crate::game::models::{GameStatus, SaveFileInfo, SaveKind};
+use crate::metrics::metrics;
 use crate::websocket::models::{GameState, Place, Player};
 
 /// 全局游戏状态管理器
@@ -20,6 +25,10 @@ pub struct GlobalGameStateManager {
     pool: MySqlPool,
     /// 游戏状态存储（内存中）
     game_states: Arc<DashMap<String, Arc<RwLock<GameState>>>>,
+    /// 游戏ID到定时自动存档任务的映射
+    autosave_tasks: Arc<DashMap<String, JoinHandle<()>>>,
+    /// 游戏ID到回退闸门的映射：行动处理、自动调度与建立连接持有读锁，回退持有写锁
+    operation_gates: Arc<DashMap<String, Arc<RwLock<()>>>>,
 }
 
 impl GlobalGameStateManager {
@@ -28,9 +37,30 @@ impl GlobalGameStateManager {
         Self {
             pool,
             game_states: Arc::new(DashMap::new()),
+            autosave_tasks: Arc::new(DashMap::new()),
+            operation_gates: Arc::new(DashMap::new()),
         }
     }
 
+    /// 开始一次需要与回退互斥的操作（行动处理、自动调度、建立连接）
+    ///
+    /// 返回的守卫须持有到操作的状态修改与记录写入全部完成；游戏正在回退时返回`None`。
+    pub fn begin_operation(&self, game_id: &str) -> Option<OwnedRwLockReadGuard<()>> {
+        self.operation_gate(game_id).try_read_owned().ok()
+    }
+
+    /// 开始回退：等待进行中的操作完成，返回的守卫释放前拒绝新的操作
+    pub async fn begin_rewind(&self, game_id: &str) -> OwnedRwLockWriteGuard<()> {
+        self.operation_gate(game_id).write_owned().await
+    }
+
+    fn operation_gate(&self, game_id: &str) -> Arc<RwLock<()>> {
+        self.operation_gates
+            .entry(game_id.to_string())
+            .or_default()
+            .clone()
+    }
+
     /// 获取游戏状态（如果不存在则返回错误）
     pub async fn get_game_state(&self, game_id: &str) -> Result<Arc<RwLock<GameState>>, String> {
         // 检查内存中是否已存在游戏状态
@@ -42,11 +72,12 @@ impl GlobalGameStateManager {
         Err("Game state not found in memory".to_string())
     }
 
-    /// 创建新的游戏状态（仅由首次开始游戏时调用）
+    /// 创建新的游戏状态（仅由首次开始游戏时调用），可指定随机数种子
     pub async fn create_game_state(
         &self,
         game_id: &str,
         rules_config: JsonValue,
+        rng_seed: Option<u64>,
     ) -> Result<Arc<RwLock<GameState>>, String> {
         // 检查内存中是否已存在游戏状态
         if self.game_states.contains_key(game_id) {
@@ -55,22 +86,23 @@ impl GlobalGameStateManager {
 
         // 创建新的游戏状态
         let mut game_state = GameState::new(game_id.to_string(), rules_config);
+        if let Some(seed) = rng_seed {
+            game_state.rng = GameRng::from_seed(seed);
+        }
 
         // 从数据库加载玩家信息
-        let players_result = sqlx::query!(
-            "SELECT id, name, password, team_id FROM actors WHERE game_id = ?",
-            game_id
-        )
-        .fetch_all(&self.pool)
-        .await;
+        let players_result: Result<Vec<(String, String, i32)>, sqlx::Error> =
+            sqlx::query_as("SELECT id, name, team_id FROM actors WHERE game_id = ?")
+                .bind(game_id)
+                .fetch_all(&self.pool)
+                .await;
 
         if let Ok(players) = players_result {
-            for player_record in players {
+            for (player_id, player_name, team_id) in players {
                 let player = Player::new(
-                    player_record.id,
-                    player_record.name,
-                    player_record.password,
-                    player_record.team_id as u32,
+                    player_id,
+                    player_name,
+                    team_id as u32,
                     &game_state.rule_engine, // 传递规则引擎引用
                 );
                 game_state.players.insert(player.id.clone(), player);
@@ -93,58 +125,176 @@ impl GlobalGameStateManager {
 
     /// 保存游戏状态到磁盘（使用时间戳生成文件名）
     pub async fn save_game_state_to_disk(&self, game_id: &str) -> Result<String, String> {
-        // 生成带时间戳的文件名（使用Windows兼容格式）
-        let timestamp = Utc::now().format("%Y-%m-%dT%H-%M-%S%.3fZ").to_string();
-        let file_name = format!("{}.json", timestamp);
+        self.save_game_state_snapshot(game_id, SaveKind::Manual)
+            .await
+    }
 
+    /// 按存档类型保存游戏状态到磁盘，返回存档文件名
+    pub async fn save_game_state_snapshot(
+        &self,
+        game_id: &str,
+        kind: SaveKind,
+    ) -> Result<String, String> {
+        let file_name = save_file_name(kind, Utc::now());
         self.save_game_state_to_disk_with_name(game_id, &file_name)
             .await?;
         Ok(file_name)
     }
 
+    /// 将给定的游戏状态保存为检查点存档（夜晚结算后使用结算完成时的状态）
+    pub fn save_checkpoint(game_id: &str, game_state: &GameState) -> Result<String, String> {
+        let file_name = save_file_name(SaveKind::Checkpoint, Utc::now());
+        Self::write_save_file(game_id, &file_name, game_state.clone())?;
+        Ok(file_name)
+    }
+
     /// 保存游戏状态到磁盘（指定文件名）
     pub async fn save_game_state_to_disk_with_name(
         &self,
         game_id: &str,
         file_name: &str,
     ) -> Result<(), String> {
-        if let Some(game_state) = self.game_states.get(game_id) {
-            // 在代码块中获取锁、克隆数据并更新save_time
-            let cloned_game_state = {
-                let game_state_guard = game_state.read().await;
-                // 克隆游戏状态
-                let mut cloned = (*game_state_guard).clone();
-                // 更新克隆的游戏状态的保存时间
-                cloned.save_time = Some(Utc::now());
-                // 代码块结束时自动释放锁
-                cloned
-            };
-
-            let serialized = serde_json::to_string(&cloned_game_state)
-                .map_err(|e| format!("Failed to serialize game state: {}", e))?;
-
-            let file_path = format!("game_states/{}/{}", game_id, file_name);
-            if let Some(parent) = Path::new(&file_path).parent() {
-                fs::create_dir_all(parent)
-                    .map_err(|e| format!("Failed to create directory: {}", e))?;
+        // 释放映射引用后再等待读锁，避免阻塞其他游戏的插入与移除
+        let game_state = self
+            .game_states
+            .get(game_id)
+            .map(|game_state| game_state.clone())
+            .ok_or_else(|| "Game state not found".to_string())?;
+
+        // 在代码块中获取锁并克隆数据，代码块结束时自动释放锁
+        let cloned_game_state = game_state.read().await.clone();
+        Self::write_save_file(game_id, file_name, cloned_game_state)
+    }
+
+    /// 更新保存时间并写入存档文件
+    fn write_save_file(
+        game_id: &str,
+        file_name: &str,
+        mut game_state: GameState,
+    ) -> Result<(), String> {
+        let started = Instant::now();
+        game_state.save_time = Some(Utc::now());
+
+        let serialized = serde_json::to_string(&game_state)
+            .map_err(|e| format!("Failed to serialize game state: {}", e))?;
+
+        let file_path = format!("game_states/{}/{}", game_id, file_name);
+        if let Some(parent) = Path::new(&file_path).parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
+        }
+
+        tracing::debug!("Saving game state to disk: {}", file_path);
+        fs::write(&file_path, serialized)
+            .map_err(|e| format!("Failed to write game state to disk: {}", e))?;
+        metrics().observe_save_duration(started.elapsed());
+
+        Ok(())
+    }
+
+    /// 为指定游戏启动定时自动存档任务（规则中存档间隔为0时不启动）
+    pub async fn start_autosave(&self, game_id: &str) {
+        self.stop_autosave(game_id);
+
+        let interval_seconds = match self.get_game_state(game_id).await {
+            Ok(game_state_ref) => {
+                game_state_ref
+                    .read()
+                    .await
+                    .rule_engine
+                    .autosave
+                    .interval_seconds
             }
+            Err(_) => return,
+        };
+        if interval_seconds == 0 {
+            return;
+        }
 
-            tracing::debug!("Saving game state to disk: {}", file_path);
-            fs::write(&file_path, serialized)
-                .map_err(|e| format!("Failed to write game state to disk: {}", e))?;
+        let manager = self.clone();
+        let task_game_id = game_id.to_string();
+        let handle = tokio::spawn(async move {
+            let period = Duration::from_secs(interval_seconds);
+            let mut interval =
+                tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+            loop {
+                interval.tick().await;
+                if let Err(e) = manager.autosave(&task_game_id).await {
+                    warn!("Autosave failed for game {}: {}", task_game_id, e);
+                }
+            }
+        });
+        self.autosave_tasks.insert(game_id.to_string(), handle);
+    }
 
-            Ok(())
-        } else {
-            Err("Game state not found".to_string())
+    /// 停止指定游戏的定时自动存档任务
+    pub fn stop_autosave(&self, game_id: &str) {
+        if let Some((_, handle)) = self.autosave_tasks.remove(game_id) {
+            handle.abort();
         }
     }
 
-    /// 从磁盘恢复游戏状态（指定文件名）
-    pub async fn load_game_state_from_disk_with_name(
-        &self,
-        game_id: &str,
-        file_name: &str,
-    ) -> Result<(), String> {
+    /// 保存自动存档，并删除超出规则保留数量的旧自动存档
+    pub async fn autosave(&self, game_id: &str) -> Result<String, String> {
+        let keep_saves = self
+            .get_game_state(game_id)
+            .await?
+            .read()
+            .await
+            .rule_engine
+            .autosave
+            .keep_saves;
+
+        let file_name = self
+            .save_game_state_snapshot(game_id, SaveKind::Autosave)
+            .await?;
+        self.prune_autosaves(game_id, keep_saves).await?;
+        Ok(file_name)
+    }
+
+    /// 删除最近`keep_saves`个之外的自动存档，返回删除的文件数量
+    ///
+    /// 手动存档与夜晚结算检查点不受影响。
+    pub async fn prune_autosaves(&self, game_id: &str, keep_saves: usize) -> Result<usize, String> {
+        let expired: Vec<SaveFileInfo> = self
+            .list_save_files(game_id)
+            .await?
+            .into_iter()
+            .filter(|save_file| save_file.kind == SaveKind::Autosave)
+            .skip(keep_saves)
+            .collect();
+
+        for save_file in &expired {
+            let file_path = format!("game_states/{}/{}", game_id, save_file.file_name);
+            fs::remove_file(&file_path)
+                .map_err(|e| format!("Failed to delete save file: {}", e))?;
+        }
+        Ok(expired.len())
+    }
+
+    /// 为内存中的所有游戏保存自动存档（服务器关闭前调用），返回成功保存的游戏数量
+    pub async fn save_all_game_states(&self) -> usize {
+        let game_ids: Vec<String> = self
+            .game_states
+            .iter()
+            .map(|entry| entry.key().clone())
+            .collect();
+
+        let mut saved = 0;
+        for game_id in game_ids {
+            self.stop_autosave(&game_id);
+            match self.autosave(&game_id).await {
+                Ok(file_name) => {
+                    info!("Saved game {} to {} before shutdown", game_id, file_name);
+                    saved += 1;
+                }
+                Err(e) => warn!("Failed to save game {} before shutdown: {}", game_id, e),
+            }
+        }
+        saved
+    }
+
+    /// 从磁盘读取存档中的游戏状态（不加载到内存）
+    pub fn read_game_state_from_disk(game_id: &str, file_name: &str) -> Result<GameState, String> {
         let file_path = format!("game_states/{}/{}", game_id, file_name);
         if !Path::new(&file_path).exists() {
             return Err("Game state file not found".to_string());
@@ -153,8 +303,47 @@ impl GlobalGameStateManager {
         let serialized = fs::read_to_string(&file_path)
             .map_err(|e| format!("Failed to read game state from disk: {}", e))?;
 
-        let game_state: GameState = serde_json::from_str(&serialized)
-            .map_err(|e| format!("Failed to deserialize game state: {}", e))?;
+        serde_json::from_str(&serialized)
+            .map_err(|e| format!("Failed to deserialize game state: {}", e))
+    }
+
+    /// 读取存档文件的原始内容（用于归档导出）
+    pub fn read_save_file_json(
+        game_id: &str,
+        file_name: &str,
+    ) -> Result<serde_json::Value, String> {
+        let file_path = format!("game_states/{}/{}", game_id, file_name);
+        let serialized = fs::read_to_string(&file_path)
+            .map_err(|e| format!("Failed to read save file {}: {}", file_name, e))?;
+
+        serde_json::from_str(&serialized)
+            .map_err(|e| format!("Failed to parse save file {}: {}", file_name, e))
+    }
+
+    /// 原样写入存档文件（用于归档导入）
+    pub fn write_save_file_json(
+        game_id: &str,
+        file_name: &str,
+        content: &serde_json::Value,
+    ) -> Result<(), String> {
+        let serialized = serde_json::to_string(content)
+            .map_err(|e| format!("Failed to serialize save file {}: {}", file_name, e))?;
+
+        let file_path = format!("game_states/{}/{}", game_id, file_name);
+        if let Some(parent) = Path::new(&file_path).parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
+        }
+        fs::write(&file_path, serialized)
+            .map_err(|e| format!("Failed to write save file {}: {}", file_name, e))
+    }
+
+    /// 从磁盘恢复游戏状态（指定文件名）
+    pub async fn load_game_state_from_disk_with_name(
+        &self,
+        game_id: &str,
+        file_name: &str,
+    ) -> Result<(), String> {
+        let game_state = Self::read_game_state_from_disk(game_id, file_name)?;
 
         let game_state_arc = Arc::new(RwLock::new(game_state));
         self.game_states.insert(game_id.to_string(), game_state_arc);
@@ -182,9 +371,13 @@ impl GlobalGameStateManager {
             // 只处理.json文件
             if path.is_file() && path.extension().map_or(false, |ext| ext == "json") {
                 if let Some(file_name) = path.file_name().and_then(|name| name.to_str()) {
+                    let kind = SaveKind::from_file_name(file_name);
                     // 尝试从文件名解析时间戳
-                    // 文件名格式: 2023-01-01T10-00-00.000Z.json (Windows兼容格式)
-                    let created_at = if let Some(timestamp_str) = file_name.strip_suffix(".json") {
+                    // 文件名格式: [类型前缀]2023-01-01T10-00-00.000Z.json (Windows兼容格式)
+                    let created_at = if let Some(timestamp_str) = file_name
+                        .strip_prefix(kind.prefix())
+                        .and_then(|name| name.strip_suffix(".json"))
+                    {
                         // 将Windows兼容格式转换回ISO 8601格式进行解析
                         // 将 "T" 后面的 "-" 替换为 ":" 来构造有效的 RFC3339 时间字符串
                         if let Some(t_pos) = timestamp_str.find('T') {
@@ -204,6 +397,7 @@ impl GlobalGameStateManager {
                     save_files.push(SaveFileInfo {
                         file_name: file_name.to_string(),
                         created_at,
+                        kind,
                     });
                 }
             }
@@ -223,8 +417,15 @@ impl GlobalGameStateManager {
         Ok(save_files)
     }
 
-    /// 移除内存中的游戏状态
+    /// 内存中的游戏数量
+    pub fn active_game_count(&self) -> usize {
+        self.game_states.len()
+    }
+
+    /// 移除内存中的游戏状态，同时停止其自动存档任务
     pub fn remove_game_state(&self, game_id: &str) -> bool {
+        self.stop_autosave(game_id);
+        self.operation_gates.remove(game_id);
         self.game_states.remove(game_id).is_some()
     }
 
@@ -233,3 +434,12 @@ impl GlobalGameStateManager {
         matches!(status, GameStatus::Running)
     }
 }
+
+/// 生成带类型前缀与时间戳的存档文件名（使用Windows兼容格式）
+fn save_file_name(kind: SaveKind, time: DateTime<Utc>) -> String {
+    format!(
+        "{}{}.json",
+        kind.prefix(),
+        time.format("%Y-%m-%dT%H-%M-%S%.3fZ")
+    )
+}
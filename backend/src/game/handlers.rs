@@ -1,24 +1,17 @@
 use axum::{
-    extract::{Path, Query, Request, State},
-    response::Json,
+    extract::{Path, Query, State},
+    http::header,
+    response::{IntoResponse, Json},
 };
 use serde::Deserialize;
 use serde_json::json;
-use std::collections::HashMap;
 
+use super::archive::GameArchive;
 use super::errors::GameError;
+use super::log_query::{KillRecordQuery, MessageQuery};
 use super::models::*;
-use crate::admin::models::JwtClaims;
 use crate::routes::AppState;
 
-/// 导演密码查询参数
-#[derive(Debug, Deserialize)]
-pub struct DirectorPasswordQuery {
-    pub password: String,
-    #[serde(default)]
-    pub limit: Option<usize>,
-}
-
 /// 删除日志的时间戳参数
 #[derive(Debug, Deserialize)]
 pub struct DeleteLogsQuery {
@@ -31,6 +24,14 @@ pub struct DeleteKillRecordsQuery {
     pub after_timestamp: Option<String>,
 }
 
+/// 排行榜查询参数
+#[derive(Debug, Deserialize)]
+pub struct LeaderboardQuery {
+    /// 限制返回的玩家数量
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
 /// 创建游戏 (管理员接口)
 pub async fn create_game(
     State(state): State<AppState>,
@@ -65,7 +66,8 @@ pub async fn delete_game(
 ) -> Result<Json<serde_json::Value>, GameError> {
     state.game_service.delete_game(&game_id).await?;
 
-    // 删除数据库记录后，同步清理内存状态与WebSocket连接
+    // 删除数据库记录后，同步清理调度任务、内存状态与WebSocket连接
+    state.game_phase_scheduler.stop(&game_id);
     state.game_state_manager.remove_game_state(&game_id);
     state
         .global_connection_manager
@@ -82,15 +84,8 @@ pub async fn delete_game(
 pub async fn get_games(
     State(state): State<AppState>,
     Query(query): Query<GameListQuery>,
-    req: Request,
 ) -> Result<Json<serde_json::Value>, GameError> {
-    // 检查是否有管理员权限
-    let has_admin_privileges = req.extensions().get::<JwtClaims>().is_some();
-
-    let games = state
-        .game_service
-        .get_games(&query, has_admin_privileges)
-        .await?;
+    let games = state.game_service.get_games(&query).await?;
 
     Ok(Json(json!({
         "success": true,
@@ -114,14 +109,10 @@ pub async fn get_games_rules_config_view(
 pub async fn get_game_with_rules(
     State(state): State<AppState>,
     Path(game_id): Path<String>,
-    req: Request,
 ) -> Result<Json<serde_json::Value>, GameError> {
-    // 检查是否有管理员权限
-    let has_admin_privileges = req.extensions().get::<JwtClaims>().is_some();
-
     let game = state
         .game_service
-        .get_game_by_id_with_player_counts(&game_id, has_admin_privileges)
+        .get_game_by_id_with_player_counts(&game_id)
         .await?;
 
     Ok(Json(json!({
@@ -134,21 +125,18 @@ pub async fn get_game_with_rules(
 pub async fn get_player_messages(
     State(state): State<AppState>,
     Path((game_id, player_id)): Path<(String, String)>,
-    Json(request): Json<GetPlayerMessagesRequest>,
+    Query(query): Query<MessageQuery>,
 ) -> Result<Json<serde_json::Value>, GameError> {
-    // 验证请求参数
-    request.validate().map_err(GameError::ValidationError)?;
-
-    // 获取玩家消息记录
-    let limit = request.limit;
+    // 获取玩家消息记录（身份由游戏会话令牌验证）
     let messages = state
         .game_log_service
-        .get_player_messages(&game_id, &player_id, &request.password, limit)
+        .get_player_messages(&game_id, &player_id, &query)
         .await?;
 
     Ok(Json(json!({
         "success": true,
-        "data": messages
+        "data": messages.items,
+        "page": messages.page
     })))
 }
 
@@ -156,17 +144,18 @@ pub async fn get_player_messages(
 pub async fn get_director_messages(
     State(state): State<AppState>,
     Path(game_id): Path<String>,
-    Query(query): Query<DirectorPasswordQuery>,
+    Query(query): Query<MessageQuery>,
 ) -> Result<Json<serde_json::Value>, GameError> {
     // 获取导演消息记录
     let messages = state
         .game_log_service
-        .get_director_messages(&game_id, &query.password, query.limit)
+        .get_director_messages(&game_id, &query)
         .await?;
 
     Ok(Json(json!({
         "success": true,
-        "data": messages
+        "data": messages.items,
+        "page": messages.page
     })))
 }
 
@@ -200,20 +189,72 @@ pub async fn delete_game_logs(
 }
 
 /// 游戏身份验证处理函数
+/// 验证成功时签发游戏会话令牌，后续导演与玩家接口及WebSocket连接均使用该令牌
 pub async fn authenticate_game(
     State(state): State<AppState>,
     Path(game_id): Path<String>,
-    Query(params): Query<HashMap<String, String>>,
+    Json(request): Json<GameAuthenticationRequest>,
 ) -> Result<Json<GameAuthenticationResponse>, GameError> {
-    let password = params
-        .get("password")
-        .ok_or_else(|| GameError::ValidationError("Password is required".to_string()))?;
+    if request.password.is_empty() {
+        return Err(GameError::ValidationError(
+            "Password is required".to_string(),
+        ));
+    }
+
+    // 空白的演员名称视为未提供，按导演身份验证
+    let actor_name = request
+        .actor_name
+        .as_deref()
+        .map(str::trim)
+        .filter(|name| !name.is_empty());
+
+    let mut result = state
+        .game_service
+        .authenticate_game(&game_id, actor_name, &request.password)
+        .await?;
+
+    let session = match (&result.role, &result.actor_id) {
+        (GameAuthenticationRole::Actor, Some(actor_id)) => {
+            Some((GameSessionRole::Actor, actor_id.clone()))
+        }
+        (GameAuthenticationRole::Director, _) => Some((GameSessionRole::Director, game_id.clone())),
+        _ => None,
+    };
+    if let Some((role, subject)) = session {
+        let (token, expires_in) = state
+            .auth_service
+            .issue_game_token(&game_id, role, &subject)
+            .await
+            .map_err(|e| GameError::OtherError(e.to_string()))?;
+        result.token = Some(token);
+        result.expires_in = Some(expires_in);
+    }
 
-    let result = state
+    Ok(Json(result))
+}
+
+/// 观战身份验证处理函数
+/// 验证成功时签发观战令牌，用于以观众身份连接WebSocket
+pub async fn authenticate_spectator(
+    State(state): State<AppState>,
+    Path(game_id): Path<String>,
+    Json(request): Json<SpectatorAuthenticationRequest>,
+) -> Result<Json<GameAuthenticationResponse>, GameError> {
+    let mut result = state
         .game_service
-        .authenticate_game(&game_id, password)
+        .authenticate_spectator(&game_id, request.code.as_deref())
         .await?;
 
+    if result.role == GameAuthenticationRole::Spectator {
+        let (token, expires_in) = state
+            .auth_service
+            .issue_game_token(&game_id, GameSessionRole::Spectator, &game_id)
+            .await
+            .map_err(|e| GameError::OtherError(e.to_string()))?;
+        result.token = Some(token);
+        result.expires_in = Some(expires_in);
+    }
+
     Ok(Json(result))
 }
 
@@ -221,17 +262,18 @@ pub async fn authenticate_game(
 pub async fn get_player_kill_records(
     State(state): State<AppState>,
     Path((game_id, player_id)): Path<(String, String)>,
-    Json(request): Json<GetPlayerKillRecordsRequest>,
+    Query(query): Query<KillRecordQuery>,
 ) -> Result<Json<serde_json::Value>, GameError> {
     // 获取玩家击杀记录
     let kill_records = state
         .game_log_service
-        .get_player_kill_records(&game_id, &player_id, &request.password)
+        .get_player_kill_records(&game_id, &player_id, &query)
         .await?;
 
     Ok(Json(json!({
         "success": true,
-        "data": kill_records
+        "data": kill_records.items,
+        "page": kill_records.page
     })))
 }
 
@@ -239,17 +281,18 @@ pub async fn get_player_kill_records(
 pub async fn get_director_kill_records(
     State(state): State<AppState>,
     Path(game_id): Path<String>,
-    Query(query): Query<DirectorPasswordQuery>,
+    Query(query): Query<KillRecordQuery>,
 ) -> Result<Json<serde_json::Value>, GameError> {
     // 获取导演击杀记录
     let kill_records = state
         .game_log_service
-        .get_director_kill_records(&game_id, &query.password)
+        .get_director_kill_records(&game_id, &query)
         .await?;
 
     Ok(Json(json!({
         "success": true,
-        "data": kill_records
+        "data": kill_records.items,
+        "page": kill_records.page
     })))
 }
 
@@ -281,3 +324,59 @@ pub async fn delete_game_kill_records(
         "message": format!("Deleted {} kill records", deleted_count)
     })))
 }
+
+/// 导出游戏归档文件 (管理员接口，仅限已结束的游戏)
+pub async fn export_game_archive(
+    State(state): State<AppState>,
+    Path(game_id): Path<String>,
+) -> Result<impl IntoResponse, GameError> {
+    let archive = state
+        .game_service
+        .export_game_archive(&game_id, &state.game_state_manager)
+        .await?;
+
+    let disposition = format!("attachment; filename=\"game-archive-{}.json\"", game_id);
+    Ok(([(header::CONTENT_DISPOSITION, disposition)], Json(archive)))
+}
+
+/// 导入游戏归档 (管理员接口，导入后游戏为已结束状态)
+pub async fn import_game_archive(
+    State(state): State<AppState>,
+    Json(archive): Json<GameArchive>,
+) -> Result<Json<serde_json::Value>, GameError> {
+    let game = state.game_service.import_game_archive(archive).await?;
+
+    Ok(Json(json!({
+        "success": true,
+        "data": game
+    })))
+}
+
+/// 获取赛后统计 (公开接口，仅限已结束的游戏)
+pub async fn get_game_stats(
+    State(state): State<AppState>,
+    Path(game_id): Path<String>,
+) -> Result<Json<serde_json::Value>, GameError> {
+    let stats = state.game_service.get_game_stats(&game_id).await?;
+
+    Ok(Json(json!({
+        "success": true,
+        "data": stats
+    })))
+}
+
+/// 获取跨游戏排行榜 (公开接口)
+pub async fn get_leaderboard(
+    State(state): State<AppState>,
+    Query(query): Query<LeaderboardQuery>,
+) -> Result<Json<serde_json::Value>, GameError> {
+    let mut leaderboard = state.game_service.get_leaderboard().await?;
+    if let Some(limit) = query.limit {
+        leaderboard.truncate(limit);
+    }
+
+    Ok(Json(json!({
+        "success": true,
+        "data": leaderboard
+    })))
+}
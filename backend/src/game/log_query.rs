@@ -0,0 +1,256 @@
+//! 日志查询
+//! 游戏日志与击杀记录查询的筛选条件与游标分页，游标由记录时间戳与记录ID组成
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{MySql, QueryBuilder};
+
+use super::errors::GameError;
+use super::models::MessageType;
+
+/// 分页游标，指向一条记录在（时间戳，ID）排序中的位置
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogCursor {
+    pub timestamp: DateTime<Utc>,
+    pub id: String,
+}
+
+impl LogCursor {
+    /// 创建指向指定记录的游标
+    pub fn new(timestamp: DateTime<Utc>, id: &str) -> Self {
+        Self {
+            timestamp,
+            id: id.to_string(),
+        }
+    }
+
+    /// 编码为查询参数中使用的字符串（微秒时间戳_记录ID）
+    pub fn encode(&self) -> String {
+        format!("{}_{}", self.timestamp.timestamp_micros(), self.id)
+    }
+
+    /// 解析查询参数中的游标
+    pub fn decode(value: &str) -> Result<Self, GameError> {
+        let invalid = || GameError::ValidationError("无效的分页游标".to_string());
+        let (micros, id) = value.split_once('_').ok_or_else(invalid)?;
+        let timestamp = micros
+            .parse()
+            .ok()
+            .and_then(DateTime::from_timestamp_micros)
+            .ok_or_else(invalid)?;
+        if id.is_empty() {
+            return Err(invalid());
+        }
+        Ok(Self::new(timestamp, id))
+    }
+}
+
+/// 消息记录查询参数
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct MessageQuery {
+    /// 每页条数，未指定游标时返回最近的若干条
+    #[serde(default)]
+    pub limit: Option<usize>,
+    /// 返回早于该游标的记录
+    #[serde(default)]
+    pub before: Option<String>,
+    /// 返回晚于该游标的记录
+    #[serde(default)]
+    pub after: Option<String>,
+    /// 消息类型
+    #[serde(default, rename = "type")]
+    pub message_type: Option<MessageType>,
+    /// 相关玩家ID
+    #[serde(default)]
+    pub player_id: Option<String>,
+    /// 起始时间（含）
+    #[serde(default)]
+    pub from: Option<DateTime<Utc>>,
+    /// 结束时间（含）
+    #[serde(default)]
+    pub to: Option<DateTime<Utc>>,
+    /// 消息内容包含的文本
+    #[serde(default)]
+    pub q: Option<String>,
+}
+
+/// 击杀记录查询参数
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct KillRecordQuery {
+    /// 每页条数，未指定游标时返回最近的若干条
+    #[serde(default)]
+    pub limit: Option<usize>,
+    /// 返回早于该游标的记录
+    #[serde(default)]
+    pub before: Option<String>,
+    /// 返回晚于该游标的记录
+    #[serde(default)]
+    pub after: Option<String>,
+    /// 击杀者ID
+    #[serde(default)]
+    pub killer_id: Option<String>,
+    /// 被击杀者ID
+    #[serde(default)]
+    pub victim_id: Option<String>,
+    /// 击杀原因
+    #[serde(default)]
+    pub cause: Option<String>,
+    /// 击杀地点
+    #[serde(default)]
+    pub location: Option<String>,
+    /// 起始时间（含）
+    #[serde(default)]
+    pub from: Option<DateTime<Utc>>,
+    /// 结束时间（含）
+    #[serde(default)]
+    pub to: Option<DateTime<Utc>>,
+}
+
+/// 分页信息
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct PageInfo {
+    /// 本页第一条记录的游标，作为`before`参数获取更早的记录
+    pub prev_cursor: Option<String>,
+    /// 本页最后一条记录的游标，作为`after`参数获取更晚的记录
+    pub next_cursor: Option<String>,
+    /// 翻页方向上是否还有更多记录（未指定`after`时为更早的记录，否则为更晚的记录）
+    pub has_more: bool,
+}
+
+/// 一页查询结果，记录按时间从早到晚排列
+#[derive(Debug, Clone)]
+pub struct LogPage<T> {
+    pub items: Vec<T>,
+    pub page: PageInfo,
+}
+
+/// 未指定`limit`时每页返回的条数
+pub const DEFAULT_PAGE_SIZE: usize = 100;
+
+/// 每页最多返回的条数
+pub const MAX_PAGE_SIZE: usize = 500;
+
+/// 游标分页参数
+#[derive(Debug, Clone)]
+pub struct Pagination {
+    limit: usize,
+    before: Option<LogCursor>,
+    after: Option<LogCursor>,
+}
+
+impl Pagination {
+    /// 校验并解析分页参数，未指定`limit`时使用[`DEFAULT_PAGE_SIZE`]
+    pub fn new(
+        limit: Option<usize>,
+        before: Option<&str>,
+        after: Option<&str>,
+    ) -> Result<Self, GameError> {
+        let limit = limit.unwrap_or(DEFAULT_PAGE_SIZE);
+        if limit == 0 {
+            return Err(GameError::ValidationError("请求条数必须大于0".to_string()));
+        }
+        if limit > MAX_PAGE_SIZE {
+            return Err(GameError::ValidationError(format!(
+                "请求条数不能超过{}",
+                MAX_PAGE_SIZE
+            )));
+        }
+
+        Ok(Self {
+            limit,
+            before: before.map(LogCursor::decode).transpose()?,
+            after: after.map(LogCursor::decode).transpose()?,
+        })
+    }
+
+    /// 是否从较晚的一端向前取记录（未指定`after`时取最近的记录）
+    fn is_backward(&self) -> bool {
+        self.after.is_none()
+    }
+
+    /// 追加游标条件、排序与条数限制（多取一条用于判断是否还有更多记录）
+    pub fn push_order(&self, builder: &mut QueryBuilder<'_, MySql>, time_column: &str) {
+        if let Some(cursor) = &self.before {
+            push_cursor_condition(builder, time_column, "<", cursor);
+        }
+        if let Some(cursor) = &self.after {
+            push_cursor_condition(builder, time_column, ">", cursor);
+        }
+
+        let direction = if self.is_backward() { "DESC" } else { "ASC" };
+        builder.push(format!(
+            " ORDER BY {time_column} {direction}, id {direction}"
+        ));
+        builder.push(" LIMIT ").push_bind((self.limit + 1) as i64);
+    }
+
+    /// 将查询结果整理为按时间从早到晚排列的一页
+    pub fn into_page<T>(self, mut items: Vec<T>, cursor: impl Fn(&T) -> LogCursor) -> LogPage<T> {
+        let has_more = items.len() > self.limit;
+        items.truncate(self.limit);
+        if self.is_backward() {
+            items.reverse();
+        }
+
+        let page = PageInfo {
+            prev_cursor: items.first().map(|item| cursor(item).encode()),
+            next_cursor: items.last().map(|item| cursor(item).encode()),
+            has_more,
+        };
+        LogPage { items, page }
+    }
+}
+
+/// 追加（时间戳，ID）与游标比较的条件
+fn push_cursor_condition(
+    builder: &mut QueryBuilder<'_, MySql>,
+    time_column: &str,
+    operator: &str,
+    cursor: &LogCursor,
+) {
+    builder
+        .push(format!(" AND ({time_column} {operator} "))
+        .push_bind(cursor.timestamp)
+        .push(format!(" OR ({time_column} = "))
+        .push_bind(cursor.timestamp)
+        .push(format!(" AND id {operator} "))
+        .push_bind(cursor.id.clone())
+        .push("))");
+}
+
+/// 追加时间范围条件
+pub fn push_time_range(
+    builder: &mut QueryBuilder<'_, MySql>,
+    time_column: &str,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+) {
+    if let Some(from) = from {
+        builder
+            .push(format!(" AND {time_column} >= "))
+            .push_bind(from);
+    }
+    if let Some(to) = to {
+        builder
+            .push(format!(" AND {time_column} <= "))
+            .push_bind(to);
+    }
+}
+
+/// 追加字段等于指定值的条件
+pub fn push_equals(builder: &mut QueryBuilder<'_, MySql>, column: &str, value: Option<&str>) {
+    if let Some(value) = value {
+        builder
+            .push(format!(" AND {column} = "))
+            .push_bind(value.to_string());
+    }
+}
+
+/// 转义LIKE模式中的通配符，返回匹配包含该文本的模式
+pub fn like_pattern(text: &str) -> String {
+    let escaped = text
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    format!("%{}%", escaped)
+}
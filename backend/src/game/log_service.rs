@@ -2,12 +2,10 @@
 //! 负责处理游戏日志的数据库操作
 
 use crate::game::errors::GameError;
-use crate::game::models::{
-    GetPlayerMessagesRequest, KillRecord, MessageRecord, MessageType, NewKillRecord,
-};
+use crate::game::log_query::{self, KillRecordQuery, LogCursor, LogPage, MessageQuery, Pagination};
+use crate::game::models::{KillRecord, MessageRecord, MessageType, NewDamageRecord, NewKillRecord};
 use chrono::{DateTime, Utc};
-use sqlx::MySqlPool;
-use std::convert::TryFrom;
+use sqlx::{MySql, MySqlPool, QueryBuilder, Transaction};
 use uuid::Uuid;
 
 /// 游戏日志服务
@@ -60,6 +58,29 @@ impl GameLogService {
         })
     }
 
+    /// 新增伤害记录
+    pub async fn add_damage_record(&self, params: &NewDamageRecord) -> Result<(), String> {
+        sqlx::query(
+            r#"
+            INSERT INTO damage_records (id, game_id, attacker_id, victim_id, damage, cause, weapon, damage_time)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(&params.game_id)
+        .bind(params.attacker_id.as_deref())
+        .bind(&params.victim_id)
+        .bind(params.damage)
+        .bind(&params.cause)
+        .bind(params.weapon.as_deref())
+        .bind(params.damage_time)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| format!("Failed to create damage record: {}", e))?;
+
+        Ok(())
+    }
+
     /// 创建游戏日志
     pub async fn create_log(
         &self,
@@ -113,155 +134,78 @@ impl GameLogService {
     }
 
     /// 获取玩家消息记录
+    ///
+    /// 仅返回与该玩家相关或对所有玩家可见的消息，筛选条件在此基础上叠加。
     pub async fn get_player_messages(
         &self,
         game_id: &str,
         player_id: &str,
-        password: &str,
-        limit: Option<usize>,
-    ) -> Result<Vec<MessageRecord>, GameError> {
-        // 验证请求参数
-        let request = GetPlayerMessagesRequest {
-            password: password.to_string(),
-            limit,
-        };
-        request.validate().map_err(GameError::ValidationError)?;
-        let limit = request.limit;
-
-        // 验证玩家是否存在且密码正确
-        let actor = sqlx::query!(
-            "SELECT id FROM actors WHERE id = ? AND game_id = ? AND password = ?",
-            player_id,
-            game_id,
-            password
-        )
-        .fetch_optional(&self.pool)
-        .await
-        .map_err(GameError::DatabaseError)?;
-
-        if actor.is_none() {
-            return Err(GameError::ValidationError(
-                "Invalid player credentials".to_string(),
-            ));
-        }
-
-        // 查询玩家相关的消息记录，包括所有标记为visible_to_all_players为true的记录
-        let messages = if let Some(limit) = limit {
-            let limit = i64::try_from(limit)
-                .map_err(|_| GameError::ValidationError("请求条数过大".to_string()))?;
-            let mut result = sqlx::query_as!(
-                MessageRecord,
-                r#"
-                SELECT id, game_id, type as "message_type: MessageType", message, player_id, timestamp as "timestamp!: DateTime<Utc>",
-                    visible_to_all_players as "visible_to_all_players: bool",
-                    visible_to_director as "visible_to_director: bool"
-                FROM game_logs
-                WHERE game_id = ? AND (player_id = ? OR visible_to_all_players = TRUE)
-                ORDER BY timestamp DESC
-                LIMIT ?
-                "#,
-                game_id,
-                player_id,
-                limit
-            )
-            .fetch_all(&self.pool)
-            .await
-            .map_err(GameError::DatabaseError)?;
-            result.reverse();
-            result
-        } else {
-            sqlx::query_as!(
-                MessageRecord,
-                r#"
-                SELECT id, game_id, type as "message_type: MessageType", message, player_id, timestamp as "timestamp!: DateTime<Utc>",
-                    visible_to_all_players as "visible_to_all_players: bool",
-                    visible_to_director as "visible_to_director: bool"
-                FROM game_logs
-                WHERE game_id = ? AND (player_id = ? OR visible_to_all_players = TRUE)
-                ORDER BY timestamp ASC
-                "#,
-                game_id,
-                player_id
-            )
-            .fetch_all(&self.pool)
-            .await
-            .map_err(GameError::DatabaseError)?
-        };
-
-        Ok(messages)
+        query: &MessageQuery,
+    ) -> Result<LogPage<MessageRecord>, GameError> {
+        let mut builder = Self::message_query_builder(game_id);
+        builder
+            .push(" AND (player_id = ")
+            .push_bind(player_id.to_string())
+            .push(" OR visible_to_all_players = TRUE)");
+        self.fetch_messages(builder, query).await
     }
 
     /// 获取导演消息记录
     pub async fn get_director_messages(
         &self,
         game_id: &str,
-        password: &str,
-        limit: Option<usize>,
-    ) -> Result<Vec<MessageRecord>, GameError> {
-        if let Some(limit) = limit {
-            if limit == 0 {
-                return Err(GameError::ValidationError("请求条数必须大于0".to_string()));
-            }
-        }
+        query: &MessageQuery,
+    ) -> Result<LogPage<MessageRecord>, GameError> {
+        let mut builder = Self::message_query_builder(game_id);
+        builder.push(" AND visible_to_director = TRUE");
+        self.fetch_messages(builder, query).await
+    }
 
-        // 验证导演密码
-        let game = sqlx::query!(
-            "SELECT id FROM games WHERE id = ? AND director_password = ?",
-            game_id,
-            password
-        )
-        .fetch_optional(&self.pool)
-        .await
-        .map_err(GameError::DatabaseError)?;
+    /// 构造消息记录查询的基础语句
+    fn message_query_builder(game_id: &str) -> QueryBuilder<'static, MySql> {
+        let mut builder = QueryBuilder::new(
+            r#"
+            SELECT id, game_id, type AS message_type, message, player_id, timestamp,
+                visible_to_all_players, visible_to_director
+            FROM game_logs
+            WHERE game_id = "#,
+        );
+        builder.push_bind(game_id.to_string());
+        builder
+    }
 
-        if game.is_none() {
-            return Err(GameError::ValidationError(
-                "Invalid director credentials".to_string(),
-            ));
+    /// 追加消息筛选条件与分页后执行查询
+    async fn fetch_messages(
+        &self,
+        mut builder: QueryBuilder<'_, MySql>,
+        query: &MessageQuery,
+    ) -> Result<LogPage<MessageRecord>, GameError> {
+        let pagination =
+            Pagination::new(query.limit, query.before.as_deref(), query.after.as_deref())?;
+
+        if let Some(message_type) = &query.message_type {
+            builder
+                .push(" AND type = ")
+                .push_bind(message_type.as_str());
+        }
+        log_query::push_equals(&mut builder, "player_id", query.player_id.as_deref());
+        log_query::push_time_range(&mut builder, "timestamp", query.from, query.to);
+        if let Some(text) = query.q.as_deref().filter(|text| !text.is_empty()) {
+            builder
+                .push(" AND message LIKE ")
+                .push_bind(log_query::like_pattern(text));
         }
+        pagination.push_order(&mut builder, "timestamp");
 
-        // 查询所有标记为visible_to_director为true的记录
-        let messages = if let Some(limit) = limit {
-            let limit = i64::try_from(limit)
-                .map_err(|_| GameError::ValidationError("请求条数过大".to_string()))?;
-            let mut result = sqlx::query_as!(
-                MessageRecord,
-                r#"
-                SELECT id, game_id, type as "message_type: MessageType", message, player_id, timestamp as "timestamp!: DateTime<Utc>",
-                    visible_to_all_players as "visible_to_all_players: bool",
-                    visible_to_director as "visible_to_director: bool"
-                FROM game_logs
-                WHERE game_id = ? AND visible_to_director = TRUE
-                ORDER BY timestamp DESC
-                LIMIT ?
-                "#,
-                game_id,
-                limit
-            )
+        let messages = builder
+            .build_query_as::<MessageRecord>()
             .fetch_all(&self.pool)
             .await
             .map_err(GameError::DatabaseError)?;
-            result.reverse();
-            result
-        } else {
-            sqlx::query_as!(
-                MessageRecord,
-                r#"
-                SELECT id, game_id, type as "message_type: MessageType", message, player_id, timestamp as "timestamp!: DateTime<Utc>",
-                    visible_to_all_players as "visible_to_all_players: bool",
-                    visible_to_director as "visible_to_director: bool"
-                FROM game_logs
-                WHERE game_id = ? AND visible_to_director = TRUE
-                ORDER BY timestamp ASC
-                "#,
-                game_id
-            )
-            .fetch_all(&self.pool)
-            .await
-            .map_err(GameError::DatabaseError)?
-        };
 
-        Ok(messages)
+        Ok(pagination.into_page(messages, |message| {
+            LogCursor::new(message.timestamp, &message.id)
+        }))
     }
 
     /// 删除指定时间戳之后的日志记录
@@ -290,86 +234,62 @@ impl GameLogService {
     }
 
     /// 获取玩家视角击杀记录
+    ///
+    /// 仅返回该玩家作为击杀者的记录，查询参数中的击杀者条件会被忽略。
     pub async fn get_player_kill_records(
         &self,
         game_id: &str,
         player_id: &str,
-        password: &str,
-    ) -> Result<Vec<KillRecord>, GameError> {
-        // 验证玩家是否存在且密码正确
-        let actor = sqlx::query!(
-            "SELECT id FROM actors WHERE id = ? AND game_id = ? AND password = ?",
-            player_id,
-            game_id,
-            password
-        )
-        .fetch_optional(&self.pool)
-        .await
-        .map_err(GameError::DatabaseError)?;
-
-        if actor.is_none() {
-            return Err(GameError::ValidationError(
-                "Invalid player credentials".to_string(),
-            ));
-        }
-
-        // 查询玩家相关的击杀记录（作为击杀者）
-        let kill_records = sqlx::query_as!(
-            KillRecord,
-            r#"
-            SELECT id, game_id, killer_id, victim_id, kill_time as "kill_time!: DateTime<Utc>", cause, weapon, location
-            FROM kill_records
-            WHERE game_id = ? AND killer_id = ?
-            ORDER BY kill_time ASC
-            "#,
-            game_id,
-            player_id
-        )
-        .fetch_all(&self.pool)
-        .await
-        .map_err(GameError::DatabaseError)?;
-
-        Ok(kill_records)
+        query: &KillRecordQuery,
+    ) -> Result<LogPage<KillRecord>, GameError> {
+        let query = KillRecordQuery {
+            killer_id: Some(player_id.to_string()),
+            ..query.clone()
+        };
+        self.fetch_kill_records(game_id, &query).await
     }
 
     /// 获取导演视角击杀记录
     pub async fn get_director_kill_records(
         &self,
         game_id: &str,
-        password: &str,
-    ) -> Result<Vec<KillRecord>, GameError> {
-        // 验证导演密码
-        let game = sqlx::query!(
-            "SELECT id FROM games WHERE id = ? AND director_password = ?",
-            game_id,
-            password
-        )
-        .fetch_optional(&self.pool)
-        .await
-        .map_err(GameError::DatabaseError)?;
+        query: &KillRecordQuery,
+    ) -> Result<LogPage<KillRecord>, GameError> {
+        self.fetch_kill_records(game_id, query).await
+    }
 
-        if game.is_none() {
-            return Err(GameError::ValidationError(
-                "Invalid director credentials".to_string(),
-            ));
-        }
+    /// 按筛选条件与分页查询击杀记录
+    async fn fetch_kill_records(
+        &self,
+        game_id: &str,
+        query: &KillRecordQuery,
+    ) -> Result<LogPage<KillRecord>, GameError> {
+        let pagination =
+            Pagination::new(query.limit, query.before.as_deref(), query.after.as_deref())?;
 
-        // 查询所有击杀记录
-        let kill_records = sqlx::query_as!(
-            KillRecord,
+        let mut builder = QueryBuilder::<MySql>::new(
             r#"
-            SELECT id, game_id, killer_id, victim_id, kill_time as "kill_time!: DateTime<Utc>", cause, weapon, location
+            SELECT id, game_id, killer_id, victim_id, kill_time, cause, weapon, location
             FROM kill_records
-            WHERE game_id = ?
-            ORDER BY kill_time ASC
-            "#,
-            game_id
-        )
-        .fetch_all(&self.pool)
-        .await
-        .map_err(GameError::DatabaseError)?;
+            WHERE game_id = "#,
+        );
+        builder.push_bind(game_id.to_string());
+        log_query::push_equals(&mut builder, "killer_id", query.killer_id.as_deref());
+        log_query::push_equals(&mut builder, "victim_id", query.victim_id.as_deref());
+        log_query::push_equals(&mut builder, "cause", query.cause.as_deref());
+        log_query::push_equals(&mut builder, "location", query.location.as_deref());
+        log_query::push_time_range(&mut builder, "kill_time", query.from, query.to);
+        pagination.push_order(&mut builder, "kill_time");
+
+        let kill_records = builder
+            .build_query_as::<KillRecord>()
+            .fetch_all(&self.pool)
+            .await
+            .map_err(GameError::DatabaseError)?;
 
-        Ok(kill_records)
+        Ok(pagination.into_page(kill_records, |record| {
+            LogCursor::new(record.kill_time, &record.id)
+        }))
     }
 
     /// 删除指定时间戳之后的击杀记录
@@ -396,4 +316,78 @@ impl GameLogService {
 
         Ok(rows_affected)
     }
+
+    /// 删除指定时间戳之后的伤害记录
+    pub async fn delete_damage_records_after_timestamp(
+        &self,
+        game_id: &str,
+        timestamp: Option<DateTime<Utc>>,
+    ) -> Result<u64, GameError> {
+        let rows_affected = if let Some(ts) = timestamp {
+            sqlx::query("DELETE FROM damage_records WHERE game_id = ? AND damage_time > ?")
+                .bind(game_id)
+                .bind(ts)
+                .execute(&self.pool)
+                .await
+        } else {
+            sqlx::query("DELETE FROM damage_records WHERE game_id = ?")
+                .bind(game_id)
+                .execute(&self.pool)
+                .await
+        }
+        .map_err(GameError::DatabaseError)?
+        .rows_affected();
+
+        Ok(rows_affected)
+    }
+
+    /// 在事务中删除指定时间戳之后的日志、击杀与伤害记录（用于回退），返回各自删除的条数
+    pub async fn delete_records_after_timestamp_in(
+        tx: &mut Transaction<'_, MySql>,
+        game_id: &str,
+        timestamp: DateTime<Utc>,
+    ) -> Result<(u64, u64, u64), GameError> {
+        let mut deleted = Vec::new();
+        for sql in [
+            "DELETE FROM game_logs WHERE game_id = ? AND timestamp > ?",
+            "DELETE FROM kill_records WHERE game_id = ? AND kill_time > ?",
+            "DELETE FROM damage_records WHERE game_id = ? AND damage_time > ?",
+        ] {
+            let rows_affected = sqlx::query(sql)
+                .bind(game_id)
+                .bind(timestamp)
+                .execute(&mut **tx)
+                .await
+                .map_err(GameError::DatabaseError)?
+                .rows_affected();
+            deleted.push(rows_affected);
+        }
+
+        Ok((deleted[0], deleted[1], deleted[2]))
+    }
+
+    /// 在事务中写入对所有玩家与导演可见的系统日志
+    pub async fn create_system_log_in(
+        tx: &mut Transaction<'_, MySql>,
+        game_id: &str,
+        message: &str,
+        timestamp: DateTime<Utc>,
+    ) -> Result<(), GameError> {
+        sqlx::query(
+            r#"
+            INSERT INTO game_logs (id, game_id, type, message, player_id, timestamp, visible_to_all_players, visible_to_director)
+            VALUES (?, ?, ?, ?, NULL, ?, TRUE, TRUE)
+            "#,
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(game_id)
+        .bind(MessageType::SystemNotice.as_str())
+        .bind(message)
+        .bind(timestamp)
+        .execute(&mut **tx)
+        .await
+        .map_err(GameError::DatabaseError)?;
+
+        Ok(())
+    }
 }
@@ -5,7 +5,7 @@ use std::fmt;
 use std::str::FromStr;
 
 /// 击杀记录模型
-#[derive(Debug, Clone, FromRow, Serialize)]
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
 pub struct KillRecord {
     pub id: String,
     pub game_id: String,
@@ -29,11 +29,29 @@ pub struct NewKillRecord {
     pub location: Option<String>,
 }
 
-/// 获取玩家击杀记录请求
-#[derive(Debug, Deserialize)]
-pub struct GetPlayerKillRecordsRequest {
-    /// 玩家密码
-    pub password: String,
+/// 伤害记录模型
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct DamageRecord {
+    pub id: String,
+    pub game_id: String,
+    pub attacker_id: Option<String>,
+    pub victim_id: String,
+    pub damage: i32,
+    pub cause: String,
+    pub weapon: Option<String>,
+    pub damage_time: DateTime<Utc>,
+}
+
+/// 新增伤害记录的数据载体
+#[derive(Debug, Clone)]
+pub struct NewDamageRecord {
+    pub game_id: String,
+    pub attacker_id: Option<String>,
+    pub victim_id: String,
+    pub damage: i32,
+    pub cause: String,
+    pub weapon: Option<String>,
+    pub damage_time: DateTime<Utc>,
 }
 
 /// 游戏身份角色类型
@@ -42,6 +60,7 @@ pub struct GetPlayerKillRecordsRequest {
 pub enum GameAuthenticationRole {
     Director,
     Actor,
+    Spectator,
     Invalid,
 }
 
@@ -53,6 +72,66 @@ pub struct GameAuthenticationResponse {
     pub actor_id: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub actor_name: Option<String>,
+    /// 游戏会话令牌（验证成功时签发）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    /// 令牌有效期（秒）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_in: Option<u64>,
+    /// 验证失败原因（身份无效时返回）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+/// 游戏身份验证请求
+#[derive(Debug, Deserialize)]
+pub struct GameAuthenticationRequest {
+    /// 演员名称（演员登录时必填，省略时按导演身份验证）
+    #[serde(default)]
+    pub actor_name: Option<String>,
+    /// 导演密码或演员密码
+    pub password: String,
+}
+
+/// 观战身份验证请求
+#[derive(Debug, Deserialize)]
+pub struct SpectatorAuthenticationRequest {
+    /// 观战码（游戏未设置观战码时可省略）
+    #[serde(default)]
+    pub code: Option<String>,
+}
+
+/// 游戏会话角色
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GameSessionRole {
+    Director,
+    Actor,
+    Spectator,
+}
+
+/// 游戏会话令牌声明
+///
+/// 由游戏身份验证签发，仅在所属游戏内有效。
+/// `ver`须与数据库中的会话版本号一致，版本号递增即吊销此前签发的令牌。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameSessionClaims {
+    pub sub: String,           // 演员ID（导演与观众令牌为游戏ID）
+    pub game_id: String,       // 所属游戏ID
+    pub role: GameSessionRole, // 会话角色
+    pub ver: i32,              // 会话版本号
+    pub exp: usize,            // 过期时间戳
+    pub iat: usize,            // 签发时间戳
+}
+
+impl GameSessionClaims {
+    /// 演员令牌对应的演员ID
+    pub fn actor_id(&self) -> Option<&str> {
+        match self.role {
+            GameSessionRole::Actor => Some(&self.sub),
+            GameSessionRole::Director | GameSessionRole::Spectator => None,
+        }
+    }
 }
 
 /// 游戏状态枚举
@@ -102,6 +181,37 @@ impl FromStr for GameStatus {
     }
 }
 
+/// 存档类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SaveKind {
+    /// 导演手动存档以及暂停、结束游戏时的存档
+    Manual,
+    /// 定时自动存档与服务器关闭时的存档，超出保留数量的旧存档会被删除
+    Autosave,
+    /// 夜晚结算后的检查点存档，不会被自动删除
+    Checkpoint,
+}
+
+impl SaveKind {
+    /// 存档文件名前缀
+    pub fn prefix(self) -> &'static str {
+        match self {
+            SaveKind::Manual => "",
+            SaveKind::Autosave => "autosave-",
+            SaveKind::Checkpoint => "checkpoint-",
+        }
+    }
+
+    /// 根据文件名前缀判断存档类型
+    pub fn from_file_name(file_name: &str) -> Self {
+        [SaveKind::Autosave, SaveKind::Checkpoint]
+            .into_iter()
+            .find(|kind| file_name.starts_with(kind.prefix()))
+            .unwrap_or(SaveKind::Manual)
+    }
+}
+
 /// 存档文件信息
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SaveFileInfo {
@@ -109,6 +219,8 @@ pub struct SaveFileInfo {
     pub file_name: String,
     /// 创建时间
     pub created_at: Option<DateTime<Utc>>,
+    /// 存档类型
+    pub kind: SaveKind,
 }
 
 /// 游戏实体模型
@@ -117,6 +229,8 @@ pub struct Game {
     pub id: String,
     pub name: String,
     pub description: Option<String>,
+    /// 导演密码哈希（不对外输出）
+    #[serde(skip_serializing)]
     pub director_password: String,
     pub max_players: i32,
     pub status: GameStatus,
@@ -145,8 +259,6 @@ pub struct GameListItem {
     pub player_count: i32,
     pub max_players: i32,
     pub created_at: DateTime<Utc>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub director_password: Option<String>,
 }
 
 /// 游戏查询结果（用于query_as宏）
@@ -159,7 +271,6 @@ pub struct GameQueryResult {
     pub max_players: i32,
     pub created_at: DateTime<Utc>,
     pub player_count: i64,
-    pub director_password: Option<String>,
 }
 
 impl From<GameQueryResult> for GameListItem {
@@ -174,7 +285,6 @@ impl From<GameQueryResult> for GameListItem {
             player_count: result.player_count as i32,
             max_players: result.max_players,
             created_at: result.created_at,
-            director_password: result.director_password,
         }
     }
 }
@@ -189,10 +299,12 @@ pub struct GameWithPlayerCounts {
     pub player_count: i32,
     pub max_players: i32,
     pub created_at: DateTime<Utc>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub director_password: Option<String>,
     // 修改：直接包含规则配置而非模板信息，且为非Option类型
     pub rules_config: serde_json::Value,
+    /// 创建游戏时使用的规则模版ID
+    pub template_id: Option<String>,
+    /// 创建游戏时使用的规则模版版本号
+    pub template_revision: Option<i32>,
 }
 
 /// 创建游戏请求
@@ -217,16 +329,6 @@ pub struct UpdateGameRequest {
     pub rules_config: Option<serde_json::Value>,
 }
 
-/// 获取玩家消息记录请求
-#[derive(Debug, Deserialize)]
-pub struct GetPlayerMessagesRequest {
-    /// 玩家密码
-    pub password: String,
-    /// 限制返回的消息条数
-    #[serde(default)]
-    pub limit: Option<usize>,
-}
-
 /// 消息记录类型枚举
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, sqlx::Type)]
 #[sqlx(type_name = "type", rename_all = "PascalCase")]
@@ -257,7 +359,7 @@ impl fmt::Display for MessageType {
 }
 
 /// 消息记录模型
-#[derive(Debug, Clone, FromRow, Serialize)]
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
 pub struct MessageRecord {
     pub id: String,
     pub game_id: String,
@@ -387,27 +489,6 @@ impl UpdateGameRequest {
     }
 }
 
-impl GetPlayerMessagesRequest {
-    /// 验证获取玩家消息记录请求的参数
-    pub fn validate(&self) -> Result<(), String> {
-        if self.password.trim().is_empty() {
-            return Err("玩家密码不能为空".to_string());
-        }
-
-        if self.password.is_empty() || self.password.len() > 40 {
-            return Err("玩家密码长度必须在1-40字符之间".to_string());
-        }
-
-        if let Some(limit) = self.limit {
-            if limit == 0 {
-                return Err("请求条数必须大于0".to_string());
-            }
-        }
-
-        Ok(())
-    }
-}
-
 impl NewKillRecord {
     pub fn build_kill_record_params(
         game_id: &str,
@@ -451,6 +532,28 @@ impl NewKillRecord {
     }
 }
 
+impl NewDamageRecord {
+    pub fn build_damage_record_params(
+        game_id: &str,
+        action_result: &crate::websocket::models::ActionResult,
+    ) -> Vec<NewDamageRecord> {
+        action_result
+            .damage_events
+            .iter()
+            .filter(|event| event.damage > 0)
+            .map(|event| NewDamageRecord {
+                game_id: game_id.to_string(),
+                attacker_id: event.attacker_id.clone(),
+                victim_id: event.victim_id.clone(),
+                damage: event.damage,
+                cause: event.cause.clone(),
+                weapon: event.weapon.clone(),
+                damage_time: action_result.timestamp,
+            })
+            .collect()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
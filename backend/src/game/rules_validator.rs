@@ -0,0 +1,570 @@
+//! 规则配置校验
+//! 在保存规则模版或修改游戏规则前检查规则JSON，一次性返回所有问题及其JSON路径与严重程度，
+//! 避免配置错误直到游戏进行中才以“未在规则JSON中找到物品”等形式暴露。
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value as JsonValue};
+use std::collections::{HashMap, HashSet};
+
+use super::game_rule_engine::{
+    ArmorConfig, ConsumableConfig, CurrencyConfig, GameRuleEngine, MapEdge, RarityLevel,
+    UpgradeRecipe, UpgraderConfig, UtilityConfig, WeaponConfig,
+};
+
+/// 玩家配置中的整数字段
+const PLAYER_FIELDS: [&str; 7] = [
+    "max_life",
+    "max_strength",
+    "daily_life_recovery",
+    "daily_strength_recovery",
+    "search_cooldown",
+    "max_backpack_items",
+    "unarmed_damage",
+];
+
+/// 行动消耗配置中的整数字段
+const ACTION_COST_FIELDS: [&str; 8] = [
+    "move", "search", "pick", "attack", "equip", "use", "throw", "deliver",
+];
+
+/// 静养模式配置中的整数字段
+const REST_MODE_FIELDS: [&str; 3] = ["life_recovery", "strength_recovery", "max_moves"];
+
+/// 队友行为规则位的全部取值
+const TEAMMATE_BEHAVIOR_MASK: i64 = 15;
+
+/// 问题严重程度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ValidationSeverity {
+    /// 错误：配置无法使用，保存时被拒绝
+    Error,
+    /// 警告：配置可以保存，但可能与预期不符
+    Warning,
+}
+
+/// 单个校验问题
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RuleValidationIssue {
+    /// 问题所在的JSON路径（如`$.map.safe_places[2]`）
+    pub path: String,
+    /// 严重程度
+    pub severity: ValidationSeverity,
+    /// 问题描述
+    pub message: String,
+}
+
+/// 规则配置校验结果
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RulesValidationReport {
+    /// 是否不存在错误级别的问题
+    pub valid: bool,
+    /// 全部问题（按发现顺序）
+    pub issues: Vec<RuleValidationIssue>,
+}
+
+impl RulesValidationReport {
+    /// 获取错误级别的问题
+    pub fn errors(&self) -> impl Iterator<Item = &RuleValidationIssue> {
+        self.issues
+            .iter()
+            .filter(|issue| issue.severity == ValidationSeverity::Error)
+    }
+
+    /// 将错误级别的问题汇总为一行文本
+    pub fn summary(&self) -> String {
+        self.errors()
+            .map(|issue| format!("{}: {}", issue.path, issue.message))
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+}
+
+/// 规则配置校验器
+pub struct RulesValidator {
+    issues: Vec<RuleValidationIssue>,
+}
+
+impl RulesValidator {
+    /// 校验规则配置，返回所有发现的问题
+    pub fn validate(rules_config: &JsonValue) -> RulesValidationReport {
+        let mut validator = Self { issues: Vec::new() };
+
+        match rules_config.as_object() {
+            Some(root) => {
+                validator.check_map(root);
+                validator.check_integer_section(root, "player", &PLAYER_FIELDS);
+                validator.check_integer_section(root, "action_costs", &ACTION_COST_FIELDS);
+                validator.check_integer_section(root, "rest_mode", &REST_MODE_FIELDS);
+                validator.check_teammate_behavior(root);
+                validator.check_game_flow(root);
+                validator.check_spectator(root);
+                validator.check_autosave(root);
+                validator.check_items_config(root);
+            }
+            None => validator.error("$", "规则配置必须是JSON对象"),
+        }
+
+        // 逐项检查未发现错误时，再由规则引擎完整解析一次，覆盖逐项检查未涉及的格式要求
+        let mut valid = !validator.has_errors();
+        if valid && let Err(e) = GameRuleEngine::from_json(&rules_config.to_string()) {
+            validator.error("$", format!("规则引擎无法解析该配置: {}", e));
+            valid = false;
+        }
+        RulesValidationReport {
+            valid,
+            issues: validator.issues,
+        }
+    }
+
+    fn has_errors(&self) -> bool {
+        self.issues
+            .iter()
+            .any(|issue| issue.severity == ValidationSeverity::Error)
+    }
+
+    fn error(&mut self, path: impl Into<String>, message: impl Into<String>) {
+        self.push(path, ValidationSeverity::Error, message);
+    }
+
+    fn warning(&mut self, path: impl Into<String>, message: impl Into<String>) {
+        self.push(path, ValidationSeverity::Warning, message);
+    }
+
+    fn push(
+        &mut self,
+        path: impl Into<String>,
+        severity: ValidationSeverity,
+        message: impl Into<String>,
+    ) {
+        self.issues.push(RuleValidationIssue {
+            path: path.into(),
+            severity,
+            message: message.into(),
+        });
+    }
+
+    /// 获取配置节对象；缺失的必需配置节与类型错误均记为错误
+    fn section<'a>(
+        &mut self,
+        parent: &'a Map<String, JsonValue>,
+        parent_path: &str,
+        key: &str,
+        required: bool,
+    ) -> Option<&'a Map<String, JsonValue>> {
+        let path = format!("{}.{}", parent_path, key);
+        match parent.get(key) {
+            Some(JsonValue::Object(object)) => Some(object),
+            Some(_) => {
+                self.error(path, "必须是JSON对象");
+                None
+            }
+            None => {
+                if required {
+                    self.error(path, "缺少该配置节");
+                }
+                None
+            }
+        }
+    }
+
+    /// 检查整数字段，返回其值；缺失的必需字段记为错误
+    fn integer_field(
+        &mut self,
+        object: &Map<String, JsonValue>,
+        section_path: &str,
+        field: &str,
+        required: bool,
+    ) -> Option<i64> {
+        let path = format!("{}.{}", section_path, field);
+        match object.get(field) {
+            Some(value) => match value.as_i64() {
+                Some(number) => {
+                    if number < 0 {
+                        self.error(path, "不能为负数");
+                    }
+                    Some(number)
+                }
+                None => {
+                    self.error(path, "必须是整数");
+                    None
+                }
+            },
+            None => {
+                if required {
+                    self.error(path, "缺少该字段");
+                }
+                None
+            }
+        }
+    }
+
+    /// 检查可选的整数属性不为负数
+    fn non_negative(&mut self, path: String, value: Option<i32>) {
+        if value.is_some_and(|value| value < 0) {
+            self.error(path, "不能为负数");
+        }
+    }
+
+    /// 检查字符串数组，返回数组中的字符串及其下标
+    fn string_array(&mut self, value: &JsonValue, path: &str) -> Vec<(usize, String)> {
+        let Some(array) = value.as_array() else {
+            self.error(path, "必须是字符串数组");
+            return vec![];
+        };
+
+        let mut strings = Vec::new();
+        for (index, element) in array.iter().enumerate() {
+            match element.as_str() {
+                Some(text) => strings.push((index, text.to_string())),
+                None => self.error(format!("{}[{}]", path, index), "必须是字符串"),
+            }
+        }
+        strings
+    }
+
+    /// 逐项解析数组元素，解析失败的元素记为错误，返回成功解析的元素及其路径
+    fn parse_entries<T: DeserializeOwned>(
+        &mut self,
+        value: &JsonValue,
+        path: &str,
+    ) -> Vec<(String, T)> {
+        let Some(array) = value.as_array() else {
+            self.error(path, "必须是数组");
+            return vec![];
+        };
+
+        let mut entries = Vec::new();
+        for (index, element) in array.iter().enumerate() {
+            let entry_path = format!("{}[{}]", path, index);
+            match serde_json::from_value::<T>(element.clone()) {
+                Ok(entry) => entries.push((entry_path, entry)),
+                Err(e) => self.error(entry_path, format!("格式错误: {}", e)),
+            }
+        }
+        entries
+    }
+
+    /// 解析物品分类下的全部物品配置
+    fn parse_category<T: DeserializeOwned>(
+        &mut self,
+        items: Option<&Map<String, JsonValue>>,
+        category: &str,
+    ) -> Vec<(String, T)> {
+        match items.and_then(|items| items.get(category)) {
+            Some(value) => self.parse_entries(value, &format!("$.items_config.items.{}", category)),
+            None => vec![],
+        }
+    }
+
+    /// 地图配置：地点、安全区与地点连接
+    fn check_map(&mut self, root: &Map<String, JsonValue>) {
+        let Some(map) = self.section(root, "$", "map", true) else {
+            return;
+        };
+
+        let mut places: HashSet<String> = HashSet::new();
+        match map.get("places") {
+            Some(value) => {
+                for (index, place) in self.string_array(value, "$.map.places") {
+                    if !places.insert(place.clone()) {
+                        self.warning(
+                            format!("$.map.places[{}]", index),
+                            format!("地点 {} 重复", place),
+                        );
+                    }
+                }
+            }
+            None => self.error("$.map.places", "缺少该字段"),
+        }
+
+        match map.get("safe_places") {
+            Some(value) => {
+                for (index, place) in self.string_array(value, "$.map.safe_places") {
+                    if !places.contains(&place) {
+                        self.error(
+                            format!("$.map.safe_places[{}]", index),
+                            format!("安全区 {} 不在地点列表中", place),
+                        );
+                    }
+                }
+            }
+            None => self.error("$.map.safe_places", "缺少该字段"),
+        }
+
+        if let Some(value) = map.get("edges") {
+            for (path, edge) in self.parse_entries::<MapEdge>(value, "$.map.edges") {
+                for (field, place) in [("from", &edge.from), ("to", &edge.to)] {
+                    if !places.contains(place) {
+                        self.error(
+                            format!("{}.{}", path, field),
+                            format!("地点 {} 不在地点列表中", place),
+                        );
+                    }
+                }
+                if edge.from == edge.to {
+                    self.warning(path.clone(), "连接的两端是同一个地点");
+                }
+                self.non_negative(format!("{}.cost", path), edge.cost);
+            }
+        }
+    }
+
+    /// 由整数字段组成的必需配置节（玩家、行动消耗、静养模式）
+    fn check_integer_section(&mut self, root: &Map<String, JsonValue>, key: &str, fields: &[&str]) {
+        let Some(section) = self.section(root, "$", key, true) else {
+            return;
+        };
+
+        let section_path = format!("$.{}", key);
+        for field in fields {
+            self.integer_field(section, &section_path, field, true);
+        }
+    }
+
+    /// 队友行为规则位
+    fn check_teammate_behavior(&mut self, root: &Map<String, JsonValue>) {
+        let Some(mode) = self.integer_field(root, "$", "teammate_behavior", false) else {
+            return;
+        };
+
+        if mode & !TEAMMATE_BEHAVIOR_MASK != 0 {
+            self.warning("$.teammate_behavior", "包含未定义的规则位，将被忽略");
+        }
+    }
+
+    /// 游戏流程配置（可选）
+    fn check_game_flow(&mut self, root: &Map<String, JsonValue>) {
+        let Some(game_flow) = self.section(root, "$", "game_flow", false) else {
+            return;
+        };
+
+        self.integer_field(game_flow, "$.game_flow", "day_duration", false);
+        self.integer_field(game_flow, "$.game_flow", "night_duration", false);
+
+        if let Some(value) = game_flow.get("night_start_time")
+            && !value.is_null()
+        {
+            let is_valid_time = value
+                .as_str()
+                .is_some_and(|time| chrono::NaiveTime::parse_from_str(time, "%H:%M").is_ok());
+            if !is_valid_time {
+                self.error("$.game_flow.night_start_time", "必须是HH:MM格式的时间");
+            }
+        }
+
+        if game_flow
+            .get("auto_settlement")
+            .is_some_and(|value| !value.is_boolean())
+        {
+            self.error("$.game_flow.auto_settlement", "必须是布尔值");
+        }
+    }
+
+    /// 观战配置（可选）
+    fn check_spectator(&mut self, root: &Map<String, JsonValue>) {
+        let Some(spectator) = self.section(root, "$", "spectator", false) else {
+            return;
+        };
+
+        self.integer_field(spectator, "$.spectator", "delay_seconds", false);
+    }
+
+    /// 自动存档配置（可选）
+    fn check_autosave(&mut self, root: &Map<String, JsonValue>) {
+        let Some(autosave) = self.section(root, "$", "autosave", false) else {
+            return;
+        };
+
+        self.integer_field(autosave, "$.autosave", "interval_seconds", false);
+        if self.integer_field(autosave, "$.autosave", "keep_saves", false) == Some(0) {
+            self.error("$.autosave.keep_saves", "必须大于0");
+        }
+    }
+
+    /// 物品系统配置：稀有度、物品、显示名称与升级配方
+    fn check_items_config(&mut self, root: &Map<String, JsonValue>) {
+        let Some(items_config) = self.section(root, "$", "items_config", false) else {
+            return;
+        };
+
+        let rarity_levels: HashSet<String> = match items_config.get("rarity_levels") {
+            Some(value) => self
+                .parse_entries::<RarityLevel>(value, "$.items_config.rarity_levels")
+                .into_iter()
+                .map(|(_, level)| level.internal_name)
+                .collect(),
+            None => HashSet::new(),
+        };
+
+        let items = self.section(items_config, "$.items_config", "items", false);
+        let weapons: Vec<(String, WeaponConfig)> = self.parse_category(items, "weapons");
+        let armors: Vec<(String, ArmorConfig)> = self.parse_category(items, "armors");
+        let utilities: Vec<(String, UtilityConfig)> = self.parse_category(items, "utilities");
+        let consumables: Vec<(String, ConsumableConfig)> =
+            self.parse_category(items, "consumables");
+        let upgraders: Vec<(String, UpgraderConfig)> = self.parse_category(items, "upgraders");
+        let currencies: Vec<(String, CurrencyConfig)> = self.parse_category(items, "currencies");
+
+        // 稀有度必须在稀有度等级中定义
+        let rarities = weapons
+            .iter()
+            .map(|(path, config)| (path, &config.rarity))
+            .chain(armors.iter().map(|(path, config)| (path, &config.rarity)))
+            .chain(
+                utilities
+                    .iter()
+                    .map(|(path, config)| (path, &config.rarity)),
+            )
+            .chain(
+                consumables
+                    .iter()
+                    .map(|(path, config)| (path, &config.rarity)),
+            )
+            .chain(
+                upgraders
+                    .iter()
+                    .map(|(path, config)| (path, &config.rarity)),
+            )
+            .chain(
+                currencies
+                    .iter()
+                    .map(|(path, config)| (path, &config.rarity)),
+            );
+        let mut unknown_rarities = Vec::new();
+        for (path, rarity) in rarities {
+            if let Some(rarity) = rarity
+                && !rarity_levels.contains(rarity)
+            {
+                unknown_rarities.push((format!("{}.rarity", path), rarity.clone()));
+            }
+        }
+        for (path, rarity) in unknown_rarities {
+            self.error(path, format!("稀有度 {} 未在 rarity_levels 中定义", rarity));
+        }
+
+        // 武器与防具的显示名称全局唯一（物品按显示名称查找）
+        let mut display_names: HashMap<String, String> = HashMap::new();
+        let named_equipment = weapons
+            .iter()
+            .map(|(path, config)| (path.clone(), config.display_names.clone()))
+            .chain(
+                armors
+                    .iter()
+                    .map(|(path, config)| (path.clone(), config.display_names.clone())),
+            )
+            .collect::<Vec<_>>();
+        for (path, names) in named_equipment {
+            if names.is_empty() {
+                self.warning(
+                    format!("{}.display_names", path),
+                    "未配置显示名称，该物品无法生成",
+                );
+            }
+            for (index, name) in names.into_iter().enumerate() {
+                let name_path = format!("{}.display_names[{}]", path, index);
+                match display_names.get(&name) {
+                    Some(first_path) => {
+                        let message = format!("显示名称 {} 与 {} 重复", name, first_path);
+                        self.error(name_path, message);
+                    }
+                    None => {
+                        display_names.insert(name, name_path);
+                    }
+                }
+            }
+        }
+
+        // 数值属性不能为负数
+        for (path, config) in &weapons {
+            let properties = &config.properties;
+            self.non_negative(
+                format!("{}.properties.damage", path),
+                Some(properties.damage),
+            );
+            self.non_negative(format!("{}.properties.votes", path), Some(properties.votes));
+            self.non_negative(format!("{}.properties.uses", path), properties.uses);
+            self.non_negative(
+                format!("{}.properties.aoe_damage", path),
+                properties.aoe_damage,
+            );
+            self.non_negative(
+                format!("{}.properties.bleed_damage", path),
+                properties.bleed_damage,
+            );
+        }
+        for (path, config) in &armors {
+            let properties = &config.properties;
+            self.non_negative(
+                format!("{}.properties.defense", path),
+                Some(properties.defense),
+            );
+            self.non_negative(format!("{}.properties.votes", path), Some(properties.votes));
+            self.non_negative(format!("{}.properties.uses", path), properties.uses);
+        }
+        for (path, config) in &utilities {
+            let properties = &config.properties;
+            self.non_negative(format!("{}.properties.votes", path), properties.votes);
+            self.non_negative(format!("{}.properties.uses", path), properties.uses);
+            self.non_negative(format!("{}.properties.targets", path), properties.targets);
+            self.non_negative(format!("{}.properties.damage", path), properties.damage);
+            self.non_negative(
+                format!("{}.properties.uses_night", path),
+                properties.uses_night,
+            );
+        }
+        for (path, config) in &currencies {
+            self.non_negative(
+                format!("{}.properties.value", path),
+                Some(config.properties.value),
+            );
+        }
+
+        // 升级配方必须引用已定义的升级器与装备
+        let Some(recipes) = self.section(items_config, "$.items_config", "upgrade_recipes", false)
+        else {
+            return;
+        };
+        let upgrader_names: HashSet<&str> = upgraders
+            .iter()
+            .map(|(_, config)| config.internal_name.as_str())
+            .collect();
+        let equipment_names: HashSet<&str> = weapons
+            .iter()
+            .map(|(_, config)| config.internal_name.as_str())
+            .chain(
+                armors
+                    .iter()
+                    .map(|(_, config)| config.internal_name.as_str()),
+            )
+            .collect();
+
+        for (upgrader, value) in recipes {
+            let recipes_path = format!("$.items_config.upgrade_recipes.{}", upgrader);
+            if !upgrader_names.contains(upgrader.as_str()) {
+                self.error(
+                    recipes_path.clone(),
+                    format!("未找到内部名称为 {} 的升级器", upgrader),
+                );
+            }
+
+            for (path, recipe) in self.parse_entries::<UpgradeRecipe>(value, &recipes_path) {
+                for (index, ingredient) in recipe.ingredients.iter().enumerate() {
+                    if !equipment_names.contains(ingredient.as_str()) {
+                        self.error(
+                            format!("{}.ingredients[{}]", path, index),
+                            format!("未找到内部名称为 {} 的武器或防具", ingredient),
+                        );
+                    }
+                }
+                if !equipment_names.contains(recipe.result.as_str()) {
+                    self.error(
+                        format!("{}.result", path),
+                        format!("未找到内部名称为 {} 的武器或防具", recipe.result),
+                    );
+                }
+            }
+        }
+    }
+}
@@ -1,18 +1,30 @@
 use chrono::{DateTime, Utc};
-use sqlx::MySqlPool;
-
+use sqlx::{MySql, MySqlPool, QueryBuilder};
+use uuid::Uuid;
+
+use super::action_journal::ActionJournal;
+use super::archive::{
+    ArchivedGame, ArchivedPlayer, ArchivedSave, GameArchive, is_archivable_status,
+};
+use super::credentials::GameCredentials;
 use super::errors::GameError;
+use super::global_game_state_manager::GlobalGameStateManager;
 use super::models::*;
+use super::stats::{GameStats, LeaderboardEntry, StatsPlayer};
 use crate::rule_template::models::RuleTemplate;
 
+/// 导入归档时每条批量插入语句包含的记录数
+const IMPORT_BATCH_SIZE: usize = 500;
+
 #[derive(Clone)]
 pub struct GameService {
     pool: MySqlPool,
+    bcrypt_cost: u32,
 }
 
 impl GameService {
-    pub fn new(pool: MySqlPool) -> Self {
-        Self { pool }
+    pub fn new(pool: MySqlPool, bcrypt_cost: u32) -> Self {
+        Self { pool, bcrypt_cost }
     }
 
     /// 创建新游戏
@@ -51,24 +63,31 @@ impl GameService {
 
         // 从规则模板获取配置
         let template = self.get_rule_template(&rule_template_id).await?;
-        let rules_config = template.rules_config;
+        let rules_config = template.rules_config.clone();
+
+        let director_password_hash =
+            GameCredentials::hash_password_blocking(&director_password, self.bcrypt_cost)
+                .await
+                .map_err(|e| GameError::OtherError(e.to_string()))?;
 
         let game_id_for_insert = normalized_id.clone();
         let game_id_for_fetch = normalized_id.clone();
 
-        // 插入新游戏记录
-        sqlx::query!(
+        // 插入新游戏记录（记录所使用的模版及版本）
+        sqlx::query(
             r#"
-            INSERT INTO games (id, name, description, director_password, max_players, status, rules_config)
-            VALUES (?, ?, ?, ?, ?, 'waiting', ?)
+            INSERT INTO games (id, name, description, director_password, max_players, status, rules_config, template_id, template_revision)
+            VALUES (?, ?, ?, ?, ?, 'waiting', ?, ?, ?)
             "#,
-            game_id_for_insert,
-            name,
-            description,
-            director_password,
-            max_players,
-            &rules_config
         )
+        .bind(game_id_for_insert)
+        .bind(name)
+        .bind(description)
+        .bind(director_password_hash)
+        .bind(max_players)
+        .bind(&rules_config)
+        .bind(&template.id)
+        .bind(template.current_revision)
         .execute(&self.pool)
         .await?;
 
@@ -153,8 +172,17 @@ impl GameService {
             .await?;
         }
         if let Some(ref password) = request.director_password {
-            sqlx::query!("UPDATE games SET director_password = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?", password, game_id)
-                .execute(&self.pool).await?;
+            let password_hash = GameCredentials::hash_password_blocking(password, self.bcrypt_cost)
+                .await
+                .map_err(|e| GameError::OtherError(e.to_string()))?;
+            // 修改导演密码的同时递增会话版本号，吊销已签发的导演令牌
+            sqlx::query(
+                "UPDATE games SET director_password = ?, director_session_version = director_session_version + 1, updated_at = CURRENT_TIMESTAMP WHERE id = ?",
+            )
+            .bind(password_hash)
+            .bind(game_id)
+            .execute(&self.pool)
+            .await?;
         }
         if let Some(max_players) = request.max_players {
             sqlx::query!(
@@ -195,15 +223,10 @@ impl GameService {
     }
 
     /// 获取游戏列表（支持筛选）
-    pub async fn get_games(
-        &self,
-        query: &GameListQuery,
-        include_director_password: bool,
-    ) -> Result<Vec<GameListItem>, GameError> {
+    pub async fn get_games(&self, query: &GameListQuery) -> Result<Vec<GameListItem>, GameError> {
         let base_query = r#"
             SELECT g.id, g.name, g.description, g.status, g.max_players, g.created_at,
-                   COUNT(a.id) as player_count,
-                   g.director_password
+                   COUNT(a.id) as player_count
             FROM games g
             LEFT JOIN actors a ON g.id = a.game_id
         "#;
@@ -241,15 +264,7 @@ impl GameService {
                 .await?
         };
 
-        let game_list = results
-            .into_iter()
-            .map(|mut result| {
-                if !include_director_password {
-                    result.director_password = None;
-                }
-                GameListItem::from(result)
-            })
-            .collect();
+        let game_list = results.into_iter().map(GameListItem::from).collect();
 
         Ok(game_list)
     }
@@ -275,7 +290,6 @@ impl GameService {
     pub async fn get_game_by_id_with_player_counts(
         &self,
         game_id: &str,
-        include_director_password: bool,
     ) -> Result<GameWithPlayerCounts, GameError> {
         // 直接查询游戏表，无需JOIN规则模板表
         let game_info = sqlx::query!(
@@ -295,6 +309,9 @@ impl GameService {
         // 获取玩家数量
         let player_count = self.get_player_count(game_id).await?;
 
+        // 获取创建时使用的规则模版及版本
+        let (template_id, template_revision) = self.get_game_template_source(game_id).await?;
+
         // 构建响应
         Ok(GameWithPlayerCounts {
             id: game_info.id,
@@ -304,56 +321,421 @@ impl GameService {
             player_count,
             max_players: game_info.max_players,
             created_at: game_info.created_at,
-            director_password: if include_director_password {
-                Some(game_info.director_password)
-            } else {
-                None
-            },
             rules_config: game_info.rules_config,
+            template_id,
+            template_revision,
         })
     }
 
     /// 验证游戏身份
+    ///
+    /// 提供演员名称时只校验该演员的密码，否则只校验导演密码；
+    /// 验证失败时在 `message` 中返回原因，提示演员登录必须提供名称。
     pub async fn authenticate_game(
         &self,
         game_id: &str,
+        actor_name: Option<&str>,
         password: &str,
     ) -> Result<GameAuthenticationResponse, GameError> {
-        let actor = sqlx::query!(
-            "SELECT id, name FROM actors WHERE game_id = ? AND password = ?",
+        if let Some(actor_name) = actor_name {
+            let actor =
+                GameCredentials::find_actor(&self.pool, game_id, actor_name, password).await?;
+            return Ok(match actor {
+                Some(actor) => GameAuthenticationResponse {
+                    role: GameAuthenticationRole::Actor,
+                    actor_id: Some(actor.id),
+                    actor_name: Some(actor.name),
+                    token: None,
+                    expires_in: None,
+                    message: None,
+                },
+                None => GameAuthenticationResponse {
+                    role: GameAuthenticationRole::Invalid,
+                    actor_id: None,
+                    actor_name: None,
+                    token: None,
+                    expires_in: None,
+                    message: Some("演员名称或密码错误".to_string()),
+                },
+            });
+        }
+
+        let director_verified = match self.get_game_by_id(game_id).await {
+            Ok(game) => {
+                GameCredentials::verify_password_blocking(password, &game.director_password).await
+            }
+            Err(GameError::GameNotFound) => false,
+            Err(e) => return Err(e),
+        };
+        if director_verified {
+            return Ok(GameAuthenticationResponse {
+                role: GameAuthenticationRole::Director,
+                actor_id: None,
+                actor_name: None,
+                token: None,
+                expires_in: None,
+                message: None,
+            });
+        }
+
+        // 未提供演员名称时只按导演密码验证，提示演员登录需要同时提供名称
+        Ok(GameAuthenticationResponse {
+            role: GameAuthenticationRole::Invalid,
+            actor_id: None,
+            actor_name: None,
+            token: None,
+            expires_in: None,
+            message: Some("导演密码错误；演员登录需要同时提供演员名称(actor_name)".to_string()),
+        })
+    }
+
+    /// 观战身份验证
+    ///
+    /// 游戏未设置观战码时任何人均可观战，否则须提供正确的观战码。
+    pub async fn authenticate_spectator(
+        &self,
+        game_id: &str,
+        code: Option<&str>,
+    ) -> Result<GameAuthenticationResponse, GameError> {
+        let row: Option<(Option<String>,)> =
+            sqlx::query_as("SELECT spectator_code FROM games WHERE id = ?")
+                .bind(game_id)
+                .fetch_optional(&self.pool)
+                .await?;
+
+        let verified = match row {
+            Some((None,)) => true,
+            Some((Some(code_hash),)) => match code {
+                Some(code) => GameCredentials::verify_password_blocking(code, &code_hash).await,
+                None => false,
+            },
+            None => false,
+        };
+
+        Ok(GameAuthenticationResponse {
+            role: if verified {
+                GameAuthenticationRole::Spectator
+            } else {
+                GameAuthenticationRole::Invalid
+            },
+            actor_id: None,
+            actor_name: None,
+            token: None,
+            expires_in: None,
+            message: (!verified).then(|| "观战码错误".to_string()),
+        })
+    }
+
+    /// 获取已结束游戏的赛后统计
+    pub async fn get_game_stats(&self, game_id: &str) -> Result<GameStats, GameError> {
+        let game = self.get_game_by_id(game_id).await?;
+        if game.status != GameStatus::Ended {
+            return Err(GameError::InvalidGameState);
+        }
+
+        let players = sqlx::query_as::<_, StatsPlayer>(
+            "SELECT id, game_id, name, team_id FROM actors WHERE game_id = ?",
+        )
+        .bind(game_id)
+        .fetch_all(&self.pool)
+        .await?;
+        let kill_records = sqlx::query_as::<_, KillRecord>(
+            r#"
+            SELECT id, game_id, killer_id, victim_id, kill_time, cause, weapon, location
+            FROM kill_records
+            WHERE game_id = ?
+            ORDER BY kill_time ASC
+            "#,
+        )
+        .bind(game_id)
+        .fetch_all(&self.pool)
+        .await?;
+        let damage_records = sqlx::query_as::<_, DamageRecord>(
+            r#"
+            SELECT id, game_id, attacker_id, victim_id, damage, cause, weapon, damage_time
+            FROM damage_records
+            WHERE game_id = ?
+            ORDER BY damage_time ASC
+            "#,
+        )
+        .bind(game_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(GameStats::compute(
             game_id,
-            password
+            &players,
+            &kill_records,
+            &damage_records,
+        ))
+    }
+
+    /// 按玩家名称汇总所有已结束游戏的排行榜
+    pub async fn get_leaderboard(&self) -> Result<Vec<LeaderboardEntry>, GameError> {
+        let players = sqlx::query_as::<_, StatsPlayer>(
+            r#"
+            SELECT a.id, a.game_id, a.name, a.team_id
+            FROM actors a JOIN games g ON g.id = a.game_id
+            WHERE g.status = 'ended'
+            "#,
         )
-        .fetch_optional(&self.pool)
+        .fetch_all(&self.pool)
+        .await?;
+        let kill_records = sqlx::query_as::<_, KillRecord>(
+            r#"
+            SELECT k.id, k.game_id, k.killer_id, k.victim_id, k.kill_time, k.cause, k.weapon, k.location
+            FROM kill_records k JOIN games g ON g.id = k.game_id
+            WHERE g.status = 'ended'
+            ORDER BY k.kill_time ASC
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        let damage_records = sqlx::query_as::<_, DamageRecord>(
+            r#"
+            SELECT d.id, d.game_id, d.attacker_id, d.victim_id, d.damage, d.cause, d.weapon, d.damage_time
+            FROM damage_records d JOIN games g ON g.id = d.game_id
+            WHERE g.status = 'ended'
+            ORDER BY d.damage_time ASC
+            "#,
+        )
+        .fetch_all(&self.pool)
         .await?;
 
-        if let Some(actor) = actor {
-            return Ok(GameAuthenticationResponse {
-                role: GameAuthenticationRole::Actor,
-                actor_id: Some(actor.id),
-                actor_name: Some(actor.name),
+        let game_stats = GameStats::compute_by_game(&players, &kill_records, &damage_records);
+        Ok(LeaderboardEntry::from_games(&game_stats))
+    }
+
+    /// 导出已结束游戏的归档
+    ///
+    /// 包含游戏信息、规则配置、演员名单（不含密码）、全部日志、击杀与伤害记录、行动日志和存档快照。
+    pub async fn export_game_archive(
+        &self,
+        game_id: &str,
+        state_manager: &GlobalGameStateManager,
+    ) -> Result<GameArchive, GameError> {
+        let game = self.get_game_by_id(game_id).await?;
+        if !is_archivable_status(&game.status) {
+            return Err(GameError::InvalidGameState);
+        }
+
+        let players = sqlx::query_as::<_, ArchivedPlayer>(
+            "SELECT id, name, team_id FROM actors WHERE game_id = ? ORDER BY created_at ASC, id ASC",
+        )
+        .bind(game_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut archive = GameArchive::new(
+            ArchivedGame {
+                id: game.id,
+                name: game.name,
+                description: game.description,
+                max_players: game.max_players,
+                status: game.status,
+                rules_config: game.rules_config,
+                created_at: game.created_at,
+                updated_at: game.updated_at,
+            },
+            players,
+        );
+
+        archive.logs = sqlx::query_as::<_, MessageRecord>(
+            r#"
+            SELECT id, game_id, type AS message_type, message, player_id, timestamp,
+                visible_to_all_players, visible_to_director
+            FROM game_logs
+            WHERE game_id = ?
+            ORDER BY timestamp ASC, id ASC
+            "#,
+        )
+        .bind(game_id)
+        .fetch_all(&self.pool)
+        .await?;
+        archive.kill_records = sqlx::query_as::<_, KillRecord>(
+            r#"
+            SELECT id, game_id, killer_id, victim_id, kill_time, cause, weapon, location
+            FROM kill_records
+            WHERE game_id = ?
+            ORDER BY kill_time ASC, id ASC
+            "#,
+        )
+        .bind(game_id)
+        .fetch_all(&self.pool)
+        .await?;
+        archive.damage_records = sqlx::query_as::<_, DamageRecord>(
+            r#"
+            SELECT id, game_id, attacker_id, victim_id, damage, cause, weapon, damage_time
+            FROM damage_records
+            WHERE game_id = ?
+            ORDER BY damage_time ASC, id ASC
+            "#,
+        )
+        .bind(game_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        archive.journal = ActionJournal::read_entries(game_id)?;
+        let mut save_files = state_manager.list_save_files(game_id).await?;
+        // 归档中的存档按时间从早到晚排列
+        save_files.reverse();
+        for save_file in save_files {
+            let state = GlobalGameStateManager::read_save_file_json(game_id, &save_file.file_name)?;
+            archive.saves.push(ArchivedSave {
+                file_name: save_file.file_name,
+                kind: save_file.kind,
+                created_at: save_file.created_at,
+                state,
             });
         }
 
-        let game = sqlx::query!("SELECT director_password FROM games WHERE id = ?", game_id)
+        Ok(archive)
+    }
+
+    /// 导入游戏归档，恢复为已结束的游戏
+    ///
+    /// 保留归档中的游戏ID与演员ID（存档快照与行动日志均引用这些ID），游戏ID、名称或演员ID
+    /// 与本服务器已有数据冲突时拒绝导入。导入的游戏无法再以原密码登录，导演密码与演员密码
+    /// 统一设置为随机生成的密码。
+    pub async fn import_game_archive(&self, archive: GameArchive) -> Result<Game, GameError> {
+        archive.validate().map_err(GameError::ValidationError)?;
+        let game_id = archive.game.id.clone();
+
+        let existing_id = sqlx::query("SELECT id FROM games WHERE id = ?")
+            .bind(&game_id)
             .fetch_optional(&self.pool)
             .await?;
-
-        if let Some(game) = game {
-            if game.director_password == password {
-                return Ok(GameAuthenticationResponse {
-                    role: GameAuthenticationRole::Director,
-                    actor_id: None,
-                    actor_name: None,
-                });
+        if existing_id.is_some() {
+            return Err(GameError::GameIdExists);
+        }
+        let existing_name = sqlx::query("SELECT id FROM games WHERE name = ?")
+            .bind(&archive.game.name)
+            .fetch_optional(&self.pool)
+            .await?;
+        if existing_name.is_some() {
+            return Err(GameError::GameNameExists);
+        }
+        if !archive.players.is_empty() {
+            let mut builder = QueryBuilder::<MySql>::new("SELECT id FROM actors WHERE id IN (");
+            let mut ids = builder.separated(", ");
+            for player in &archive.players {
+                ids.push_bind(player.id.clone());
+            }
+            builder.push(")");
+            let conflicts: Vec<(String,)> = builder.build_query_as().fetch_all(&self.pool).await?;
+            if let Some((actor_id,)) = conflicts.first() {
+                return Err(GameError::ValidationError(format!(
+                    "演员ID {} 已存在",
+                    actor_id
+                )));
             }
         }
 
-        Ok(GameAuthenticationResponse {
-            role: GameAuthenticationRole::Invalid,
-            actor_id: None,
-            actor_name: None,
-        })
+        let password_hash =
+            GameCredentials::hash_password_blocking(&Uuid::new_v4().to_string(), self.bcrypt_cost)
+                .await
+                .map_err(|e| GameError::OtherError(e.to_string()))?;
+
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(
+            r#"
+            INSERT INTO games (id, name, description, director_password, max_players, status, rules_config, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, 'ended', ?, ?, ?)
+            "#,
+        )
+        .bind(&game_id)
+        .bind(&archive.game.name)
+        .bind(&archive.game.description)
+        .bind(&password_hash)
+        .bind(archive.game.max_players)
+        .bind(&archive.game.rules_config)
+        .bind(archive.game.created_at)
+        .bind(archive.game.updated_at)
+        .execute(&mut *tx)
+        .await?;
+
+        for players in archive.players.chunks(IMPORT_BATCH_SIZE) {
+            QueryBuilder::<MySql>::new(
+                "INSERT INTO actors (id, game_id, name, password, team_id) ",
+            )
+            .push_values(players, |mut row, player| {
+                row.push_bind(&player.id)
+                    .push_bind(&game_id)
+                    .push_bind(&player.name)
+                    .push_bind(&password_hash)
+                    .push_bind(player.team_id);
+            })
+            .build()
+            .execute(&mut *tx)
+            .await?;
+        }
+        for logs in archive.logs.chunks(IMPORT_BATCH_SIZE) {
+            QueryBuilder::<MySql>::new(
+                "INSERT INTO game_logs (id, game_id, type, message, player_id, timestamp, visible_to_all_players, visible_to_director) ",
+            )
+            .push_values(logs, |mut row, log| {
+                row.push_bind(&log.id)
+                    .push_bind(&game_id)
+                    .push_bind(log.message_type.as_str())
+                    .push_bind(&log.message)
+                    .push_bind(&log.player_id)
+                    .push_bind(log.timestamp)
+                    .push_bind(log.visible_to_all_players)
+                    .push_bind(log.visible_to_director);
+            })
+            .build()
+            .execute(&mut *tx)
+            .await?;
+        }
+        for kills in archive.kill_records.chunks(IMPORT_BATCH_SIZE) {
+            QueryBuilder::<MySql>::new(
+                "INSERT INTO kill_records (id, game_id, killer_id, victim_id, kill_time, cause, weapon, location) ",
+            )
+            .push_values(kills, |mut row, kill| {
+                row.push_bind(&kill.id)
+                    .push_bind(&game_id)
+                    .push_bind(&kill.killer_id)
+                    .push_bind(&kill.victim_id)
+                    .push_bind(kill.kill_time)
+                    .push_bind(&kill.cause)
+                    .push_bind(&kill.weapon)
+                    .push_bind(&kill.location);
+            })
+            .build()
+            .execute(&mut *tx)
+            .await?;
+        }
+        for damages in archive.damage_records.chunks(IMPORT_BATCH_SIZE) {
+            QueryBuilder::<MySql>::new(
+                "INSERT INTO damage_records (id, game_id, attacker_id, victim_id, damage, cause, weapon, damage_time) ",
+            )
+            .push_values(damages, |mut row, damage| {
+                row.push_bind(&damage.id)
+                    .push_bind(&game_id)
+                    .push_bind(&damage.attacker_id)
+                    .push_bind(&damage.victim_id)
+                    .push_bind(damage.damage)
+                    .push_bind(&damage.cause)
+                    .push_bind(&damage.weapon)
+                    .push_bind(damage.damage_time);
+            })
+            .build()
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        // 提交事务之前写入磁盘文件；写入或提交失败时删除已写入的文件并回滚数据库记录
+        if let Err(e) = archive.write_files() {
+            archive.remove_files();
+            tx.rollback().await?;
+            return Err(e.into());
+        }
+        if let Err(e) = tx.commit().await {
+            archive.remove_files();
+            return Err(e.into());
+        }
+
+        self.get_game_by_id(&game_id).await
     }
 
     /// 根据ID获取游戏信息
@@ -388,23 +770,27 @@ impl GameService {
 
     /// 新增辅助方法：获取规则模板
     async fn get_rule_template(&self, template_id: &str) -> Result<RuleTemplate, GameError> {
-        let template = sqlx::query!(
-            "SELECT id, template_name, description, rules_config FROM rule_templates WHERE id = ? AND is_active = true",
-            template_id
+        let template = sqlx::query_as::<_, RuleTemplate>(
+            "SELECT id, template_name, description, is_active, rules_config, current_revision, created_at, updated_at FROM rule_templates WHERE id = ? AND is_active = true",
         )
+        .bind(template_id)
         .fetch_optional(&self.pool)
         .await?;
 
-        template
-            .ok_or(GameError::RuleTemplateNotFound)
-            .map(|t| RuleTemplate {
-                id: t.id,
-                template_name: t.template_name,
-                description: t.description,
-                is_active: true, // 查询中已经限制了is_active = true
-                rules_config: t.rules_config,
-                created_at: chrono::Utc::now(), // 这些字段在当前上下文中不重要
-                updated_at: chrono::Utc::now(),
-            })
+        template.ok_or(GameError::RuleTemplateNotFound)
+    }
+
+    /// 获取游戏创建时使用的规则模版ID与版本号
+    async fn get_game_template_source(
+        &self,
+        game_id: &str,
+    ) -> Result<(Option<String>, Option<i32>), GameError> {
+        let source: (Option<String>, Option<i32>) =
+            sqlx::query_as("SELECT template_id, template_revision FROM games WHERE id = ?")
+                .bind(game_id)
+                .fetch_one(&self.pool)
+                .await?;
+
+        Ok(source)
     }
 }
@@ -0,0 +1,318 @@
+//! 赛后统计
+//! 根据击杀记录与伤害记录计算游戏的最终排名、击杀与伤害数据，并按玩家名称汇总多场游戏的排行榜
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::FromRow;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use super::models::{DamageRecord, KillRecord};
+
+/// 参与统计的玩家
+#[derive(Debug, Clone, FromRow)]
+pub struct StatsPlayer {
+    pub id: String,
+    pub game_id: String,
+    pub name: String,
+    pub team_id: i32,
+}
+
+/// 单个玩家在一场游戏中的统计
+#[derive(Debug, Clone, Serialize)]
+pub struct PlayerGameStats {
+    pub player_id: String,
+    pub player_name: String,
+    pub team_id: i32,
+    /// 最终名次，存活者并列第1名，其余按死亡时间从晚到早排列
+    pub placement: usize,
+    pub is_alive: bool,
+    /// 最后一次死亡时间
+    pub death_time: Option<DateTime<Utc>>,
+    /// 最后一次死亡原因（武器名称或缩圈、流血致死等）
+    pub death_cause: Option<String>,
+    /// 最后一次死亡的击杀者ID
+    pub killer_id: Option<String>,
+    pub kills: usize,
+    pub damage_dealt: i64,
+    pub damage_taken: i64,
+}
+
+/// 击杀原因统计
+#[derive(Debug, Clone, Serialize)]
+pub struct KillCauseStats {
+    /// 致死的武器或道具名称，没有武器时为死亡原因
+    pub cause: String,
+    pub count: usize,
+}
+
+/// 武器使用统计
+#[derive(Debug, Clone, Serialize)]
+pub struct WeaponStats {
+    pub weapon: String,
+    /// 造成伤害的次数
+    pub hits: usize,
+    pub damage: i64,
+    pub kills: usize,
+}
+
+/// 一场游戏的赛后统计
+#[derive(Debug, Clone, Serialize)]
+pub struct GameStats {
+    pub game_id: String,
+    pub total_players: usize,
+    pub survivors: usize,
+    pub total_kills: usize,
+    pub total_damage: i64,
+    /// 按名次排列的玩家统计
+    pub players: Vec<PlayerGameStats>,
+    /// 按次数从多到少排列的击杀原因
+    pub kill_causes: Vec<KillCauseStats>,
+    /// 按使用次数从多到少排列的武器
+    pub weapons: Vec<WeaponStats>,
+}
+
+/// 击杀记录的原因标签
+fn kill_cause_label(record: &KillRecord) -> String {
+    record
+        .weapon
+        .clone()
+        .unwrap_or_else(|| record.cause.clone())
+}
+
+impl GameStats {
+    /// 根据玩家列表、击杀记录与伤害记录计算统计
+    ///
+    /// 被复活过的玩家以最后一次死亡为准。
+    pub fn compute(
+        game_id: &str,
+        players: &[StatsPlayer],
+        kill_records: &[KillRecord],
+        damage_records: &[DamageRecord],
+    ) -> Self {
+        let mut last_deaths: HashMap<&str, &KillRecord> = HashMap::new();
+        let mut kills: HashMap<&str, usize> = HashMap::new();
+        let mut kill_causes: HashMap<String, usize> = HashMap::new();
+        let mut weapons: HashMap<String, WeaponStats> = HashMap::new();
+
+        for record in kill_records {
+            let replace = last_deaths
+                .get(record.victim_id.as_str())
+                .is_none_or(|last| last.kill_time <= record.kill_time);
+            if replace {
+                last_deaths.insert(&record.victim_id, record);
+            }
+            if let Some(killer_id) = record.killer_id.as_deref()
+                && killer_id != record.victim_id
+            {
+                *kills.entry(killer_id).or_default() += 1;
+            }
+            *kill_causes.entry(kill_cause_label(record)).or_default() += 1;
+            if let Some(weapon) = &record.weapon {
+                weapons
+                    .entry(weapon.clone())
+                    .or_insert_with(|| WeaponStats::new(weapon))
+                    .kills += 1;
+            }
+        }
+
+        let mut damage_dealt: HashMap<&str, i64> = HashMap::new();
+        let mut damage_taken: HashMap<&str, i64> = HashMap::new();
+        let mut total_damage = 0;
+        for record in damage_records {
+            let damage = i64::from(record.damage);
+            total_damage += damage;
+            *damage_taken.entry(&record.victim_id).or_default() += damage;
+            if let Some(attacker_id) = record.attacker_id.as_deref() {
+                *damage_dealt.entry(attacker_id).or_default() += damage;
+            }
+            if let Some(weapon) = &record.weapon {
+                let stats = weapons
+                    .entry(weapon.clone())
+                    .or_insert_with(|| WeaponStats::new(weapon));
+                stats.hits += 1;
+                stats.damage += damage;
+            }
+        }
+
+        let mut player_stats: Vec<PlayerGameStats> = players
+            .iter()
+            .map(|player| {
+                let death = last_deaths.get(player.id.as_str());
+                PlayerGameStats {
+                    player_id: player.id.clone(),
+                    player_name: player.name.clone(),
+                    team_id: player.team_id,
+                    placement: 0,
+                    is_alive: death.is_none(),
+                    death_time: death.map(|record| record.kill_time),
+                    death_cause: death.map(|record| kill_cause_label(record)),
+                    killer_id: death.and_then(|record| record.killer_id.clone()),
+                    kills: kills.get(player.id.as_str()).copied().unwrap_or(0),
+                    damage_dealt: damage_dealt.get(player.id.as_str()).copied().unwrap_or(0),
+                    damage_taken: damage_taken.get(player.id.as_str()).copied().unwrap_or(0),
+                }
+            })
+            .collect();
+
+        // 存活者排在最前，死亡者按死亡时间从晚到早排列，同一时刻死亡的玩家名次相同
+        player_stats
+            .sort_by(|a, b| compare_survival(a, b).then_with(|| a.player_name.cmp(&b.player_name)));
+        for index in 0..player_stats.len() {
+            player_stats[index].placement = if index > 0
+                && compare_survival(&player_stats[index - 1], &player_stats[index]).is_eq()
+            {
+                player_stats[index - 1].placement
+            } else {
+                index + 1
+            };
+        }
+
+        let mut kill_causes: Vec<KillCauseStats> = kill_causes
+            .into_iter()
+            .map(|(cause, count)| KillCauseStats { cause, count })
+            .collect();
+        kill_causes.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.cause.cmp(&b.cause)));
+
+        let mut weapons: Vec<WeaponStats> = weapons.into_values().collect();
+        weapons.sort_by(|a, b| {
+            b.hits
+                .cmp(&a.hits)
+                .then_with(|| b.damage.cmp(&a.damage))
+                .then_with(|| b.kills.cmp(&a.kills))
+                .then_with(|| a.weapon.cmp(&b.weapon))
+        });
+
+        Self {
+            game_id: game_id.to_string(),
+            total_players: player_stats.len(),
+            survivors: player_stats.iter().filter(|player| player.is_alive).count(),
+            total_kills: kill_records.len(),
+            total_damage,
+            players: player_stats,
+            kill_causes,
+            weapons,
+        }
+    }
+
+    /// 按游戏分组后分别计算每场游戏的统计，不属于任何给定玩家所在游戏的记录会被忽略
+    pub fn compute_by_game(
+        players: &[StatsPlayer],
+        kill_records: &[KillRecord],
+        damage_records: &[DamageRecord],
+    ) -> Vec<GameStats> {
+        let mut game_players: HashMap<&str, Vec<StatsPlayer>> = HashMap::new();
+        for player in players {
+            game_players
+                .entry(&player.game_id)
+                .or_default()
+                .push(player.clone());
+        }
+
+        game_players
+            .into_iter()
+            .map(|(game_id, players)| {
+                let kill_records: Vec<KillRecord> = kill_records
+                    .iter()
+                    .filter(|record| record.game_id == game_id)
+                    .cloned()
+                    .collect();
+                let damage_records: Vec<DamageRecord> = damage_records
+                    .iter()
+                    .filter(|record| record.game_id == game_id)
+                    .cloned()
+                    .collect();
+                Self::compute(game_id, &players, &kill_records, &damage_records)
+            })
+            .collect()
+    }
+}
+
+impl WeaponStats {
+    fn new(weapon: &str) -> Self {
+        Self {
+            weapon: weapon.to_string(),
+            hits: 0,
+            damage: 0,
+            kills: 0,
+        }
+    }
+}
+
+/// 比较两名玩家的存活情况，存活更久的玩家排在前面
+fn compare_survival(a: &PlayerGameStats, b: &PlayerGameStats) -> Ordering {
+    match (a.death_time, b.death_time) {
+        (None, None) => Ordering::Equal,
+        (None, Some(_)) => Ordering::Less,
+        (Some(_), None) => Ordering::Greater,
+        (Some(a_time), Some(b_time)) => b_time.cmp(&a_time),
+    }
+}
+
+/// 排行榜中单个玩家（按名称）的汇总统计
+#[derive(Debug, Clone, Serialize)]
+pub struct LeaderboardEntry {
+    pub player_name: String,
+    pub games_played: usize,
+    /// 获得第1名（存活到最后）的场次
+    pub wins: usize,
+    pub kills: usize,
+    pub deaths: usize,
+    pub damage_dealt: i64,
+    pub damage_taken: i64,
+    pub best_placement: usize,
+    /// 平均名次（保留两位小数）
+    pub average_placement: f64,
+}
+
+impl LeaderboardEntry {
+    /// 按玩家名称汇总多场游戏的统计
+    ///
+    /// 排序依次为：获胜场次、击杀数（从多到少），平均名次（从小到大），玩家名称。
+    pub fn from_games(games: &[GameStats]) -> Vec<LeaderboardEntry> {
+        let mut entries: HashMap<&str, (LeaderboardEntry, usize)> = HashMap::new();
+        for player in games.iter().flat_map(|game| &game.players) {
+            let (entry, placement_sum) = entries.entry(&player.player_name).or_insert_with(|| {
+                (
+                    LeaderboardEntry {
+                        player_name: player.player_name.clone(),
+                        games_played: 0,
+                        wins: 0,
+                        kills: 0,
+                        deaths: 0,
+                        damage_dealt: 0,
+                        damage_taken: 0,
+                        best_placement: player.placement,
+                        average_placement: 0.0,
+                    },
+                    0,
+                )
+            });
+            entry.games_played += 1;
+            entry.wins += usize::from(player.placement == 1);
+            entry.kills += player.kills;
+            entry.deaths += usize::from(!player.is_alive);
+            entry.damage_dealt += player.damage_dealt;
+            entry.damage_taken += player.damage_taken;
+            entry.best_placement = entry.best_placement.min(player.placement);
+            *placement_sum += player.placement;
+        }
+
+        let mut leaderboard: Vec<LeaderboardEntry> = entries
+            .into_values()
+            .map(|(mut entry, placement_sum)| {
+                let average = placement_sum as f64 / entry.games_played as f64;
+                entry.average_placement = (average * 100.0).round() / 100.0;
+                entry
+            })
+            .collect();
+        leaderboard.sort_by(|a, b| {
+            b.wins
+                .cmp(&a.wins)
+                .then_with(|| b.kills.cmp(&a.kills))
+                .then_with(|| a.average_placement.total_cmp(&b.average_placement))
+                .then_with(|| a.player_name.cmp(&b.player_name))
+        });
+        leaderboard
+    }
+}
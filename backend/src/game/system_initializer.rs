@@ -3,6 +3,7 @@
 
 use crate::admin::{AdminService, CreateAdminRequest};
 use crate::database::DatabasePool;
+use crate::game::credentials::GameCredentials;
 use tracing::info;
 
 /// 系统初始化器
@@ -69,13 +70,28 @@ impl SystemInitializer {
         Ok(())
     }
 
+    /// 将升级前遗留的明文游戏密码替换为哈希
+    async fn hash_plaintext_passwords(
+        pool: &DatabasePool,
+        bcrypt_cost: u32,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let updated = GameCredentials::hash_plaintext_passwords(pool, bcrypt_cost).await?;
+        if updated > 0 {
+            info!("系统初始化：已将 {} 个明文游戏密码转换为哈希", updated);
+        }
+
+        Ok(())
+    }
+
     /// 系统初始化入口
     /// 依次执行游戏状态和管理员账户的初始化逻辑
     pub async fn initialize_system(
         pool: &DatabasePool,
         admin_service: &AdminService,
+        bcrypt_cost: u32,
     ) -> Result<(), Box<dyn std::error::Error>> {
         Self::run_migrations(pool).await?;
+        Self::hash_plaintext_passwords(pool, bcrypt_cost).await?;
         Self::initialize_game_states(pool).await?;
         Self::ensure_default_admin(admin_service).await?;
 
@@ -5,6 +5,7 @@ pub mod database;
 pub mod director;
 pub mod errors;
 pub mod game;
+pub mod metrics;
 pub mod routes;
 pub mod rule_template;
 pub mod websocket;
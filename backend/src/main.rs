@@ -5,6 +5,7 @@ mod database;
 mod director;
 mod errors;
 mod game;
+mod metrics;
 mod routes;
 mod rule_template;
 mod websocket;
@@ -72,17 +73,20 @@ async fn main() {
     let admin_service = AdminService::new(pool.clone(), config.bcrypt_cost);
 
     // 系统初始化
-    if let Err(e) = SystemInitializer::initialize_system(&pool, &admin_service).await {
+    if let Err(e) =
+        SystemInitializer::initialize_system(&pool, &admin_service, config.bcrypt_cost).await
+    {
         eprintln!("系统初始化错误: {}", e);
     }
 
     // 创建 JWT 管理器
-    let jwt_manager = JwtManager::new(&config.jwt_secret, config.jwt_expiration_hours);
+    let jwt_manager = JwtManager::new(&config.jwt_secret, config.jwt_expiration_hours)
+        .with_game_token_expiration(config.game_token_expiration_minutes);
 
     // 创建服务实例
     let auth_service = AuthService::new(pool.clone(), jwt_manager);
-    let director_service = DirectorService::new(pool.clone());
-    let game_service = GameService::new(pool.clone());
+    let director_service = DirectorService::new(pool.clone(), config.bcrypt_cost);
+    let game_service = GameService::new(pool.clone(), config.bcrypt_cost);
     let game_state_manager = GlobalGameStateManager::new(pool.clone());
     let rule_template_service = RuleTemplateService::new(pool.clone());
     // websocket_service 在路由中创建，不需要在这里创建
@@ -93,9 +97,9 @@ async fn main() {
         admin_service,
         director_service,
         game_service,
-        game_state_manager,
+        game_state_manager.clone(),
         rule_template_service,
-        &config.api_prefix,
+        &config,
     )
     .layer(TraceLayer::new_for_http());
 
@@ -108,5 +112,38 @@ async fn main() {
 
     // 运行服务器
     let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
-    axum::serve(listener, app).await.unwrap();
+    axum::serve(listener, app)
+        .with_graceful_shutdown(shutdown_signal(game_state_manager))
+        .await
+        .unwrap();
+}
+
+/// 等待关闭信号（Ctrl+C或SIGTERM），并在停止服务前保存内存中的所有游戏状态
+async fn shutdown_signal(game_state_manager: GlobalGameStateManager) {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("Failed to install Ctrl+C handler");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("Failed to install SIGTERM handler")
+            .recv()
+            .await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+
+    // 连接关闭前先保存，避免WebSocket连接迟迟不断开导致存档丢失
+    info!("shutdown signal received, saving game states");
+    let saved = game_state_manager.save_all_game_states().await;
+    info!("saved {} game states before shutdown", saved);
 }
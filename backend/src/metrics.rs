@@ -0,0 +1,242 @@
+//! Prometheus监控指标
+//! 统一注册服务器、游戏与连接相关的指标，由`/metrics`端点以Prometheus文本格式导出
+
+use prometheus::{
+    Encoder, Histogram, HistogramOpts, HistogramVec, IntCounterVec, IntGauge, IntGaugeVec, Opts,
+    Registry, TextEncoder,
+};
+use std::sync::LazyLock;
+use std::time::Duration;
+
+/// 玩家行动的`actor`标签值
+pub const ACTOR_PLAYER: &str = "player";
+
+/// 导演行动的`actor`标签值
+pub const ACTOR_DIRECTOR: &str = "director";
+
+/// 行动处理耗时与写锁等待时间的分桶（秒）
+const LATENCY_BUCKETS: &[f64] = &[
+    0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// 广播送达连接数量的分桶
+const FANOUT_BUCKETS: &[f64] = &[0.0, 1.0, 2.0, 5.0, 10.0, 20.0, 50.0, 100.0, 200.0];
+
+/// 存档写入耗时的分桶（秒）
+const SAVE_BUCKETS: &[f64] = &[0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5];
+
+static METRICS: LazyLock<Metrics> = LazyLock::new(Metrics::new);
+
+/// 获取全局指标
+pub fn metrics() -> &'static Metrics {
+    &METRICS
+}
+
+/// 服务器监控指标
+pub struct Metrics {
+    registry: Registry,
+    /// 已处理的行动数量：actor、action、outcome
+    actions_total: IntCounterVec,
+    /// 行动处理耗时（含游戏状态写锁等待）：actor
+    action_duration_seconds: HistogramVec,
+    /// 游戏状态写锁等待时间：actor
+    action_lock_wait_seconds: HistogramVec,
+    /// 正在等待写锁或处理中的行动数量：actor
+    actions_in_flight: IntGaugeVec,
+    /// 内存中的游戏数量
+    active_games: IntGauge,
+    /// 打开的WebSocket连接数量：role
+    websocket_connections: IntGaugeVec,
+    /// 每个动作结果送达的WebSocket连接数量
+    broadcast_fanout: Histogram,
+    /// 存档写入耗时
+    save_duration_seconds: Histogram,
+    /// 数据库查询错误数量：component
+    db_query_errors_total: IntCounterVec,
+}
+
+impl Metrics {
+    /// 创建并注册所有指标
+    pub fn new() -> Self {
+        let registry = Registry::new_custom(Some("royale_arena".to_string()), None)
+            .expect("metrics registry prefix is valid");
+
+        let actions_total = IntCounterVec::new(
+            Opts::new(
+                "actions_total",
+                "Processed game actions by type and outcome",
+            ),
+            &["actor", "action", "outcome"],
+        )
+        .expect("metric definition is valid");
+        let action_duration_seconds = HistogramVec::new(
+            HistogramOpts::new(
+                "action_duration_seconds",
+                "Action processing latency including game state write-lock wait",
+            )
+            .buckets(LATENCY_BUCKETS.to_vec()),
+            &["actor"],
+        )
+        .expect("metric definition is valid");
+        let action_lock_wait_seconds = HistogramVec::new(
+            HistogramOpts::new(
+                "action_lock_wait_seconds",
+                "Time spent waiting for the game state write lock",
+            )
+            .buckets(LATENCY_BUCKETS.to_vec()),
+            &["actor"],
+        )
+        .expect("metric definition is valid");
+        let actions_in_flight = IntGaugeVec::new(
+            Opts::new(
+                "actions_in_flight",
+                "Actions waiting for the game state write lock or being processed",
+            ),
+            &["actor"],
+        )
+        .expect("metric definition is valid");
+        let active_games = IntGauge::new("active_games", "Games loaded in memory")
+            .expect("metric definition is valid");
+        let websocket_connections = IntGaugeVec::new(
+            Opts::new(
+                "websocket_connections",
+                "Open WebSocket connections by role",
+            ),
+            &["role"],
+        )
+        .expect("metric definition is valid");
+        let broadcast_fanout = Histogram::with_opts(
+            HistogramOpts::new(
+                "broadcast_fanout",
+                "WebSocket connections reached by each broadcast action result",
+            )
+            .buckets(FANOUT_BUCKETS.to_vec()),
+        )
+        .expect("metric definition is valid");
+        let save_duration_seconds = Histogram::with_opts(
+            HistogramOpts::new(
+                "save_duration_seconds",
+                "Game state save-file write duration",
+            )
+            .buckets(SAVE_BUCKETS.to_vec()),
+        )
+        .expect("metric definition is valid");
+        let db_query_errors_total = IntCounterVec::new(
+            Opts::new(
+                "db_query_errors_total",
+                "Failed database queries by component",
+            ),
+            &["component"],
+        )
+        .expect("metric definition is valid");
+
+        for collector in [
+            Box::new(actions_total.clone()) as Box<dyn prometheus::core::Collector>,
+            Box::new(action_duration_seconds.clone()),
+            Box::new(action_lock_wait_seconds.clone()),
+            Box::new(actions_in_flight.clone()),
+            Box::new(active_games.clone()),
+            Box::new(websocket_connections.clone()),
+            Box::new(broadcast_fanout.clone()),
+            Box::new(save_duration_seconds.clone()),
+            Box::new(db_query_errors_total.clone()),
+        ] {
+            registry
+                .register(collector)
+                .expect("metric names are unique");
+        }
+
+        Self {
+            registry,
+            actions_total,
+            action_duration_seconds,
+            action_lock_wait_seconds,
+            actions_in_flight,
+            active_games,
+            websocket_connections,
+            broadcast_fanout,
+            save_duration_seconds,
+            db_query_errors_total,
+        }
+    }
+
+    /// 记录一次行动的处理结果与总耗时
+    pub fn record_action(&self, actor: &str, action: &str, success: bool, duration: Duration) {
+        let outcome = if success { "success" } else { "error" };
+        self.actions_total
+            .with_label_values(&[actor, action, outcome])
+            .inc();
+        self.action_duration_seconds
+            .with_label_values(&[actor])
+            .observe(duration.as_secs_f64());
+    }
+
+    /// 记录一次游戏状态写锁等待时间
+    pub fn observe_lock_wait(&self, actor: &str, wait: Duration) {
+        self.action_lock_wait_seconds
+            .with_label_values(&[actor])
+            .observe(wait.as_secs_f64());
+    }
+
+    /// 标记一个行动开始处理，返回的守卫释放时标记处理结束
+    pub fn action_in_flight(&self, actor: &str) -> InFlightGuard {
+        let gauge = self.actions_in_flight.with_label_values(&[actor]);
+        gauge.inc();
+        InFlightGuard { gauge }
+    }
+
+    /// 设置内存中的游戏数量
+    pub fn set_active_games(&self, count: usize) {
+        self.active_games.set(count as i64);
+    }
+
+    /// 设置指定角色的WebSocket连接数量
+    pub fn set_websocket_connections(&self, role: &str, count: usize) {
+        self.websocket_connections
+            .with_label_values(&[role])
+            .set(count as i64);
+    }
+
+    /// 记录一个动作结果送达的连接数量
+    pub fn observe_broadcast_fanout(&self, connections: usize) {
+        self.broadcast_fanout.observe(connections as f64);
+    }
+
+    /// 记录一次存档写入耗时
+    pub fn observe_save_duration(&self, duration: Duration) {
+        self.save_duration_seconds.observe(duration.as_secs_f64());
+    }
+
+    /// 记录一次数据库查询错误
+    pub fn record_db_error(&self, component: &str) {
+        self.db_query_errors_total
+            .with_label_values(&[component])
+            .inc();
+    }
+
+    /// 以Prometheus文本格式导出所有指标
+    pub fn render(&self) -> String {
+        let mut buffer = Vec::new();
+        TextEncoder::new()
+            .encode(&self.registry.gather(), &mut buffer)
+            .expect("text encoding into a Vec does not fail");
+        String::from_utf8(buffer).unwrap_or_default()
+    }
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 处理中行动计数的守卫
+pub struct InFlightGuard {
+    gauge: IntGauge,
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        self.gauge.dec();
+    }
+}
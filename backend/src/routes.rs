@@ -1,29 +1,47 @@
 use axum::{
-    Router, middleware,
+    Router,
+    extract::{DefaultBodyLimit, State},
+    http::header,
+    middleware,
+    response::IntoResponse,
     routing::{delete, get, post, put},
 };
+use prometheus::TEXT_FORMAT;
+use std::time::Duration;
 use yawc::IncomingUpgrade;
 
 use crate::admin::service::AdminService;
 use crate::admin::{
     admin_login, create_admin, delete_admin, list_admins, reset_admin_password, update_admin,
 };
-use crate::auth::{AuthService, jwt_auth_middleware, super_admin_middleware};
+use crate::auth::{
+    AuthService, actor_session_middleware, director_session_middleware, game_session_middleware,
+    jwt_auth_middleware, super_admin_middleware,
+};
+use crate::config::AppConfig;
 use crate::director::{
-    DirectorService, batch_add_players, batch_delete_players, edit_game, get_players,
-    list_save_files, manual_save, update_game_status,
+    DirectorService, batch_add_players, batch_delete_players, edit_game, get_action_journal,
+    get_connection_metrics, get_players, list_save_files, manual_save, replay_game_state,
+    reset_player_password, rewind_game, update_game_status, update_spectator_code,
 };
 use crate::game::global_game_state_manager::GlobalGameStateManager;
 use crate::game::{
-    GameLogService, GameService, authenticate_game, create_game, delete_game,
-    delete_game_kill_records, delete_game_logs, get_director_kill_records, get_director_messages,
-    get_game_with_rules, get_games, get_games_rules_config_view, get_player_kill_records,
-    get_player_messages, update_game,
+    GameLogService, GameService, authenticate_game, authenticate_spectator, create_game,
+    delete_game, delete_game_kill_records, delete_game_logs, export_game_archive,
+    get_director_kill_records, get_director_messages, get_game_stats, get_game_with_rules,
+    get_games, get_games_rules_config_view, get_leaderboard, get_player_kill_records,
+    get_player_messages, import_game_archive, update_game,
 };
+use crate::metrics::metrics;
 use crate::rule_template::{
-    RuleTemplateService, create_template, delete_template, get_templates, update_template,
+    RuleTemplateService, create_template, delete_template, diff_template_revisions,
+    export_all_templates, export_template, get_template_revisions, get_templates, import_templates,
+    rollback_template, update_template, validate_rules_config,
 };
+use crate::websocket::game_phase_scheduler::GamePhaseScheduler;
 use crate::websocket::global_connection_manager::GlobalConnectionManager;
+use crate::websocket::heartbeat::HeartbeatConfig;
+use crate::websocket::models::{ConnectionType, WebSocketConnectQuery};
 use crate::websocket::service::WebSocketService;
 
 #[derive(Clone)]
@@ -36,6 +54,7 @@ pub struct AppState {
     pub game_state_manager: GlobalGameStateManager,
     pub rule_template_service: RuleTemplateService,
     pub global_connection_manager: GlobalConnectionManager,
+    pub game_phase_scheduler: GamePhaseScheduler,
 }
 
 pub fn create_routes(
@@ -45,10 +64,16 @@ pub fn create_routes(
     game_service: GameService,
     game_state_manager: GlobalGameStateManager,
     rule_template_service: RuleTemplateService,
-    api_prefix: &str,
+    config: &AppConfig,
 ) -> Router {
     let game_log_service = GameLogService::new(director_service.pool.clone());
-    let global_connection_manager = GlobalConnectionManager::new();
+    let global_connection_manager = GlobalConnectionManager::new()
+        .with_queue_capacity(config.ws_outbound_queue_capacity)
+        .with_heartbeat(HeartbeatConfig {
+            ping_interval: Duration::from_secs(config.ws_ping_interval_seconds),
+            idle_timeout: Duration::from_secs(config.ws_idle_timeout_seconds),
+        });
+    let game_phase_scheduler = GamePhaseScheduler::new();
 
     let app_state = AppState {
         auth_service: auth_service.clone(),
@@ -59,34 +84,25 @@ pub fn create_routes(
         game_state_manager,
         rule_template_service,
         global_connection_manager,
+        game_phase_scheduler,
     };
 
     // 公开路由（不需要认证）
-    let public_routes =
-        Router::new()
-            .route("/health", get(health_check))
-            .route("/admin/login", post(admin_login))
-            // 规则模版公开查询接口
-            .route("/rule-templates", get(get_templates))
-            // 公开游戏查询接口
-            .route("/games", get(get_games))
-            .route("/games/rules-config", get(get_games_rules_config_view))
-            .route("/games/{game_id}", get(get_game_with_rules))
-            // WebSocket连接端点
-            .route(
-                "/ws/{game_id}",
-                get(
-                    |ws: IncomingUpgrade,
-                     state: axum::extract::State<AppState>,
-                     path: axum::extract::Path<String>,
-                     query: axum::extract::Query<
-                        crate::websocket::models::WebSocketAuthRequest,
-                    >| {
-                        WebSocketService::handle_websocket_upgrade(ws, state, path, query)
-                    },
-                ),
-            )
-            .with_state(app_state.clone());
+    let public_routes = Router::new()
+        .route("/health", get(health_check))
+        .route("/metrics", get(metrics_handler))
+        .route("/admin/login", post(admin_login))
+        // 规则模版公开查询接口
+        .route("/rule-templates", get(get_templates))
+        .route("/rule-templates/validate", post(validate_rules_config))
+        // 公开游戏查询接口
+        .route("/games", get(get_games))
+        .route("/games/rules-config", get(get_games_rules_config_view))
+        .route("/games/{game_id}", get(get_game_with_rules))
+        // 赛后统计与跨游戏排行榜
+        .route("/games/{game_id}/stats", get(get_game_stats))
+        .route("/leaderboard", get(get_leaderboard))
+        .with_state(app_state.clone());
 
     // 需要超级管理员权限的路由
     let admin_routes = Router::new()
@@ -121,6 +137,14 @@ pub fn create_routes(
         .route("/{game_id}/logs", delete(delete_game_logs))
         // 新增的删除游戏击杀记录路由
         .route("/{game_id}/kill-records", delete(delete_game_kill_records))
+        // 游戏归档导出与导入（归档可能包含大量日志与存档快照，放宽请求体大小限制）
+        .route("/{game_id}/archive", get(export_game_archive))
+        .route(
+            "/import",
+            post(import_game_archive).layer(DefaultBodyLimit::max(
+                config.archive_import_max_mb * 1024 * 1024,
+            )),
+        )
         .layer(middleware::from_fn_with_state(
             auth_service.clone(),
             jwt_auth_middleware,
@@ -131,15 +155,21 @@ pub fn create_routes(
     let rule_template_admin_routes = Router::new()
         .route("/", get(get_templates))
         .route("/", post(create_template))
+        .route("/export", get(export_all_templates))
+        .route("/import", post(import_templates))
         .route("/{id}", put(update_template))
         .route("/{id}", delete(delete_template))
+        .route("/{id}/export", get(export_template))
+        .route("/{id}/revisions", get(get_template_revisions))
+        .route("/{id}/revisions/diff", get(diff_template_revisions))
+        .route("/{id}/rollback", post(rollback_template))
         .layer(middleware::from_fn_with_state(
-            auth_service,
+            auth_service.clone(),
             jwt_auth_middleware,
         ))
         .with_state(app_state.clone());
 
-    // 导演接口路由（无需JWT认证，使用导演密码验证）
+    // 导演接口路由（使用导演的游戏会话令牌验证）
     let director_routes = Router::new()
         .route(
             "/game/{game_id}/players",
@@ -147,12 +177,26 @@ pub fn create_routes(
                 .get(get_players)
                 .delete(batch_delete_players),
         )
+        // 重置演员密码接口（同时吊销该演员的游戏会话令牌）
+        .route(
+            "/game/{game_id}/players/{player_id}/password",
+            put(reset_player_password),
+        )
+        // 设置观战码接口（同时吊销已签发的观战令牌）
+        .route("/game/{game_id}/spectator-code", put(update_spectator_code))
         // 导演更新游戏状态接口
         .route("/game/{game_id}/status", put(update_game_status))
         // 手动存盘接口
         .route("/game/{game_id}/save", post(manual_save))
         // 查询存档文件列表接口
         .route("/game/{game_id}/saves", get(list_save_files))
+        // 回退到指定存档接口（同时清理存档之后的日志与击杀记录）
+        .route("/game/{game_id}/rewind", post(rewind_game))
+        // 行动日志查询与重放接口
+        .route("/game/{game_id}/journal", get(get_action_journal))
+        .route("/game/{game_id}/replay", get(replay_game_state))
+        // WebSocket连接统计接口
+        .route("/game/{game_id}/connections", get(get_connection_metrics))
         // 导演编辑游戏接口
         .route("/game/{game_id}/edit", put(edit_game))
         // 新增的导演查询日志接口
@@ -162,25 +206,57 @@ pub fn create_routes(
             "/game/{game_id}/director/kill-records",
             get(get_director_kill_records),
         )
+        .route_layer(middleware::from_fn(director_session_middleware))
+        .route_layer(middleware::from_fn_with_state(
+            auth_service.clone(),
+            game_session_middleware,
+        ))
         .with_state(app_state.clone());
 
-    // 玩家接口路由（无需JWT认证，使用玩家密码验证）
+    // 玩家接口路由（使用玩家的游戏会话令牌验证）
     let player_routes = Router::new()
         // 获取玩家消息记录接口
         .route(
             "/game/{game_id}/player/{player_id}/messages",
-            post(get_player_messages),
+            get(get_player_messages),
         )
         // 新增的获取玩家击杀记录接口
         .route(
             "/game/{game_id}/player/{player_id}/kill-records",
-            post(get_player_kill_records),
+            get(get_player_kill_records),
+        )
+        .route_layer(middleware::from_fn(actor_session_middleware))
+        .route_layer(middleware::from_fn_with_state(
+            auth_service.clone(),
+            game_session_middleware,
+        ))
+        .with_state(app_state.clone());
+
+    // WebSocket连接端点（导演与玩家均使用游戏会话令牌，通过token查询参数传递）
+    let websocket_routes = Router::new()
+        .route(
+            "/ws/{game_id}",
+            get(
+                |ws: IncomingUpgrade,
+                 state: axum::extract::State<AppState>,
+                 path: axum::extract::Path<String>,
+                 query: axum::extract::Query<WebSocketConnectQuery>,
+                 claims: axum::Extension<crate::game::models::GameSessionClaims>| {
+                    WebSocketService::handle_websocket_upgrade(ws, state, path, query, claims)
+                },
+            ),
         )
+        .route_layer(middleware::from_fn_with_state(
+            auth_service,
+            game_session_middleware,
+        ))
         .with_state(app_state.clone());
 
-    // 游戏认证路由
+    // 游戏认证路由（验证密码并签发游戏会话令牌）
     let auth_routes = Router::new()
-        .route("/game/{game_id}/auth", get(authenticate_game))
+        .route("/game/{game_id}/auth", post(authenticate_game))
+        // 观战认证路由（验证观战码并签发观战令牌）
+        .route("/game/{game_id}/spectate", post(authenticate_spectator))
         .with_state(app_state.clone());
 
     // 组装 API 路由
@@ -192,9 +268,11 @@ pub fn create_routes(
         .merge(public_routes)
         .merge(director_routes)
         .merge(player_routes)
+        .merge(websocket_routes)
         .merge(auth_routes);
 
     // 最终应用路由
+    let api_prefix = config.api_prefix.as_str();
     if api_prefix.is_empty() || api_prefix == "/" {
         api_routes
     } else {
@@ -206,3 +284,23 @@ pub fn create_routes(
 async fn health_check() -> &'static str {
     "OK"
 }
+
+// Prometheus监控指标端点，游戏与连接数量在采集时更新
+async fn metrics_handler(State(state): State<AppState>) -> impl IntoResponse {
+    let metrics = metrics();
+    metrics.set_active_games(state.game_state_manager.active_game_count());
+    let connection_counts = state.global_connection_manager.connection_counts().await;
+    for connection_type in [
+        ConnectionType::Actor,
+        ConnectionType::Director,
+        ConnectionType::Spectator,
+    ] {
+        let count = connection_counts
+            .get(&connection_type)
+            .copied()
+            .unwrap_or(0);
+        metrics.set_websocket_connections(connection_type.label(), count);
+    }
+
+    ([(header::CONTENT_TYPE, TEXT_FORMAT)], metrics.render())
+}
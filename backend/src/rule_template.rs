@@ -1,3 +1,5 @@
+pub mod bundle;
+pub mod diff;
 pub mod errors;
 pub mod handlers;
 pub mod models;
@@ -0,0 +1,229 @@
+//! 规则模版导入导出包
+//! 自描述的JSON文件格式，用于在不同服务器之间迁移规则模版（单个或全部）
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+use super::models::{RuleTemplate, RuleTemplateResponse};
+use crate::game::rules_validator::{
+    RuleValidationIssue, RulesValidationReport, RulesValidator, ValidationSeverity,
+};
+
+/// 导出包格式标识
+pub const BUNDLE_FORMAT: &str = "royale-arena/rule-templates";
+
+/// 当前导出包格式版本
+pub const BUNDLE_FORMAT_VERSION: u32 = 1;
+
+/// 规则模版导出包
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleTemplateBundle {
+    /// 格式标识，固定为`royale-arena/rule-templates`
+    pub format: String,
+    /// 格式版本
+    pub format_version: u32,
+    /// 导出时间
+    pub exported_at: DateTime<Utc>,
+    /// 包含的模版
+    pub templates: Vec<RuleTemplateBundleEntry>,
+}
+
+/// 导出包中的单个模版
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleTemplateBundleEntry {
+    pub template_name: String,
+    pub description: Option<String>,
+    #[serde(default = "default_is_active")]
+    pub is_active: bool,
+    /// 导出时的规则配置版本号（仅供参考，导入时不沿用）
+    #[serde(default)]
+    pub revision: Option<i32>,
+    pub rules_config: serde_json::Value,
+}
+
+fn default_is_active() -> bool {
+    true
+}
+
+/// 导入方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportAction {
+    /// 新建模版
+    Created,
+    /// 覆盖同名模版
+    Overwritten,
+}
+
+/// 单个模版的导入结果
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RuleTemplateImportResult {
+    pub action: ImportAction,
+    pub template: RuleTemplateResponse,
+}
+
+/// 查询参数：导入选项
+#[derive(Debug, Default, Deserialize)]
+pub struct ImportRuleTemplatesQuery {
+    /// 是否覆盖同名模版（默认存在同名模版时拒绝导入）
+    #[serde(default)]
+    pub overwrite: bool,
+}
+
+impl From<RuleTemplate> for RuleTemplateBundleEntry {
+    fn from(template: RuleTemplate) -> Self {
+        Self {
+            template_name: template.template_name,
+            description: template.description,
+            is_active: template.is_active,
+            revision: Some(template.current_revision),
+            rules_config: template.rules_config,
+        }
+    }
+}
+
+impl RuleTemplateBundle {
+    /// 以当前格式版本创建导出包
+    pub fn new(templates: Vec<RuleTemplateBundleEntry>) -> Self {
+        Self {
+            format: BUNDLE_FORMAT.to_string(),
+            format_version: BUNDLE_FORMAT_VERSION,
+            exported_at: Utc::now(),
+            templates,
+        }
+    }
+
+    /// 校验导出包：格式、模版名称以及每个模版的规则配置
+    ///
+    /// 规则配置问题的路径以`$.templates[i].rules_config`为前缀。
+    pub fn validate(&self) -> RulesValidationReport {
+        let mut issues = Vec::new();
+        let mut error = |path: String, message: String| {
+            issues.push(RuleValidationIssue {
+                path,
+                severity: ValidationSeverity::Error,
+                message,
+            })
+        };
+
+        if self.format != BUNDLE_FORMAT {
+            error(
+                "$.format".to_string(),
+                format!("不是规则模版导出包（应为 {}）", BUNDLE_FORMAT),
+            );
+        }
+        if self.format_version == 0 || self.format_version > BUNDLE_FORMAT_VERSION {
+            error(
+                "$.format_version".to_string(),
+                format!(
+                    "不支持的格式版本 {}（支持 1 至 {}）",
+                    self.format_version, BUNDLE_FORMAT_VERSION
+                ),
+            );
+        }
+        if self.templates.is_empty() {
+            error("$.templates".to_string(), "导出包中没有模版".to_string());
+        }
+
+        let mut names = HashSet::new();
+        for (index, entry) in self.templates.iter().enumerate() {
+            let name_path = format!("$.templates[{}].template_name", index);
+            let name = entry.template_name.trim();
+            if name.is_empty() {
+                error(name_path, "模版名称不能为空".to_string());
+            } else if entry.template_name.len() > 100 {
+                error(name_path, "模版名称不能超过100个字符".to_string());
+            } else if !names.insert(entry.template_name.clone()) {
+                error(
+                    name_path,
+                    format!("模版名称 {} 在导出包中重复", entry.template_name),
+                );
+            }
+        }
+
+        for (index, entry) in self.templates.iter().enumerate() {
+            let prefix = format!("$.templates[{}].rules_config", index);
+            let report = RulesValidator::validate(&entry.rules_config);
+            issues.extend(report.issues.into_iter().map(|issue| RuleValidationIssue {
+                path: issue.path.replacen('$', &prefix, 1),
+                ..issue
+            }));
+        }
+
+        let valid = !issues
+            .iter()
+            .any(|issue| issue.severity == ValidationSeverity::Error);
+        RulesValidationReport { valid, issues }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn entry(name: &str, rules_config: serde_json::Value) -> RuleTemplateBundleEntry {
+        RuleTemplateBundleEntry {
+            template_name: name.to_string(),
+            description: None,
+            is_active: true,
+            revision: None,
+            rules_config,
+        }
+    }
+
+    fn complete_rules(places: serde_json::Value) -> serde_json::Value {
+        json!({
+            "map": {"places": places, "safe_places": ["码头"]},
+            "player": {
+                "max_life": 100,
+                "max_strength": 100,
+                "daily_life_recovery": 0,
+                "daily_strength_recovery": 0,
+                "search_cooldown": 0,
+                "max_backpack_items": 5,
+                "unarmed_damage": 10
+            },
+            "action_costs": {
+                "move": 5, "search": 10, "pick": 5, "attack": 15,
+                "equip": 5, "use": 5, "throw": 5, "deliver": 10
+            },
+            "rest_mode": {"life_recovery": 0, "strength_recovery": 0, "max_moves": 1}
+        })
+    }
+
+    #[test]
+    fn test_bundle_validation() {
+        let rules = complete_rules(json!(["码头"]));
+        let bundle = RuleTemplateBundle::new(vec![entry("经典", rules.clone())]);
+        assert!(bundle.validate().valid);
+
+        // 往返序列化保持自描述字段
+        let value = serde_json::to_value(&bundle).unwrap();
+        assert_eq!(value["format"], BUNDLE_FORMAT);
+        assert_eq!(value["format_version"], BUNDLE_FORMAT_VERSION);
+        let parsed: RuleTemplateBundle = serde_json::from_value(value).unwrap();
+        assert_eq!(parsed.templates[0].rules_config, rules);
+
+        // 格式、重复名称与规则配置错误全部报告，规则路径带模版前缀
+        let mut invalid = RuleTemplateBundle::new(vec![
+            entry("经典", rules.clone()),
+            entry("经典", complete_rules(json!([]))),
+        ]);
+        invalid.format = "other".to_string();
+        invalid.format_version = 2;
+        let report = invalid.validate();
+        assert!(!report.valid);
+        let paths: Vec<&str> = report.errors().map(|issue| issue.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "$.format",
+                "$.format_version",
+                "$.templates[1].template_name",
+                "$.templates[1].rules_config.map.safe_places[0]",
+            ]
+        );
+    }
+}
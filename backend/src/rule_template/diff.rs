@@ -0,0 +1,165 @@
+//! 规则配置差异比较
+//! 逐字段比较两个版本的规则JSON，输出带JSON路径的结构化差异
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+
+/// 差异类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JsonDiffOp {
+    /// 新版本中新增的字段或数组元素
+    Added,
+    /// 新版本中删除的字段或数组元素
+    Removed,
+    /// 值发生变化
+    Changed,
+}
+
+/// 单条差异
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JsonDiffEntry {
+    /// 差异所在的JSON路径（如`$.player.max_life`）
+    pub path: String,
+    /// 差异类型
+    pub op: JsonDiffOp,
+    /// 旧值（新增时为空）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old_value: Option<JsonValue>,
+    /// 新值（删除时为空）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_value: Option<JsonValue>,
+}
+
+/// 比较两个JSON值，返回从旧值到新值的全部差异
+///
+/// 对象按键递归比较（按键名排序输出），数组按下标递归比较，其他类型整体比较。
+pub fn diff_json(old: &JsonValue, new: &JsonValue) -> Vec<JsonDiffEntry> {
+    let mut entries = Vec::new();
+    diff_value("$", old, new, &mut entries);
+    entries
+}
+
+fn diff_value(path: &str, old: &JsonValue, new: &JsonValue, entries: &mut Vec<JsonDiffEntry>) {
+    match (old, new) {
+        (JsonValue::Object(old_map), JsonValue::Object(new_map)) => {
+            let mut keys: Vec<&String> = old_map.keys().chain(new_map.keys()).collect();
+            keys.sort();
+            keys.dedup();
+
+            for key in keys {
+                let child_path = format!("{}.{}", path, key);
+                match (old_map.get(key), new_map.get(key)) {
+                    (Some(old_value), Some(new_value)) => {
+                        diff_value(&child_path, old_value, new_value, entries)
+                    }
+                    (Some(old_value), None) => entries.push(JsonDiffEntry {
+                        path: child_path,
+                        op: JsonDiffOp::Removed,
+                        old_value: Some(old_value.clone()),
+                        new_value: None,
+                    }),
+                    (None, Some(new_value)) => entries.push(JsonDiffEntry {
+                        path: child_path,
+                        op: JsonDiffOp::Added,
+                        old_value: None,
+                        new_value: Some(new_value.clone()),
+                    }),
+                    (None, None) => {}
+                }
+            }
+        }
+        (JsonValue::Array(old_items), JsonValue::Array(new_items)) => {
+            for index in 0..old_items.len().max(new_items.len()) {
+                let child_path = format!("{}[{}]", path, index);
+                match (old_items.get(index), new_items.get(index)) {
+                    (Some(old_value), Some(new_value)) => {
+                        diff_value(&child_path, old_value, new_value, entries)
+                    }
+                    (Some(old_value), None) => entries.push(JsonDiffEntry {
+                        path: child_path,
+                        op: JsonDiffOp::Removed,
+                        old_value: Some(old_value.clone()),
+                        new_value: None,
+                    }),
+                    (None, Some(new_value)) => entries.push(JsonDiffEntry {
+                        path: child_path,
+                        op: JsonDiffOp::Added,
+                        old_value: None,
+                        new_value: Some(new_value.clone()),
+                    }),
+                    (None, None) => {}
+                }
+            }
+        }
+        _ => {
+            if old != new {
+                entries.push(JsonDiffEntry {
+                    path: path.to_string(),
+                    op: JsonDiffOp::Changed,
+                    old_value: Some(old.clone()),
+                    new_value: Some(new.clone()),
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_diff_json() {
+        let old = json!({
+            "map": {"places": ["码头", "工厂"], "safe_places": []},
+            "player": {"max_life": 100, "max_strength": 100},
+            "teammate_behavior": 0
+        });
+        let new = json!({
+            "map": {"places": ["码头", "学校", "灯塔"], "safe_places": []},
+            "player": {"max_life": 120, "max_strength": 100},
+            "game_flow": {"day_duration": 300}
+        });
+
+        let entries = diff_json(&old, &new);
+        assert_eq!(
+            entries,
+            vec![
+                JsonDiffEntry {
+                    path: "$.game_flow".to_string(),
+                    op: JsonDiffOp::Added,
+                    old_value: None,
+                    new_value: Some(json!({"day_duration": 300})),
+                },
+                JsonDiffEntry {
+                    path: "$.map.places[1]".to_string(),
+                    op: JsonDiffOp::Changed,
+                    old_value: Some(json!("工厂")),
+                    new_value: Some(json!("学校")),
+                },
+                JsonDiffEntry {
+                    path: "$.map.places[2]".to_string(),
+                    op: JsonDiffOp::Added,
+                    old_value: None,
+                    new_value: Some(json!("灯塔")),
+                },
+                JsonDiffEntry {
+                    path: "$.player.max_life".to_string(),
+                    op: JsonDiffOp::Changed,
+                    old_value: Some(json!(100)),
+                    new_value: Some(json!(120)),
+                },
+                JsonDiffEntry {
+                    path: "$.teammate_behavior".to_string(),
+                    op: JsonDiffOp::Removed,
+                    old_value: Some(json!(0)),
+                    new_value: None,
+                },
+            ]
+        );
+
+        assert!(diff_json(&old, &old).is_empty());
+    }
+}
@@ -6,18 +6,29 @@ use axum::{
 use serde_json::json;
 use thiserror::Error;
 
+use crate::game::rules_validator::RulesValidationReport;
+
 #[derive(Debug, Error)]
 #[allow(dead_code)]
 pub enum RuleTemplateError {
     #[error("模版名称已存在")]
     NameAlreadyExists,
 
+    #[error("导入的模版名称已存在: {}", .0.join(", "))]
+    ImportNameConflict(Vec<String>),
+
     #[error("模版不存在")]
     TemplateNotFound,
 
+    #[error("模版版本不存在")]
+    RevisionNotFound,
+
     #[error("无效的JSON配置: {0}")]
     InvalidRulesConfig(String),
 
+    #[error("规则配置校验失败: {}", .0.summary())]
+    RulesValidationFailed(RulesValidationReport),
+
     #[error("模版名称不能为空")]
     EmptyTemplateName,
 
@@ -38,25 +49,37 @@ impl IntoResponse for RuleTemplateError {
     fn into_response(self) -> Response {
         let (status, error_message) = match self {
             RuleTemplateError::NameAlreadyExists => (StatusCode::CONFLICT, "模版名称已存在"),
+            RuleTemplateError::ImportNameConflict(_) => (StatusCode::CONFLICT, "模版名称已存在"),
             RuleTemplateError::TemplateNotFound => (StatusCode::NOT_FOUND, "模版不存在"),
+            RuleTemplateError::RevisionNotFound => (StatusCode::NOT_FOUND, "模版版本不存在"),
             RuleTemplateError::InvalidRulesConfig(_) => (StatusCode::BAD_REQUEST, "无效的规则配置"),
+            RuleTemplateError::RulesValidationFailed(_) => {
+                (StatusCode::BAD_REQUEST, "规则配置校验失败")
+            }
             RuleTemplateError::EmptyTemplateName => (StatusCode::BAD_REQUEST, "模版名称不能为空"),
             RuleTemplateError::ValidationError(_) => (StatusCode::BAD_REQUEST, "参数验证失败"),
             RuleTemplateError::UuidError(_) => (StatusCode::BAD_REQUEST, "无效的ID格式"),
             RuleTemplateError::JsonError(_) => (StatusCode::BAD_REQUEST, "JSON格式错误"),
             RuleTemplateError::DatabaseError(_) => {
+                crate::metrics::metrics().record_db_error("rule_template");
                 (StatusCode::INTERNAL_SERVER_ERROR, "服务器内部错误")
             }
         };
 
-        let body = Json(json!({
+        let mut body = json!({
             "success": false,
             "error": {
                 "message": error_message,
                 "details": self.to_string()
             }
-        }));
+        });
+        if let RuleTemplateError::RulesValidationFailed(report) = &self {
+            body["error"]["issues"] = json!(report.issues);
+        }
+        if let RuleTemplateError::ImportNameConflict(conflicts) = &self {
+            body["error"]["conflicts"] = json!(conflicts);
+        }
 
-        (status, body).into_response()
+        (status, Json(body)).into_response()
     }
 }
@@ -1,14 +1,18 @@
 use axum::{
     Json,
     extract::{Path, Query, State},
-    http::StatusCode,
+    http::{StatusCode, header},
     response::IntoResponse,
 };
 use serde_json::json;
 
 use super::{
+    bundle::{ImportRuleTemplatesQuery, RuleTemplateBundle},
     errors::RuleTemplateError,
-    models::{CreateRuleTemplateRequest, GetTemplatesQuery, UpdateRuleTemplateRequest},
+    models::{
+        CreateRuleTemplateRequest, GetTemplatesQuery, RevisionDiffQuery,
+        RollbackRuleTemplateRequest, UpdateRuleTemplateRequest, ValidateRulesConfigRequest,
+    },
 };
 use crate::routes::AppState;
 
@@ -70,6 +74,127 @@ pub async fn update_template(
     Ok((StatusCode::OK, response))
 }
 
+/// 获取游戏规则模版的版本历史
+/// GET /api/admin/rule-templates/{template_id}/revisions
+pub async fn get_template_revisions(
+    State(app_state): State<AppState>,
+    Path(template_id): Path<String>,
+) -> Result<impl IntoResponse, RuleTemplateError> {
+    let revisions = app_state
+        .rule_template_service
+        .get_revisions(&template_id)
+        .await?;
+
+    let response = Json(json!({
+        "success": true,
+        "data": revisions
+    }));
+
+    Ok((StatusCode::OK, response))
+}
+
+/// 比较游戏规则模版两个版本的差异
+/// GET /api/admin/rule-templates/{template_id}/revisions/diff?from=1&to=2
+pub async fn diff_template_revisions(
+    State(app_state): State<AppState>,
+    Path(template_id): Path<String>,
+    Query(params): Query<RevisionDiffQuery>,
+) -> Result<impl IntoResponse, RuleTemplateError> {
+    let diff = app_state
+        .rule_template_service
+        .diff_revisions(&template_id, params.from, params.to)
+        .await?;
+
+    let response = Json(json!({
+        "success": true,
+        "data": diff
+    }));
+
+    Ok((StatusCode::OK, response))
+}
+
+/// 将游戏规则模版回滚到指定版本
+/// POST /api/admin/rule-templates/{template_id}/rollback
+pub async fn rollback_template(
+    State(app_state): State<AppState>,
+    Path(template_id): Path<String>,
+    Json(request): Json<RollbackRuleTemplateRequest>,
+) -> Result<impl IntoResponse, RuleTemplateError> {
+    let template = app_state
+        .rule_template_service
+        .rollback_template(template_id, request)
+        .await?;
+
+    let response = Json(json!({
+        "success": true,
+        "data": template
+    }));
+
+    Ok((StatusCode::OK, response))
+}
+
+/// 导出单个游戏规则模版为导出包文件
+/// GET /api/admin/rule-templates/{template_id}/export
+pub async fn export_template(
+    State(app_state): State<AppState>,
+    Path(template_id): Path<String>,
+) -> Result<impl IntoResponse, RuleTemplateError> {
+    let bundle = app_state
+        .rule_template_service
+        .export_template(&template_id)
+        .await?;
+
+    let disposition = format!(
+        "attachment; filename=\"rule-template-{}.json\"",
+        template_id
+    );
+    Ok((
+        StatusCode::OK,
+        [(header::CONTENT_DISPOSITION, disposition)],
+        Json(bundle),
+    ))
+}
+
+/// 导出全部游戏规则模版为一个导出包文件
+/// GET /api/admin/rule-templates/export
+pub async fn export_all_templates(
+    State(app_state): State<AppState>,
+) -> Result<impl IntoResponse, RuleTemplateError> {
+    let bundle = app_state
+        .rule_template_service
+        .export_all_templates()
+        .await?;
+
+    Ok((
+        StatusCode::OK,
+        [(
+            header::CONTENT_DISPOSITION,
+            "attachment; filename=\"rule-templates.json\"".to_string(),
+        )],
+        Json(bundle),
+    ))
+}
+
+/// 导入游戏规则模版导出包
+/// POST /api/admin/rule-templates/import?overwrite=true
+pub async fn import_templates(
+    State(app_state): State<AppState>,
+    Query(params): Query<ImportRuleTemplatesQuery>,
+    Json(bundle): Json<RuleTemplateBundle>,
+) -> Result<impl IntoResponse, RuleTemplateError> {
+    let results = app_state
+        .rule_template_service
+        .import_bundle(bundle, params.overwrite)
+        .await?;
+
+    let response = Json(json!({
+        "success": true,
+        "data": results
+    }));
+
+    Ok((StatusCode::OK, response))
+}
+
 /// 获取游戏规则模版（统一接口）
 /// GET /api/rule-templates
 /// 支持查询参数：id, is_active, search
@@ -89,3 +214,21 @@ pub async fn get_templates(
 
     Ok((StatusCode::OK, response))
 }
+
+/// 校验规则配置，返回全部问题及其JSON路径与严重程度
+/// POST /api/rule-templates/validate
+pub async fn validate_rules_config(
+    State(app_state): State<AppState>,
+    Json(request): Json<ValidateRulesConfigRequest>,
+) -> Result<impl IntoResponse, RuleTemplateError> {
+    let report = app_state
+        .rule_template_service
+        .validate_rules_config(&request.rules_config);
+
+    let response = Json(json!({
+        "success": true,
+        "data": report
+    }));
+
+    Ok((StatusCode::OK, response))
+}
@@ -2,6 +2,8 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 
+use super::diff::JsonDiffEntry;
+
 /// 数据库实体：规则模版
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct RuleTemplate {
@@ -10,10 +12,20 @@ pub struct RuleTemplate {
     pub description: Option<String>, // TEXT type in database
     pub is_active: bool,
     pub rules_config: serde_json::Value,
+    pub current_revision: i32,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+/// 数据库实体：规则模版版本
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct RuleTemplateRevision {
+    pub template_id: String,
+    pub revision: i32,
+    pub rules_config: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+}
+
 /// API响应：规则模版详情（统一格式）
 #[derive(Debug, Serialize, Deserialize)]
 pub struct RuleTemplateResponse {
@@ -22,10 +34,20 @@ pub struct RuleTemplateResponse {
     pub description: Option<String>,
     pub is_active: bool,
     pub rules_config: serde_json::Value,
+    pub current_revision: i32,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+/// API响应：两个版本之间的规则配置差异
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RuleTemplateRevisionDiff {
+    pub template_id: String,
+    pub from_revision: i32,
+    pub to_revision: i32,
+    pub changes: Vec<JsonDiffEntry>,
+}
+
 /// API请求：创建规则模版
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CreateRuleTemplateRequest {
@@ -44,6 +66,12 @@ pub struct UpdateRuleTemplateRequest {
     pub rules_config: Option<serde_json::Value>,
 }
 
+/// API请求：校验规则配置
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ValidateRulesConfigRequest {
+    pub rules_config: serde_json::Value,
+}
+
 impl From<RuleTemplate> for RuleTemplateResponse {
     fn from(template: RuleTemplate) -> Self {
         Self {
@@ -52,6 +80,7 @@ impl From<RuleTemplate> for RuleTemplateResponse {
             description: template.description,
             is_active: template.is_active,
             rules_config: template.rules_config,
+            current_revision: template.current_revision,
             created_at: template.created_at,
             updated_at: template.updated_at,
         }
@@ -118,6 +147,20 @@ pub struct GetTemplatesQuery {
     pub search: Option<String>,
 }
 
+/// 查询参数：版本差异
+#[derive(Debug, Deserialize)]
+pub struct RevisionDiffQuery {
+    pub from: i32,
+    pub to: i32,
+}
+
+/// API请求：回滚规则模版
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RollbackRuleTemplateRequest {
+    /// 要恢复的版本号（回滚会以该版本的配置创建一个新版本）
+    pub revision: i32,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -251,6 +294,7 @@ mod tests {
             description: Some("测试描述".to_string()),
             is_active: true,
             rules_config: json!({"test": "config"}),
+            current_revision: 2,
             created_at: chrono::Utc::now(),
             updated_at: chrono::Utc::now(),
         };
@@ -261,5 +305,6 @@ mod tests {
         assert_eq!(response.description, template.description);
         assert_eq!(response.is_active, template.is_active);
         assert_eq!(response.rules_config, template.rules_config);
+        assert_eq!(response.current_revision, template.current_revision);
     }
 }
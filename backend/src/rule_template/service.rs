@@ -1,10 +1,15 @@
-use sqlx::MySqlPool;
+use sqlx::{MySql, MySqlPool, Transaction};
 use uuid::Uuid;
 
+use crate::game::rules_validator::{RulesValidationReport, RulesValidator};
+
 use super::{
+    bundle::{ImportAction, RuleTemplateBundle, RuleTemplateBundleEntry, RuleTemplateImportResult},
+    diff::diff_json,
     errors::RuleTemplateError,
     models::{
-        CreateRuleTemplateRequest, RuleTemplate, RuleTemplateResponse, UpdateRuleTemplateRequest,
+        CreateRuleTemplateRequest, RollbackRuleTemplateRequest, RuleTemplate, RuleTemplateResponse,
+        RuleTemplateRevision, RuleTemplateRevisionDiff, UpdateRuleTemplateRequest,
     },
 };
 
@@ -23,16 +28,53 @@ impl RuleTemplateService {
         &self,
         request: CreateRuleTemplateRequest,
     ) -> Result<RuleTemplateResponse, RuleTemplateError> {
+        let mut tx = self.pool.begin().await?;
+        let template_id = Self::insert_template(&mut tx, &request).await?;
+        tx.commit().await?;
+
+        // 查询创建的模版并返回
+        let template = self
+            .get_template_by_id(&template_id)
+            .await?
+            .ok_or(RuleTemplateError::TemplateNotFound)?;
+
+        Ok(template.into())
+    }
+
+    /// 更新规则模版
+    pub async fn update_template(
+        &self,
+        template_id: String,
+        request: UpdateRuleTemplateRequest,
+    ) -> Result<RuleTemplateResponse, RuleTemplateError> {
+        let mut tx = self.pool.begin().await?;
+        Self::apply_update(&mut tx, &template_id, &request).await?;
+        tx.commit().await?;
+
+        // 查询更新后的模版并返回
+        let template = self
+            .get_template_by_id(&template_id)
+            .await?
+            .ok_or(RuleTemplateError::TemplateNotFound)?;
+
+        Ok(template.into())
+    }
+
+    /// 在事务中校验并插入规则模版，同时记录首个版本，返回模版ID
+    async fn insert_template(
+        tx: &mut Transaction<'_, MySql>,
+        request: &CreateRuleTemplateRequest,
+    ) -> Result<String, RuleTemplateError> {
         // 验证请求数据
         request
             .validate()
             .map_err(RuleTemplateError::ValidationError)?;
 
+        // 校验规则配置内容
+        Self::check_rules_config(&request.rules_config)?;
+
         // 检查模版名称唯一性
-        if !self
-            .check_name_uniqueness(&request.template_name, None)
-            .await?
-        {
+        if !Self::check_name_uniqueness(tx, &request.template_name, None).await? {
             return Err(RuleTemplateError::NameAlreadyExists);
         }
 
@@ -40,11 +82,10 @@ impl RuleTemplateService {
         let template_id = Uuid::new_v4().to_string();
         let is_active = request.is_active.unwrap_or(true);
 
-        // 插入数据库
         sqlx::query(
             r#"
-            INSERT INTO rule_templates (id, template_name, description, is_active, rules_config)
-            VALUES (?, ?, ?, ?, ?)
+            INSERT INTO rule_templates (id, template_name, description, is_active, rules_config, current_revision)
+            VALUES (?, ?, ?, ?, ?, 1)
             "#,
         )
         .bind(&template_id)
@@ -52,37 +93,45 @@ impl RuleTemplateService {
         .bind(&request.description)
         .bind(is_active)
         .bind(&request.rules_config)
-        .execute(&self.pool)
+        .execute(&mut **tx)
         .await?;
+        Self::insert_revision(tx, &template_id, 1, &request.rules_config).await?;
 
-        // 查询创建的模版并返回
-        let template = self
-            .get_template_by_id(&template_id)
-            .await?
-            .ok_or(RuleTemplateError::TemplateNotFound)?;
-
-        Ok(template.into())
+        Ok(template_id)
     }
 
-    /// 更新规则模版
-    pub async fn update_template(
-        &self,
-        template_id: String,
-        request: UpdateRuleTemplateRequest,
-    ) -> Result<RuleTemplateResponse, RuleTemplateError> {
+    /// 在事务中校验并更新规则模版，规则配置发生变化时记录新版本
+    async fn apply_update(
+        tx: &mut Transaction<'_, MySql>,
+        template_id: &str,
+        request: &UpdateRuleTemplateRequest,
+    ) -> Result<(), RuleTemplateError> {
         // 验证请求数据
         request
             .validate()
             .map_err(RuleTemplateError::ValidationError)?;
 
-        // 检查模版是否存在
-        if !self.check_template_exists(&template_id).await? {
-            return Err(RuleTemplateError::TemplateNotFound);
+        // 校验规则配置内容
+        if let Some(ref rules_config) = request.rules_config {
+            Self::check_rules_config(rules_config)?;
         }
 
+        // 锁定模版行读取当前版本号，避免并发更新分配相同的版本号
+        let current = Self::lock_template(tx, template_id)
+            .await?
+            .ok_or(RuleTemplateError::TemplateNotFound)?;
+
+        // 规则配置发生变化时创建新版本
+        let new_revision = match request.rules_config {
+            Some(ref config) if *config != current.rules_config => {
+                Some(current.current_revision + 1)
+            }
+            _ => None,
+        };
+
         // 如果要更新名称，检查名称唯一性
         if let Some(ref name) = request.template_name {
-            if !self.check_name_uniqueness(name, Some(&template_id)).await? {
+            if !Self::check_name_uniqueness(tx, name, Some(template_id)).await? {
                 return Err(RuleTemplateError::NameAlreadyExists);
             }
         }
@@ -102,6 +151,9 @@ impl RuleTemplateService {
         if request.rules_config.is_some() {
             update_fields.push("rules_config = ?");
         }
+        if new_revision.is_some() {
+            update_fields.push("current_revision = ?");
+        }
 
         if update_fields.is_empty() {
             return Err(RuleTemplateError::ValidationError(
@@ -130,18 +182,18 @@ impl RuleTemplateService {
         if let Some(ref config) = request.rules_config {
             query = query.bind(config);
         }
+        if let Some(revision) = new_revision {
+            query = query.bind(revision);
+        }
 
-        query = query.bind(&template_id);
-
-        query.execute(&self.pool).await?;
+        query = query.bind(template_id);
 
-        // 查询更新后的模版并返回
-        let template = self
-            .get_template_by_id(&template_id)
-            .await?
-            .ok_or(RuleTemplateError::TemplateNotFound)?;
+        query.execute(&mut **tx).await?;
+        if let (Some(revision), Some(config)) = (new_revision, &request.rules_config) {
+            Self::insert_revision(tx, template_id, revision, config).await?;
+        }
 
-        Ok(template.into())
+        Ok(())
     }
 
     /// 删除规则模版
@@ -168,7 +220,7 @@ impl RuleTemplateService {
         // 如果提供了ID，优先按ID查询
         if let Some(ref template_id) = id {
             let query = sqlx::query_as::<_, RuleTemplate>(
-                "SELECT id, template_name, description, is_active, rules_config, created_at, updated_at FROM rule_templates WHERE id = ?"
+                "SELECT id, template_name, description, is_active, rules_config, current_revision, created_at, updated_at FROM rule_templates WHERE id = ?"
             ).bind(template_id);
 
             let templates = query.fetch_all(&self.pool).await?;
@@ -184,7 +236,7 @@ impl RuleTemplateService {
             conditions.push("template_name LIKE ?");
         }
 
-        let base_sql = "SELECT id, template_name, description, is_active, rules_config, created_at, updated_at FROM rule_templates";
+        let base_sql = "SELECT id, template_name, description, is_active, rules_config, current_revision, created_at, updated_at FROM rule_templates";
 
         let templates = if conditions.is_empty() {
             let sql = format!("{} ORDER BY created_at DESC", base_sql);
@@ -210,13 +262,217 @@ impl RuleTemplateService {
         Ok(templates.into_iter().map(|t| t.into()).collect())
     }
 
+    /// 获取规则模版的全部版本（按版本号倒序）
+    pub async fn get_revisions(
+        &self,
+        template_id: &str,
+    ) -> Result<Vec<RuleTemplateRevision>, RuleTemplateError> {
+        if !self.check_template_exists(template_id).await? {
+            return Err(RuleTemplateError::TemplateNotFound);
+        }
+
+        let revisions = sqlx::query_as::<_, RuleTemplateRevision>(
+            "SELECT template_id, revision, rules_config, created_at FROM rule_template_revisions WHERE template_id = ? ORDER BY revision DESC",
+        )
+        .bind(template_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(revisions)
+    }
+
+    /// 比较规则模版两个版本之间的规则配置差异
+    pub async fn diff_revisions(
+        &self,
+        template_id: &str,
+        from: i32,
+        to: i32,
+    ) -> Result<RuleTemplateRevisionDiff, RuleTemplateError> {
+        let from_revision = self.get_revision(template_id, from).await?;
+        let to_revision = self.get_revision(template_id, to).await?;
+
+        Ok(RuleTemplateRevisionDiff {
+            template_id: template_id.to_string(),
+            from_revision: from,
+            to_revision: to,
+            changes: diff_json(&from_revision.rules_config, &to_revision.rules_config),
+        })
+    }
+
+    /// 将规则模版回滚到指定版本（以该版本的配置创建新版本，保留完整历史）
+    pub async fn rollback_template(
+        &self,
+        template_id: String,
+        request: RollbackRuleTemplateRequest,
+    ) -> Result<RuleTemplateResponse, RuleTemplateError> {
+        // 在事务中锁定模版行读取当前版本号，避免并发更新分配相同的版本号
+        let mut tx = self.pool.begin().await?;
+        let current = Self::lock_template(&mut tx, &template_id)
+            .await?
+            .ok_or(RuleTemplateError::TemplateNotFound)?;
+        let target = self.get_revision(&template_id, request.revision).await?;
+
+        // 已是目标配置时无需创建新版本
+        if target.rules_config != current.rules_config {
+            Self::check_rules_config(&target.rules_config)?;
+
+            let new_revision = current.current_revision + 1;
+            sqlx::query(
+                "UPDATE rule_templates SET rules_config = ?, current_revision = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?",
+            )
+            .bind(&target.rules_config)
+            .bind(new_revision)
+            .bind(&template_id)
+            .execute(&mut *tx)
+            .await?;
+            Self::insert_revision(&mut tx, &template_id, new_revision, &target.rules_config)
+                .await?;
+        }
+        tx.commit().await?;
+
+        let template = self
+            .get_template_by_id(&template_id)
+            .await?
+            .ok_or(RuleTemplateError::TemplateNotFound)?;
+
+        Ok(template.into())
+    }
+
+    /// 校验规则配置，返回全部问题（不修改数据）
+    pub fn validate_rules_config(&self, rules_config: &serde_json::Value) -> RulesValidationReport {
+        RulesValidator::validate(rules_config)
+    }
+
+    /// 规则配置存在错误级别的问题时拒绝保存
+    fn check_rules_config(rules_config: &serde_json::Value) -> Result<(), RuleTemplateError> {
+        let report = RulesValidator::validate(rules_config);
+        if report.valid {
+            Ok(())
+        } else {
+            Err(RuleTemplateError::RulesValidationFailed(report))
+        }
+    }
+
+    /// 导出单个规则模版
+    pub async fn export_template(
+        &self,
+        template_id: &str,
+    ) -> Result<RuleTemplateBundle, RuleTemplateError> {
+        let template = self
+            .get_template_by_id(template_id)
+            .await?
+            .ok_or(RuleTemplateError::TemplateNotFound)?;
+
+        Ok(RuleTemplateBundle::new(vec![template.into()]))
+    }
+
+    /// 导出全部规则模版（按名称排序）
+    pub async fn export_all_templates(&self) -> Result<RuleTemplateBundle, RuleTemplateError> {
+        let templates = sqlx::query_as::<_, RuleTemplate>(
+            "SELECT id, template_name, description, is_active, rules_config, current_revision, created_at, updated_at FROM rule_templates ORDER BY template_name",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(RuleTemplateBundle::new(
+            templates
+                .into_iter()
+                .map(RuleTemplateBundleEntry::from)
+                .collect(),
+        ))
+    }
+
+    /// 导入规则模版导出包
+    ///
+    /// 先校验整个导出包，再检查名称冲突：未开启覆盖时存在同名模版则整体拒绝，
+    /// 开启覆盖时同名模版更新为导出包中的内容（规则变化时生成新版本）。
+    pub async fn import_bundle(
+        &self,
+        bundle: RuleTemplateBundle,
+        overwrite: bool,
+    ) -> Result<Vec<RuleTemplateImportResult>, RuleTemplateError> {
+        let report = bundle.validate();
+        if !report.valid {
+            return Err(RuleTemplateError::RulesValidationFailed(report));
+        }
+
+        // 全部模版在同一事务中写入，任一模版失败时整个导出包都不生效
+        let mut tx = self.pool.begin().await?;
+
+        // 检查名称冲突
+        let mut existing_ids = Vec::with_capacity(bundle.templates.len());
+        for entry in &bundle.templates {
+            existing_ids.push(Self::find_template_id_by_name(&mut tx, &entry.template_name).await?);
+        }
+        if !overwrite {
+            let conflicts: Vec<String> = bundle
+                .templates
+                .iter()
+                .zip(&existing_ids)
+                .filter(|(_, id)| id.is_some())
+                .map(|(entry, _)| entry.template_name.clone())
+                .collect();
+            if !conflicts.is_empty() {
+                return Err(RuleTemplateError::ImportNameConflict(conflicts));
+            }
+        }
+
+        let mut imported = Vec::with_capacity(bundle.templates.len());
+        for (entry, existing_id) in bundle.templates.into_iter().zip(existing_ids) {
+            match existing_id {
+                Some(template_id) => {
+                    Self::apply_update(
+                        &mut tx,
+                        &template_id,
+                        &UpdateRuleTemplateRequest {
+                            template_name: None,
+                            description: entry.description,
+                            is_active: Some(entry.is_active),
+                            rules_config: Some(entry.rules_config),
+                        },
+                    )
+                    .await?;
+                    imported.push((ImportAction::Overwritten, template_id));
+                }
+                None => {
+                    let template_id = Self::insert_template(
+                        &mut tx,
+                        &CreateRuleTemplateRequest {
+                            template_name: entry.template_name,
+                            description: entry.description,
+                            is_active: Some(entry.is_active),
+                            rules_config: entry.rules_config,
+                        },
+                    )
+                    .await?;
+                    imported.push((ImportAction::Created, template_id));
+                }
+            }
+        }
+        tx.commit().await?;
+
+        let mut results = Vec::with_capacity(imported.len());
+        for (action, template_id) in imported {
+            let template = self
+                .get_template_by_id(&template_id)
+                .await?
+                .ok_or(RuleTemplateError::TemplateNotFound)?;
+            results.push(RuleTemplateImportResult {
+                action,
+                template: template.into(),
+            });
+        }
+
+        Ok(results)
+    }
+
     /// 根据ID获取单个模版
     async fn get_template_by_id(
         &self,
         template_id: &str,
     ) -> Result<Option<RuleTemplate>, RuleTemplateError> {
         let template = sqlx::query_as::<_, RuleTemplate>(
-            "SELECT id, template_name, description, is_active, rules_config, created_at, updated_at FROM rule_templates WHERE id = ?"
+            "SELECT id, template_name, description, is_active, rules_config, current_revision, created_at, updated_at FROM rule_templates WHERE id = ?"
         )
         .bind(template_id)
         .fetch_optional(&self.pool)
@@ -225,6 +481,58 @@ impl RuleTemplateService {
         Ok(template)
     }
 
+    /// 在事务中读取并锁定模版行，事务结束前其他事务无法修改该模版
+    async fn lock_template(
+        tx: &mut Transaction<'_, MySql>,
+        template_id: &str,
+    ) -> Result<Option<RuleTemplate>, sqlx::Error> {
+        sqlx::query_as::<_, RuleTemplate>(
+            "SELECT id, template_name, description, is_active, rules_config, current_revision, created_at, updated_at FROM rule_templates WHERE id = ? FOR UPDATE",
+        )
+        .bind(template_id)
+        .fetch_optional(&mut **tx)
+        .await
+    }
+
+    /// 获取规则模版的指定版本
+    async fn get_revision(
+        &self,
+        template_id: &str,
+        revision: i32,
+    ) -> Result<RuleTemplateRevision, RuleTemplateError> {
+        if !self.check_template_exists(template_id).await? {
+            return Err(RuleTemplateError::TemplateNotFound);
+        }
+
+        sqlx::query_as::<_, RuleTemplateRevision>(
+            "SELECT template_id, revision, rules_config, created_at FROM rule_template_revisions WHERE template_id = ? AND revision = ?",
+        )
+        .bind(template_id)
+        .bind(revision)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(RuleTemplateError::RevisionNotFound)
+    }
+
+    /// 在事务中写入规则模版版本
+    async fn insert_revision(
+        tx: &mut Transaction<'_, MySql>,
+        template_id: &str,
+        revision: i32,
+        rules_config: &serde_json::Value,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "INSERT INTO rule_template_revisions (template_id, revision, rules_config) VALUES (?, ?, ?)",
+        )
+        .bind(template_id)
+        .bind(revision)
+        .bind(rules_config)
+        .execute(&mut **tx)
+        .await?;
+
+        Ok(())
+    }
+
     /// 检查模版是否存在
     async fn check_template_exists(&self, template_id: &str) -> Result<bool, sqlx::Error> {
         let count: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM rule_templates WHERE id = ?")
@@ -235,9 +543,23 @@ impl RuleTemplateService {
         Ok(count.0 > 0)
     }
 
+    /// 根据名称查找模版ID
+    async fn find_template_id_by_name(
+        tx: &mut Transaction<'_, MySql>,
+        name: &str,
+    ) -> Result<Option<String>, sqlx::Error> {
+        let row: Option<(String,)> =
+            sqlx::query_as("SELECT id FROM rule_templates WHERE template_name = ?")
+                .bind(name)
+                .fetch_optional(&mut **tx)
+                .await?;
+
+        Ok(row.map(|(id,)| id))
+    }
+
     /// 检查模版名称唯一性
     async fn check_name_uniqueness(
-        &self,
+        tx: &mut Transaction<'_, MySql>,
         name: &str,
         exclude_id: Option<&str>,
     ) -> Result<bool, sqlx::Error> {
@@ -247,12 +569,12 @@ impl RuleTemplateService {
             )
             .bind(name)
             .bind(id)
-            .fetch_one(&self.pool)
+            .fetch_one(&mut **tx)
             .await?
         } else {
             sqlx::query_as("SELECT COUNT(*) FROM rule_templates WHERE template_name = ?")
                 .bind(name)
-                .fetch_one(&self.pool)
+                .fetch_one(&mut **tx)
                 .await?
         };
 
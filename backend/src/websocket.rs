@@ -4,7 +4,14 @@
 pub mod actions;
 pub mod broadcaster;
 pub mod game_connection_manager;
+pub mod game_phase_scheduler;
 pub mod global_connection_manager;
+pub mod heartbeat;
 pub mod message_formatter;
 pub mod models;
+pub mod outbound_queue;
+pub mod presence;
+pub mod replay_buffer;
 pub mod service;
+pub mod spectator_feed;
+pub mod state_patch;
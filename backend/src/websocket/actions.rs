@@ -2,10 +2,16 @@ pub mod game_state_common;
 
 pub mod director_common_actions;
 pub mod director_night_settlement_action;
+pub mod director_undo_action;
+pub mod director_vote_settlement_action;
+pub mod game_phase_actions;
 pub mod player_attack_action;
 pub mod player_common_actions;
+pub mod player_gift_action;
+pub mod player_trade_action;
 pub mod player_upgrade_action;
 pub mod player_use_action;
+pub mod player_vote_action;
 pub mod utils;
 
 pub mod director_action_scheduler;
@@ -2,6 +2,7 @@
 //!
 //! 负责导演行动的分发调度，导演拥有特殊权限，无需验证前置条件
 
+use crate::websocket::actions::director_undo_action::{DirectorUndoSnapshot, NON_UNDOABLE_ACTIONS};
 use crate::websocket::models::{ActionResults, AirdropItem, GameState, ItemDeletionItem};
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
@@ -45,6 +46,9 @@ pub struct DirectorActionParams {
     pub shop_listing_id: Option<String>,
     pub price: Option<i32>,
     pub quantity: Option<i32>,
+
+    /// 撤销操作
+    pub steps: Option<usize>, // 撤销最近的导演行动数量，默认1
 }
 
 impl DirectorActionParams {
@@ -59,7 +63,36 @@ impl DirectorActionParams {
 pub struct DirectorActionScheduler;
 
 impl DirectorActionScheduler {
-    /// 调度导演行动
+    /// 调度器支持的导演行动类型
+    pub const SUPPORTED_ACTIONS: &'static [&'static str] = &[
+        "set_night_start_time",
+        "set_night_end_time",
+        "modify_place",
+        "set_destroy_places",
+        "batch_airdrop",
+        "batch_item_deletion",
+        "weather",
+        "life",
+        "strength",
+        "coins",
+        "move_player",
+        "add_player_item",
+        "remove_player_item",
+        "broadcast",
+        "message_to_player",
+        "night_settlement",
+        "vote_settlement",
+        "shop_list_item",
+        "shop_delist_item",
+        "undo",
+    ];
+
+    /// 判断导演行动类型是否受支持
+    pub fn supports(action_type: &str) -> bool {
+        Self::SUPPORTED_ACTIONS.contains(&action_type)
+    }
+
+    /// 调度导演行动，并为可撤销的导演行动记录修改前后的状态差异
     ///
     /// # 参数
     /// - `game_state`: 可变游戏状态引用
@@ -73,6 +106,26 @@ impl DirectorActionScheduler {
         game_state: &mut GameState,
         action_type: &str,
         action_params: DirectorActionParams,
+    ) -> Result<ActionResults, String> {
+        if action_type == "undo" {
+            let steps = action_params.steps.unwrap_or(1);
+            return game_state.handle_director_undo(steps);
+        }
+
+        let snapshot = (!NON_UNDOABLE_ACTIONS.contains(&action_type))
+            .then(|| DirectorUndoSnapshot::capture(game_state));
+        let result = Self::dispatch_action(game_state, action_type, action_params);
+        if let (Ok(_), Some(snapshot)) = (&result, snapshot) {
+            game_state.record_director_undo(action_type, &snapshot);
+        }
+        result
+    }
+
+    /// 按行动类型分发到对应的处理方法
+    fn dispatch_action(
+        game_state: &mut GameState,
+        action_type: &str,
+        action_params: DirectorActionParams,
     ) -> Result<ActionResults, String> {
         match action_type {
             "set_night_start_time" => {
@@ -215,6 +268,8 @@ impl DirectorActionScheduler {
                 game_state.handle_night_settlement(rest_enabled)
             }
 
+            "vote_settlement" => game_state.handle_vote_settlement(),
+
             "shop_list_item" => {
                 let item_name = action_params
                     .item_name
@@ -710,7 +710,11 @@ impl GameState {
             broadcast_players,
             format!("导演向全部玩家广播消息: {}", message),
             true,
-        );
+        )
+        .with_spectator_event(serde_json::json!({
+            "kind": "broadcast",
+            "message": message,
+        }));
         action_result.broadcast_to_all = true;
 
         Ok(action_result.as_results())
@@ -4,11 +4,12 @@
 //! 持续流血：所有身上bleed_damage大于0的玩家受到一次流血伤害；如果此次流血导致死亡，结算杀死玩家`kill_player`事件（无击杀者）。
 //! 加体力逻辑：为所有存活玩家执行每日自动恢复，生命值`daily_health_recovery`，`daily_strength_recovery`。
 //! 每日清除：清空`next_night_destroyed_places`。调用daily_reset()清除玩家状态。
+//! 结算标记：设置`night_settled`，自动调度在夜晚结束时据此跳过已结算夜晚的自动结算。
 //! 休养逻辑：如果夜晚结束时`rest_mode`仍然为真，额外恢复规则`rest_mode`中设置的生命值`life_recovery`，体力值`strength_recovery`。
 //! 休养备注：（除向导演发送消息外）玩家进行任何移动以外的行动时，将`rest_mode`置为假；玩家进行移动时累加`rest_moves_used`，当`rest_moves_used`超过规则设置中的`rest_mode.max_moves`时，将`rest_mode`置为假。
 
 use crate::websocket::actions::utils::format_delta;
-use crate::websocket::models::{ActionResult, ActionResults, GameState};
+use crate::websocket::models::{ActionResult, ActionResults, DamageEvent, GameState};
 
 impl GameState {
     /// 调整地点状态
@@ -41,20 +42,27 @@ impl GameState {
             }
         });
 
-        let mut results = vec![ActionResult::new_system_message(
-            data,
-            vec![],
-            format!(
-                "导演调整地点 {} 状态为 {}",
-                place_name,
-                if is_destroyed {
-                    "已摧毁"
-                } else {
-                    "未摧毁"
-                }
-            ),
-            true,
-        )];
+        let mut results = vec![
+            ActionResult::new_system_message(
+                data,
+                vec![],
+                format!(
+                    "导演调整地点 {} 状态为 {}",
+                    place_name,
+                    if is_destroyed {
+                        "已摧毁"
+                    } else {
+                        "未摧毁"
+                    }
+                ),
+                true,
+            )
+            .with_spectator_event(serde_json::json!({
+                "kind": "place_status",
+                "place": place_name,
+                "is_destroyed": is_destroyed,
+            })),
+        ];
 
         if is_destroyed {
             for player_id in players_to_kill {
@@ -151,12 +159,19 @@ impl GameState {
                 "bleed_damage": bleed_damage,
             });
 
-            results.push(ActionResult::new_system_message(
-                data,
-                vec![player_id.clone()],
-                log_message,
-                true,
-            ));
+            // 流血伤害计入施加流血效果的玩家
+            let damage_event = DamageEvent {
+                attacker_id: player.bleed_inflictor.clone(),
+                victim_id: player_id.clone(),
+                damage: before_life - after_life,
+                cause: "流血".to_string(),
+                weapon: None,
+            };
+
+            results.push(
+                ActionResult::new_system_message(data, vec![player_id.clone()], log_message, true)
+                    .with_damage_events(vec![damage_event]),
+            );
         }
 
         let bleed_death_names: Vec<String> = pending_bleed_deaths
@@ -320,6 +335,7 @@ impl GameState {
             place.reset_nightly_item_uses(&self.rule_engine);
         }
         self.next_night_destroyed_places.clear();
+        self.night_settled = true;
 
         // ===== 总结输出 =====
         let destroyed_count = destroyed_places.len();
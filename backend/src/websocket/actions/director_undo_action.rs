@@ -0,0 +1,401 @@
+//! GameState 导演行动撤销实现
+//! 记录方式：每次导演行动执行前保存玩家、地点与全局字段的快照，执行后与快照逐字段比较，
+//! 仅保留被修改字段的修改前后取值，作为一条撤销记录压入撤销栈（最多保留`DIRECTOR_UNDO_DEPTH`条）。
+//! 撤销逻辑：从最近的记录开始逐条恢复修改前的取值；若某个字段在导演行动之后又被其他行动修改，
+//! 则停止撤销，避免覆盖之后发生的变化。
+//! 撤销只恢复游戏状态，已写入的日志与击杀记录保留，撤销本身也会写入日志。
+
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value as JsonValue};
+use std::collections::HashMap;
+
+use crate::websocket::models::{ActionResult, ActionResults, GameState, Place, Player};
+
+/// 撤销栈最多保留的导演行动数量
+pub const DIRECTOR_UNDO_DEPTH: usize = 20;
+
+/// 不记录撤销信息的导演行动（结算涉及随机判定与每日重置，消息无法撤回）
+pub const NON_UNDOABLE_ACTIONS: &[&str] = &[
+    "night_settlement",
+    "vote_settlement",
+    "broadcast",
+    "message_to_player",
+];
+
+/// 被修改的对象
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum UndoTarget {
+    /// 游戏全局字段（天气、夜晚时间、缩圈计划、商店、投票）
+    Game,
+    /// 玩家
+    Player { player_id: String },
+    /// 地点
+    Place { place_name: String },
+}
+
+/// 单个字段的修改
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FieldChange {
+    pub target: UndoTarget,
+    pub field: String,
+    /// 导演行动执行前的取值
+    pub before: JsonValue,
+    /// 导演行动执行后的取值
+    pub after: JsonValue,
+}
+
+/// 一次导演行动的撤销记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DirectorUndoRecord {
+    /// 导演行动在行动日志中的序号
+    pub sequence: u64,
+    /// 导演行动类型
+    pub action: String,
+    /// 导演行动发生时间
+    pub timestamp: DateTime<Utc>,
+    /// 被修改的字段
+    pub changes: Vec<FieldChange>,
+}
+
+/// 导演行动执行前的状态快照
+pub struct DirectorUndoSnapshot {
+    globals: Map<String, JsonValue>,
+    players: HashMap<String, Player>,
+    places: HashMap<String, Place>,
+}
+
+/// 将对象序列化为字段映射
+fn to_fields<T: Serialize>(value: &T) -> Map<String, JsonValue> {
+    match serde_json::to_value(value) {
+        Ok(JsonValue::Object(fields)) => fields,
+        _ => Map::new(),
+    }
+}
+
+/// 比较两组字段，返回发生变化的字段
+fn diff_fields(
+    target: &UndoTarget,
+    before: &Map<String, JsonValue>,
+    after: &Map<String, JsonValue>,
+    changes: &mut Vec<FieldChange>,
+) {
+    for (field, after_value) in after {
+        let before_value = before.get(field).cloned().unwrap_or(JsonValue::Null);
+        if &before_value != after_value {
+            changes.push(FieldChange {
+                target: target.clone(),
+                field: field.clone(),
+                before: before_value,
+                after: after_value.clone(),
+            });
+        }
+    }
+}
+
+/// 按字段恢复对象中的取值
+fn restore_fields<T: Serialize + DeserializeOwned>(
+    value: &mut T,
+    changes: &[&FieldChange],
+) -> Result<(), String> {
+    let mut fields = to_fields(value);
+    for change in changes {
+        fields.insert(change.field.clone(), change.before.clone());
+    }
+    *value = serde_json::from_value(JsonValue::Object(fields))
+        .map_err(|e| format!("Failed to restore state: {}", e))?;
+    Ok(())
+}
+
+impl DirectorUndoSnapshot {
+    /// 在导演行动执行前保存快照
+    pub fn capture(game_state: &GameState) -> Self {
+        Self {
+            globals: game_state.undo_globals(),
+            players: game_state.players.clone(),
+            places: game_state.places.clone(),
+        }
+    }
+
+    /// 与导演行动执行后的状态比较，返回被修改的字段
+    pub fn diff(&self, game_state: &GameState) -> Vec<FieldChange> {
+        let mut changes = Vec::new();
+        diff_fields(
+            &UndoTarget::Game,
+            &self.globals,
+            &game_state.undo_globals(),
+            &mut changes,
+        );
+
+        let mut player_ids: Vec<&String> = game_state.players.keys().collect();
+        player_ids.sort();
+        for player_id in player_ids {
+            if let Some(before) = self.players.get(player_id) {
+                diff_fields(
+                    &UndoTarget::Player {
+                        player_id: player_id.clone(),
+                    },
+                    &to_fields(before),
+                    &to_fields(&game_state.players[player_id]),
+                    &mut changes,
+                );
+            }
+        }
+
+        let mut place_names: Vec<&String> = game_state.places.keys().collect();
+        place_names.sort();
+        for place_name in place_names {
+            if let Some(before) = self.places.get(place_name) {
+                diff_fields(
+                    &UndoTarget::Place {
+                        place_name: place_name.clone(),
+                    },
+                    &to_fields(before),
+                    &to_fields(&game_state.places[place_name]),
+                    &mut changes,
+                );
+            }
+        }
+
+        changes
+    }
+}
+
+impl GameState {
+    /// 可被导演行动修改并撤销的全局字段
+    fn undo_globals(&self) -> Map<String, JsonValue> {
+        let globals = serde_json::json!({
+            "weather": self.weather,
+            "night_start_time": self.night_start_time,
+            "night_end_time": self.night_end_time,
+            "next_night_destroyed_places": self.next_night_destroyed_places,
+            "shop": self.shop,
+            "votes": self.votes,
+        });
+        match globals {
+            JsonValue::Object(fields) => fields,
+            _ => Map::new(),
+        }
+    }
+
+    /// 恢复全局字段
+    fn restore_global(&mut self, change: &FieldChange) -> Result<(), String> {
+        fn parse<T: DeserializeOwned>(value: &JsonValue) -> Result<T, String> {
+            serde_json::from_value(value.clone())
+                .map_err(|e| format!("Failed to restore state: {}", e))
+        }
+
+        match change.field.as_str() {
+            "weather" => self.weather = parse(&change.before)?,
+            "night_start_time" => self.night_start_time = parse(&change.before)?,
+            "night_end_time" => self.night_end_time = parse(&change.before)?,
+            "next_night_destroyed_places" => {
+                self.next_night_destroyed_places = parse(&change.before)?
+            }
+            "shop" => self.shop = parse(&change.before)?,
+            "votes" => self.votes = parse(&change.before)?,
+            field => return Err(format!("Unknown game field: {}", field)),
+        }
+        Ok(())
+    }
+
+    /// 读取被修改字段的当前取值
+    fn current_field_value(&self, target: &UndoTarget, field: &str) -> Option<JsonValue> {
+        match target {
+            UndoTarget::Game => self.undo_globals().remove(field),
+            UndoTarget::Player { player_id } => self
+                .players
+                .get(player_id)
+                .and_then(|player| to_fields(player).remove(field)),
+            UndoTarget::Place { place_name } => self
+                .places
+                .get(place_name)
+                .and_then(|place| to_fields(place).remove(field)),
+        }
+    }
+
+    /// 导演行动执行后记录撤销信息（未修改任何状态的行动不记录）
+    pub fn record_director_undo(&mut self, action: &str, snapshot: &DirectorUndoSnapshot) {
+        let changes = snapshot.diff(self);
+        if changes.is_empty() {
+            return;
+        }
+
+        self.director_undo.push(DirectorUndoRecord {
+            sequence: self.journal_sequence,
+            action: action.to_string(),
+            timestamp: self.current_time(),
+            changes,
+        });
+        if self.director_undo.len() > DIRECTOR_UNDO_DEPTH {
+            let overflow = self.director_undo.len() - DIRECTOR_UNDO_DEPTH;
+            self.director_undo.drain(..overflow);
+        }
+    }
+
+    /// 撤销最近的若干次导演行动
+    pub fn handle_director_undo(&mut self, steps: usize) -> Result<ActionResults, String> {
+        if steps == 0 || steps > DIRECTOR_UNDO_DEPTH {
+            return Err(format!("撤销步数必须在 1 到 {} 之间", DIRECTOR_UNDO_DEPTH));
+        }
+
+        if self.director_undo.is_empty() {
+            let data = serde_json::json!({
+                "undone": 0,
+                "remaining": 0,
+            });
+            return Ok(ActionResult::new_info_message(
+                data,
+                vec![],
+                "没有可撤销的导演行动".to_string(),
+                true,
+            )
+            .as_results());
+        }
+
+        let mut results = Vec::new();
+        for _ in 0..steps {
+            let Some(record) = self.director_undo.last() else {
+                break;
+            };
+
+            // 之后又被其他行动修改的字段不能撤销
+            if let Some(change) = record.changes.iter().find(|change| {
+                self.current_field_value(&change.target, &change.field)
+                    .as_ref()
+                    != Some(&change.after)
+            }) {
+                let data = serde_json::json!({
+                    "action": record.action,
+                    "sequence": record.sequence,
+                    "target": change.target,
+                    "field": change.field,
+                    "remaining": self.director_undo.len(),
+                });
+                let log_message = format!(
+                    "无法撤销导演行动 {}（序号 {}）：{} 在之后已被其他行动修改",
+                    record.action,
+                    record.sequence,
+                    self.describe_target(&change.target, &change.field)
+                );
+                results.push(ActionResult::new_info_message(
+                    data,
+                    vec![],
+                    log_message,
+                    true,
+                ));
+                break;
+            }
+
+            let record = self.director_undo.pop().unwrap();
+            results.push(self.revert_director_record(&record)?);
+        }
+
+        Ok(ActionResults { results })
+    }
+
+    /// 恢复一条撤销记录中的全部字段，并生成通知受影响玩家的结果
+    fn revert_director_record(
+        &mut self,
+        record: &DirectorUndoRecord,
+    ) -> Result<ActionResult, String> {
+        let mut player_changes: HashMap<&str, Vec<&FieldChange>> = HashMap::new();
+        let mut place_changes: HashMap<&str, Vec<&FieldChange>> = HashMap::new();
+        let mut notify_all = false;
+        let mut public_change = false;
+
+        for change in &record.changes {
+            match &change.target {
+                UndoTarget::Game => {
+                    self.restore_global(change)?;
+                    // 玩家视角包含天气、夜晚时间、缩圈计划与商店
+                    notify_all |= change.field != "votes";
+                    public_change |= change.field != "votes" && change.field != "shop";
+                }
+                UndoTarget::Player { player_id } => {
+                    player_changes
+                        .entry(player_id.as_str())
+                        .or_default()
+                        .push(change);
+                    public_change |= change.field == "is_alive" || change.field == "location";
+                }
+                UndoTarget::Place { place_name } => {
+                    place_changes
+                        .entry(place_name.as_str())
+                        .or_default()
+                        .push(change);
+                    if change.field == "is_destroyed" {
+                        notify_all = true;
+                        public_change = true;
+                    }
+                }
+            }
+        }
+
+        for (player_id, changes) in &player_changes {
+            if let Some(player) = self.players.get_mut(*player_id) {
+                restore_fields(player, changes)?;
+            }
+        }
+        for (place_name, changes) in &place_changes {
+            if let Some(place) = self.places.get_mut(*place_name) {
+                restore_fields(place, changes)?;
+            }
+        }
+
+        let mut broadcast_players: Vec<String> = if notify_all {
+            self.players.keys().cloned().collect()
+        } else {
+            player_changes
+                .keys()
+                .map(|player_id| player_id.to_string())
+                .collect()
+        };
+        broadcast_players.sort();
+
+        let data = serde_json::json!({
+            "action": record.action,
+            "sequence": record.sequence,
+            "undone": true,
+            "remaining": self.director_undo.len(),
+        });
+
+        let mut action_result = ActionResult::new_system_message(
+            data,
+            broadcast_players,
+            format!(
+                "导演撤销了行动 {}（序号 {}），恢复了 {} 处修改",
+                record.action,
+                record.sequence,
+                record.changes.len()
+            ),
+            true,
+        );
+        action_result.broadcast_to_all = notify_all;
+        if public_change {
+            action_result = action_result.with_spectator_event(serde_json::json!({
+                "kind": "director_undo",
+            }));
+        }
+
+        Ok(action_result)
+    }
+
+    /// 描述被修改的字段（用于日志）
+    fn describe_target(&self, target: &UndoTarget, field: &str) -> String {
+        match target {
+            UndoTarget::Game => format!("游戏的 {}", field),
+            UndoTarget::Player { player_id } => {
+                let name = self
+                    .players
+                    .get(player_id)
+                    .map(|player| player.name.as_str())
+                    .unwrap_or(player_id.as_str());
+                format!("{} 的 {}", name, field)
+            }
+            UndoTarget::Place { place_name } => format!("地点 {} 的 {}", place_name, field),
+        }
+    }
+}
@@ -0,0 +1,148 @@
+//! GameState 导演白天投票结算逻辑实现
+//! 导演在白天投票结束后手动触发投票结算：
+//! 计票逻辑：仅统计存活玩家投给存活玩家的选票，每张选票权重为 1 + 投票者已装备武器与防具的`votes`。
+//! 平票处理：若最高票数出现并列，则白天无事发生。
+//! 公示逻辑：向所有玩家公布最高票玩家的名称、生命值、体力值、装备及道具。
+//! 每日清除：无论结果如何，结算后清空`votes`。
+
+use std::collections::HashMap;
+
+use crate::websocket::models::{ActionResult, ActionResults, GameState};
+
+impl GameState {
+    /// 白天投票结算
+    pub fn handle_vote_settlement(&mut self) -> Result<ActionResults, String> {
+        // ===== 计票 =====
+        let mut tally: HashMap<String, i32> = HashMap::new();
+        let mut ballot_count = 0;
+
+        for (voter_id, target_id) in &self.votes {
+            let voter = match self.players.get(voter_id) {
+                Some(voter) if voter.is_alive => voter,
+                _ => continue,
+            };
+            if !self.players.get(target_id).is_some_and(|t| t.is_alive) {
+                continue;
+            }
+
+            ballot_count += 1;
+            *tally.entry(target_id.clone()).or_insert(0) += voter.get_vote_weight();
+        }
+
+        self.votes.clear();
+
+        let broadcast_players: Vec<String> = self.players.keys().cloned().collect();
+
+        let max_votes = tally.values().copied().max().unwrap_or(0);
+        let mut leaders: Vec<&String> = tally
+            .iter()
+            .filter(|(_, count)| **count == max_votes)
+            .map(|(player_id, _)| player_id)
+            .collect();
+        leaders.sort();
+
+        let tally_data: Vec<serde_json::Value> = tally
+            .iter()
+            .map(|(player_id, count)| {
+                serde_json::json!({
+                    "player_id": player_id,
+                    "player_name": self.players.get(player_id).map(|p| p.name.clone()),
+                    "votes": count,
+                })
+            })
+            .collect();
+
+        // ===== 无有效投票 =====
+        if ballot_count == 0 || max_votes <= 0 {
+            let data = serde_json::json!({
+                "ballot_count": ballot_count,
+                "tally": tally_data,
+            });
+            let mut action_result = ActionResult::new_system_message(
+                data,
+                broadcast_players,
+                "投票结算：没有有效投票，白天无事发生".to_string(),
+                true,
+            );
+            action_result.broadcast_to_all = true;
+            return Ok(action_result.as_results());
+        }
+
+        // ===== 平票 =====
+        if leaders.len() > 1 {
+            let data = serde_json::json!({
+                "ballot_count": ballot_count,
+                "tally": tally_data,
+                "is_tie": true,
+                "max_votes": max_votes,
+            });
+            let mut action_result = ActionResult::new_system_message(
+                data,
+                broadcast_players,
+                format!("投票结算：最高票 {} 票出现平票，白天无事发生", max_votes),
+                true,
+            );
+            action_result.broadcast_to_all = true;
+            return Ok(action_result.as_results());
+        }
+
+        // ===== 公示最高票玩家 =====
+        let winner = self.players.get(leaders[0]).unwrap();
+        let inventory_names: Vec<String> = winner
+            .inventory
+            .iter()
+            .map(|item| item.name.clone())
+            .collect();
+        let weapon_name = winner
+            .equipped_weapon
+            .as_ref()
+            .map(|item| item.name.clone())
+            .unwrap_or_else(|| "无".to_string());
+        let armor_name = winner
+            .equipped_armor
+            .as_ref()
+            .map(|item| item.name.clone())
+            .unwrap_or_else(|| "无".to_string());
+
+        let data = serde_json::json!({
+            "ballot_count": ballot_count,
+            "tally": tally_data,
+            "is_tie": false,
+            "max_votes": max_votes,
+            "winner": {
+                "id": winner.id,
+                "name": winner.name,
+                "life": winner.life,
+                "max_life": winner.max_life,
+                "strength": winner.strength,
+                "max_strength": winner.max_strength,
+                "equipped_weapon": winner.equipped_weapon,
+                "equipped_armor": winner.equipped_armor,
+                "inventory": winner.inventory,
+            },
+        });
+
+        let log_message = format!(
+            "投票结算：{} 以 {} 票成为最高票，生命 {}/{}，体力 {}/{}，武器：{}，防具：{}，道具：{}",
+            winner.name,
+            max_votes,
+            winner.life,
+            winner.max_life,
+            winner.strength,
+            winner.max_strength,
+            weapon_name,
+            armor_name,
+            if inventory_names.is_empty() {
+                "无".to_string()
+            } else {
+                inventory_names.join("、")
+            },
+        );
+
+        let mut action_result =
+            ActionResult::new_system_message(data, broadcast_players, log_message, true);
+        action_result.broadcast_to_all = true;
+
+        Ok(action_result.as_results())
+    }
+}
@@ -0,0 +1,109 @@
+//! GameState 昼夜自动调度阶段处理
+//! 由昼夜调度器在服务器时钟到达阶段边界时调用：
+//! 安排夜晚：根据规则中的`game_flow`计算下一个夜晚时间窗口，并写入`night_start_time`/`night_end_time`。
+//! 夜晚开始：清除夜晚结算标记，向所有玩家公告夜晚开始。
+//! 夜晚结束：若启用自动结算且导演本夜尚未手动结算则执行夜晚结算，随后公告白天开始。
+
+use chrono::{DateTime, Utc};
+
+use crate::websocket::models::{ActionResult, ActionResults, GameState};
+
+impl GameState {
+    /// 当前是否需要安排新的夜晚（没有夜晚时间窗口或当前窗口已结束）
+    pub fn needs_night_schedule(&self, now: DateTime<Utc>) -> bool {
+        match (self.night_start_time, self.night_end_time) {
+            (Some(start), Some(end)) => start >= end || end <= now,
+            _ => true,
+        }
+    }
+
+    /// 安排下一个自动调度的夜晚
+    ///
+    /// 当前夜晚时间窗口尚未结束时保持不变（例如导演手动设置或从存档恢复），返回空结果；
+    /// 未启用自动调度时返回`None`。
+    pub fn schedule_next_night(&mut self, now: DateTime<Utc>) -> Option<ActionResults> {
+        if !self.needs_night_schedule(now) {
+            return Some(ActionResults { results: vec![] });
+        }
+
+        let (night_start, night_end) = self
+            .rule_engine
+            .game_flow
+            .next_night_window(now, self.night_end_time)?;
+
+        self.night_start_time = Some(night_start);
+        self.night_end_time = Some(night_end);
+
+        let data = serde_json::json!({
+            "night_start_time": night_start,
+            "night_end_time": night_end,
+        });
+
+        // 仅通知导演（与手动设置夜晚时间一致）
+        let action_result = ActionResult::new_system_message(
+            data,
+            vec![],
+            format!(
+                "系统自动安排夜晚时间：{} 至 {}",
+                night_start.to_rfc3339(),
+                night_end.to_rfc3339()
+            ),
+            true,
+        );
+
+        Some(action_result.as_results())
+    }
+
+    /// 自动调度：夜晚开始
+    pub fn handle_scheduled_night_start(&mut self) -> ActionResults {
+        self.night_settled = false;
+
+        let data = serde_json::json!({
+            "phase": "night",
+            "night_start_time": self.night_start_time,
+            "night_end_time": self.night_end_time,
+        });
+
+        let mut action_result = ActionResult::new_system_message(
+            data,
+            self.players.keys().cloned().collect(),
+            "夜晚开始".to_string(),
+            true,
+        )
+        .with_spectator_event(serde_json::json!({ "kind": "phase", "phase": "night" }));
+        action_result.broadcast_to_all = true;
+
+        action_result.as_results()
+    }
+
+    /// 自动调度：夜晚结束，按规则执行夜晚结算并进入白天
+    pub fn handle_scheduled_night_end(&mut self) -> Result<ActionResults, String> {
+        let mut results: Vec<ActionResult> = Vec::new();
+
+        let auto_settlement = self.rule_engine.game_flow.auto_settlement;
+        // 导演本夜已手动结算时跳过自动结算，避免重复执行缩圈与恢复
+        let already_settled = self.night_settled;
+        if auto_settlement && !already_settled {
+            let mut settlement = self.handle_night_settlement(true)?;
+            results.append(&mut settlement.results);
+        }
+
+        let data = serde_json::json!({
+            "phase": "day",
+            "auto_settlement": auto_settlement,
+            "already_settled": already_settled,
+        });
+
+        let mut action_result = ActionResult::new_system_message(
+            data,
+            self.players.keys().cloned().collect(),
+            "夜晚结束，白天开始".to_string(),
+            true,
+        )
+        .with_spectator_event(serde_json::json!({ "kind": "phase", "phase": "day" }));
+        action_result.broadcast_to_all = true;
+        results.push(action_result);
+
+        Ok(ActionResults { results })
+    }
+}
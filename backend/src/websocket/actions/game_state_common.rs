@@ -3,9 +3,9 @@
 use std::collections::HashSet;
 use std::mem;
 
-use rand::{rng, seq::SliceRandom};
+use rand::seq::SliceRandom;
 
-use crate::game::game_rule_engine::Item;
+use crate::game::game_rule_engine::{Item, TeammateBehavior};
 use crate::websocket::models::{
     ActionResult, ActionResults, GameState, SearchResult, SearchResultType, SearchTarget,
 };
@@ -39,6 +39,24 @@ impl GameState {
         loot_recipient_id: Option<&str>,
         record_killer_id: Option<&str>,
         reason: &str,
+    ) -> Result<ActionResults, String> {
+        self.kill_player_with_weapon(
+            target_player_id,
+            loot_recipient_id,
+            record_killer_id,
+            reason,
+            None,
+        )
+    }
+
+    /// 杀死指定玩家，并在击杀记录中记录致死的武器或道具
+    pub fn kill_player_with_weapon(
+        &mut self,
+        target_player_id: &str,
+        loot_recipient_id: Option<&str>,
+        record_killer_id: Option<&str>,
+        reason: &str,
+        weapon: Option<&str>,
     ) -> Result<ActionResults, String> {
         if !self.players.contains_key(target_player_id) {
             return Err("Player not found".to_string());
@@ -138,8 +156,7 @@ impl GameState {
             }
 
             if !loot_items.is_empty() {
-                let mut rng = rng();
-                loot_items.shuffle(&mut rng);
+                loot_items.shuffle(&mut self.rng);
 
                 match disposition {
                     DeathDisposition::Vanish => {
@@ -237,6 +254,7 @@ impl GameState {
             "reason": reason,
             "killer_id": record_killer_id.map(|id| id.to_string()),
             "killer_name": record_killer_name,
+            "weapon": weapon,
             "loot_recipient_id": loot_recipient_id.map(|id| id.to_string()),
             "loot_recipient_name": loot_recipient_name,
             "location_before_death": location_option.map(|loc| loc.to_string()),
@@ -246,8 +264,14 @@ impl GameState {
             "vanished_items": vanished_item_names,
         });
 
+        // 观众只能看到死亡者与死因，不暴露击杀者、地点与物品
         let action_result =
-            ActionResult::new_system_message(data, broadcast_players, log_message, true);
+            ActionResult::new_system_message(data, broadcast_players, log_message, true)
+                .with_spectator_event(serde_json::json!({
+                    "kind": "death",
+                    "player_name": player_name,
+                    "reason": reason,
+                }));
 
         Ok(action_result.as_results())
     }
@@ -356,9 +380,55 @@ impl GameState {
         }
     }
 
+    /// 判断两名玩家是否为队友（队伍ID为0或未设置视为无队伍）
+    pub fn are_teammates(&self, player_id: &str, other_player_id: &str) -> bool {
+        if player_id == other_player_id {
+            return false;
+        }
+
+        match (
+            self.players.get(player_id).and_then(|p| p.team_id),
+            self.players.get(other_player_id).and_then(|p| p.team_id),
+        ) {
+            (Some(team_id), Some(other_team_id)) => team_id != 0 && team_id == other_team_id,
+            _ => false,
+        }
+    }
+
+    /// 获取与指定地点相连且未被摧毁的地点
+    pub fn place_neighbors(&self, place_name: &str) -> Vec<String> {
+        self.rule_engine
+            .map_config
+            .neighbors(place_name)
+            .into_iter()
+            .filter(|name| {
+                self.places
+                    .get(name)
+                    .is_some_and(|place| !place.is_destroyed)
+            })
+            .collect()
+    }
+
+    /// 获取从一个地点移动到另一个地点的体力消耗，两地不相邻时返回None
+    pub fn get_move_cost(&self, from: &str, to: &str) -> Option<i32> {
+        let default_cost = self.rule_engine.action_costs.move_cost;
+        let map_config = &self.rule_engine.map_config;
+        if !map_config.has_edges() {
+            return Some(default_cost);
+        }
+
+        map_config
+            .find_edge(from, to)
+            .map(|edge| edge.cost.unwrap_or(default_cost))
+    }
+
     /// 汇总当前地点的所有搜索目标
     pub fn collect_search_targets(&self, player_id: &str) -> Vec<SearchTarget> {
         let mut targets = Vec::new();
+        let hide_teammates = self
+            .rule_engine
+            .teammate_behavior
+            .has(TeammateBehavior::NO_TEAMMATE_SEARCH);
 
         // 获取玩家当前位置
         let player_location = &self.players[player_id].location;
@@ -367,6 +437,10 @@ impl GameState {
             // 添加其他玩家到搜索目标
             for other_player_id in &place.players {
                 if other_player_id != player_id {
+                    // 队友行为规则：禁止搜索到队友
+                    if hide_teammates && self.are_teammates(player_id, other_player_id) {
+                        continue;
+                    }
                     // 只搜索存活的玩家
                     if let Some(other_player) = self.players.get(other_player_id) {
                         if other_player.is_alive {
@@ -386,10 +460,9 @@ impl GameState {
     }
 
     /// 等概率随机选择一个搜索目标
-    pub fn select_random_target(&self, targets: &[SearchTarget]) -> SearchTarget {
+    pub fn select_random_target(&mut self, targets: &[SearchTarget]) -> SearchTarget {
         use rand::Rng;
-        let mut rng = rand::rng();
-        let index = rng.random_range(0..targets.len());
+        let index = self.rng.random_range(0..targets.len());
         targets[index].clone()
     }
 
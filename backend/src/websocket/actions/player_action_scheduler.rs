@@ -2,6 +2,7 @@
 //!
 //! 负责玩家行动的权限验证和分发调度
 
+use crate::websocket::actions::player_trade_action::TradeOfferRequest;
 use crate::websocket::models::{ActionResult, ActionResults, GameState, Player, ShopBuyItem};
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
@@ -35,6 +36,21 @@ pub struct ActionParams {
 
     /// 商店购买请求列表
     pub shop_buy_items: Option<Vec<ShopBuyItem>>,
+
+    /// 道具ID列表（交易时给出的道具）
+    pub item_ids: Option<Vec<String>>,
+
+    /// 货币数量（交易时给出的货币）
+    pub coins: Option<i32>,
+
+    /// 交易时向对方索取的道具ID列表
+    pub request_item_ids: Option<Vec<String>>,
+
+    /// 交易时向对方索取的货币数量
+    pub request_coins: Option<i32>,
+
+    /// 交易ID
+    pub trade_id: Option<String>,
 }
 
 impl ActionParams {
@@ -55,6 +71,7 @@ enum ValidationType {
     Strength(i32),   // 体力验证（带消耗值）
     InventorySpace,  // 背包空间验证
     NightActionTime, // 夜晚行动时间验证
+    DayActionTime,   // 白天行动时间验证（不在夜晚行动时间内）
 }
 
 /// 玩家行动调度器
@@ -73,6 +90,34 @@ macro_rules! validate_or_return {
 }
 
 impl PlayerActionScheduler {
+    /// 调度器支持的玩家行动类型
+    pub const SUPPORTED_ACTIONS: &'static [&'static str] = &[
+        "born",
+        "move",
+        "search",
+        "pick",
+        "attack",
+        "equip",
+        "use",
+        "upgrade_equip",
+        "throw",
+        "unequip",
+        "deliver",
+        "gift",
+        "trade_offer",
+        "trade_accept",
+        "trade_decline",
+        "trade_cancel",
+        "shop_buy",
+        "vote",
+        "send",
+    ];
+
+    /// 判断玩家行动类型是否受支持
+    pub fn supports(action_type: &str) -> bool {
+        Self::SUPPORTED_ACTIONS.contains(&action_type)
+    }
+
     /// 调度玩家行动
     ///
     /// # 参数
@@ -110,7 +155,16 @@ impl PlayerActionScheduler {
                 return game_state.handle_born_action(player_id, place_name);
             }
             "move" => {
-                let move_cost = game_state.rule_engine.action_costs.move_cost;
+                // 体力验证使用实际移动路线的消耗（不相邻时由行动处理返回提示）
+                let move_cost = match (
+                    game_state.players.get(player_id),
+                    action_params.target_place.as_ref(),
+                ) {
+                    (Some(player), Some(target_place)) => game_state
+                        .get_move_cost(&player.location, target_place)
+                        .unwrap_or(game_state.rule_engine.action_costs.move_cost),
+                    _ => game_state.rule_engine.action_costs.move_cost,
+                };
                 validate_or_return!(
                     game_state,
                     player_id,
@@ -302,6 +356,84 @@ impl PlayerActionScheduler {
                 game_state.end_rest_mode_for_action(player_id);
                 return game_state.handle_deliver_action(player_id, target_player_id, message);
             }
+            "gift" => {
+                validate_or_return!(
+                    game_state,
+                    player_id,
+                    vec![
+                        ValidationType::NightActionTime,
+                        ValidationType::Alive,
+                        ValidationType::Born,
+                        ValidationType::NotBound
+                    ]
+                );
+                let target_player_id = action_params
+                    .target_player_id
+                    .as_ref()
+                    .ok_or("Missing target_player_id parameter")?;
+                let item_id = action_params
+                    .item_id
+                    .as_ref()
+                    .ok_or("Missing item_id parameter")?;
+                game_state.end_rest_mode_for_action(player_id);
+                return game_state.handle_gift_action(player_id, target_player_id, item_id);
+            }
+            "trade_offer" => {
+                validate_or_return!(
+                    game_state,
+                    player_id,
+                    vec![
+                        ValidationType::NightActionTime,
+                        ValidationType::Alive,
+                        ValidationType::Born,
+                        ValidationType::NotBound
+                    ]
+                );
+                let target_player_id = action_params
+                    .target_player_id
+                    .as_ref()
+                    .ok_or("Missing target_player_id parameter")?;
+                let request = TradeOfferRequest {
+                    offered_item_ids: action_params.item_ids.clone().unwrap_or_default(),
+                    offered_coins: action_params.coins.unwrap_or(0),
+                    requested_item_ids: action_params.request_item_ids.clone().unwrap_or_default(),
+                    requested_coins: action_params.request_coins.unwrap_or(0),
+                };
+                game_state.end_rest_mode_for_action(player_id);
+                return game_state.handle_trade_offer_action(player_id, target_player_id, request);
+            }
+            "trade_accept" => {
+                validate_or_return!(
+                    game_state,
+                    player_id,
+                    vec![
+                        ValidationType::NightActionTime,
+                        ValidationType::Alive,
+                        ValidationType::Born,
+                        ValidationType::NotBound
+                    ]
+                );
+                let trade_id = action_params
+                    .trade_id
+                    .as_ref()
+                    .ok_or("Missing trade_id parameter")?;
+                game_state.end_rest_mode_for_action(player_id);
+                return game_state.handle_trade_accept_action(player_id, trade_id);
+            }
+            "trade_decline" => {
+                let trade_id = action_params
+                    .trade_id
+                    .as_ref()
+                    .ok_or("Missing trade_id parameter")?;
+                return game_state.handle_trade_decline_action(player_id, trade_id);
+            }
+            "trade_cancel" => {
+                let trade_id = action_params
+                    .trade_id
+                    .as_ref()
+                    .ok_or("Missing trade_id parameter")?;
+                return game_state.handle_trade_cancel_action(player_id, trade_id);
+            }
             "shop_buy" => {
                 validate_or_return!(
                     game_state,
@@ -318,6 +450,18 @@ impl PlayerActionScheduler {
                     .ok_or("Missing shop_buy_items parameter")?;
                 return game_state.handle_shop_buy_action(player_id, buy_items);
             }
+            "vote" => {
+                validate_or_return!(
+                    game_state,
+                    player_id,
+                    vec![ValidationType::DayActionTime, ValidationType::Alive]
+                );
+                let target_player_id = action_params
+                    .target_player_id
+                    .as_ref()
+                    .ok_or("Missing target_player_id parameter")?;
+                return game_state.handle_vote_action(player_id, target_player_id);
+            }
             "send" => {
                 let message = action_params
                     .message
@@ -363,6 +507,7 @@ impl PlayerActionScheduler {
                 Self::check_inventory_space_from_ref(player, game_state, player_id)
             }
             ValidationType::NightActionTime => Self::check_night_action_time(game_state, player_id),
+            ValidationType::DayActionTime => Self::check_day_action_time(game_state, player_id),
         }
     }
 
@@ -487,7 +632,7 @@ impl PlayerActionScheduler {
         match (&game_state.night_start_time, &game_state.night_end_time) {
             // 如果都设置了，检查当前时间是否在范围内
             (Some(start_time), Some(end_time)) => {
-                let current_time = chrono::Utc::now();
+                let current_time = game_state.current_time();
 
                 // 检查当前时间是否在夜晚行动时间范围内
                 if current_time < *start_time || current_time > *end_time {
@@ -516,4 +661,24 @@ impl PlayerActionScheduler {
         // 时间验证通过
         Ok(())
     }
+
+    /// 验证白天行动时间（夜晚行动时间范围之外均视为白天）
+    fn check_day_action_time(game_state: &GameState, player_id: &str) -> Result<(), ActionResults> {
+        if let (Some(start_time), Some(end_time)) =
+            (&game_state.night_start_time, &game_state.night_end_time)
+        {
+            let current_time = game_state.current_time();
+            if current_time >= *start_time && current_time <= *end_time {
+                let data = serde_json::json!({});
+                return Err(ActionResult::new_info_message(
+                    data,
+                    vec![player_id.to_string()],
+                    "当前为夜晚行动时间，无法进行白天行动".to_string(),
+                    false,
+                )
+                .as_results());
+            }
+        }
+        Ok(())
+    }
 }
@@ -1,6 +1,9 @@
 //! 玩家攻击行动处理
 
-use crate::websocket::models::{ActionResult, ActionResults, GameState, SearchResultType};
+use crate::game::game_rule_engine::TeammateBehavior;
+use crate::websocket::models::{
+    ActionResult, ActionResults, DamageEvent, GameState, SearchResultType,
+};
 
 impl GameState {
     /// 处理攻击行动
@@ -42,6 +45,21 @@ impl GameState {
             return Ok(action_result.as_results());
         }
 
+        // 队友行为规则：禁止队友伤害
+        let no_teammate_damage = self
+            .rule_engine
+            .teammate_behavior
+            .has(TeammateBehavior::NO_TEAMMATE_DAMAGE);
+        if no_teammate_damage && self.are_teammates(player_id, &target_player_id) {
+            let action_result = ActionResult::new_info_message(
+                serde_json::json!({}),
+                vec![player_id.to_string()],
+                "无法攻击队友".to_string(),
+                false,
+            );
+            return Ok(action_result.as_results());
+        }
+
         // 获取目标玩家信息
         let (target_player_location, target_player_alive, target_player_name) = {
             let target_player = self
@@ -79,16 +97,22 @@ impl GameState {
         }
 
         // 根据是否装备武器计算伤害及附加效果
-        let (base_damage, attack_method, weapon_aoe_damage, weapon_bleed_damage) = {
+        let (base_damage, attack_method, weapon_aoe_damage, weapon_bleed_damage, weapon_name) = {
             let attacker = self.players.get(player_id).unwrap();
             if let Some(weapon) = &attacker.equipped_weapon
                 && let Some(attributes) = weapon.as_weapon()
             {
                 let aoe = attributes.aoe_damage.filter(|value| *value > 0);
                 let bleed = attributes.bleed_damage.filter(|value| *value > 0);
-                (attributes.damage, "武器", aoe, bleed)
+                (attributes.damage, "武器", aoe, bleed, weapon.name.clone())
             } else {
-                (self.rule_engine.get_unarmed_damage(), "挥拳", None, None)
+                (
+                    self.rule_engine.get_unarmed_damage(),
+                    "挥拳",
+                    None,
+                    None,
+                    "挥拳".to_string(),
+                )
             }
         };
 
@@ -116,6 +140,10 @@ impl GameState {
                         if other_id.as_str() == player_id || other_id == &target_player_id {
                             return None;
                         }
+                        // 禁止队友伤害时，溅射同样不波及队友
+                        if no_teammate_damage && self.are_teammates(player_id, other_id) {
+                            return None;
+                        }
                         let is_alive = self
                             .players
                             .get(other_id)
@@ -258,11 +286,12 @@ impl GameState {
 
         let mut death_results: Vec<ActionResult> = Vec::new();
         if main_requires_kill {
-            let mut death_outcome = self.kill_player(
+            let mut death_outcome = self.kill_player_with_weapon(
                 &target_player_id,
                 Some(player_id),
                 Some(player_id),
                 "攻击致死",
+                Some(&weapon_name),
             )?;
             death_results.append(&mut death_outcome.results);
         }
@@ -303,11 +332,12 @@ impl GameState {
                 }
 
                 if requires_kill {
-                    let mut death_outcome = self.kill_player(
+                    let mut death_outcome = self.kill_player_with_weapon(
                         &aoe_target_id,
                         Some(player_id),
                         Some(player_id),
                         "攻击致死",
+                        Some(&weapon_name),
                     )?;
                     death_results.append(&mut death_outcome.results);
                 }
@@ -435,13 +465,30 @@ impl GameState {
 
         self.consume_strength(player_id, attack_cost)?;
 
+        // 记录主目标与溅射目标实际受到的伤害
+        let damage_event = |victim_id: &str, damage: i32, cause: &str| DamageEvent {
+            attacker_id: Some(player_id.to_string()),
+            victim_id: victim_id.to_string(),
+            damage,
+            cause: cause.to_string(),
+            weapon: Some(weapon_name.clone()),
+        };
+        let mut damage_events = Vec::new();
+        if main_actual_damage > 0 {
+            damage_events.push(damage_event(&target_player_id, main_actual_damage, "攻击"));
+        }
+        for impact in &aoe_impacts {
+            damage_events.push(damage_event(&impact.0, impact.2, "溅射"));
+        }
+
         // 创建动作结果
         let full_action_result = ActionResult::new_system_message(
             data,
             vec![player_id.to_string()],
             attacker_formatted_message,
             true,
-        );
+        )
+        .with_damage_events(damage_events);
 
         let diff_action_result = ActionResult::new_system_message(
             target_data,
@@ -69,9 +69,6 @@ impl GameState {
         player_id: &str,
         target_place: &str,
     ) -> Result<ActionResults, String> {
-        // 使用规则引擎获取移动消耗
-        let move_cost = self.rule_engine.action_costs.move_cost;
-
         // 验证目标地点是否存在且未被摧毁
         let place = self
             .places
@@ -88,6 +85,23 @@ impl GameState {
             return Ok(action_result.as_results());
         }
 
+        // 配置了地点连接时只能移动到相邻地点，移动消耗取连接上的设置
+        let current_location = self.players.get(player_id).unwrap().location.clone();
+        let move_cost = match self.get_move_cost(&current_location, target_place) {
+            Some(cost) => cost,
+            None => {
+                let action_result = ActionResult::new_info_message(
+                    serde_json::json!({
+                        "neighbors": self.place_neighbors(&current_location),
+                    }),
+                    vec![player_id.to_string()],
+                    "目标地点与当前位置不相邻".to_string(),
+                    false,
+                );
+                return Ok(action_result.as_results());
+            }
+        };
+
         // 获取玩家引用并移动玩家到目标位置
         let player = self.players.get_mut(player_id).unwrap();
         let player_location = player.location.clone();
@@ -136,8 +150,9 @@ impl GameState {
 
         // 使用规则引擎获取搜索冷却时间
         let search_cooldown = self.rule_engine.get_search_cooldown();
+        let now = self.current_time();
         if let Some(last_search_time) = player_last_search_time {
-            let elapsed = chrono::Utc::now().signed_duration_since(last_search_time);
+            let elapsed = now.signed_duration_since(last_search_time);
             if elapsed.num_seconds() < search_cooldown {
                 let remaining_time = search_cooldown - elapsed.num_seconds();
                 let data = serde_json::json!({});
@@ -154,7 +169,7 @@ impl GameState {
         // 更新玩家状态
         {
             let player = self.players.get_mut(player_id).unwrap();
-            player.last_search_time = Some(chrono::Utc::now());
+            player.last_search_time = Some(now);
         }
 
         // 消耗体力值
@@ -175,8 +190,7 @@ impl GameState {
         match selected_target {
             SearchTarget::Player(target_player_id) => {
                 use rand::Rng;
-                let mut rng = rand::rng();
-                let reveal_roll: f64 = rng.random_range(0.0..1.0);
+                let reveal_roll: f64 = self.rng.random_range(0.0..1.0);
                 let reveal_name = reveal_roll <= self.weather;
                 self.handle_player_search_result(player_id, &target_player_id, reveal_name)
             }
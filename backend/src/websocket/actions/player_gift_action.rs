@@ -0,0 +1,104 @@
+//! 玩家赠送队友物品行动处理
+//! 仅在队友行为规则启用“允许赠送队友物品”时可用，将背包中的道具直接转移到队友背包。
+
+use crate::game::game_rule_engine::TeammateBehavior;
+use crate::websocket::models::{ActionResult, ActionResults, GameState};
+
+impl GameState {
+    /// 处理赠送物品行动
+    pub fn handle_gift_action(
+        &mut self,
+        player_id: &str,
+        target_player_id: &str,
+        item_id: &str,
+    ) -> Result<ActionResults, String> {
+        let info_result = |message: &str| -> Result<ActionResults, String> {
+            let result = ActionResult::new_info_message(
+                serde_json::json!({}),
+                vec![player_id.to_string()],
+                message.to_string(),
+                false,
+            );
+            Ok(result.as_results())
+        };
+
+        if !self
+            .rule_engine
+            .teammate_behavior
+            .has(TeammateBehavior::GIFT_TEAMMATE_ITEMS)
+        {
+            return info_result("当前规则不允许赠送队友物品");
+        }
+
+        let (target_name, target_alive, target_item_count) =
+            match self.players.get(target_player_id) {
+                Some(target) => (
+                    target.name.clone(),
+                    target.is_alive,
+                    target.get_total_item_count(),
+                ),
+                None => return info_result("赠送目标不存在"),
+            };
+
+        if !self.are_teammates(player_id, target_player_id) {
+            return info_result("只能向队友赠送物品");
+        }
+
+        if !target_alive {
+            return info_result("赠送目标已死亡");
+        }
+
+        let max_backpack_items = self.rule_engine.player_config.max_backpack_items;
+        if target_item_count >= max_backpack_items {
+            return info_result("队友背包已满，无法赠送");
+        }
+
+        // 从赠送者背包中移除物品
+        let (player_name, item) = {
+            let player = self.players.get_mut(player_id).unwrap();
+            match player.inventory.iter().position(|item| item.id == item_id) {
+                Some(index) => (player.name.clone(), player.inventory.remove(index)),
+                None => return info_result("背包中没有该道具"),
+            }
+        };
+        let item_name = item.name.clone();
+
+        // 将物品放入队友背包
+        let target_inventory = {
+            let target = self.players.get_mut(target_player_id).unwrap();
+            target.inventory.push(item);
+            target.inventory.clone()
+        };
+
+        let sender_inventory = self.players.get(player_id).unwrap().inventory.clone();
+
+        let sender_result = ActionResult::new_system_message(
+            serde_json::json!({
+                "inventory": sender_inventory,
+                "target_player_id": target_player_id,
+                "item_name": item_name,
+            }),
+            vec![player_id.to_string()],
+            format!(
+                "{} 将 {} 赠送给队友 {}",
+                player_name, item_name, target_name
+            ),
+            true,
+        );
+
+        let receiver_result = ActionResult::new_system_message(
+            serde_json::json!({
+                "inventory": target_inventory,
+                "from_player_id": player_id,
+                "item_name": item_name,
+            }),
+            vec![target_player_id.to_string()],
+            format!("你收到了队友 {} 赠送的 {}", player_name, item_name),
+            false,
+        );
+
+        Ok(ActionResults {
+            results: vec![sender_result, receiver_result],
+        })
+    }
+}
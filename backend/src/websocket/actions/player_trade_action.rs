@@ -0,0 +1,460 @@
+//! 玩家交易行动处理
+//! 两阶段交易：发起者向同一地点的玩家提出报价（给出与索取的道具、货币），
+//! 对方在超时前接受或拒绝。接受时重新校验双方状态，全部通过后一次性完成交换。
+
+use chrono::Duration;
+use std::collections::HashSet;
+
+use crate::game::game_rule_engine::Item;
+use crate::websocket::models::{ActionResult, ActionResults, GameState, Player, TradeOffer};
+
+/// 交易报价的有效时长（秒）
+pub const TRADE_OFFER_TIMEOUT_SECONDS: i64 = 60;
+
+/// 发起交易的参数
+#[derive(Debug, Clone, Default)]
+pub struct TradeOfferRequest {
+    /// 发起者给出的背包道具ID
+    pub offered_item_ids: Vec<String>,
+    /// 发起者给出的货币数
+    pub offered_coins: i32,
+    /// 向对方索取的背包道具ID
+    pub requested_item_ids: Vec<String>,
+    /// 向对方索取的货币数
+    pub requested_coins: i32,
+}
+
+impl GameState {
+    /// 处理发起交易行动
+    pub fn handle_trade_offer_action(
+        &mut self,
+        player_id: &str,
+        target_player_id: &str,
+        request: TradeOfferRequest,
+    ) -> Result<ActionResults, String> {
+        self.prune_expired_trade_offers();
+
+        let info_result = |message: &str| -> Result<ActionResults, String> {
+            let result = ActionResult::new_info_message(
+                serde_json::json!({}),
+                vec![player_id.to_string()],
+                message.to_string(),
+                false,
+            );
+            Ok(result.as_results())
+        };
+
+        if player_id == target_player_id {
+            return info_result("不能与自己交易");
+        }
+        if request.offered_coins < 0 || request.requested_coins < 0 {
+            return info_result("交易货币数不能为负数");
+        }
+        if request.offered_item_ids.is_empty()
+            && request.offered_coins == 0
+            && request.requested_item_ids.is_empty()
+            && request.requested_coins == 0
+        {
+            return info_result("交易内容不能为空");
+        }
+        if has_duplicates(&request.offered_item_ids) || has_duplicates(&request.requested_item_ids)
+        {
+            return info_result("交易道具不能重复");
+        }
+
+        let player = self
+            .players
+            .get(player_id)
+            .ok_or("Player not found".to_string())?;
+        let target = match self.players.get(target_player_id) {
+            Some(target) => target,
+            None => return info_result("交易对象不存在"),
+        };
+
+        if let Err(message) = self.check_trade_parties(player, target) {
+            return info_result(message);
+        }
+        if let Err(message) = check_trade_side(
+            player,
+            &request.offered_item_ids,
+            request.offered_coins,
+            "你",
+        ) {
+            return info_result(&message);
+        }
+        if let Err(message) = check_trade_side(
+            target,
+            &request.requested_item_ids,
+            request.requested_coins,
+            "对方",
+        ) {
+            return info_result(&message);
+        }
+
+        let player_name = player.name.clone();
+        let target_name = target.name.clone();
+        let offered_items = describe_items(player, &request.offered_item_ids);
+        let requested_items = describe_items(target, &request.requested_item_ids);
+
+        self.trade_sequence += 1;
+        let now = self.current_time();
+        let offer = TradeOffer {
+            id: format!("trade-{}", self.trade_sequence),
+            from_player_id: player_id.to_string(),
+            to_player_id: target_player_id.to_string(),
+            offered_item_ids: request.offered_item_ids,
+            offered_coins: request.offered_coins,
+            requested_item_ids: request.requested_item_ids,
+            requested_coins: request.requested_coins,
+            created_at: now,
+            expires_at: now + Duration::seconds(TRADE_OFFER_TIMEOUT_SECONDS),
+        };
+
+        let data = serde_json::json!({
+            "trade": offer,
+            "offered_items": offered_items,
+            "requested_items": requested_items,
+        });
+        let summary = format!(
+            "给出 {}，换取 {}",
+            describe_side(&offered_items, offer.offered_coins),
+            describe_side(&requested_items, offer.requested_coins)
+        );
+
+        let offerer_result = ActionResult::new_system_message(
+            data.clone(),
+            vec![player_id.to_string()],
+            format!("{} 向 {} 发起交易：{}", player_name, target_name, summary),
+            true,
+        );
+        let receiver_result = ActionResult::new_system_message(
+            data,
+            vec![target_player_id.to_string()],
+            format!(
+                "{} 向你发起交易：{}（{}秒内有效）",
+                player_name, summary, TRADE_OFFER_TIMEOUT_SECONDS
+            ),
+            false,
+        );
+
+        self.trade_offers.insert(offer.id.clone(), offer);
+
+        Ok(ActionResults {
+            results: vec![offerer_result, receiver_result],
+        })
+    }
+
+    /// 处理接受交易行动
+    ///
+    /// 重新校验双方状态、道具、货币与背包容量，全部通过后才修改状态，保证交换的原子性。
+    pub fn handle_trade_accept_action(
+        &mut self,
+        player_id: &str,
+        trade_id: &str,
+    ) -> Result<ActionResults, String> {
+        let info_result = |message: &str| -> Result<ActionResults, String> {
+            let result = ActionResult::new_info_message(
+                serde_json::json!({ "trade_id": trade_id }),
+                vec![player_id.to_string()],
+                message.to_string(),
+                false,
+            );
+            Ok(result.as_results())
+        };
+
+        let offer = match self.trade_offers.get(trade_id) {
+            Some(offer) if offer.to_player_id == player_id => offer.clone(),
+            _ => return info_result("交易不存在或已失效"),
+        };
+        if self.current_time() > offer.expires_at {
+            self.trade_offers.remove(trade_id);
+            return info_result("交易已过期");
+        }
+
+        let (offerer, receiver) = match (
+            self.players.get(&offer.from_player_id),
+            self.players.get(player_id),
+        ) {
+            (Some(offerer), Some(receiver)) => (offerer, receiver),
+            _ => {
+                self.trade_offers.remove(trade_id);
+                return info_result("交易对象不存在");
+            }
+        };
+
+        let validation = self
+            .check_trade_parties(receiver, offerer)
+            .map_err(str::to_string)
+            .and_then(|_| {
+                check_trade_side(
+                    offerer,
+                    &offer.offered_item_ids,
+                    offer.offered_coins,
+                    "对方",
+                )
+            })
+            .and_then(|_| {
+                check_trade_side(
+                    receiver,
+                    &offer.requested_item_ids,
+                    offer.requested_coins,
+                    "你",
+                )
+            })
+            .and_then(|_| {
+                let max_items = self.rule_engine.player_config.max_backpack_items;
+                let offered = offer.offered_item_ids.len();
+                let requested = offer.requested_item_ids.len();
+                if offerer.get_total_item_count() - offered + requested > max_items {
+                    Err("对方背包空间不足，无法完成交易".to_string())
+                } else if receiver.get_total_item_count() - requested + offered > max_items {
+                    Err("背包空间不足，无法完成交易".to_string())
+                } else {
+                    Ok(())
+                }
+            });
+        if let Err(message) = validation {
+            self.trade_offers.remove(trade_id);
+            return info_result(&message);
+        }
+
+        let offerer_name = offerer.name.clone();
+        let receiver_name = receiver.name.clone();
+        let offered_items = describe_items(offerer, &offer.offered_item_ids);
+        let requested_items = describe_items(receiver, &offer.requested_item_ids);
+
+        // 校验全部通过，一次性完成交换
+        self.trade_offers.remove(trade_id);
+        let given = take_items(
+            self.players.get_mut(&offer.from_player_id).unwrap(),
+            &offer.offered_item_ids,
+            offer.offered_coins,
+        );
+        let received = take_items(
+            self.players.get_mut(player_id).unwrap(),
+            &offer.requested_item_ids,
+            offer.requested_coins,
+        );
+        let offerer = self.players.get_mut(&offer.from_player_id).unwrap();
+        offerer.inventory.extend(received);
+        offerer.coins += offer.requested_coins;
+        let (offerer_inventory, offerer_coins) = (offerer.inventory.clone(), offerer.coins);
+        let receiver = self.players.get_mut(player_id).unwrap();
+        receiver.inventory.extend(given);
+        receiver.coins += offer.offered_coins;
+        let (receiver_inventory, receiver_coins) = (receiver.inventory.clone(), receiver.coins);
+
+        let summary = format!(
+            "{} 给出 {}，{} 给出 {}",
+            offerer_name,
+            describe_side(&offered_items, offer.offered_coins),
+            receiver_name,
+            describe_side(&requested_items, offer.requested_coins)
+        );
+
+        let offerer_result = ActionResult::new_system_message(
+            serde_json::json!({
+                "trade_id": trade_id,
+                "inventory": offerer_inventory,
+                "coins": offerer_coins,
+            }),
+            vec![offer.from_player_id.clone()],
+            format!(
+                "{} 与 {} 完成交易：{}",
+                offerer_name, receiver_name, summary
+            ),
+            true,
+        );
+        let receiver_result = ActionResult::new_system_message(
+            serde_json::json!({
+                "trade_id": trade_id,
+                "inventory": receiver_inventory,
+                "coins": receiver_coins,
+            }),
+            vec![player_id.to_string()],
+            format!("你接受了 {} 的交易：{}", offerer_name, summary),
+            false,
+        );
+
+        Ok(ActionResults {
+            results: vec![offerer_result, receiver_result],
+        })
+    }
+
+    /// 处理拒绝交易行动
+    pub fn handle_trade_decline_action(
+        &mut self,
+        player_id: &str,
+        trade_id: &str,
+    ) -> Result<ActionResults, String> {
+        self.close_trade_offer(player_id, trade_id, false)
+    }
+
+    /// 处理撤回交易行动
+    pub fn handle_trade_cancel_action(
+        &mut self,
+        player_id: &str,
+        trade_id: &str,
+    ) -> Result<ActionResults, String> {
+        self.close_trade_offer(player_id, trade_id, true)
+    }
+
+    /// 获取与指定玩家相关且未过期的交易报价（按交易ID排序）
+    pub fn pending_trade_offers_for(&self, player_id: &str) -> Vec<&TradeOffer> {
+        let now = self.current_time();
+        let mut offers: Vec<&TradeOffer> = self
+            .trade_offers
+            .values()
+            .filter(|offer| offer.expires_at >= now)
+            .filter(|offer| offer.from_player_id == player_id || offer.to_player_id == player_id)
+            .collect();
+        offers.sort_by(|a, b| a.created_at.cmp(&b.created_at).then(a.id.cmp(&b.id)));
+        offers
+    }
+
+    /// 移除已过期的交易报价
+    fn prune_expired_trade_offers(&mut self) {
+        let now = self.current_time();
+        self.trade_offers.retain(|_, offer| offer.expires_at >= now);
+    }
+
+    /// 检查交易双方是否都存活、未被捆绑且位于同一地点
+    fn check_trade_parties(&self, player: &Player, other: &Player) -> Result<(), &'static str> {
+        if !other.is_alive {
+            return Err("交易对象已死亡");
+        }
+        if other.is_bound {
+            return Err("交易对象被捆绑，无法交易");
+        }
+        if player.location.is_empty() || player.location != other.location {
+            return Err("只能与同一地点的玩家交易");
+        }
+        Ok(())
+    }
+
+    /// 拒绝（接收方）或撤回（发起方）交易报价
+    fn close_trade_offer(
+        &mut self,
+        player_id: &str,
+        trade_id: &str,
+        by_offerer: bool,
+    ) -> Result<ActionResults, String> {
+        let matches = self.trade_offers.get(trade_id).is_some_and(|offer| {
+            if by_offerer {
+                offer.from_player_id == player_id
+            } else {
+                offer.to_player_id == player_id
+            }
+        });
+        if !matches {
+            let result = ActionResult::new_info_message(
+                serde_json::json!({ "trade_id": trade_id }),
+                vec![player_id.to_string()],
+                "交易不存在或已失效".to_string(),
+                false,
+            );
+            return Ok(result.as_results());
+        }
+
+        let offer = self.trade_offers.remove(trade_id).unwrap();
+        let name_of = |id: &str| {
+            self.players
+                .get(id)
+                .map(|player| player.name.clone())
+                .unwrap_or_default()
+        };
+        let offerer_name = name_of(&offer.from_player_id);
+        let receiver_name = name_of(&offer.to_player_id);
+
+        let (log_message, notice) = if by_offerer {
+            (
+                format!("{} 撤回了向 {} 发起的交易", offerer_name, receiver_name),
+                format!("{} 撤回了交易", offerer_name),
+            )
+        } else {
+            (
+                format!("{} 拒绝了 {} 的交易", receiver_name, offerer_name),
+                format!("{} 拒绝了你的交易", receiver_name),
+            )
+        };
+        let other_id = if by_offerer {
+            offer.to_player_id.clone()
+        } else {
+            offer.from_player_id.clone()
+        };
+
+        let data = serde_json::json!({ "trade_id": trade_id });
+        let actor_result = ActionResult::new_system_message(
+            data.clone(),
+            vec![player_id.to_string()],
+            log_message,
+            true,
+        );
+        let other_result = ActionResult::new_system_message(data, vec![other_id], notice, false);
+
+        Ok(ActionResults {
+            results: vec![actor_result, other_result],
+        })
+    }
+}
+
+/// 检查一方是否持有交易所需的背包道具与货币
+fn check_trade_side(
+    player: &Player,
+    item_ids: &[String],
+    coins: i32,
+    who: &str,
+) -> Result<(), String> {
+    if let Some(missing) = item_ids
+        .iter()
+        .find(|id| !player.inventory.iter().any(|item| &item.id == *id))
+    {
+        return Err(format!("{}的背包中没有道具 {}", who, missing));
+    }
+    if player.coins < coins {
+        return Err(format!("{}的货币不足", who));
+    }
+    Ok(())
+}
+
+/// 从玩家背包中取出指定道具并扣除货币（调用前必须已校验）
+fn take_items(player: &mut Player, item_ids: &[String], coins: i32) -> Vec<Item> {
+    player.coins -= coins;
+    let mut taken = Vec::with_capacity(item_ids.len());
+    for item_id in item_ids {
+        if let Some(index) = player.inventory.iter().position(|item| &item.id == item_id) {
+            taken.push(player.inventory.remove(index));
+        }
+    }
+    taken
+}
+
+/// 交易道具的ID与名称
+fn describe_items(player: &Player, item_ids: &[String]) -> Vec<serde_json::Value> {
+    item_ids
+        .iter()
+        .filter_map(|id| player.inventory.iter().find(|item| &item.id == id))
+        .map(|item| serde_json::json!({ "id": item.id, "name": item.name }))
+        .collect()
+}
+
+/// 交易一方内容的文字描述
+fn describe_side(items: &[serde_json::Value], coins: i32) -> String {
+    let mut parts: Vec<String> = items
+        .iter()
+        .filter_map(|item| item["name"].as_str().map(str::to_string))
+        .collect();
+    if coins > 0 {
+        parts.push(format!("{} 货币", coins));
+    }
+    if parts.is_empty() {
+        "无".to_string()
+    } else {
+        parts.join("、")
+    }
+}
+
+fn has_duplicates(ids: &[String]) -> bool {
+    let mut seen = HashSet::new();
+    ids.iter().any(|id| !seen.insert(id))
+}
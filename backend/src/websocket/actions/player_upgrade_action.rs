@@ -135,8 +135,7 @@ impl GameState {
         let new_display_name = if available_names.len() == 1 {
             available_names[0].clone()
         } else {
-            let mut rng = rand::rng();
-            let index = rng.random_range(0..available_names.len());
+            let index = self.rng.random_range(0..available_names.len());
             available_names[index].clone()
         };
 
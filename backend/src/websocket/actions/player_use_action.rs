@@ -7,7 +7,7 @@ use crate::websocket::actions::player_action_scheduler::ActionParams;
 use crate::websocket::actions::utils::{
     UseOutcome, decrement_uses, format_delta, format_use_remaining_suffix,
 };
-use crate::websocket::models::{ActionResult, ActionResults, GameState};
+use crate::websocket::models::{ActionResult, ActionResults, DamageEvent, GameState};
 use rand::seq::{IteratorRandom, SliceRandom};
 use serde_json::json;
 
@@ -514,8 +514,9 @@ impl GameState {
             }
         }
 
-        let mut rng = rand::rng();
-        let location_result = locations.iter().choose(&mut rng).cloned();
+        // 地点按名称排序，保证相同随机数序列得到相同结果
+        locations.sort();
+        let location_result = locations.iter().choose(&mut self.rng).cloned();
         let found = location_result.is_some();
         let strength_after = self.predict_strength_after_use(player_id, use_cost);
         let strength_delta = strength_after - strength_before;
@@ -594,7 +595,6 @@ impl GameState {
 
         let mut summaries: Vec<serde_json::Value> = Vec::new();
         let mut log_segments: Vec<String> = Vec::new();
-        let mut rng = rand::rng();
         for target_id in &unique_targets {
             let target_player = self
                 .players
@@ -614,7 +614,7 @@ impl GameState {
                 inventory_names.push(armor.name.clone());
             }
 
-            inventory_names.shuffle(&mut rng);
+            inventory_names.shuffle(&mut self.rng);
 
             let inventory_snapshot = inventory_names.clone();
             let inventory_display = if inventory_names.is_empty() {
@@ -721,8 +721,13 @@ impl GameState {
             };
 
             if requires_kill {
-                let mut kill_outcome =
-                    self.kill_player(&target_id, None, Some(player_id), "遥控地雷爆炸")?;
+                let mut kill_outcome = self.kill_player_with_weapon(
+                    &target_id,
+                    None,
+                    Some(player_id),
+                    "遥控地雷爆炸",
+                    Some(item_display_name),
+                )?;
                 death_results.append(&mut kill_outcome.results);
             }
 
@@ -792,18 +797,32 @@ impl GameState {
             })
             .collect();
 
-        results.push(ActionResult::new_system_message(
-            json!({
-                "impacts": impacts_json,
-                "strength": strength_after,
-                "strength_delta": strength_delta,
-                "uses_remaining": use_outcome.remaining_total,
-                "uses_night_remaining": use_outcome.remaining_night,
-            }),
-            vec![player_id.to_string()],
-            log_message,
-            true,
-        ));
+        let damage_events = impact_records
+            .iter()
+            .map(|record| DamageEvent {
+                attacker_id: Some(player_id.to_string()),
+                victim_id: record.0.clone(),
+                damage: record.2,
+                cause: "遥控地雷".to_string(),
+                weapon: Some(item_display_name.to_string()),
+            })
+            .collect();
+
+        results.push(
+            ActionResult::new_system_message(
+                json!({
+                    "impacts": impacts_json,
+                    "strength": strength_after,
+                    "strength_delta": strength_delta,
+                    "uses_remaining": use_outcome.remaining_total,
+                    "uses_night_remaining": use_outcome.remaining_night,
+                }),
+                vec![player_id.to_string()],
+                log_message,
+                true,
+            )
+            .with_damage_events(damage_events),
+        );
 
         results.extend(death_results);
 
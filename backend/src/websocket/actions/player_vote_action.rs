@@ -0,0 +1,74 @@
+//! 玩家白天投票行动处理
+//! 投票为匿名投票：投票结果仅告知投票者本人和导演，重复投票会覆盖之前的选择。
+
+use crate::websocket::models::{ActionResult, ActionResults, GameState};
+
+impl GameState {
+    /// 处理投票行动
+    pub fn handle_vote_action(
+        &mut self,
+        player_id: &str,
+        target_player_id: &str,
+    ) -> Result<ActionResults, String> {
+        let voter_name = self
+            .players
+            .get(player_id)
+            .ok_or("Player not found".to_string())?
+            .name
+            .clone();
+
+        // 验证投票目标存在且存活
+        let (target_name, target_alive) = match self.players.get(target_player_id) {
+            Some(target) => (target.name.clone(), target.is_alive),
+            None => {
+                let action_result = ActionResult::new_info_message(
+                    serde_json::json!({}),
+                    vec![player_id.to_string()],
+                    "投票目标不存在".to_string(),
+                    false,
+                );
+                return Ok(action_result.as_results());
+            }
+        };
+
+        if !target_alive {
+            let action_result = ActionResult::new_info_message(
+                serde_json::json!({}),
+                vec![player_id.to_string()],
+                "投票目标已死亡".to_string(),
+                false,
+            );
+            return Ok(action_result.as_results());
+        }
+
+        let previous_target = self
+            .votes
+            .insert(player_id.to_string(), target_player_id.to_string());
+
+        let vote_weight = self.players.get(player_id).unwrap().get_vote_weight();
+
+        let data = serde_json::json!({
+            "target_player_id": target_player_id,
+            "target_player_name": target_name,
+            "vote_weight": vote_weight,
+        });
+
+        let log_message = if previous_target.is_some() {
+            format!(
+                "{} 将投票改为 {}（{} 票）",
+                voter_name, target_name, vote_weight
+            )
+        } else {
+            format!(
+                "{} 投票给 {}（{} 票）",
+                voter_name, target_name, vote_weight
+            )
+        };
+
+        // 匿名投票：仅向投票者本人和导演广播
+        let action_result =
+            ActionResult::new_system_message(data, vec![player_id.to_string()], log_message, true);
+
+        Ok(action_result.as_results())
+    }
+}
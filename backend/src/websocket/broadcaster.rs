@@ -1,11 +1,15 @@
 //! 新的WebSocket消息广播器
-//! 负责向玩家和导演广播游戏状态更新消息，提供隐私保护机制
+//! 负责向玩家、导演和观众广播游戏状态更新消息，提供隐私保护机制
 
+use crate::game::game_rule_engine::TeammateBehavior;
 use crate::websocket::game_connection_manager::GameConnectionManager;
 use crate::websocket::models::SearchResultType;
 use crate::websocket::models::{ActionResult, GameState, Place, Player};
+use crate::websocket::presence::PlayerPresence;
 use chrono::Utc;
 use serde_json::{Value as JsonValue, json};
+use std::collections::HashMap;
+use std::time::Duration;
 
 /// 消息广播器
 #[derive(Clone)]
@@ -21,15 +25,29 @@ impl MessageBroadcaster {
     }
 
     /// 私有函数 - 生成导演视角消息
+    ///
+    /// `presence`为连接管理器记录的玩家在线状态，从未连接过的玩家视为离线。
     pub fn generate_director_message(
         game_state: &GameState,
         action_result: Option<&ActionResult>,
+        presence: &HashMap<String, PlayerPresence>,
     ) -> JsonValue {
+        let player_presence: serde_json::Map<String, JsonValue> = game_state
+            .players
+            .keys()
+            .map(|player_id| {
+                let status = presence.get(player_id).cloned().unwrap_or_default();
+                (player_id.clone(), json!(status))
+            })
+            .collect();
+
         json!({
             "global_state": game_state.to_director_client_json(),
             "game_data": {
                 "players": game_state.players,
                 "places": game_state.places,
+                "trade_offers": game_state.trade_offers,
+                "presence": player_presence,
             },
             "action_result": action_result.map(|res| res.to_client_response())
         })
@@ -41,18 +59,33 @@ impl MessageBroadcaster {
         player: &Player,
         action_result: Option<&ActionResult>,
     ) -> JsonValue {
-        // 构建玩家视角的地点信息（不包含其他玩家信息和物品信息）
+        // 构建玩家视角的地点信息（不包含其他玩家信息和物品信息，包含可移动到的相邻地点）
         let actor_places: Vec<JsonValue> = game_state
             .places
             .values()
-            .map(|place| place.to_player_client_json())
+            .map(|place| {
+                let mut place_json = place.to_player_client_json();
+                place_json["neighbors"] = json!(game_state.place_neighbors(&place.name));
+                place_json
+            })
             .collect();
 
         // 构建玩家视角的玩家列表信息（不包括玩家id和名字以外的任何信息）
+        // 队友行为规则允许观看队友状态时，队友额外包含生命值、体力值与位置
+        let show_teammate_status = game_state
+            .rule_engine
+            .teammate_behavior
+            .has(TeammateBehavior::VIEW_TEAMMATE_STATUS);
         let actor_players: Vec<JsonValue> = game_state
             .players
             .values()
-            .map(|p| p.to_player_client_json_for_other_players())
+            .map(|p| {
+                if show_teammate_status && game_state.are_teammates(&player.id, &p.id) {
+                    p.to_player_client_json_for_teammates()
+                } else {
+                    p.to_player_client_json_for_other_players()
+                }
+            })
             .collect();
 
         json!({
@@ -61,21 +94,93 @@ impl MessageBroadcaster {
                 "player": player.to_player_client_clone_for_self(), // 使用处理过的Player实例，last_search_result被设置为None
                 "actor_players": actor_players,
                 "actor_places": actor_places,
+                "trade_offers": game_state.pending_trade_offers_for(&player.id),
             },
             "action_result": action_result.map(|res| res.to_client_response())
         })
     }
 
+    /// 私有函数 - 生成观众视角消息
+    ///
+    /// 观众只能看到公开信息：天气与夜晚时间、各地点状态与存活人数、存活人数统计，
+    /// 以及动作结果中显式标记的公开事件，不包含玩家ID、物品、背包和私信。
+    pub fn generate_spectator_message(
+        game_state: &GameState,
+        action_result: Option<&ActionResult>,
+    ) -> JsonValue {
+        let alive_player_count = |place: &Place| {
+            place
+                .players
+                .iter()
+                .filter(|player_id| {
+                    game_state
+                        .players
+                        .get(player_id.as_str())
+                        .is_some_and(|player| player.is_alive)
+                })
+                .count()
+        };
+        let places: Vec<JsonValue> = game_state
+            .rule_engine
+            .map_config
+            .places
+            .iter()
+            .filter_map(|place_name| game_state.places.get(place_name))
+            .map(|place| {
+                json!({
+                    "name": place.name,
+                    "is_destroyed": place.is_destroyed,
+                    "player_count": alive_player_count(place),
+                })
+            })
+            .collect();
+
+        json!({
+            "global_state": {
+                "weather": game_state.weather,
+                "night_start_time": game_state.night_start_time,
+                "night_end_time": game_state.night_end_time,
+                "next_night_destroyed_places": game_state.next_night_destroyed_places,
+            },
+            "game_data": {
+                "places": places,
+                "alive_players": game_state.players.values().filter(|p| p.is_alive).count(),
+                "total_players": game_state.players.len(),
+            },
+            "action_result": action_result
+                .and_then(|res| res.spectator_event.as_ref().map(|event| (res, event)))
+                .map(|(res, event)| json!({
+                    "event": event,
+                    "timestamp": res.timestamp,
+                })),
+        })
+    }
+
+    /// 私有函数 - 生成观众占位视图
+    ///
+    /// 首个延迟视图推送之前连接的观众收到该视图，公开状态为空，直到延迟视图到期推送。
+    pub fn generate_spectator_placeholder() -> JsonValue {
+        json!({
+            "global_state": null,
+            "game_data": null,
+            "action_result": null,
+        })
+    }
+
     /// 公有函数 - 向所有导演广播
     pub async fn broadcast_to_directors(
         &self,
         game_state: &GameState,
         action_result: &ActionResult,
     ) -> Result<(), String> {
-        let message =
-            MessageBroadcaster::generate_director_message(game_state, Some(action_result));
+        let presence = self.connection_manager.presence().await;
+        let message = MessageBroadcaster::generate_director_message(
+            game_state,
+            Some(action_result),
+            &presence,
+        );
         self.connection_manager
-            .broadcast_to_directors(message)
+            .broadcast_to_directors(message, action_result.request_origin.as_ref())
             .await
     }
 
@@ -95,12 +200,26 @@ impl MessageBroadcaster {
                     Some(action_result),
                 );
                 self.connection_manager
-                    .broadcast_to_player(player_id, message)
+                    .broadcast_to_player(player_id, message, action_result.request_origin.as_ref())
                     .await?;
             }
         }
         Ok(())
     }
+
+    /// 公有函数 - 向观众广播（按规则配置延迟推送）
+    pub async fn broadcast_to_spectators(
+        &self,
+        game_state: &GameState,
+        action_result: &ActionResult,
+    ) {
+        let message =
+            MessageBroadcaster::generate_spectator_message(game_state, Some(action_result));
+        let delay = Duration::from_secs(game_state.rule_engine.spectator.delay_seconds);
+        self.connection_manager
+            .broadcast_to_spectators(message, delay)
+            .await;
+    }
 }
 
 // 广播相关的JSON转换函数
@@ -133,6 +252,20 @@ impl Player {
             "name": self.name,
         })
     }
+
+    pub fn to_player_client_json_for_teammates(&self) -> JsonValue {
+        json!({
+            "id": self.id,
+            "name": self.name,
+            "is_teammate": true,
+            "is_alive": self.is_alive,
+            "life": self.life,
+            "max_life": self.max_life,
+            "strength": self.strength,
+            "max_strength": self.max_strength,
+            "location": self.location,
+        })
+    }
 }
 
 impl Place {
@@ -155,6 +288,8 @@ impl GameState {
             "rules_config": self.rules_config,
             "server_now": Utc::now(),
             "shop": self.shop,
+            "rng_seed": self.rng.seed(),
+            "undo_available": self.director_undo.len(),
         })
     }
 
@@ -174,12 +309,15 @@ impl GameState {
 
 impl ActionResult {
     /// 创建用于返回给前端的数据结构，排除`broadcast_players`字段
+    ///
+    /// `request_id`在此始终为空，由连接管理器只在推送给发起请求的连接时填入。
     pub fn to_client_response(&self) -> JsonValue {
         json!({
             "data": self.data,
             "log_message": self.log_message,
             "message_type": self.message_type,
-            "timestamp": self.timestamp
+            "timestamp": self.timestamp,
+            "request_id": null
         })
     }
 }
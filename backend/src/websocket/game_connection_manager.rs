@@ -1,11 +1,24 @@
 //! WebSocket游戏连接管理器
-//! 负责管理单个游戏的所有WebSocket连接，包括玩家和导演连接
+//! 负责管理单个游戏的所有WebSocket连接，包括玩家、导演和观众连接
 
-use crate::websocket::models::ConnectionType;
+use crate::websocket::models::{ConnectionType, RequestOrigin};
+use crate::websocket::outbound_queue::{
+    CloseReason, OutboundReceiver, OutboundSender, QueueMetrics, outbound_queue,
+};
+use crate::websocket::presence::{PlayerPresence, PresenceEvent, PresenceTracker};
+use crate::websocket::replay_buffer::{
+    DEFAULT_REPLAY_BUFFER_CAPACITY, ReplayBuffer, ReplayEvent, ReplayEventType,
+};
+use crate::websocket::spectator_feed::{SpectatorFeed, SpectatorSnapshot};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
 use serde_json::{Value as JsonValue, json};
 use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::time::Duration;
+use tokio::sync::{Mutex, RwLock};
+use tokio::time::Instant;
+use tracing::warn;
 
 /// 连接句柄，用于标识每个WebSocket连接
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -14,10 +27,86 @@ pub struct ConnectionHandle {
     pub id: String,
     /// 用户ID（玩家ID或导演ID）
     pub user_id: String,
-    /// 连接类型（玩家或导演）
+    /// 连接类型（玩家、导演或观众）
     pub connection_type: ConnectionType,
 }
 
+/// 推送给单个连接的消息
+#[derive(Debug, Clone)]
+pub enum ConnectionMessage {
+    /// 广播器生成的游戏状态视图，由连接按同步模式编码为快照或增量补丁
+    GameState(JsonValue),
+    /// 系统消息数据
+    System(JsonValue),
+    /// 玩家在线状态变化事件（仅推送给导演）
+    Presence(JsonValue),
+    /// 游戏状态视图在发送队列中被更新的状态覆盖后保留的动作结果，随连接最近推送的状态下发
+    ActionResult(JsonValue),
+}
+
+impl ConnectionMessage {
+    /// 需要保留以便重连补发的事件（不含动作结果的状态推送无需补发）
+    fn replay_event(&self) -> Option<(ReplayEventType, JsonValue)> {
+        match self {
+            ConnectionMessage::GameState(view) => view
+                .get("action_result")
+                .filter(|action_result| !action_result.is_null())
+                .map(|action_result| (ReplayEventType::ActionResult, action_result.clone())),
+            ConnectionMessage::System(data) => Some((ReplayEventType::SystemMessage, data.clone())),
+            ConnectionMessage::Presence(data) => Some((ReplayEventType::Presence, data.clone())),
+            ConnectionMessage::ActionResult(data) => {
+                Some((ReplayEventType::ActionResult, data.clone()))
+            }
+        }
+    }
+
+    /// 在游戏状态视图的动作结果中填入客户端请求ID
+    fn with_request_id(&self, request_id: &str) -> Self {
+        let mut message = self.clone();
+        let action_result = match &mut message {
+            ConnectionMessage::GameState(view) => view
+                .get_mut("action_result")
+                .and_then(JsonValue::as_object_mut),
+            _ => None,
+        };
+        if let Some(action_result) = action_result {
+            action_result.insert("request_id".to_string(), json!(request_id));
+        }
+        message
+    }
+}
+
+/// 带接收者序号的推送消息
+#[derive(Debug, Clone)]
+pub struct SequencedMessage {
+    /// 接收者维度的消息序号
+    pub seq: u64,
+    /// 消息内容
+    pub message: ConnectionMessage,
+}
+
+/// 单个连接的统计信息
+#[derive(Debug, Clone, Serialize)]
+pub struct ConnectionMetrics {
+    /// 连接ID
+    pub connection_id: String,
+    /// 用户ID（玩家ID、导演或观众）
+    pub user_id: String,
+    /// 连接类型
+    pub connection_type: ConnectionType,
+    /// 连接建立时间
+    pub connected_at: DateTime<Utc>,
+    /// 发送队列统计
+    #[serde(flatten)]
+    pub queue: QueueMetrics,
+}
+
+/// 导演连接使用的固定用户ID，所有导演连接共享同一个消息序列
+pub const DIRECTOR_USER_ID: &str = "director";
+
+/// 观众连接使用的固定用户ID，所有观众连接共享同一个消息序列
+pub const SPECTATOR_USER_ID: &str = "spectator";
+
 /// WebSocket游戏连接管理器
 /// 管理单个游戏的所有WebSocket连接
 #[derive(Clone)]
@@ -26,28 +115,44 @@ pub struct GameConnectionManager {
     player_connections: Arc<RwLock<HashMap<String, Vec<ConnectionHandle>>>>,
     /// 导演连接列表
     director_connections: Arc<RwLock<Vec<ConnectionHandle>>>,
-    /// 实际的WebSocket连接：连接句柄 -> WebSocket发送端
-    connections:
-        Arc<RwLock<HashMap<ConnectionHandle, tokio::sync::mpsc::UnboundedSender<JsonValue>>>>,
+    /// 观众连接列表
+    spectator_connections: Arc<RwLock<Vec<ConnectionHandle>>>,
+    /// 实际的WebSocket连接：连接句柄 -> 发送队列
+    connections: Arc<RwLock<HashMap<ConnectionHandle, OutboundSender>>>,
+    /// 每个连接的发送队列容量
+    queue_capacity: usize,
+    /// 每个接收者（玩家ID或导演）的事件补发缓冲区
+    replay_buffers: Arc<RwLock<HashMap<String, ReplayBuffer>>>,
+    /// 观众延迟推送队列
+    spectator_feed: Arc<Mutex<SpectatorFeed>>,
+    /// 玩家在线状态
+    presence: Arc<Mutex<PresenceTracker>>,
 }
 
 impl GameConnectionManager {
-    /// 创建新的游戏连接管理器
-    pub fn new() -> Self {
+    /// 创建新的游戏连接管理器，`queue_capacity`为每个连接的发送队列容量
+    pub fn new(queue_capacity: usize) -> Self {
         Self {
             player_connections: Arc::new(RwLock::new(HashMap::new())),
             director_connections: Arc::new(RwLock::new(Vec::new())),
+            spectator_connections: Arc::new(RwLock::new(Vec::new())),
             connections: Arc::new(RwLock::new(HashMap::new())),
+            queue_capacity,
+            replay_buffers: Arc::new(RwLock::new(HashMap::new())),
+            spectator_feed: Arc::new(Mutex::new(SpectatorFeed::new())),
+            presence: Arc::new(Mutex::new(PresenceTracker::new())),
         }
     }
 
-    /// 添加新的WebSocket连接
+    /// 添加新的WebSocket连接，返回连接句柄与该连接发送队列的接收端
+    ///
+    /// 玩家建立第一个连接时标记为在线，并向导演推送上线事件。
     pub async fn add_connection(
         &self,
         user_id: String,
         connection_type: ConnectionType,
-        sender: tokio::sync::mpsc::UnboundedSender<JsonValue>,
-    ) -> ConnectionHandle {
+    ) -> (ConnectionHandle, OutboundReceiver) {
+        let (sender, receiver) = outbound_queue(self.queue_capacity);
         let connection_id = uuid::Uuid::new_v4().to_string();
         let handle = ConnectionHandle {
             id: connection_id.clone(),
@@ -62,36 +167,56 @@ impl GameConnectionManager {
             .insert(handle.clone(), sender);
 
         // 根据连接类型添加到对应的映射中
+        let mut presence_event = None;
         match connection_type {
             ConnectionType::Actor => {
                 let mut player_connections = self.player_connections.write().await;
-                player_connections
-                    .entry(user_id)
-                    .or_insert_with(Vec::new)
-                    .push(handle.clone());
+                let connections = player_connections.entry(user_id).or_insert_with(Vec::new);
+                connections.push(handle.clone());
+                if connections.len() == 1 {
+                    presence_event = self.presence.lock().await.join(&handle.user_id, Utc::now());
+                }
             }
             ConnectionType::Director => {
                 self.director_connections.write().await.push(handle.clone());
             }
+            ConnectionType::Spectator => {
+                self.spectator_connections
+                    .write()
+                    .await
+                    .push(handle.clone());
+            }
         }
 
-        handle
+        if let Some(event) = presence_event {
+            self.send_presence_event(event).await;
+        }
+
+        (handle, receiver)
     }
 
     /// 移除WebSocket连接
+    ///
+    /// 玩家的最后一个连接断开时标记为离线，并向导演推送离线事件。
     pub async fn remove_connection(&self, handle: &ConnectionHandle) {
         // 从连接映射中移除
         self.connections.write().await.remove(handle);
 
         // 从对应的用户连接列表中移除
+        let mut presence_event = None;
         match handle.connection_type {
             ConnectionType::Actor => {
                 let mut player_connections = self.player_connections.write().await;
                 if let Some(connections) = player_connections.get_mut(&handle.user_id) {
                     connections.retain(|conn| conn.id != handle.id);
-                    // 如果该玩家没有其他连接，移除该玩家的条目
+                    // 如果该玩家没有其他连接，移除该玩家的条目并标记为离线
                     if connections.is_empty() {
                         player_connections.remove(&handle.user_id);
+                        presence_event = self
+                            .presence
+                            .lock()
+                            .await
+                            .leave(&handle.user_id, Utc::now());
                     }
                 }
             }
@@ -101,7 +226,33 @@ impl GameConnectionManager {
                     .await
                     .retain(|conn| conn.id != handle.id);
             }
+            ConnectionType::Spectator => {
+                self.spectator_connections
+                    .write()
+                    .await
+                    .retain(|conn| conn.id != handle.id);
+            }
         }
+
+        if let Some(event) = presence_event {
+            self.send_presence_event(event).await;
+        }
+    }
+
+    /// 向所有导演连接推送玩家在线状态变化事件
+    async fn send_presence_event(&self, event: PresenceEvent) {
+        let handles = self.get_director_connections().await;
+        self.publish(
+            DIRECTOR_USER_ID,
+            &handles,
+            ConnectionMessage::Presence(json!(event)),
+        )
+        .await;
+    }
+
+    /// 所有连接过的玩家的在线状态：玩家ID -> 在线状态
+    pub async fn presence(&self) -> HashMap<String, PlayerPresence> {
+        self.presence.lock().await.snapshot()
     }
 
     /// 获取指定玩家的所有连接句柄
@@ -118,61 +269,243 @@ impl GameConnectionManager {
         self.director_connections.read().await.clone()
     }
 
+    /// 获取所有观众连接句柄
+    pub async fn get_spectator_connections(&self) -> Vec<ConnectionHandle> {
+        self.spectator_connections.read().await.clone()
+    }
+
+    /// 指定玩家与（可选的）导演当前的连接总数
+    pub async fn recipient_connection_count(
+        &self,
+        player_ids: &[String],
+        include_directors: bool,
+    ) -> usize {
+        let player_connections = self.player_connections.read().await;
+        let players: usize = player_ids
+            .iter()
+            .filter_map(|player_id| player_connections.get(player_id))
+            .map(Vec::len)
+            .sum();
+        drop(player_connections);
+        if include_directors {
+            players + self.director_connections.read().await.len()
+        } else {
+            players
+        }
+    }
+
     /// 获取所有连接句柄
     pub async fn get_all_connections(&self) -> Vec<ConnectionHandle> {
         let connections = self.connections.read().await;
         connections.keys().cloned().collect()
     }
 
-    /// 向指定连接发送消息
-    pub async fn send_message_to_connection(
+    /// 获取所有连接的统计信息
+    pub async fn connection_metrics(&self) -> Vec<ConnectionMetrics> {
+        let connections = self.connections.read().await;
+        let mut metrics: Vec<ConnectionMetrics> = connections
+            .iter()
+            .map(|(handle, sender)| ConnectionMetrics {
+                connection_id: handle.id.clone(),
+                user_id: handle.user_id.clone(),
+                connection_type: handle.connection_type.clone(),
+                connected_at: sender.connected_at(),
+                queue: sender.metrics(),
+            })
+            .collect();
+        metrics.sort_by_key(|metrics| metrics.connected_at);
+        metrics
+    }
+
+    /// 为接收者分配消息序号并发送到其指定连接
+    ///
+    /// 接收者当前没有连接时同样分配序号并保留事件，供其重连后补发。
+    /// 发送队列已满的连接被强制断开，客户端可凭最后收到的序号重连补发。
+    async fn publish(
         &self,
-        handle: &ConnectionHandle,
-        message: JsonValue,
-    ) -> Result<(), String> {
+        recipient: &str,
+        handles: &[ConnectionHandle],
+        message: ConnectionMessage,
+    ) {
+        self.publish_with_origin(recipient, handles, message, None)
+            .await;
+    }
+
+    /// 与`publish`相同，但只向发起请求的连接回传请求ID
+    ///
+    /// 补发缓冲区保留不含请求ID的事件，重连后的连接不再是发起请求的连接。
+    async fn publish_with_origin(
+        &self,
+        recipient: &str,
+        handles: &[ConnectionHandle],
+        message: ConnectionMessage,
+        request_origin: Option<&RequestOrigin>,
+    ) {
+        // 持有缓冲区写锁直到发送完成，保证同一接收者的消息按序号顺序入队
+        let mut replay_buffers = self.replay_buffers.write().await;
+        let seq = replay_buffers
+            .entry(recipient.to_string())
+            .or_insert_with(|| ReplayBuffer::new(DEFAULT_REPLAY_BUFFER_CAPACITY))
+            .push(message.replay_event());
+
+        let mut overflowed = Vec::new();
         let connections = self.connections.read().await;
-        if let Some(sender) = connections.get(handle) {
-            sender
-                .send(message)
-                .map_err(|e| format!("Failed to send message: {}", e))
-        } else {
-            Err("Connection not found".to_string())
+        for handle in handles {
+            if let Some(sender) = connections.get(handle) {
+                let message = match request_origin {
+                    Some(origin) if origin.connection_id == handle.id => {
+                        message.with_request_id(&origin.request_id)
+                    }
+                    _ => message.clone(),
+                };
+                let result = sender.send(SequencedMessage { seq, message });
+                if result == Err(CloseReason::SlowConsumer) {
+                    overflowed.push(handle.clone());
+                }
+            }
+        }
+        drop(connections);
+        drop(replay_buffers);
+
+        for handle in overflowed {
+            warn!(
+                "Disconnecting slow WebSocket consumer {} ({:?} {})",
+                handle.id, handle.connection_type, handle.user_id
+            );
+            // 移除玩家连接会再次推送离线事件，装箱以打破异步递归
+            Box::pin(self.remove_connection(&handle)).await;
         }
     }
 
-    /// 向指定玩家的所有连接广播消息
+    /// 获取接收者当前的消息序号，以及`last_seen_seq`之后错过的事件
+    ///
+    /// 未提供`last_seen_seq`或缺口超出缓冲区范围时事件为`None`。
+    pub async fn catch_up(
+        &self,
+        recipient: &str,
+        last_seen_seq: Option<u64>,
+    ) -> (u64, Option<Vec<ReplayEvent>>) {
+        let replay_buffers = self.replay_buffers.read().await;
+        match replay_buffers.get(recipient) {
+            Some(buffer) => (
+                buffer.last_seq(),
+                last_seen_seq.and_then(|last_seen_seq| buffer.events_since(last_seen_seq)),
+            ),
+            // 尚无任何推送时，只有序号为0的客户端视为未错过事件
+            None => (0, last_seen_seq.filter(|seq| *seq == 0).map(|_| Vec::new())),
+        }
+    }
+
+    /// 向指定玩家的所有连接广播游戏状态视图
+    ///
+    /// `request_origin`为产生该视图的客户端请求，请求ID只回传给发起请求的连接。
     pub async fn broadcast_to_player(
         &self,
         player_id: &str,
         message: JsonValue,
+        request_origin: Option<&RequestOrigin>,
     ) -> Result<(), String> {
         let handles = self.get_player_connections(player_id).await;
-        for handle in handles {
-            let _ = self
-                .send_message_to_connection(&handle, message.clone())
-                .await;
-        }
+        self.publish_with_origin(
+            player_id,
+            &handles,
+            ConnectionMessage::GameState(message),
+            request_origin,
+        )
+        .await;
         Ok(())
     }
 
-    /// 向所有导演连接广播消息
-    pub async fn broadcast_to_directors(&self, message: JsonValue) -> Result<(), String> {
+    /// 向指定玩家的所有连接发送消息
+    async fn send_to_player(&self, player_id: &str, message: ConnectionMessage) {
+        let handles = self.get_player_connections(player_id).await;
+        self.publish(player_id, &handles, message).await;
+    }
+
+    /// 向所有导演连接广播游戏状态视图，请求ID只回传给发起请求的连接
+    pub async fn broadcast_to_directors(
+        &self,
+        message: JsonValue,
+        request_origin: Option<&RequestOrigin>,
+    ) -> Result<(), String> {
         let handles = self.get_director_connections().await;
-        for handle in handles {
-            let _ = self
-                .send_message_to_connection(&handle, message.clone())
-                .await;
-        }
+        self.publish_with_origin(
+            DIRECTOR_USER_ID,
+            &handles,
+            ConnectionMessage::GameState(message),
+            request_origin,
+        )
+        .await;
         Ok(())
     }
 
+    /// 向所有导演连接推送系统消息
+    pub async fn notify_directors(&self, message: &str) {
+        let handles = self.get_director_connections().await;
+        self.publish(
+            DIRECTOR_USER_ID,
+            &handles,
+            ConnectionMessage::System(json!({ "message": message })),
+        )
+        .await;
+    }
+
+    /// 向观众广播游戏状态视图，视图在`delay`之后按顺序推送
+    ///
+    /// 公开状态未变化且没有公开事件的视图会被忽略。
+    pub async fn broadcast_to_spectators(&self, message: JsonValue, delay: Duration) {
+        let due = Instant::now() + delay;
+        if !self.spectator_feed.lock().await.enqueue(message, due) {
+            return;
+        }
+        if delay.is_zero() {
+            self.publish_due_spectator_views().await;
+            return;
+        }
+
+        // 到期后推送所有已到期的视图；多个任务同时到期时由首个任务按入队顺序推送
+        let manager = self.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep_until(due).await;
+            manager.publish_due_spectator_views().await;
+        });
+    }
+
+    /// 推送所有已到推送时间的观众视图
+    async fn publish_due_spectator_views(&self) {
+        // 持有队列锁直到发送完成，保证视图按入队顺序分配序号
+        let mut spectator_feed = self.spectator_feed.lock().await;
+        let views = spectator_feed.take_due(Instant::now());
+        if views.is_empty() {
+            return;
+        }
+        let handles = self.get_spectator_connections().await;
+        for view in views {
+            self.publish(
+                SPECTATOR_USER_ID,
+                &handles,
+                ConnectionMessage::GameState(view),
+            )
+            .await;
+        }
+    }
+
+    /// 新观众连接的初始视图，见[`SpectatorSnapshot`]
+    pub async fn spectator_snapshot(&self) -> SpectatorSnapshot {
+        self.spectator_feed.lock().await.snapshot()
+    }
+
     /// 向所有连接广播消息
-    pub async fn broadcast_to_all(&self, message: JsonValue) -> Result<(), String> {
-        let handles = self.get_all_connections().await;
-        for handle in handles {
-            let _ = self
-                .send_message_to_connection(&handle, message.clone())
-                .await;
+    pub async fn broadcast_to_all(&self, message: ConnectionMessage) -> Result<(), String> {
+        let mut handles_by_user: HashMap<String, Vec<ConnectionHandle>> = HashMap::new();
+        for handle in self.get_all_connections().await {
+            handles_by_user
+                .entry(handle.user_id.clone())
+                .or_default()
+                .push(handle);
+        }
+        for (user_id, handles) in handles_by_user {
+            self.publish(&user_id, &handles, message.clone()).await;
         }
         Ok(())
     }
@@ -185,12 +518,7 @@ impl GameConnectionManager {
 
     /// 使用自定义消息断开所有连接
     pub async fn disconnect_all_connections_with_message(&self, message: &str) {
-        let disconnect_message = json!({
-            "type": "system_message",
-            "data": {
-                "message": message
-            }
-        });
+        let disconnect_message = ConnectionMessage::System(json!({ "message": message }));
 
         // 向所有连接广播断开消息
         let _ = self.broadcast_to_all(disconnect_message).await;
@@ -199,5 +527,32 @@ impl GameConnectionManager {
         self.connections.write().await.clear();
         self.player_connections.write().await.clear();
         self.director_connections.write().await.clear();
+        self.spectator_connections.write().await.clear();
+        self.presence.lock().await.leave_all(Utc::now());
+    }
+
+    /// 断开指定玩家的所有连接并向其发送断开消息
+    pub async fn disconnect_player_connections(&self, player_id: &str, message: &str) {
+        let disconnect_message = ConnectionMessage::System(json!({ "message": message }));
+
+        self.send_to_player(player_id, disconnect_message).await;
+
+        // 移除连接后发送端被释放，连接的消息转发任务随之关闭WebSocket
+        for handle in self.get_player_connections(player_id).await {
+            self.remove_connection(&handle).await;
+        }
+    }
+
+    /// 断开所有观众连接并向其发送断开消息
+    pub async fn disconnect_spectator_connections(&self, message: &str) {
+        let disconnect_message = ConnectionMessage::System(json!({ "message": message }));
+
+        let handles = self.get_spectator_connections().await;
+        self.publish(SPECTATOR_USER_ID, &handles, disconnect_message)
+            .await;
+
+        for handle in handles {
+            self.remove_connection(&handle).await;
+        }
     }
 }
@@ -0,0 +1,223 @@
+//! 昼夜自动调度器
+//! 为启用了`game_flow`自动调度的进行中游戏维护后台任务，按服务器时钟推进昼夜阶段
+
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use serde_json::json;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+use tracing::error;
+
+use super::models::{ActionResults, GameState};
+use super::service::{WebSocketService, save_settlement_checkpoint};
+use crate::game::action_journal::{ActionJournal, JournalActor};
+use crate::routes::AppState;
+
+/// 昼夜自动调度器
+/// 每个进行中的游戏最多对应一个后台调度任务
+#[derive(Clone, Default)]
+pub struct GamePhaseScheduler {
+    /// 游戏ID到调度任务的映射
+    tasks: Arc<DashMap<String, JoinHandle<()>>>,
+}
+
+impl GamePhaseScheduler {
+    /// 创建新的昼夜调度器
+    pub fn new() -> Self {
+        Self {
+            tasks: Arc::new(DashMap::new()),
+        }
+    }
+
+    /// 为指定游戏启动调度任务（规则未启用自动调度时不启动）
+    pub async fn start(&self, app_state: &AppState, game_id: &str) {
+        self.stop(game_id);
+
+        let game_state_ref = match app_state.game_state_manager.get_game_state(game_id).await {
+            Ok(game_state_ref) => game_state_ref,
+            Err(_) => return,
+        };
+
+        if !game_state_ref
+            .read()
+            .await
+            .rule_engine
+            .game_flow
+            .is_scheduled()
+        {
+            return;
+        }
+
+        let handle = tokio::spawn(run_phase_loop(
+            app_state.clone(),
+            game_id.to_string(),
+            game_state_ref,
+        ));
+        self.tasks.insert(game_id.to_string(), handle);
+    }
+
+    /// 停止指定游戏的调度任务
+    pub fn stop(&self, game_id: &str) {
+        if let Some((_, handle)) = self.tasks.remove(game_id) {
+            handle.abort();
+        }
+    }
+}
+
+/// 昼夜调度主循环
+async fn run_phase_loop(
+    app_state: AppState,
+    game_id: String,
+    game_state_ref: Arc<RwLock<GameState>>,
+) {
+    loop {
+        // 安排下一个夜晚
+        let (night_start, night_end) = {
+            // 游戏正在回退时结束任务，回退完成后会重新启动调度
+            let Some(_operation) = app_state.game_state_manager.begin_operation(&game_id) else {
+                return;
+            };
+            let mut game_state = game_state_ref.write().await;
+            let mut scheduled = None;
+            if game_state.needs_night_schedule(Utc::now()) {
+                let result = match apply_system_action(&mut game_state, "schedule_next_night").await
+                {
+                    Ok(result) => result,
+                    Err(e) => {
+                        drop(game_state);
+                        stop_on_journal_failure(&app_state, &game_id, &e).await;
+                        return;
+                    }
+                };
+                scheduled = Some((result, game_state.clone()));
+            }
+            // 未能安排夜晚（规则未启用自动调度）时结束任务
+            if game_state.needs_night_schedule(Utc::now()) {
+                return;
+            }
+            let window = match (game_state.night_start_time, game_state.night_end_time) {
+                (Some(start), Some(end)) => (start, end),
+                _ => return,
+            };
+            drop(game_state);
+            if let Some((result, updated_game_state)) = scheduled {
+                broadcast_results(&app_state, &game_id, result, updated_game_state).await;
+            }
+            window
+        };
+
+        // 夜晚开始
+        sleep_until(night_start).await;
+        {
+            let Some(_operation) = app_state.game_state_manager.begin_operation(&game_id) else {
+                return;
+            };
+            let mut game_state = game_state_ref.write().await;
+            // 导演在等待期间修改了夜晚时间，重新安排
+            if !is_same_window(&game_state, night_start, night_end) {
+                continue;
+            }
+            let result = match apply_system_action(&mut game_state, "scheduled_night_start").await {
+                Ok(result) => result,
+                Err(e) => {
+                    drop(game_state);
+                    stop_on_journal_failure(&app_state, &game_id, &e).await;
+                    return;
+                }
+            };
+            let updated_game_state = game_state.clone();
+            drop(game_state);
+            broadcast_results(&app_state, &game_id, result, updated_game_state).await;
+        }
+
+        // 夜晚结束
+        sleep_until(night_end).await;
+        {
+            let Some(_operation) = app_state.game_state_manager.begin_operation(&game_id) else {
+                return;
+            };
+            let mut game_state = game_state_ref.write().await;
+            if !is_same_window(&game_state, night_start, night_end) {
+                continue;
+            }
+            // 导演本夜已手动结算时不会再次结算，也不再保存检查点
+            let settles =
+                game_state.rule_engine.game_flow.auto_settlement && !game_state.night_settled;
+            let result = match apply_system_action(&mut game_state, "scheduled_night_end").await {
+                Ok(result) => result,
+                Err(e) => {
+                    drop(game_state);
+                    stop_on_journal_failure(&app_state, &game_id, &e).await;
+                    return;
+                }
+            };
+            let updated_game_state = game_state.clone();
+            drop(game_state);
+            // 自动结算完成后保存检查点存档
+            if settles && result.is_ok() {
+                save_settlement_checkpoint(&game_id, &updated_game_state);
+            }
+            broadcast_results(&app_state, &game_id, result, updated_game_state).await;
+        }
+    }
+}
+
+/// 写入行动日志并执行系统调度事件
+///
+/// 行动日志写入失败时不执行该事件，返回外层错误；内层为事件本身的处理结果。
+async fn apply_system_action(
+    game_state: &mut GameState,
+    action: &str,
+) -> Result<Result<ActionResults, String>, String> {
+    let entry = ActionJournal::record(game_state, JournalActor::System, action, json!({})).await?;
+    Ok(entry.apply(game_state))
+}
+
+/// 行动日志无法写入时停止自动调度，并通知导演改为手动推进昼夜
+async fn stop_on_journal_failure(app_state: &AppState, game_id: &str, error: &str) {
+    error!(game_id, "Phase scheduler stopped: {}", error);
+    app_state
+        .global_connection_manager
+        .get_manager(game_id.to_string())
+        .notify_directors(&format!("行动日志写入失败，昼夜自动调度已停止：{}", error))
+        .await;
+}
+
+/// 检查游戏状态中的夜晚时间窗口是否仍为调度器安排的窗口
+fn is_same_window(
+    game_state: &GameState,
+    night_start: DateTime<Utc>,
+    night_end: DateTime<Utc>,
+) -> bool {
+    game_state.night_start_time == Some(night_start) && game_state.night_end_time == Some(night_end)
+}
+
+/// 等待至指定时刻
+async fn sleep_until(target: DateTime<Utc>) {
+    let duration = (target - Utc::now()).to_std().unwrap_or_default();
+    tokio::time::sleep(duration).await;
+}
+
+/// 复用WebSocket服务的结果处理流程进行广播与日志记录
+async fn broadcast_results(
+    app_state: &AppState,
+    game_id: &str,
+    result: Result<ActionResults, String>,
+    updated_game_state: GameState,
+) {
+    if result
+        .as_ref()
+        .is_ok_and(|results| results.results.is_empty())
+    {
+        return;
+    }
+
+    let connection_manager = app_state
+        .global_connection_manager
+        .get_manager(game_id.to_string());
+    let ws_service = WebSocketService::new(app_state.clone(), connection_manager);
+    let _ = ws_service
+        .handle_action_results(result, updated_game_state)
+        .await;
+}
@@ -2,9 +2,13 @@
 //! 负责管理所有游戏的GameConnectionManager实例
 
 use dashmap::DashMap;
+use std::collections::HashMap;
 use std::sync::Arc;
 
-use super::game_connection_manager::GameConnectionManager;
+use super::game_connection_manager::{ConnectionMetrics, GameConnectionManager};
+use super::heartbeat::HeartbeatConfig;
+use super::models::ConnectionType;
+use super::outbound_queue::DEFAULT_OUTBOUND_QUEUE_CAPACITY;
 
 /// 全局WebSocket连接管理器
 /// 管理所有游戏的GameConnectionManager实例
@@ -12,6 +16,10 @@ use super::game_connection_manager::GameConnectionManager;
 pub struct GlobalConnectionManager {
     /// 游戏ID到连接管理器的映射
     managers: Arc<DashMap<String, Arc<GameConnectionManager>>>,
+    /// 每个连接的发送队列容量
+    queue_capacity: usize,
+    /// 连接心跳配置
+    heartbeat: HeartbeatConfig,
 }
 
 impl GlobalConnectionManager {
@@ -19,9 +27,28 @@ impl GlobalConnectionManager {
     pub fn new() -> Self {
         Self {
             managers: Arc::new(DashMap::new()),
+            queue_capacity: DEFAULT_OUTBOUND_QUEUE_CAPACITY,
+            heartbeat: HeartbeatConfig::default(),
         }
     }
 
+    /// 设置每个连接的发送队列容量
+    pub fn with_queue_capacity(mut self, queue_capacity: usize) -> Self {
+        self.queue_capacity = queue_capacity;
+        self
+    }
+
+    /// 设置连接心跳配置
+    pub fn with_heartbeat(mut self, heartbeat: HeartbeatConfig) -> Self {
+        self.heartbeat = heartbeat;
+        self
+    }
+
+    /// 连接心跳配置
+    pub fn heartbeat(&self) -> HeartbeatConfig {
+        self.heartbeat
+    }
+
     /// 获取指定游戏的连接管理器，如果不存在则创建新的
     pub fn get_manager(&self, game_id: String) -> Arc<GameConnectionManager> {
         // 尝试获取现有的GameConnectionManager
@@ -30,7 +57,7 @@ impl GlobalConnectionManager {
         }
 
         // 如果不存在，则创建新的GameConnectionManager
-        let new_manager = Arc::new(GameConnectionManager::new());
+        let new_manager = Arc::new(GameConnectionManager::new(self.queue_capacity));
         self.managers.insert(game_id, new_manager.clone());
         new_manager
     }
@@ -58,6 +85,58 @@ impl GlobalConnectionManager {
         }
     }
 
+    /// 断开指定游戏中某个玩家的所有连接
+    pub async fn disconnect_player_connections(
+        &self,
+        game_id: &str,
+        player_id: &str,
+        message: &str,
+    ) {
+        if let Some(manager_entry) = self.managers.get(game_id) {
+            let manager = manager_entry.value().clone();
+            drop(manager_entry);
+            manager
+                .disconnect_player_connections(player_id, message)
+                .await;
+        }
+    }
+
+    /// 断开指定游戏中所有观众连接
+    pub async fn disconnect_spectator_connections(&self, game_id: &str, message: &str) {
+        if let Some(manager_entry) = self.managers.get(game_id) {
+            let manager = manager_entry.value().clone();
+            drop(manager_entry);
+            manager.disconnect_spectator_connections(message).await;
+        }
+    }
+
+    /// 获取指定游戏所有连接的统计信息
+    pub async fn connection_metrics(&self, game_id: &str) -> Vec<ConnectionMetrics> {
+        let Some(manager_entry) = self.managers.get(game_id) else {
+            return Vec::new();
+        };
+        let manager = manager_entry.value().clone();
+        drop(manager_entry);
+        manager.connection_metrics().await
+    }
+
+    /// 所有游戏中各类型连接的数量
+    pub async fn connection_counts(&self) -> HashMap<ConnectionType, usize> {
+        let managers: Vec<Arc<GameConnectionManager>> = self
+            .managers
+            .iter()
+            .map(|entry| entry.value().clone())
+            .collect();
+
+        let mut counts = HashMap::new();
+        for manager in managers {
+            for handle in manager.get_all_connections().await {
+                *counts.entry(handle.connection_type).or_insert(0) += 1;
+            }
+        }
+        counts
+    }
+
     // 获取所有游戏连接管理器
     // pub fn get_all_managers(&self) -> Vec<Arc<GameConnectionManager>> {
     //     self.managers
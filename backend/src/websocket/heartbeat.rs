@@ -0,0 +1,29 @@
+//! WebSocket心跳配置
+//! 服务器定期向客户端发送Ping帧，超过空闲超时时间未收到客户端任何数据（包括Pong）时关闭连接，
+//! 以便及时清理半开连接并更新玩家在线状态
+
+use std::time::Duration;
+
+/// 默认的Ping发送间隔
+pub const DEFAULT_PING_INTERVAL: Duration = Duration::from_secs(30);
+
+/// 默认的空闲超时时间
+pub const DEFAULT_IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+
+/// 心跳配置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HeartbeatConfig {
+    /// 服务器发送Ping帧的间隔
+    pub ping_interval: Duration,
+    /// 未收到客户端任何数据时关闭连接的超时时间
+    pub idle_timeout: Duration,
+}
+
+impl Default for HeartbeatConfig {
+    fn default() -> Self {
+        Self {
+            ping_interval: DEFAULT_PING_INTERVAL,
+            idle_timeout: DEFAULT_IDLE_TIMEOUT,
+        }
+    }
+}
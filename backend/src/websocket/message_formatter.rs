@@ -4,6 +4,7 @@
 //! 使用方法：
 //! 1. 调用相应的消息生成函数（如system_message, game_state_message等）直接生成可发送的WebSocket消息
 
+use crate::websocket::models::WebSocketError;
 use serde_json::{Value as JsonValue, json};
 use yawc::frame::FrameView;
 
@@ -26,7 +27,22 @@ pub fn game_state_message(data: JsonValue) -> FrameView {
     generate_message("game_state", data)
 }
 
-/// 生成错误消息，直接返回可发送的WebSocket消息
-pub fn error_message(data: JsonValue) -> FrameView {
-    generate_message("error", data)
+/// 生成游戏状态增量补丁消息，直接返回可发送的WebSocket消息
+pub fn game_state_patch_message(data: JsonValue) -> FrameView {
+    generate_message("game_state_patch", data)
+}
+
+/// 生成玩家在线状态变化消息，直接返回可发送的WebSocket消息
+pub fn presence_message(data: JsonValue) -> FrameView {
+    generate_message("presence", data)
+}
+
+/// 生成重连事件补发消息，直接返回可发送的WebSocket消息
+pub fn event_replay_message(data: JsonValue) -> FrameView {
+    generate_message("event_replay", data)
+}
+
+/// 生成错误消息（包含错误码、描述与请求ID），直接返回可发送的WebSocket消息
+pub fn error_message(error: &WebSocketError) -> FrameView {
+    generate_message("error", json!(error))
 }
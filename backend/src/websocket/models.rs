@@ -1,8 +1,11 @@
 //! WebSocket相关模型定义
 
+use crate::game::game_rng::GameRng;
 use crate::game::game_rule_engine::{GameRuleEngine, Item};
 use crate::game::models::MessageType;
+use crate::websocket::actions::director_undo_action::DirectorUndoRecord;
 use crate::websocket::actions::utils::restore_item_nightly_uses;
+use crate::websocket::state_patch::StateSyncMode;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Deserializer, Serialize};
 use serde_json::Value as JsonValue;
@@ -17,15 +20,20 @@ pub enum ConnectionType {
     /// 导演连接
     #[serde(rename = "director")]
     Director,
+    /// 观众连接
+    #[serde(rename = "spectator")]
+    Spectator,
 }
 
-/// WebSocket认证请求
-#[derive(Debug, Clone, Deserialize, Serialize)]
-pub struct WebSocketAuthRequest {
-    /// 用户类型
-    pub user_type: ConnectionType,
-    /// 密码
-    pub password: String,
+impl ConnectionType {
+    /// 连接类型名称（与序列化名称一致）
+    pub fn label(&self) -> &'static str {
+        match self {
+            ConnectionType::Actor => "actor",
+            ConnectionType::Director => "director",
+            ConnectionType::Spectator => "spectator",
+        }
+    }
 }
 
 /// WebSocket消息类型
@@ -37,6 +45,19 @@ pub enum WebSocketMessageType {
     /// 导演控制
     #[serde(rename = "director_action")]
     DirectorAction,
+    /// 请求重新推送完整游戏状态（客户端发现状态版本缺口时发送）
+    #[serde(rename = "resync")]
+    Resync,
+}
+
+/// WebSocket连接查询参数
+#[derive(Debug, Clone, Deserialize)]
+pub struct WebSocketConnectQuery {
+    /// 状态同步模式，默认推送完整状态
+    #[serde(default)]
+    pub protocol: StateSyncMode,
+    /// 重连时客户端最后收到的消息序号，提供时补发之后错过的事件
+    pub last_seen_seq: Option<u64>,
 }
 
 /// WebSocket客户端消息
@@ -46,7 +67,59 @@ pub struct WebSocketClientMessage {
     #[serde(rename = "type")]
     pub message_type: WebSocketMessageType,
     /// 消息数据
+    #[serde(default)]
     pub data: JsonValue,
+    /// 客户端请求ID，会原样回传到该请求产生的动作结果与错误消息中
+    #[serde(default)]
+    pub request_id: Option<String>,
+}
+
+/// WebSocket错误码
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebSocketErrorCode {
+    /// 连接被拒绝（游戏不存在或当前状态不接受连接）
+    ConnectionRejected,
+    /// 消息不是合法的客户端消息格式
+    InvalidMessage,
+    /// 消息类型与连接身份不匹配
+    InvalidMessageType,
+    /// 未知的行动类型
+    UnknownAction,
+    /// 行动参数无法解析
+    InvalidParams,
+    /// 行动处理失败
+    ActionFailed,
+    /// 服务器内部错误
+    SystemError,
+}
+
+/// WebSocket错误消息数据
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct WebSocketError {
+    /// 错误码
+    pub code: WebSocketErrorCode,
+    /// 错误描述
+    pub message: String,
+    /// 触发该错误的客户端请求ID
+    pub request_id: Option<String>,
+}
+
+impl WebSocketError {
+    /// 创建新的错误消息数据
+    pub fn new(code: WebSocketErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            request_id: None,
+        }
+    }
+
+    /// 附加客户端请求ID
+    pub fn with_request_id(mut self, request_id: Option<String>) -> Self {
+        self.request_id = request_id;
+        self
+    }
 }
 
 /// 批量空投请求项结构
@@ -90,6 +163,29 @@ pub struct ShopBuyItem {
     pub quantity: i32,
 }
 
+/// 玩家之间的交易报价（等待对方接受或拒绝）
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TradeOffer {
+    /// 交易ID
+    pub id: String,
+    /// 发起交易的玩家ID
+    pub from_player_id: String,
+    /// 交易对象玩家ID
+    pub to_player_id: String,
+    /// 发起者给出的背包道具ID
+    pub offered_item_ids: Vec<String>,
+    /// 发起者给出的货币数
+    pub offered_coins: i32,
+    /// 向对方索取的背包道具ID
+    pub requested_item_ids: Vec<String>,
+    /// 向对方索取的货币数
+    pub requested_coins: i32,
+    /// 发起时间
+    pub created_at: DateTime<Utc>,
+    /// 过期时间
+    pub expires_at: DateTime<Utc>,
+}
+
 // /// WebSocket服务端消息
 // #[derive(Debug, Clone, Deserialize, Serialize)]
 // pub struct WebSocketServerMessage {
@@ -124,11 +220,32 @@ pub struct GameState {
     pub night_end_time: Option<DateTime<Utc>>,
     /// 下一夜晚缩圈地点集合
     pub next_night_destroyed_places: Vec<String>,
+    /// 当前夜晚是否已结算（手动或自动结算时设置，夜晚开始时清除，避免重复结算）
+    #[serde(default)]
+    pub night_settled: bool,
     /// 存档时间
     pub save_time: Option<DateTime<Utc>>,
     /// 商店上架物品列表
     #[serde(default)]
     pub shop: Vec<ShopListing>,
+    /// 已写入行动日志的最后序号
+    #[serde(default)]
+    pub journal_sequence: u64,
+    /// 当前处理中行动的发生时间（重放行动日志时使用日志记录的时间）
+    #[serde(skip)]
+    pub action_time: Option<DateTime<Utc>>,
+    /// 游戏内所有随机判定使用的随机数生成器（随存档保存）
+    #[serde(default)]
+    pub rng: GameRng,
+    /// 等待回应的交易报价，键为交易ID
+    #[serde(default)]
+    pub trade_offers: HashMap<String, TradeOffer>,
+    /// 已发起的交易数量（用于生成交易ID，保证重放行动日志时ID一致）
+    #[serde(default)]
+    pub trade_sequence: u64,
+    /// 最近导演行动的撤销记录（随存档保存，保证重放行动日志时撤销结果一致）
+    #[serde(default)]
+    pub director_undo: Vec<DirectorUndoRecord>,
 }
 
 /// 玩家类
@@ -138,8 +255,6 @@ pub struct Player {
     pub id: String,
     /// 玩家名称
     pub name: String,
-    /// 玩家密码
-    pub password: String,
     /// 当前位置
     pub location: String,
     /// 当前生命值
@@ -182,20 +297,13 @@ pub struct Player {
 
 impl Player {
     /// 创建新的玩家（使用规则引擎的默认值）
-    pub fn new(
-        id: String,
-        name: String,
-        password: String,
-        team_id: u32,
-        rule_engine: &GameRuleEngine,
-    ) -> Self {
+    pub fn new(id: String, name: String, team_id: u32, rule_engine: &GameRuleEngine) -> Self {
         let max_life = rule_engine.player_config.max_life;
         let max_strength = rule_engine.player_config.max_strength;
 
         Self {
             id,
             name,
-            password,
             location: String::new(),
             life: max_life,
             strength: max_strength,
@@ -228,6 +336,24 @@ impl Player {
         }
         count
     }
+
+    /// 计算投票权重（基础1票 + 已装备武器与防具的额外票数）
+    pub fn get_vote_weight(&self) -> i32 {
+        let weapon_votes = self
+            .equipped_weapon
+            .as_ref()
+            .and_then(|item| item.as_weapon())
+            .map(|properties| properties.votes)
+            .unwrap_or(0);
+        let armor_votes = self
+            .equipped_armor
+            .as_ref()
+            .and_then(|item| item.as_armor())
+            .map(|properties| properties.votes)
+            .unwrap_or(0);
+        (1 + weapon_votes + armor_votes).max(0)
+    }
+
     /// 设置持续伤害效果
     pub fn update_bleed_effect(&mut self, damage: i32, inflictor: Option<String>) -> bool {
         if damage >= self.bleed_damage {
@@ -361,6 +487,25 @@ pub struct ActionResults {
     pub results: Vec<ActionResult>,
 }
 
+impl ActionResults {
+    /// 为所有结果附加产生它们的客户端请求
+    pub fn with_request_origin(mut self, request_origin: Option<RequestOrigin>) -> Self {
+        for result in &mut self.results {
+            result.request_origin = request_origin.clone();
+        }
+        self
+    }
+}
+
+/// 客户端请求的来源，请求ID只回传给发起请求的连接
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestOrigin {
+    /// 发起请求的连接ID
+    pub connection_id: String,
+    /// 客户端提供的请求ID
+    pub request_id: String,
+}
+
 /// 动作处理结果，包含广播信息
 #[derive(Debug, Clone)]
 pub struct ActionResult {
@@ -378,6 +523,27 @@ pub struct ActionResult {
     pub broadcast_to_director: bool,
     /// 是否向所有玩家广播，用于写入数据库
     pub broadcast_to_all: bool,
+    /// 产生该结果的客户端请求
+    pub request_origin: Option<RequestOrigin>,
+    /// 向观众公开的事件（仅包含可公开的信息），为空时观众不会收到该结果
+    pub spectator_event: Option<JsonValue>,
+    /// 该结果包含的伤害事件，不发送给客户端，仅写入伤害记录用于赛后统计
+    pub damage_events: Vec<DamageEvent>,
+}
+
+/// 一次对玩家造成的实际伤害
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DamageEvent {
+    /// 造成伤害的玩家ID（缩圈等非玩家伤害为空）
+    pub attacker_id: Option<String>,
+    /// 受到伤害的玩家ID
+    pub victim_id: String,
+    /// 实际损失的生命值
+    pub damage: i32,
+    /// 伤害来源（攻击、溅射、流血、遥控地雷）
+    pub cause: String,
+    /// 使用的武器或道具名称，徒手攻击为`挥拳`
+    pub weapon: Option<String>,
 }
 
 impl ActionResult {
@@ -397,6 +563,9 @@ impl ActionResult {
             timestamp: Utc::now(),
             broadcast_to_director,
             broadcast_to_all: false,
+            request_origin: None,
+            spectator_event: None,
+            damage_events: Vec::new(),
         }
     }
 
@@ -448,6 +617,18 @@ impl ActionResult {
         )
     }
 
+    /// 附加向观众公开的事件
+    pub fn with_spectator_event(mut self, event: JsonValue) -> Self {
+        self.spectator_event = Some(event);
+        self
+    }
+
+    /// 附加伤害事件
+    pub fn with_damage_events(mut self, events: Vec<DamageEvent>) -> Self {
+        self.damage_events.extend(events);
+        self
+    }
+
     /// 将单个ActionResult转换为ActionResults
     pub fn as_results(self) -> ActionResults {
         ActionResults {
@@ -476,10 +657,22 @@ impl GameState {
             night_start_time: None,
             night_end_time: None,
             next_night_destroyed_places: Vec::new(),
+            night_settled: false,
             save_time: None,
             shop: Vec::new(),
+            journal_sequence: 0,
+            action_time: None,
+            rng: GameRng::default(),
+            trade_offers: HashMap::new(),
+            trade_sequence: 0,
+            director_undo: Vec::new(),
         }
     }
+
+    /// 获取行动判定使用的当前时间
+    pub fn current_time(&self) -> DateTime<Utc> {
+        self.action_time.unwrap_or_else(Utc::now)
+    }
 }
 
 // 为GameState实现自定义反序列化
@@ -500,9 +693,21 @@ impl<'de> Deserialize<'de> for GameState {
             night_start_time: Option<DateTime<Utc>>,
             night_end_time: Option<DateTime<Utc>>,
             next_night_destroyed_places: Vec<String>,
+            #[serde(default)]
+            night_settled: bool,
             save_time: Option<DateTime<Utc>>,
             #[serde(default)]
             shop: Vec<ShopListing>,
+            #[serde(default)]
+            journal_sequence: u64,
+            #[serde(default)]
+            rng: GameRng,
+            #[serde(default)]
+            trade_offers: HashMap<String, TradeOffer>,
+            #[serde(default)]
+            trade_sequence: u64,
+            #[serde(default)]
+            director_undo: Vec<DirectorUndoRecord>,
         }
 
         let helper = GameStateHelper::deserialize(deserializer)?;
@@ -524,8 +729,15 @@ impl<'de> Deserialize<'de> for GameState {
             night_start_time: helper.night_start_time,
             night_end_time: helper.night_end_time,
             next_night_destroyed_places: helper.next_night_destroyed_places,
+            night_settled: helper.night_settled,
             save_time: helper.save_time,
             shop: helper.shop,
+            journal_sequence: helper.journal_sequence,
+            action_time: None,
+            rng: helper.rng,
+            trade_offers: helper.trade_offers,
+            trade_sequence: helper.trade_sequence,
+            director_undo: helper.director_undo,
         })
     }
 }
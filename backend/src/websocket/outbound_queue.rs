@@ -0,0 +1,257 @@
+//! WebSocket连接的有界发送队列
+//! 连接管理器向队列推送消息，连接的发送任务从队列取出消息写入WebSocket；
+//! 队列中尚未发送的状态推送会被更新的状态覆盖（只保留其中的动作结果），系统消息始终保留，
+//! 客户端消费过慢导致队列写满时强制断开连接
+
+use crate::websocket::game_connection_manager::{ConnectionMessage, SequencedMessage};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, MutexGuard};
+use tokio::sync::Notify;
+
+/// 每个连接默认的发送队列容量
+pub const DEFAULT_OUTBOUND_QUEUE_CAPACITY: usize = 256;
+
+/// 发送队列关闭的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CloseReason {
+    /// 连接被服务器正常移除（如游戏结束、密码重置），队列中剩余的消息发送完毕后关闭
+    Normal,
+    /// 客户端消费过慢，发送队列已满，剩余消息被丢弃
+    SlowConsumer,
+    /// 超过空闲超时时间未收到客户端任何数据（包括心跳回应）
+    IdleTimeout,
+}
+
+impl CloseReason {
+    /// 发送给客户端的WebSocket关闭码
+    pub fn close_code(self) -> u16 {
+        match self {
+            CloseReason::Normal => 1000,
+            CloseReason::SlowConsumer => 1008,
+            CloseReason::IdleTimeout => 1001,
+        }
+    }
+
+    /// 发送给客户端的关闭原因
+    pub fn description(self) -> &'static str {
+        match self {
+            CloseReason::Normal => "connection closed by server",
+            CloseReason::SlowConsumer => {
+                "slow_consumer: outbound queue overflowed, reconnect with last_seen_seq"
+            }
+            CloseReason::IdleTimeout => {
+                "idle_timeout: no frames received within idle timeout, reconnect with last_seen_seq"
+            }
+        }
+    }
+}
+
+/// 单个连接的发送队列统计
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct QueueMetrics {
+    /// 当前排队等待发送的消息数量
+    pub queued: usize,
+    /// 排队消息数量的历史最大值
+    pub max_queued: usize,
+    /// 推送到队列的消息总数
+    pub enqueued: u64,
+    /// 被更新状态覆盖而未发送的状态推送数量
+    pub coalesced: u64,
+    /// 已写入WebSocket的消息数量
+    pub sent: u64,
+    /// 已写入WebSocket的字节数
+    pub sent_bytes: u64,
+}
+
+#[derive(Debug)]
+struct QueueState {
+    pending: VecDeque<SequencedMessage>,
+    close_reason: Option<CloseReason>,
+    metrics: QueueMetrics,
+}
+
+#[derive(Debug)]
+struct Shared {
+    capacity: usize,
+    connected_at: DateTime<Utc>,
+    state: Mutex<QueueState>,
+    notify: Notify,
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, QueueState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn close(&self, reason: CloseReason) {
+        let mut state = self.lock();
+        if state.close_reason.is_none() {
+            state.close_reason = Some(reason);
+            if reason == CloseReason::SlowConsumer {
+                state.pending.clear();
+                state.metrics.queued = 0;
+            }
+        }
+        drop(state);
+        self.notify.notify_waiters();
+    }
+
+    /// 等待队列状态满足条件，条件检查在注册唤醒之后进行，避免丢失通知
+    async fn wait_until<T>(&self, mut ready: impl FnMut(&mut QueueState) -> Option<T>) -> T {
+        loop {
+            let notified = self.notify.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+            if let Some(value) = ready(&mut self.lock()) {
+                return value;
+            }
+            notified.await;
+        }
+    }
+}
+
+/// 创建容量为`capacity`的发送队列
+pub fn outbound_queue(capacity: usize) -> (OutboundSender, OutboundReceiver) {
+    let shared = Arc::new(Shared {
+        capacity,
+        connected_at: Utc::now(),
+        state: Mutex::new(QueueState {
+            pending: VecDeque::new(),
+            close_reason: None,
+            metrics: QueueMetrics::default(),
+        }),
+        notify: Notify::new(),
+    });
+    (
+        OutboundSender {
+            shared: shared.clone(),
+        },
+        OutboundReceiver { shared },
+    )
+}
+
+/// 发送队列的推送端，由连接管理器持有；释放时队列以`Normal`原因关闭
+#[derive(Debug)]
+pub struct OutboundSender {
+    shared: Arc<Shared>,
+}
+
+impl OutboundSender {
+    /// 推送消息
+    ///
+    /// 新的游戏状态推送会覆盖队列中尚未发送的状态推送：不含动作结果的推送被移除，
+    /// 含动作结果的推送只保留动作结果；覆盖后队列仍然已满时关闭队列并丢弃剩余消息，返回关闭原因。
+    pub fn send(&self, message: SequencedMessage) -> Result<(), CloseReason> {
+        let mut state = self.shared.lock();
+        if let Some(reason) = state.close_reason {
+            return Err(reason);
+        }
+
+        if matches!(message.message, ConnectionMessage::GameState(_)) {
+            let mut coalesced = 0;
+            state
+                .pending
+                .retain_mut(|queued| match &mut queued.message {
+                    ConnectionMessage::GameState(view) => {
+                        coalesced += 1;
+                        match take_action_result(view) {
+                            Some(action_result) => {
+                                queued.message = ConnectionMessage::ActionResult(action_result);
+                                true
+                            }
+                            None => false,
+                        }
+                    }
+                    _ => true,
+                });
+            state.metrics.coalesced += coalesced;
+        }
+
+        if state.pending.len() >= self.shared.capacity {
+            drop(state);
+            self.shared.close(CloseReason::SlowConsumer);
+            return Err(CloseReason::SlowConsumer);
+        }
+
+        state.pending.push_back(message);
+        state.metrics.enqueued += 1;
+        state.metrics.queued = state.pending.len();
+        state.metrics.max_queued = state.metrics.max_queued.max(state.metrics.queued);
+        drop(state);
+        self.shared.notify.notify_waiters();
+        Ok(())
+    }
+
+    /// 连接建立时间
+    pub fn connected_at(&self) -> DateTime<Utc> {
+        self.shared.connected_at
+    }
+
+    /// 当前的队列统计
+    pub fn metrics(&self) -> QueueMetrics {
+        self.shared.lock().metrics.clone()
+    }
+}
+
+impl Drop for OutboundSender {
+    fn drop(&mut self) {
+        self.shared.close(CloseReason::Normal);
+    }
+}
+
+/// 发送队列的接收端，由连接的发送任务持有
+#[derive(Debug)]
+pub struct OutboundReceiver {
+    shared: Arc<Shared>,
+}
+
+impl OutboundReceiver {
+    /// 取出下一条消息；队列关闭且没有剩余消息时返回`None`
+    pub async fn recv(&self) -> Option<SequencedMessage> {
+        self.shared
+            .wait_until(|state| match state.pending.pop_front() {
+                Some(message) => {
+                    state.metrics.queued = state.pending.len();
+                    Some(Some(message))
+                }
+                None if state.close_reason.is_some() => Some(None),
+                None => None,
+            })
+            .await
+    }
+
+    /// 等待队列因客户端消费过慢而关闭
+    ///
+    /// 发送任务在写入WebSocket阻塞时据此中止发送。
+    pub async fn overflowed(&self) {
+        self.shared
+            .wait_until(|state| {
+                (state.close_reason == Some(CloseReason::SlowConsumer)).then_some(())
+            })
+            .await
+    }
+
+    /// 记录一条已写入WebSocket的消息
+    pub fn record_sent(&self, bytes: usize) {
+        let mut state = self.shared.lock();
+        state.metrics.sent += 1;
+        state.metrics.sent_bytes += bytes as u64;
+    }
+
+    /// 队列关闭的原因，尚未关闭时返回`None`
+    pub fn close_reason(&self) -> Option<CloseReason> {
+        self.shared.lock().close_reason
+    }
+}
+
+/// 取出状态视图中的动作结果，不含动作结果时返回`None`
+fn take_action_result(view: &mut JsonValue) -> Option<JsonValue> {
+    view.get_mut("action_result")
+        .map(JsonValue::take)
+        .filter(|action_result| !action_result.is_null())
+}
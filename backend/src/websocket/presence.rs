@@ -0,0 +1,96 @@
+//! 玩家在线状态
+//! 记录每名玩家是否有活跃的WebSocket连接以及上线、离线时间，玩家上线或离线时生成通知导演的事件
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// 单个玩家的在线状态
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct PlayerPresence {
+    /// 是否至少有一个活跃连接
+    pub online: bool,
+    /// 本次上线时间（离线时为空）
+    pub connected_at: Option<DateTime<Utc>>,
+    /// 最近一次离线时间（从未离线时为空）
+    pub last_seen: Option<DateTime<Utc>>,
+}
+
+/// 在线状态变化
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PresenceStatus {
+    /// 玩家上线（建立第一个连接）
+    Join,
+    /// 玩家离线（最后一个连接断开）
+    Leave,
+}
+
+/// 推送给导演的在线状态事件
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct PresenceEvent {
+    /// 玩家ID
+    pub player_id: String,
+    /// 状态变化
+    pub status: PresenceStatus,
+    /// 变化发生时间
+    pub timestamp: DateTime<Utc>,
+}
+
+/// 单个游戏的玩家在线状态表
+#[derive(Debug, Clone, Default)]
+pub struct PresenceTracker {
+    players: HashMap<String, PlayerPresence>,
+}
+
+impl PresenceTracker {
+    /// 创建新的在线状态表
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 记录玩家上线，玩家原本已在线时返回`None`
+    pub fn join(&mut self, player_id: &str, now: DateTime<Utc>) -> Option<PresenceEvent> {
+        let presence = self.players.entry(player_id.to_string()).or_default();
+        if presence.online {
+            return None;
+        }
+        presence.online = true;
+        presence.connected_at = Some(now);
+        Some(PresenceEvent {
+            player_id: player_id.to_string(),
+            status: PresenceStatus::Join,
+            timestamp: now,
+        })
+    }
+
+    /// 记录玩家离线，玩家原本不在线时返回`None`
+    pub fn leave(&mut self, player_id: &str, now: DateTime<Utc>) -> Option<PresenceEvent> {
+        let presence = self.players.get_mut(player_id)?;
+        if !presence.online {
+            return None;
+        }
+        presence.online = false;
+        presence.connected_at = None;
+        presence.last_seen = Some(now);
+        Some(PresenceEvent {
+            player_id: player_id.to_string(),
+            status: PresenceStatus::Leave,
+            timestamp: now,
+        })
+    }
+
+    /// 将所有玩家标记为离线（断开全部连接时使用，不生成事件）
+    pub fn leave_all(&mut self, now: DateTime<Utc>) {
+        for presence in self.players.values_mut().filter(|presence| presence.online) {
+            presence.online = false;
+            presence.connected_at = None;
+            presence.last_seen = Some(now);
+        }
+    }
+
+    /// 所有记录过的玩家在线状态
+    pub fn snapshot(&self) -> HashMap<String, PlayerPresence> {
+        self.players.clone()
+    }
+}
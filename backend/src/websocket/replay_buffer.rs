@@ -0,0 +1,101 @@
+//! 断线重连事件补发缓冲区
+//! 为每个消息接收者（玩家或导演）保留最近推送的有序事件，客户端重连时据此补发错过的事件
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::collections::VecDeque;
+
+/// 每个接收者默认保留的事件数量
+pub const DEFAULT_REPLAY_BUFFER_CAPACITY: usize = 200;
+
+/// 可补发的事件类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReplayEventType {
+    /// 动作结果（对应`game_state`消息中的`action_result`）
+    ActionResult,
+    /// 系统消息
+    SystemMessage,
+    /// 玩家在线状态变化（仅推送给导演）
+    Presence,
+}
+
+/// 带序号的可补发事件
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ReplayEvent {
+    /// 接收者维度的消息序号
+    pub seq: u64,
+    /// 事件类型
+    #[serde(rename = "type")]
+    pub event_type: ReplayEventType,
+    /// 事件数据
+    pub data: JsonValue,
+}
+
+/// 单个接收者的事件环形缓冲区
+///
+/// 每条推送的消息都会分配递增序号，其中只有动作结果与系统消息需要保留；
+/// 不含动作结果的纯状态推送只占用序号，重连时由最新快照覆盖。
+#[derive(Debug, Clone)]
+pub struct ReplayBuffer {
+    /// 最多保留的事件数量
+    capacity: usize,
+    /// 最近分配的消息序号
+    last_seq: u64,
+    /// 已被淘汰的最大序号，不大于该序号的事件无法补发
+    evicted_through: u64,
+    /// 保留的事件
+    events: VecDeque<ReplayEvent>,
+}
+
+impl ReplayBuffer {
+    /// 创建新的事件缓冲区
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            last_seq: 0,
+            evicted_through: 0,
+            events: VecDeque::new(),
+        }
+    }
+
+    /// 最近分配的消息序号
+    pub fn last_seq(&self) -> u64 {
+        self.last_seq
+    }
+
+    /// 分配下一个消息序号，并保留可补发的事件
+    pub fn push(&mut self, event: Option<(ReplayEventType, JsonValue)>) -> u64 {
+        self.last_seq += 1;
+        if let Some((event_type, data)) = event {
+            self.events.push_back(ReplayEvent {
+                seq: self.last_seq,
+                event_type,
+                data,
+            });
+            while self.events.len() > self.capacity {
+                if let Some(evicted) = self.events.pop_front() {
+                    self.evicted_through = evicted.seq;
+                }
+            }
+        }
+        self.last_seq
+    }
+
+    /// 获取序号大于`last_seen_seq`的事件
+    ///
+    /// 缺口超出缓冲区范围（事件已被淘汰）或序号不属于当前缓冲区（如服务器重启）时返回`None`，
+    /// 客户端应以完整快照为准。
+    pub fn events_since(&self, last_seen_seq: u64) -> Option<Vec<ReplayEvent>> {
+        if last_seen_seq < self.evicted_through || last_seen_seq > self.last_seq {
+            return None;
+        }
+        Some(
+            self.events
+                .iter()
+                .filter(|event| event.seq > last_seen_seq)
+                .cloned()
+                .collect(),
+        )
+    }
+}
@@ -1,27 +1,82 @@
 //! WebSocket服务实现
 
 use axum::{
+    Extension,
     extract::{Path, Query, State},
     http::StatusCode,
     response::{IntoResponse, Response},
 };
-use futures::{sink::SinkExt, stream::StreamExt};
+use futures::{
+    sink::SinkExt,
+    stream::{SplitSink, SplitStream, StreamExt},
+};
 use serde_json::json;
 use std::str;
 use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::OwnedRwLockReadGuard;
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
+use tokio::task::JoinHandle;
+use tokio::time::{Instant, MissedTickBehavior};
 use tracing::{debug, error, warn};
-use yawc::{CompressionLevel, IncomingUpgrade, Options, WebSocket, frame::OpCode};
+use yawc::{
+    CompressionLevel, IncomingUpgrade, Options, WebSocket,
+    close::CloseCode,
+    frame::{FrameView, OpCode},
+};
 
 use super::models::*;
-use crate::game::models::{GameStatus, NewKillRecord};
+use crate::game::models::{
+    GameSessionClaims, GameSessionRole, GameStatus, NewDamageRecord, NewKillRecord,
+};
 use crate::routes::AppState;
 
+use crate::game::action_journal::{ActionJournal, JournalActor};
+use crate::game::global_game_state_manager::GlobalGameStateManager;
+use crate::metrics::{ACTOR_DIRECTOR, ACTOR_PLAYER, metrics};
 use crate::websocket::actions::director_action_scheduler::{
     DirectorActionParams, DirectorActionScheduler,
 };
 use crate::websocket::actions::player_action_scheduler::{ActionParams, PlayerActionScheduler};
 use crate::websocket::broadcaster::MessageBroadcaster;
-use crate::websocket::game_connection_manager::GameConnectionManager;
+use crate::websocket::game_connection_manager::{
+    ConnectionMessage, DIRECTOR_USER_ID, GameConnectionManager, SPECTATOR_USER_ID, SequencedMessage,
+};
+use crate::websocket::heartbeat::HeartbeatConfig;
+use crate::websocket::outbound_queue::{CloseReason, OutboundReceiver};
+use crate::websocket::spectator_feed::SpectatorSnapshot;
+use crate::websocket::state_patch::{StateStream, StateUpdate};
+
+/// 通过认证的连接身份
+enum AuthenticatedConnection {
+    /// 玩家连接，附带令牌所属的演员ID
+    Actor(String),
+    /// 导演连接
+    Director,
+    /// 观众连接（只读）
+    Spectator,
+}
+
+/// 连接内部的控制消息，由消息处理循环发给该连接的发送任务
+enum ConnectionControl {
+    /// 向客户端发送错误消息
+    Error(WebSocketError),
+    /// 重新推送完整游戏状态
+    Resync,
+    /// 以指定原因发送关闭帧并结束发送任务
+    Close(CloseReason),
+}
+
+impl AuthenticatedConnection {
+    /// 日志中使用的连接角色名称
+    fn role(&self) -> &'static str {
+        match self {
+            AuthenticatedConnection::Actor(_) => "Player",
+            AuthenticatedConnection::Director => "Director",
+            AuthenticatedConnection::Spectator => "Spectator",
+        }
+    }
+}
 
 /// WebSocket服务
 #[derive(Clone)]
@@ -33,17 +88,21 @@ pub struct WebSocketService {
     connection_manager: Arc<GameConnectionManager>,
     /// 消息广播器
     message_broadcaster: MessageBroadcaster,
+    /// 连接心跳配置
+    heartbeat: HeartbeatConfig,
 }
 
 impl WebSocketService {
     /// 创建新的WebSocket服务
     pub fn new(app_state: AppState, connection_manager: Arc<GameConnectionManager>) -> Self {
         let message_broadcaster = MessageBroadcaster::new(connection_manager.as_ref().clone());
+        let heartbeat = app_state.global_connection_manager.heartbeat();
 
         Self {
             app_state,
             connection_manager,
             message_broadcaster,
+            heartbeat,
         }
     }
 
@@ -52,7 +111,8 @@ impl WebSocketService {
         ws: IncomingUpgrade,
         State(state): State<AppState>,
         Path(game_id): Path<String>,
-        Query(query): Query<WebSocketAuthRequest>,
+        Query(query): Query<WebSocketConnectQuery>,
+        Extension(claims): Extension<GameSessionClaims>,
     ) -> Response {
         // 获取游戏对应的连接管理器
         let game_connection_manager = state.global_connection_manager.get_manager(game_id.clone());
@@ -71,7 +131,7 @@ impl WebSocketService {
                     match fut.await {
                         Ok(socket) => {
                             ws_service
-                                .handle_websocket_connection(socket, game_id, query)
+                                .handle_websocket_connection(socket, game_id, claims, query)
                                 .await;
                         }
                         Err(err) => {
@@ -93,11 +153,18 @@ impl WebSocketService {
         self,
         mut socket: WebSocket,
         game_id: String,
-        auth_request: WebSocketAuthRequest,
+        claims: GameSessionClaims,
+        query: WebSocketConnectQuery,
     ) {
-        // 验证连接认证
-        match self.authenticate_connection(&game_id, &auth_request).await {
-            Ok(user_type) => {
+        // 验证连接认证；回退期间拒绝连接，守卫持有到连接加入连接管理器并生成初始状态
+        let authenticated = match self.authenticate_connection(&game_id, &claims).await {
+            Ok(connection) => self
+                .begin_connection(&game_id)
+                .map(|operation| (connection, operation)),
+            Err(error_msg) => Err(error_msg),
+        };
+        match authenticated {
+            Ok((connection, operation)) => {
                 // 发送连接成功消息
                 let websocket_message = super::message_formatter::system_message(
                     json!({ "message": "WebSocket connection established successfully" }),
@@ -107,21 +174,26 @@ impl WebSocketService {
                 }
 
                 // 根据用户类型处理连接
-                match user_type {
-                    ConnectionType::Actor => {
-                        self.handle_player_connection(socket, game_id, auth_request.password)
+                match connection {
+                    AuthenticatedConnection::Actor(actor_id) => {
+                        self.handle_player_connection(socket, game_id, actor_id, query, operation)
+                            .await;
+                    }
+                    AuthenticatedConnection::Director => {
+                        self.handle_director_connection(socket, game_id, query, operation)
                             .await;
                     }
-                    ConnectionType::Director => {
-                        self.handle_director_connection(socket, game_id, auth_request.password)
+                    AuthenticatedConnection::Spectator => {
+                        self.handle_spectator_connection(socket, game_id, query, operation)
                             .await;
                     }
                 }
             }
             Err(error_msg) => {
                 // 发送认证失败消息
-                let websocket_message =
-                    super::message_formatter::error_message(json!({ "message": error_msg }));
+                let websocket_message = super::message_formatter::error_message(
+                    &WebSocketError::new(WebSocketErrorCode::ConnectionRejected, error_msg),
+                );
                 let _ = socket.send(websocket_message).await;
                 // 关闭连接
                 let _ = socket.close().await;
@@ -133,8 +205,8 @@ impl WebSocketService {
     async fn authenticate_connection(
         &self,
         game_id: &str,
-        auth_request: &WebSocketAuthRequest,
-    ) -> Result<ConnectionType, String> {
+        claims: &GameSessionClaims,
+    ) -> Result<AuthenticatedConnection, String> {
         // 检查游戏是否存在
         let game = self
             .app_state
@@ -160,42 +232,11 @@ impl WebSocketService {
             }
         }
 
-        // 根据用户类型验证密码
-        match &auth_request.user_type {
-            ConnectionType::Actor => {
-                // 验证玩家密码
-                let actor = sqlx::query!(
-                    "SELECT id FROM actors WHERE game_id = ? AND password = ?",
-                    game_id,
-                    auth_request.password
-                )
-                .fetch_optional(&self.app_state.director_service.pool)
-                .await
-                .map_err(|_| "数据库错误".to_string())?;
-
-                if actor.is_none() {
-                    return Err("玩家密码错误".to_string());
-                }
-
-                Ok(ConnectionType::Actor)
-            }
-            ConnectionType::Director => {
-                // 验证导演密码
-                let game_record = sqlx::query!(
-                    "SELECT id FROM games WHERE id = ? AND director_password = ?",
-                    game_id,
-                    auth_request.password
-                )
-                .fetch_optional(&self.app_state.director_service.pool)
-                .await
-                .map_err(|_| "数据库错误".to_string())?;
-
-                if game_record.is_none() {
-                    return Err("导演密码错误".to_string());
-                }
-
-                Ok(ConnectionType::Director)
-            }
+        // 会话令牌已由中间件校验，这里根据令牌角色确定连接类型
+        match claims.role {
+            GameSessionRole::Actor => Ok(AuthenticatedConnection::Actor(claims.sub.clone())),
+            GameSessionRole::Director => Ok(AuthenticatedConnection::Director),
+            GameSessionRole::Spectator => Ok(AuthenticatedConnection::Spectator),
         }
     }
 
@@ -204,82 +245,82 @@ impl WebSocketService {
         self,
         socket: WebSocket,
         game_id: String,
-        player_password: String,
+        actor_id: String,
+        query: WebSocketConnectQuery,
+        operation: OwnedRwLockReadGuard<()>,
     ) {
-        // 获取玩家信息
-        let actor = sqlx::query!(
-            "SELECT id, name FROM actors WHERE game_id = ? AND password = ?",
-            game_id,
-            player_password
-        )
-        .fetch_one(&self.app_state.director_service.pool)
-        .await
-        .unwrap(); // 已经验证过密码，这里不会失败
+        let (mut sender, receiver) = socket.split();
 
-        let init_msg = {
-            // let game = self.app_state.game_service.get_game_by_id(&game_id).await.unwrap();
-            let game_state_ref = self
-                .app_state
-                .game_state_manager
-                .get_game_state(&game_id)
-                .await
-                .unwrap();
-            let game_state_guard = game_state_ref.read().await;
+        // 在生成初始状态之前添加连接，期间的广播在发送队列中排队
+        let (connection_handle, rx) = self
+            .connection_manager
+            .add_connection(actor_id.clone(), ConnectionType::Actor)
+            .await;
 
-            // 检查玩家是否在游戏状态中
-            let player = game_state_guard.players.get(&actor.id).unwrap();
+        let init_msg = match self
+            .app_state
+            .game_state_manager
+            .get_game_state(&game_id)
+            .await
+        {
+            Ok(game_state_ref) => {
+                let game_state_guard = game_state_ref.read().await;
 
-            // 生成玩家初始状态消息
-            MessageBroadcaster::generate_player_message(&game_state_guard, player, None)
+                // 检查玩家是否在游戏状态中，生成玩家初始状态消息
+                game_state_guard.players.get(&actor_id).map(|player| {
+                    MessageBroadcaster::generate_player_message(&game_state_guard, player, None)
+                })
+            }
+            Err(_) => None,
         };
-        let websocket_message = super::message_formatter::game_state_message(init_msg);
-
-        let (mut sender, mut receiver) = socket.split();
-        if sender.send(websocket_message).await.is_err() {
-            return;
-        }
-
-        // 创建消息通道用于连接管理
-        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<serde_json::Value>();
 
-        // 添加连接到连接管理器
-        let connection_handle = self
-            .connection_manager
-            .add_connection(actor.id.clone(), ConnectionType::Actor, tx)
-            .await;
+        // 连接已加入连接管理器，之后开始的回退会断开该连接
+        drop(operation);
 
-        // 处理来自连接管理器的消息
-        let handle_messages = tokio::spawn(async move {
-            while let Some(message) = rx.recv().await {
-                let websocket_message = super::message_formatter::game_state_message(message);
-                if sender.send(websocket_message).await.is_err() {
-                    break;
-                }
+        // 发送初始状态以及重连期间错过的事件
+        let mut state_stream = StateStream::new(query.protocol);
+        let initial_seq = match init_msg {
+            Some(init_msg) => {
+                self.send_initial_messages(
+                    &mut sender,
+                    &mut state_stream,
+                    &actor_id,
+                    init_msg,
+                    query.last_seen_seq,
+                )
+                .await
             }
+            None => None,
+        };
+        let Some(initial_seq) = initial_seq else {
+            self.connection_manager
+                .remove_connection(&connection_handle)
+                .await;
             let _ = sender.close().await;
-        });
+            return;
+        };
+
+        // 处理来自连接管理器的消息以及本连接的控制消息（错误、重新同步、关闭）
+        let (control_tx, control_rx) = tokio::sync::mpsc::unbounded_channel::<ConnectionControl>();
+        let mut handle_messages = tokio::spawn(forward_outgoing_messages(
+            sender,
+            state_stream,
+            initial_seq,
+            rx,
+            control_rx,
+            self.heartbeat.ping_interval,
+        ));
 
         // 处理玩家消息
-        while let Some(frame) = receiver.next().await {
-            let opcode = frame.opcode;
-            let payload = frame.payload;
-            match opcode {
-                OpCode::Text => {
-                    if let Ok(text) = str::from_utf8(payload.as_ref()) {
-                        debug!("Player WS received message: {}", text);
-                        if let Err(error_msg) =
-                            self.handle_player_message(&game_id, &actor.id, text).await
-                        {
-                            eprintln!("[WebSocket] Player message processing error: {}", error_msg);
-                        }
-                    }
-                }
-                OpCode::Close => {
-                    break;
-                }
-                _ => {}
-            }
-        }
+        self.receive_client_messages(
+            receiver,
+            &mut handle_messages,
+            &control_tx,
+            &game_id,
+            &connection_handle.id,
+            &AuthenticatedConnection::Actor(actor_id),
+        )
+        .await;
 
         // 连接断开时移除连接
         self.connection_manager
@@ -293,80 +334,294 @@ impl WebSocketService {
         self,
         socket: WebSocket,
         game_id: String,
-        _director_password: String,
+        query: WebSocketConnectQuery,
+        operation: OwnedRwLockReadGuard<()>,
     ) {
-        let init_msg = {
-            // let game = self.app_state.game_service.get_game_by_id(&game_id).await.unwrap();
-            let game_state_ref = self
-                .app_state
-                .game_state_manager
-                .get_game_state(&game_id)
-                .await
-                .unwrap();
-            let game_state_guard = game_state_ref.read().await;
+        let (mut sender, receiver) = socket.split();
+
+        // 在生成初始状态之前添加连接，期间的广播在发送队列中排队
+        let (connection_handle, rx) = self
+            .connection_manager
+            .add_connection(DIRECTOR_USER_ID.to_string(), ConnectionType::Director)
+            .await;
+
+        let presence = self.connection_manager.presence().await;
+        let init_msg = match self
+            .app_state
+            .game_state_manager
+            .get_game_state(&game_id)
+            .await
+        {
+            Ok(game_state_ref) => {
+                let game_state_guard = game_state_ref.read().await;
 
-            // 生成导演初始状态消息，action_result为空
-            MessageBroadcaster::generate_director_message(&game_state_guard, None)
+                // 生成导演初始状态消息，action_result为空
+                Some(MessageBroadcaster::generate_director_message(
+                    &game_state_guard,
+                    None,
+                    &presence,
+                ))
+            }
+            Err(_) => None,
         };
-        let websocket_message = super::message_formatter::game_state_message(init_msg);
 
-        let (mut sender, mut receiver) = socket.split();
-        if sender.send(websocket_message).await.is_err() {
+        // 连接已加入连接管理器，之后开始的回退会断开该连接
+        drop(operation);
+
+        // 发送初始状态以及重连期间错过的事件
+        let mut state_stream = StateStream::new(query.protocol);
+        let initial_seq = match init_msg {
+            Some(init_msg) => {
+                self.send_initial_messages(
+                    &mut sender,
+                    &mut state_stream,
+                    DIRECTOR_USER_ID,
+                    init_msg,
+                    query.last_seen_seq,
+                )
+                .await
+            }
+            None => None,
+        };
+        let Some(initial_seq) = initial_seq else {
+            self.connection_manager
+                .remove_connection(&connection_handle)
+                .await;
+            let _ = sender.close().await;
             return;
-        }
+        };
 
-        // 创建消息通道用于连接管理
-        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<serde_json::Value>();
+        // 处理来自连接管理器的消息以及本连接的控制消息（错误、重新同步、关闭）
+        let (control_tx, control_rx) = tokio::sync::mpsc::unbounded_channel::<ConnectionControl>();
+        let mut handle_messages = tokio::spawn(forward_outgoing_messages(
+            sender,
+            state_stream,
+            initial_seq,
+            rx,
+            control_rx,
+            self.heartbeat.ping_interval,
+        ));
+
+        // 处理导演消息
+        self.receive_client_messages(
+            receiver,
+            &mut handle_messages,
+            &control_tx,
+            &game_id,
+            &connection_handle.id,
+            &AuthenticatedConnection::Director,
+        )
+        .await;
 
-        // 添加连接到连接管理器
-        let connection_handle = self
+        // 连接断开时移除连接
+        self.connection_manager
+            .remove_connection(&connection_handle)
+            .await;
+        handle_messages.abort();
+    }
+
+    /// 处理观众WebSocket连接
+    ///
+    /// 观众连接只读，仅接受重新同步请求。
+    async fn handle_spectator_connection(
+        self,
+        socket: WebSocket,
+        game_id: String,
+        query: WebSocketConnectQuery,
+        operation: OwnedRwLockReadGuard<()>,
+    ) {
+        let (mut sender, receiver) = socket.split();
+
+        // 在生成初始状态之前添加连接，期间的广播在发送队列中排队
+        let (connection_handle, rx) = self
             .connection_manager
-            .add_connection(
-                "director".to_string(), // 导演使用固定ID
-                ConnectionType::Director,
-                tx,
-            )
+            .add_connection(SPECTATOR_USER_ID.to_string(), ConnectionType::Spectator)
             .await;
 
-        // 处理来自连接管理器的消息
-        let handle_messages = tokio::spawn(async move {
-            while let Some(message) = rx.recv().await {
-                let websocket_message = super::message_formatter::game_state_message(message);
-                if sender.send(websocket_message).await.is_err() {
-                    break;
+        // 初始状态使用最近一次延迟推送的视图；尚未推送过但已有视图等待推送时
+        // 使用占位视图，避免提前公开实时状态；尚无任何视图入队时使用当前公开视图
+        let init_msg = match self.connection_manager.spectator_snapshot().await {
+            SpectatorSnapshot::Published(snapshot) => Some(snapshot),
+            SpectatorSnapshot::Pending => {
+                Some(MessageBroadcaster::generate_spectator_placeholder())
+            }
+            SpectatorSnapshot::Empty => match self
+                .app_state
+                .game_state_manager
+                .get_game_state(&game_id)
+                .await
+            {
+                Ok(game_state_ref) => {
+                    let game_state_guard = game_state_ref.read().await;
+                    Some(MessageBroadcaster::generate_spectator_message(
+                        &game_state_guard,
+                        None,
+                    ))
                 }
+                Err(_) => None,
+            },
+        };
+
+        // 连接已加入连接管理器，之后开始的回退会断开该连接
+        drop(operation);
+
+        // 发送初始状态以及重连期间错过的事件
+        let mut state_stream = StateStream::new(query.protocol);
+        let initial_seq = match init_msg {
+            Some(init_msg) => {
+                self.send_initial_messages(
+                    &mut sender,
+                    &mut state_stream,
+                    SPECTATOR_USER_ID,
+                    init_msg,
+                    query.last_seen_seq,
+                )
+                .await
             }
+            None => None,
+        };
+        let Some(initial_seq) = initial_seq else {
+            self.connection_manager
+                .remove_connection(&connection_handle)
+                .await;
             let _ = sender.close().await;
-        });
+            return;
+        };
 
-        // 处理导演消息
-        while let Some(frame) = receiver.next().await {
-            let opcode = frame.opcode;
-            let payload = frame.payload;
-            match opcode {
+        // 处理来自连接管理器的消息以及本连接的控制消息（错误、重新同步、关闭）
+        let (control_tx, control_rx) = tokio::sync::mpsc::unbounded_channel::<ConnectionControl>();
+        let mut handle_messages = tokio::spawn(forward_outgoing_messages(
+            sender,
+            state_stream,
+            initial_seq,
+            rx,
+            control_rx,
+            self.heartbeat.ping_interval,
+        ));
+
+        // 处理观众消息
+        self.receive_client_messages(
+            receiver,
+            &mut handle_messages,
+            &control_tx,
+            &game_id,
+            &connection_handle.id,
+            &AuthenticatedConnection::Spectator,
+        )
+        .await;
+
+        // 连接断开时移除连接
+        self.connection_manager
+            .remove_connection(&connection_handle)
+            .await;
+        handle_messages.abort();
+    }
+
+    /// 读取并处理客户端消息，直到客户端关闭连接、发送任务结束或空闲超时
+    ///
+    /// 任何收到的帧（包括心跳Pong）都会重置空闲计时；
+    /// 超时后通知发送任务以`idle_timeout`关闭帧断开连接。
+    async fn receive_client_messages(
+        &self,
+        mut receiver: SplitStream<WebSocket>,
+        handle_messages: &mut JoinHandle<()>,
+        control_tx: &UnboundedSender<ConnectionControl>,
+        game_id: &str,
+        connection_id: &str,
+        connection: &AuthenticatedConnection,
+    ) {
+        loop {
+            // 发送任务结束（连接被服务器移除或强制断开）时停止读取
+            let frame = tokio::select! {
+                frame = tokio::time::timeout(self.heartbeat.idle_timeout, receiver.next()) => frame,
+                _ = &mut *handle_messages => break,
+            };
+            let Ok(frame) = frame else {
+                warn!(
+                    "{} WS idle for {:?}, closing connection",
+                    connection.role(),
+                    self.heartbeat.idle_timeout
+                );
+                let _ = control_tx.send(ConnectionControl::Close(CloseReason::IdleTimeout));
+                let _ = tokio::time::timeout(CLOSE_FRAME_TIMEOUT * 2, &mut *handle_messages).await;
+                break;
+            };
+            let Some(frame) = frame else {
+                break;
+            };
+            match frame.opcode {
                 OpCode::Text => {
-                    if let Ok(text) = str::from_utf8(payload.as_ref()) {
-                        debug!("Director WS received message: {}", text);
-                        if let Err(error_msg) = self.handle_director_message(&game_id, text).await {
-                            eprintln!(
-                                "[WebSocket] Director message processing error: {}",
-                                error_msg
-                            );
+                    if let Ok(text) = str::from_utf8(frame.payload.as_ref()) {
+                        debug!("{} WS received message: {}", connection.role(), text);
+                        let result = match connection {
+                            AuthenticatedConnection::Actor(actor_id) => {
+                                self.handle_player_message(
+                                    game_id,
+                                    actor_id,
+                                    connection_id,
+                                    text,
+                                    control_tx,
+                                )
+                                .await
+                            }
+                            AuthenticatedConnection::Director => {
+                                self.handle_director_message(
+                                    game_id,
+                                    connection_id,
+                                    text,
+                                    control_tx,
+                                )
+                                .await
+                            }
+                            AuthenticatedConnection::Spectator => {
+                                handle_spectator_message(text, control_tx)
+                            }
+                        };
+                        if let Err(error) = result {
+                            warn!("{} WS message rejected: {:?}", connection.role(), error);
+                            let _ = control_tx.send(ConnectionControl::Error(error));
                         }
                     }
                 }
                 OpCode::Close => {
                     break;
                 }
+                // Pong等控制帧只用于重置空闲计时
                 _ => {}
             }
         }
+    }
 
-        // 连接断开时移除连接
-        self.connection_manager
-            .remove_connection(&connection_handle)
+    /// 发送连接的初始状态，客户端提供`last_seen_seq`时随后发送错过的事件
+    ///
+    /// 返回初始状态对应的消息序号，发送失败时返回`None`。
+    async fn send_initial_messages(
+        &self,
+        sender: &mut SplitSink<WebSocket, FrameView>,
+        state_stream: &mut StateStream,
+        recipient: &str,
+        init_msg: serde_json::Value,
+        last_seen_seq: Option<u64>,
+    ) -> Option<u64> {
+        let (initial_seq, events) = self
+            .connection_manager
+            .catch_up(recipient, last_seen_seq)
             .await;
-        handle_messages.abort();
+
+        let websocket_message =
+            state_update_message(state_stream.next_update(init_msg), Some(initial_seq));
+        sender.send(websocket_message).await.ok()?;
+
+        // 缺口超出缓冲区范围时不补发事件，客户端以初始状态为准
+        if last_seen_seq.is_some() {
+            let websocket_message = super::message_formatter::event_replay_message(json!({
+                "complete": events.is_some(),
+                "events": events.unwrap_or_default(),
+            }));
+            sender.send(websocket_message).await.ok()?;
+        }
+
+        Some(initial_seq)
     }
 
     /// 处理玩家消息
@@ -374,38 +629,67 @@ impl WebSocketService {
         &self,
         game_id: &str,
         player_id: &str,
+        connection_id: &str,
         message: &str,
-    ) -> Result<String, String> {
-        let client_message: WebSocketClientMessage =
-            serde_json::from_str(message).map_err(|_| "Invalid message format".to_string())?;
+        control_tx: &UnboundedSender<ConnectionControl>,
+    ) -> Result<(), WebSocketError> {
+        let client_message = parse_client_message(message)?;
+        let request_id = client_message.request_id;
 
         match client_message.message_type {
             WebSocketMessageType::PlayerAction => {
                 // 处理玩家行动
-                self.process_player_action(game_id, player_id, client_message.data)
-                    .await
+                self.process_player_action(
+                    game_id,
+                    player_id,
+                    client_message.data,
+                    request_origin(connection_id, request_id.as_deref()),
+                )
+                .await
             }
-            _ => Err("Invalid message type for player".to_string()),
+            WebSocketMessageType::Resync => {
+                let _ = control_tx.send(ConnectionControl::Resync);
+                Ok(())
+            }
+            _ => Err(WebSocketError::new(
+                WebSocketErrorCode::InvalidMessageType,
+                "Invalid message type for player",
+            )),
         }
+        .map_err(|error| error.with_request_id(request_id))
     }
 
     /// 处理导演消息
     async fn handle_director_message(
         &self,
         game_id: &str,
+        connection_id: &str,
         message: &str,
-    ) -> Result<String, String> {
-        let client_message: WebSocketClientMessage =
-            serde_json::from_str(message).map_err(|_| "Invalid message format".to_string())?;
+        control_tx: &UnboundedSender<ConnectionControl>,
+    ) -> Result<(), WebSocketError> {
+        let client_message = parse_client_message(message)?;
+        let request_id = client_message.request_id;
 
         match client_message.message_type {
             WebSocketMessageType::DirectorAction => {
                 // 处理导演控制
-                self.process_director_action(game_id, client_message.data)
-                    .await
+                self.process_director_action(
+                    game_id,
+                    client_message.data,
+                    request_origin(connection_id, request_id.as_deref()),
+                )
+                .await
+            }
+            WebSocketMessageType::Resync => {
+                let _ = control_tx.send(ConnectionControl::Resync);
+                Ok(())
             }
-            _ => Err("Invalid message type for director".to_string()),
+            _ => Err(WebSocketError::new(
+                WebSocketErrorCode::InvalidMessageType,
+                "Invalid message type for director",
+            )),
         }
+        .map_err(|error| error.with_request_id(request_id))
     }
 
     /// 处理玩家行动
@@ -414,37 +698,70 @@ impl WebSocketService {
         game_id: &str,
         player_id: &str,
         action_data: serde_json::Value,
-    ) -> Result<String, String> {
+        request_origin: Option<RequestOrigin>,
+    ) -> Result<(), WebSocketError> {
         // 获取行动类型
-        let action = action_data
-            .get("action")
-            .and_then(|v| v.as_str())
-            .ok_or("Missing action field")?;
+        let action = action_field(&action_data)?;
+        if !PlayerActionScheduler::supports(action) {
+            return Err(WebSocketError::new(
+                WebSocketErrorCode::UnknownAction,
+                format!("Unknown action type: {}", action),
+            ));
+        }
+
+        // 参数无法解析的请求不会被接受，也不写入行动日志
+        ActionParams::from_json(&action_data)
+            .map_err(|e| WebSocketError::new(WebSocketErrorCode::InvalidParams, e))?;
+
+        // 回退期间拒绝行动；守卫持有到日志与记录写入完成，回退会等待进行中的行动
+        let _operation = self.begin_operation(game_id)?;
 
         // 获取游戏状态引用
         let game_state_ref = self
             .app_state
             .game_state_manager
-            .get_game_state(&game_id)
+            .get_game_state(game_id)
             .await
-            .map_err(|e| format!("Failed to get game state: {}", e))?;
-
-        let action_params = ActionParams::from_json(&action_data)
-            .map_err(|e| format!("Failed to parse action params: {}", e))?;
+            .map_err(|e| {
+                WebSocketError::new(
+                    WebSocketErrorCode::SystemError,
+                    format!("Failed to get game state: {}", e),
+                )
+            })?;
 
         let (result, updated_game_state) = {
-            // 获取可写的游戏状态锁
+            // 获取可写的游戏状态锁，处理耗时从等待写锁开始计算
+            let started = Instant::now();
+            let _in_flight = metrics().action_in_flight(ACTOR_PLAYER);
             let mut game_state = game_state_ref.write().await;
+            metrics().observe_lock_wait(ACTOR_PLAYER, started.elapsed());
+
+            // 在修改游戏状态之前写入行动日志，写入失败时拒绝该行动
+            let entry = ActionJournal::record(
+                &mut game_state,
+                JournalActor::Player {
+                    player_id: player_id.to_string(),
+                },
+                action,
+                action_data.clone(),
+            )
+            .await
+            .map_err(|e| WebSocketError::new(WebSocketErrorCode::SystemError, e))?;
 
             // 使用调度器处理行动
-            let result =
-                PlayerActionScheduler::dispatch(&mut game_state, player_id, action, action_params);
+            let result = entry
+                .apply(&mut game_state)
+                .map(|results| results.with_request_origin(request_origin));
+            metrics().record_action(ACTOR_PLAYER, action, result.is_ok(), started.elapsed());
             let updated_game_state = game_state.clone();
             (result, updated_game_state)
         };
 
-        // 统一处理动作结果
-        self.handle_action_results(result, updated_game_state).await
+        // 统一处理动作结果，处理失败时通知发起请求的客户端
+        self.handle_action_results(result, updated_game_state)
+            .await
+            .map(|_| ())
+            .map_err(|e| WebSocketError::new(WebSocketErrorCode::ActionFailed, e))
     }
 
     /// 处理导演控制
@@ -452,38 +769,113 @@ impl WebSocketService {
         &self,
         game_id: &str,
         action_data: serde_json::Value,
-    ) -> Result<String, String> {
+        request_origin: Option<RequestOrigin>,
+    ) -> Result<(), WebSocketError> {
         // 获取行动类型
-        let action = action_data
-            .get("action")
-            .and_then(|v| v.as_str())
-            .ok_or("Missing action field")?;
+        let action = action_field(&action_data)?;
+        if !DirectorActionScheduler::supports(action) {
+            return Err(WebSocketError::new(
+                WebSocketErrorCode::UnknownAction,
+                format!("Unknown director action type: {}", action),
+            ));
+        }
+
+        // 参数无法解析的请求不会被接受，也不写入行动日志
+        DirectorActionParams::from_json(&action_data).map_err(|e| {
+            WebSocketError::new(
+                WebSocketErrorCode::InvalidParams,
+                format!("Failed to parse director action params: {}", e),
+            )
+        })?;
+
+        // 回退期间拒绝行动；守卫持有到日志与记录写入完成，回退会等待进行中的行动
+        let _operation = self.begin_operation(game_id)?;
+
         // 获取游戏状态引用
         let game_state_ref = self
             .app_state
             .game_state_manager
-            .get_game_state(&game_id)
+            .get_game_state(game_id)
             .await
-            .map_err(|e| format!("Failed to get game state: {}", e))?;
-
-        let action_params = DirectorActionParams::from_json(&action_data)
-            .map_err(|e| format!("Failed to parse director action params: {}", e))?;
+            .map_err(|e| {
+                WebSocketError::new(
+                    WebSocketErrorCode::SystemError,
+                    format!("Failed to get game state: {}", e),
+                )
+            })?;
 
         let (result, updated_game_state) = {
+            let started = Instant::now();
+            let _in_flight = metrics().action_in_flight(ACTOR_DIRECTOR);
             let mut game_state = game_state_ref.write().await;
+            metrics().observe_lock_wait(ACTOR_DIRECTOR, started.elapsed());
+
+            // 在修改游戏状态之前写入行动日志，写入失败时拒绝该行动
+            let entry = ActionJournal::record(
+                &mut game_state,
+                JournalActor::Director,
+                action,
+                action_data.clone(),
+            )
+            .await
+            .map_err(|e| WebSocketError::new(WebSocketErrorCode::SystemError, e))?;
 
             // 使用调度器处理导演行动
-            let result = DirectorActionScheduler::dispatch(&mut game_state, action, action_params);
+            let result = entry
+                .apply(&mut game_state)
+                .map(|results| results.with_request_origin(request_origin));
+            metrics().record_action(ACTOR_DIRECTOR, action, result.is_ok(), started.elapsed());
             let updated_game_state = game_state.clone();
             (result, updated_game_state)
         };
 
-        // 统一处理动作结果
-        self.handle_action_results(result, updated_game_state).await
+        // 夜晚结算完成后保存检查点存档
+        if action == "night_settlement" && result.is_ok() {
+            save_settlement_checkpoint(game_id, &updated_game_state);
+        }
+
+        // 统一处理动作结果，处理失败时通知发起请求的客户端
+        self.handle_action_results(result, updated_game_state)
+            .await
+            .map(|_| ())
+            .map_err(|e| WebSocketError::new(WebSocketErrorCode::ActionFailed, e))
+    }
+
+    /// 开始处理一次行动，游戏正在回退时拒绝
+    fn begin_operation(&self, game_id: &str) -> Result<OwnedRwLockReadGuard<()>, WebSocketError> {
+        self.app_state
+            .game_state_manager
+            .begin_operation(game_id)
+            .ok_or_else(|| {
+                WebSocketError::new(
+                    WebSocketErrorCode::ActionFailed,
+                    "Game is being rewound, reconnect and retry",
+                )
+            })
+    }
+
+    /// 开始建立连接，返回的守卫须持有到连接加入连接管理器并生成初始状态
+    ///
+    /// 游戏正在回退，或连接管理器已在回退中被替换时拒绝连接，客户端重新连接即可。
+    fn begin_connection(&self, game_id: &str) -> Result<OwnedRwLockReadGuard<()>, String> {
+        let rewinding = || "游戏正在回退，请稍后重新连接".to_string();
+        let operation = self
+            .app_state
+            .game_state_manager
+            .begin_operation(game_id)
+            .ok_or_else(rewinding)?;
+        let current_manager = self
+            .app_state
+            .global_connection_manager
+            .get_manager(game_id.to_string());
+        if !Arc::ptr_eq(&self.connection_manager, &current_manager) {
+            return Err(rewinding());
+        }
+        Ok(operation)
     }
 
     /// 统一处理ActionResults结果（完全破坏性修改，不保持向后兼容）
-    async fn handle_action_results(
+    pub async fn handle_action_results(
         &self,
         result: Result<ActionResults, String>,
         updated_game_state: GameState,
@@ -492,6 +884,15 @@ impl WebSocketService {
             Ok(action_results) => {
                 // 处理所有ActionResult
                 for action_result in &action_results.results {
+                    metrics().observe_broadcast_fanout(
+                        self.connection_manager
+                            .recipient_connection_count(
+                                &action_result.broadcast_players,
+                                action_result.broadcast_to_director,
+                            )
+                            .await,
+                    );
+
                     // 使用新的广播器广播消息给相关玩家
                     let _ = self
                         .message_broadcaster
@@ -510,6 +911,11 @@ impl WebSocketService {
                             .await;
                     }
 
+                    // 观众视图仅包含公开信息，由广播器过滤并延迟推送
+                    self.message_broadcaster
+                        .broadcast_to_spectators(&updated_game_state, action_result)
+                        .await;
+
                     // 使用显式的消息类型而不是通过字符串内容判断
                     let message_type = action_result.message_type.clone();
 
@@ -540,6 +946,7 @@ impl WebSocketService {
 
                             // 忽略日志记录错误，但记录日志
                             if let Err(e) = log_result {
+                                metrics().record_db_error("game_log");
                                 eprintln!("Failed to create log record: {}", e);
                             }
 
@@ -560,9 +967,25 @@ impl WebSocketService {
                             .add_kill_record(&kill_record_params)
                             .await
                         {
+                            metrics().record_db_error("game_log");
                             eprintln!("Failed to create kill record: {}", e);
                         }
                     }
+
+                    for damage_record_params in NewDamageRecord::build_damage_record_params(
+                        &updated_game_state.game_id,
+                        action_result,
+                    ) {
+                        if let Err(e) = self
+                            .app_state
+                            .game_log_service
+                            .add_damage_record(&damage_record_params)
+                            .await
+                        {
+                            metrics().record_db_error("game_log");
+                            eprintln!("Failed to create damage record: {}", e);
+                        }
+                    }
                 }
 
                 // 完全破坏性修改：返回所有ActionResult的响应，而不是只返回第一个
@@ -582,3 +1005,167 @@ impl WebSocketService {
         }
     }
 }
+
+/// 由发起请求的连接与客户端请求ID构造请求来源，请求未携带ID时为`None`
+fn request_origin(connection_id: &str, request_id: Option<&str>) -> Option<RequestOrigin> {
+    request_id.map(|request_id| RequestOrigin {
+        connection_id: connection_id.to_string(),
+        request_id: request_id.to_string(),
+    })
+}
+
+/// 保存夜晚结算完成时的检查点存档，失败时仅记录日志
+pub(crate) fn save_settlement_checkpoint(game_id: &str, game_state: &GameState) {
+    match GlobalGameStateManager::save_checkpoint(game_id, game_state) {
+        Ok(file_name) => debug!(
+            "Saved settlement checkpoint {} for game {}",
+            file_name, game_id
+        ),
+        Err(e) => warn!(
+            "Failed to save settlement checkpoint for game {}: {}",
+            game_id, e
+        ),
+    }
+}
+
+/// 解析客户端消息，格式错误时返回带错误码的错误
+fn parse_client_message(message: &str) -> Result<WebSocketClientMessage, WebSocketError> {
+    serde_json::from_str(message).map_err(|e| {
+        // 消息整体无法解析时仍尽量取出请求ID回传给客户端
+        let request_id = serde_json::from_str::<serde_json::Value>(message)
+            .ok()
+            .and_then(|value| value.get("request_id")?.as_str().map(str::to_string));
+        WebSocketError::new(
+            WebSocketErrorCode::InvalidMessage,
+            format!("Invalid message format: {}", e),
+        )
+        .with_request_id(request_id)
+    })
+}
+
+/// 处理观众消息，观众只能请求重新同步
+fn handle_spectator_message(
+    message: &str,
+    control_tx: &UnboundedSender<ConnectionControl>,
+) -> Result<(), WebSocketError> {
+    let client_message = parse_client_message(message)?;
+    match client_message.message_type {
+        WebSocketMessageType::Resync => {
+            let _ = control_tx.send(ConnectionControl::Resync);
+            Ok(())
+        }
+        _ => Err(WebSocketError::new(
+            WebSocketErrorCode::InvalidMessageType,
+            "Spectators cannot send actions",
+        )
+        .with_request_id(client_message.request_id)),
+    }
+}
+
+/// 读取行动数据中的行动类型
+fn action_field(action_data: &serde_json::Value) -> Result<&str, WebSocketError> {
+    action_data
+        .get("action")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| {
+            WebSocketError::new(WebSocketErrorCode::InvalidParams, "Missing action field")
+        })
+}
+
+/// 在消息数据中附加接收者维度的消息序号
+fn with_seq(mut data: serde_json::Value, seq: Option<u64>) -> serde_json::Value {
+    if let (Some(seq), Some(map)) = (seq, data.as_object_mut()) {
+        map.insert("seq".to_string(), json!(seq));
+    }
+    data
+}
+
+/// 将状态更新编码为WebSocket消息
+fn state_update_message(update: StateUpdate, seq: Option<u64>) -> FrameView {
+    match update {
+        StateUpdate::Snapshot(data) => {
+            super::message_formatter::game_state_message(with_seq(data, seq))
+        }
+        StateUpdate::Patch(data) => {
+            super::message_formatter::game_state_patch_message(with_seq(data, seq))
+        }
+    }
+}
+
+/// 强制断开时等待关闭帧发出的最长时间
+const CLOSE_FRAME_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// 将连接管理器推送的消息与本连接的控制消息写入WebSocket发送端，并按`ping_interval`发送心跳Ping
+///
+/// 游戏状态视图按连接的同步模式编码；序号不大于初始状态序号的消息已包含在初始状态或补发事件中，直接跳过。
+/// 发送队列被正常关闭（连接被移除）时发送完剩余消息后关闭WebSocket；
+/// 因客户端消费过慢被关闭或收到关闭控制消息时立即中止发送，并以带原因的关闭帧断开连接。
+async fn forward_outgoing_messages(
+    mut sender: SplitSink<WebSocket, FrameView>,
+    mut state_stream: StateStream,
+    initial_seq: u64,
+    rx: OutboundReceiver,
+    mut control_rx: UnboundedReceiver<ConnectionControl>,
+    ping_interval: Duration,
+) {
+    let mut ping = tokio::time::interval_at(Instant::now() + ping_interval, ping_interval);
+    ping.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    let mut close_reason = None;
+    loop {
+        let websocket_message = tokio::select! {
+            message = rx.recv() => match message {
+                Some(SequencedMessage { seq, .. }) if seq <= initial_seq => continue,
+                Some(SequencedMessage { seq, message: ConnectionMessage::GameState(view) }) => {
+                    state_update_message(state_stream.next_update(view), Some(seq))
+                }
+                Some(SequencedMessage { seq, message: ConnectionMessage::ActionResult(action_result) }) => {
+                    state_update_message(state_stream.event_update(action_result), Some(seq))
+                }
+                Some(SequencedMessage { seq, message: ConnectionMessage::System(data) }) => {
+                    super::message_formatter::system_message(with_seq(data, Some(seq)))
+                }
+                Some(SequencedMessage { seq, message: ConnectionMessage::Presence(data) }) => {
+                    super::message_formatter::presence_message(with_seq(data, Some(seq)))
+                }
+                None => break,
+            },
+            Some(control) = control_rx.recv() => match control {
+                ConnectionControl::Error(error) => super::message_formatter::error_message(&error),
+                ConnectionControl::Resync => match state_stream.resync() {
+                    Some(update) => state_update_message(update, None),
+                    None => continue,
+                },
+                ConnectionControl::Close(reason) => {
+                    close_reason = Some(reason);
+                    break;
+                }
+            },
+            _ = ping.tick() => FrameView::ping(Vec::new()),
+        };
+        // 心跳Ping不计入发送统计
+        let is_ping = matches!(websocket_message.opcode, OpCode::Ping);
+        let bytes = websocket_message.payload.len();
+        // 客户端不读取数据时写入会一直阻塞，队列溢出后不再等待
+        let sent = tokio::select! {
+            result = sender.send(websocket_message) => result.is_ok(),
+            _ = rx.overflowed() => break,
+        };
+        if !sent {
+            break;
+        }
+        if !is_ping {
+            rx.record_sent(bytes);
+        }
+    }
+
+    match close_reason.or(rx.close_reason()) {
+        Some(reason @ (CloseReason::SlowConsumer | CloseReason::IdleTimeout)) => {
+            let close_frame =
+                FrameView::close(CloseCode::from(reason.close_code()), reason.description());
+            let _ = tokio::time::timeout(CLOSE_FRAME_TIMEOUT, sender.send(close_frame)).await;
+        }
+        _ => {
+            let _ = sender.close().await;
+        }
+    }
+}
@@ -0,0 +1,85 @@
+//! 观众延迟推送队列
+//! 观众视图按规则中的`spectator.delay_seconds`延迟推送，防止观众向玩家透露实时战况
+
+use serde_json::Value as JsonValue;
+use std::collections::VecDeque;
+use tokio::time::Instant;
+
+/// 观众连接建立时可用的初始视图
+#[derive(Debug, Clone, PartialEq)]
+pub enum SpectatorSnapshot {
+    /// 最近一次推送给观众的视图（不含`action_result`）
+    Published(JsonValue),
+    /// 尚未推送过视图但已有视图在等待推送，此时的实时状态不能公开
+    Pending,
+    /// 尚无任何视图入队，实时公开状态即观众应看到的状态
+    Empty,
+}
+
+/// 单个游戏的观众推送队列
+///
+/// 视图在入队时确定推送时间，到期后按入队顺序推送；
+/// 视图中公开状态未变化且没有公开事件时不入队，避免无意义的推送。
+#[derive(Debug, Default)]
+pub struct SpectatorFeed {
+    /// 最近一次入队视图的公开状态（不含`action_result`），用于去重
+    last_state: Option<JsonValue>,
+    /// 等待推送的视图及其推送时间
+    pending: VecDeque<(Instant, JsonValue)>,
+    /// 最近一次推送给观众的视图（不含`action_result`）
+    published: Option<JsonValue>,
+}
+
+impl SpectatorFeed {
+    /// 创建新的观众推送队列
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 将观众视图加入队列，返回是否需要推送
+    pub fn enqueue(&mut self, view: JsonValue, due: Instant) -> bool {
+        let has_events = view
+            .get("action_result")
+            .is_some_and(|action_result| !action_result.is_null());
+        let state = without_action_result(&view);
+        if !has_events && self.last_state.as_ref() == Some(&state) {
+            return false;
+        }
+
+        self.last_state = Some(state);
+        self.pending.push_back((due, view));
+        true
+    }
+
+    /// 取出所有已到推送时间的视图（按入队顺序）
+    pub fn take_due(&mut self, now: Instant) -> Vec<JsonValue> {
+        let mut due_views = Vec::new();
+        while let Some((due, _)) = self.pending.front() {
+            if *due > now {
+                break;
+            }
+            if let Some((_, view)) = self.pending.pop_front() {
+                self.published = Some(without_action_result(&view));
+                due_views.push(view);
+            }
+        }
+        due_views
+    }
+
+    /// 新观众连接的初始视图
+    pub fn snapshot(&self) -> SpectatorSnapshot {
+        match &self.published {
+            Some(published) => SpectatorSnapshot::Published(published.clone()),
+            None if self.pending.is_empty() => SpectatorSnapshot::Empty,
+            None => SpectatorSnapshot::Pending,
+        }
+    }
+}
+
+fn without_action_result(view: &JsonValue) -> JsonValue {
+    let mut state = view.clone();
+    if let Some(map) = state.as_object_mut() {
+        map.remove("action_result");
+    }
+    state
+}
@@ -0,0 +1,190 @@
+//! 游戏状态增量同步
+//! 按连接维护已发送的状态视图与版本号，在增量模式下以JSON Patch（RFC 6902）推送状态变化
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Value as JsonValue, json};
+
+/// 状态同步模式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StateSyncMode {
+    /// 每次推送完整状态视图（默认）
+    #[default]
+    Snapshot,
+    /// 首次推送带版本号的完整视图，之后推送相对上一版本的增量补丁
+    Delta,
+}
+
+/// JSON Patch操作（RFC 6902中的add/remove/replace子集）
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum PatchOperation {
+    /// 添加对象字段或追加数组元素
+    Add { path: String, value: JsonValue },
+    /// 删除对象字段或数组元素
+    Remove { path: String },
+    /// 替换已有的值
+    Replace { path: String, value: JsonValue },
+}
+
+/// 计算从`old`到`new`的补丁操作列表
+///
+/// 对象按字段递归比较；数组按下标比较公共部分，多出的元素在末尾追加，
+/// 缺少的元素从末尾向前删除，保证补丁按顺序应用后得到`new`。
+pub fn diff(old: &JsonValue, new: &JsonValue) -> Vec<PatchOperation> {
+    let mut operations = Vec::new();
+    diff_at("", old, new, &mut operations);
+    operations
+}
+
+fn diff_at(path: &str, old: &JsonValue, new: &JsonValue, operations: &mut Vec<PatchOperation>) {
+    if old == new {
+        return;
+    }
+
+    match (old, new) {
+        (JsonValue::Object(old_map), JsonValue::Object(new_map)) => {
+            for (key, old_value) in old_map {
+                let child_path = child_path(path, key);
+                match new_map.get(key) {
+                    Some(new_value) => diff_at(&child_path, old_value, new_value, operations),
+                    None => operations.push(PatchOperation::Remove { path: child_path }),
+                }
+            }
+            for (key, new_value) in new_map {
+                if !old_map.contains_key(key) {
+                    operations.push(PatchOperation::Add {
+                        path: child_path(path, key),
+                        value: new_value.clone(),
+                    });
+                }
+            }
+        }
+        (JsonValue::Array(old_items), JsonValue::Array(new_items)) => {
+            let common = old_items.len().min(new_items.len());
+            for index in 0..common {
+                diff_at(
+                    &child_path(path, &index.to_string()),
+                    &old_items[index],
+                    &new_items[index],
+                    operations,
+                );
+            }
+            for (index, new_value) in new_items.iter().enumerate().skip(common) {
+                operations.push(PatchOperation::Add {
+                    path: child_path(path, &index.to_string()),
+                    value: new_value.clone(),
+                });
+            }
+            for index in (common..old_items.len()).rev() {
+                operations.push(PatchOperation::Remove {
+                    path: child_path(path, &index.to_string()),
+                });
+            }
+        }
+        _ => operations.push(PatchOperation::Replace {
+            path: path.to_string(),
+            value: new.clone(),
+        }),
+    }
+}
+
+/// 拼接JSON Pointer路径，按RFC 6901转义`~`与`/`
+fn child_path(parent: &str, key: &str) -> String {
+    format!("{}/{}", parent, key.replace('~', "~0").replace('/', "~1"))
+}
+
+/// 推送给客户端的状态更新
+#[derive(Debug, Clone, PartialEq)]
+pub enum StateUpdate {
+    /// 完整状态视图（`game_state`消息）
+    Snapshot(JsonValue),
+    /// 增量补丁（`game_state_patch`消息）
+    Patch(JsonValue),
+}
+
+/// 单个连接的状态推送流
+///
+/// 广播器生成的视图包含`action_result`字段，增量模式下该字段不参与比较，
+/// 随快照或补丁原样下发。
+#[derive(Debug, Clone)]
+pub struct StateStream {
+    /// 同步模式
+    mode: StateSyncMode,
+    /// 最近一次推送的状态版本号
+    version: u64,
+    /// 最近一次推送的状态视图（不含`action_result`）
+    last_state: Option<JsonValue>,
+}
+
+impl StateStream {
+    /// 创建新的状态推送流
+    pub fn new(mode: StateSyncMode) -> Self {
+        Self {
+            mode,
+            version: 0,
+            last_state: None,
+        }
+    }
+
+    /// 将广播器生成的视图编码为下一条状态更新
+    pub fn next_update(&mut self, mut view: JsonValue) -> StateUpdate {
+        let action_result = view
+            .as_object_mut()
+            .and_then(|map| map.remove("action_result"))
+            .unwrap_or(JsonValue::Null);
+
+        if self.mode == StateSyncMode::Snapshot {
+            let update = with_action_result(view.clone(), action_result);
+            self.last_state = Some(view);
+            return StateUpdate::Snapshot(update);
+        }
+
+        self.version += 1;
+        let update = match &self.last_state {
+            Some(last_state) => StateUpdate::Patch(json!({
+                "version": self.version,
+                "patch": diff(last_state, &view),
+                "action_result": action_result,
+            })),
+            None => StateUpdate::Snapshot(self.versioned_snapshot(view.clone(), action_result)),
+        };
+        self.last_state = Some(view);
+        update
+    }
+
+    /// 将被更新状态覆盖后保留的动作结果编码为状态更新
+    ///
+    /// 状态沿用最近一次推送的视图：快照模式下随完整状态下发，增量模式下补丁为空。
+    pub fn event_update(&mut self, action_result: JsonValue) -> StateUpdate {
+        let state = self.last_state.clone().unwrap_or_else(|| json!({}));
+        self.next_update(with_action_result(state, action_result))
+    }
+
+    /// 重新发送最近一次推送的完整状态（客户端发现版本缺口时请求）
+    ///
+    /// 快照使用当前版本号，之后的补丁继续以该版本为基础；尚未推送过状态时返回`None`。
+    pub fn resync(&self) -> Option<StateUpdate> {
+        let state = self.last_state.clone()?;
+        let update = match self.mode {
+            StateSyncMode::Snapshot => with_action_result(state, JsonValue::Null),
+            StateSyncMode::Delta => self.versioned_snapshot(state, JsonValue::Null),
+        };
+        Some(StateUpdate::Snapshot(update))
+    }
+
+    fn versioned_snapshot(&self, state: JsonValue, action_result: JsonValue) -> JsonValue {
+        let mut snapshot = with_action_result(state, action_result);
+        if let Some(map) = snapshot.as_object_mut() {
+            map.insert("version".to_string(), json!(self.version));
+        }
+        snapshot
+    }
+}
+
+fn with_action_result(mut state: JsonValue, action_result: JsonValue) -> JsonValue {
+    if let Some(map) = state.as_object_mut() {
+        map.insert("action_result".to_string(), action_result);
+    }
+    state
+}
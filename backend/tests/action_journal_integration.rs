@@ -0,0 +1,208 @@
+//! 行动日志集成测试
+//! 测试行动日志的写入、读取、截断、写入失败时的序号回退以及基于存档快照的确定性重放
+
+mod common;
+
+use chrono::{Duration, Utc};
+use common::{add_test_player, test_rules};
+use royale_arena_backend::game::action_journal::{ActionJournal, JournalActor, JournalEntry};
+use royale_arena_backend::websocket::models::GameState;
+use serde_json::json;
+
+/// 创建包含两名尚未出生玩家的游戏状态
+fn create_game_state(game_id: &str) -> GameState {
+    let rules = test_rules(json!({"map": {"places": ["位置1", "位置2", "位置3"]}}));
+    let mut game_state = common::create_game_state(game_id, rules);
+    for player_id in ["p1", "p2"] {
+        add_test_player(
+            &mut game_state,
+            player_id,
+            &format!("玩家{}", player_id),
+            "",
+        );
+    }
+    game_state
+}
+
+/// 记录并应用一条日志，返回日志条目
+fn record(
+    game_state: &mut GameState,
+    actor: JournalActor,
+    action: &str,
+    params: serde_json::Value,
+) -> JournalEntry {
+    let entry = JournalEntry::new(game_state, actor, action, params);
+    entry.apply(game_state).unwrap();
+    entry
+}
+
+fn player(player_id: &str) -> JournalActor {
+    JournalActor::Player {
+        player_id: player_id.to_string(),
+    }
+}
+
+fn player_summary(game_state: &GameState) -> Vec<(String, String, i32, i32)> {
+    let mut summary: Vec<(String, String, i32, i32)> = game_state
+        .players
+        .values()
+        .map(|p| (p.id.clone(), p.location.clone(), p.life, p.strength))
+        .collect();
+    summary.sort();
+    summary
+}
+
+/// 测试：在快照上重放日志可以重建与实时处理一致的游戏状态，并支持重放到中间序号
+#[test]
+fn test_replay_from_snapshot() {
+    let mut game_state = create_game_state("test_journal_replay");
+    record(
+        &mut game_state,
+        JournalActor::Director,
+        "set_night_start_time",
+        json!({"timestamp": (Utc::now() - Duration::hours(1)).to_rfc3339()}),
+    );
+    record(
+        &mut game_state,
+        JournalActor::Director,
+        "set_night_end_time",
+        json!({"timestamp": (Utc::now() + Duration::hours(1)).to_rfc3339()}),
+    );
+
+    // 以当前状态作为存档快照
+    let snapshot: GameState =
+        serde_json::from_str(&serde_json::to_string(&game_state).unwrap()).unwrap();
+    assert_eq!(snapshot.journal_sequence, 2);
+
+    let mut entries = vec![
+        record(
+            &mut game_state,
+            player("p1"),
+            "born",
+            json!({"action": "born", "place_name": "位置1"}),
+        ),
+        record(
+            &mut game_state,
+            player("p2"),
+            "born",
+            json!({"action": "born", "place_name": "位置2"}),
+        ),
+    ];
+    let midpoint = player_summary(&game_state);
+    entries.push(record(
+        &mut game_state,
+        player("p1"),
+        "move",
+        json!({"action": "move", "target_place": "位置3"}),
+    ));
+    entries.push(record(
+        &mut game_state,
+        JournalActor::Director,
+        "life",
+        json!({"action": "life", "player_id": "p2", "life": 42}),
+    ));
+
+    let replayed = ActionJournal::replay(snapshot.clone(), &entries, None);
+    assert_eq!(player_summary(&replayed), player_summary(&game_state));
+    assert_eq!(replayed.journal_sequence, 6);
+    assert_eq!(replayed.players.get("p1").unwrap().location, "位置3");
+
+    let partial = ActionJournal::replay(snapshot, &entries, Some(4));
+    assert_eq!(player_summary(&partial), midpoint);
+    assert_eq!(partial.journal_sequence, 4);
+}
+
+/// 测试：重放使用日志记录的时间进行夜晚行动时间判定
+#[test]
+fn test_replay_uses_journal_timestamp() {
+    let mut game_state = create_game_state("test_journal_time");
+    game_state.night_start_time = Some(Utc::now() - Duration::hours(3));
+    game_state.night_end_time = Some(Utc::now() - Duration::hours(1));
+    let snapshot = game_state.clone();
+
+    // 行动发生在夜晚时间内
+    let mut entry = JournalEntry::new(
+        &mut game_state,
+        player("p1"),
+        "born",
+        json!({"action": "born", "place_name": "位置1"}),
+    );
+    entry.timestamp = Utc::now() - Duration::hours(2);
+
+    let replayed = ActionJournal::replay(snapshot.clone(), &[entry.clone()], None);
+    assert_eq!(replayed.players.get("p1").unwrap().location, "位置1");
+
+    // 行动发生在夜晚时间之外则被拒绝
+    entry.timestamp = Utc::now();
+    let replayed = ActionJournal::replay(snapshot, &[entry], None);
+    assert!(replayed.players.get("p1").unwrap().location.is_empty());
+}
+
+/// 测试：日志文件的追加、读取、截断与删除
+#[test]
+fn test_journal_file_roundtrip() {
+    let game_id = format!("test_journal_file_{}", uuid::Uuid::new_v4());
+    let mut game_state = create_game_state(&game_id);
+
+    for place_name in ["位置1", "位置2", "位置3"] {
+        let entry = JournalEntry::new(
+            &mut game_state,
+            JournalActor::Director,
+            "weather",
+            json!({"action": "weather", "weather": 0.5, "place": place_name}),
+        );
+        ActionJournal::append(&game_id, &entry).unwrap();
+    }
+
+    let entries = ActionJournal::read_entries(&game_id).unwrap();
+    assert_eq!(entries.len(), 3);
+    assert_eq!(
+        entries.iter().map(|e| e.sequence).collect::<Vec<_>>(),
+        vec![1, 2, 3]
+    );
+    assert_eq!(entries[0].actor, JournalActor::Director);
+
+    ActionJournal::truncate_after(&game_id, 1).unwrap();
+    assert_eq!(ActionJournal::read_entries(&game_id).unwrap().len(), 1);
+
+    ActionJournal::delete(&game_id).unwrap();
+    assert!(ActionJournal::read_entries(&game_id).unwrap().is_empty());
+    let _ = std::fs::remove_dir(format!("game_states/{}", game_id));
+}
+
+/// 测试：日志写入失败时返回错误并撤回已分配的序号
+#[tokio::test]
+async fn test_record_failure_keeps_sequence() {
+    let game_id = format!("test_journal_broken_{}", uuid::Uuid::new_v4());
+    let mut game_state = create_game_state(&game_id);
+
+    // 游戏目录位置被普通文件占用，无法创建日志文件
+    std::fs::create_dir_all("game_states").unwrap();
+    let blocker = format!("game_states/{}", game_id);
+    std::fs::write(&blocker, "").unwrap();
+
+    let result = ActionJournal::record(
+        &mut game_state,
+        JournalActor::Director,
+        "weather",
+        json!({"action": "weather", "weather": 0.5}),
+    )
+    .await;
+    assert!(result.is_err());
+    assert_eq!(game_state.journal_sequence, 0);
+
+    std::fs::remove_file(&blocker).unwrap();
+    let entry = ActionJournal::record(
+        &mut game_state,
+        JournalActor::Director,
+        "weather",
+        json!({"action": "weather", "weather": 0.5}),
+    )
+    .await
+    .unwrap();
+    assert_eq!(entry.sequence, 1);
+    assert_eq!(ActionJournal::read_entries(&game_id).unwrap().len(), 1);
+
+    ActionJournal::delete(&game_id).unwrap();
+    let _ = std::fs::remove_dir(format!("game_states/{}", game_id));
+}
@@ -15,9 +15,14 @@ async fn test_admin_auth_complete_flow(pool: MySqlPool) -> Result<(), Box<dyn st
         database_url: "test://dummy".to_string(),
         jwt_secret: "test-secret-key-for-testing-purposes-only".to_string(),
         jwt_expiration_hours: 24,
+        game_token_expiration_minutes: 120,
         bcrypt_cost: 4, // 降低成本以加快测试
         server_port: 3001,
         api_prefix: "/api".to_string(),
+        ws_outbound_queue_capacity: 256,
+        ws_ping_interval_seconds: 30,
+        ws_idle_timeout_seconds: 90,
+        archive_import_max_mb: 64,
     };
 
     // 创建服务
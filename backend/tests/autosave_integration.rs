@@ -0,0 +1,177 @@
+//! 自动存档集成测试
+//! 测试存档类型与文件名前缀、自动存档规则配置与校验、自动存档保留数量的清理策略，以及回退与行动处理的互斥
+
+mod common;
+
+use common::test_rules;
+use royale_arena_backend::game::game_rule_engine::GameRuleEngine;
+use royale_arena_backend::game::global_game_state_manager::GlobalGameStateManager;
+use royale_arena_backend::game::models::SaveKind;
+use royale_arena_backend::game::rules_validator::RulesValidator;
+use serde_json::{Value as JsonValue, json};
+use sqlx::mysql::MySqlPoolOptions;
+use std::time::Duration;
+
+/// 测试规则配置
+fn get_test_rules() -> JsonValue {
+    test_rules(json!({
+      "map": {"places": ["码头"]},
+      "player": {"max_backpack_items": 3},
+      "death_item_disposition": "vanish"
+    }))
+}
+
+/// 创建不连接数据库的游戏状态管理器（加载玩家失败时游戏中没有玩家）
+fn create_manager() -> GlobalGameStateManager {
+    let pool = MySqlPoolOptions::new()
+        .acquire_timeout(Duration::from_millis(100))
+        .connect_lazy("mysql://root@127.0.0.1:1/royale_arena")
+        .unwrap();
+    GlobalGameStateManager::new(pool)
+}
+
+#[test]
+fn test_save_kind_is_encoded_in_file_name() {
+    assert_eq!(
+        SaveKind::from_file_name("2024-01-01T10-00-00.000Z.json"),
+        SaveKind::Manual
+    );
+    assert_eq!(
+        SaveKind::from_file_name("autosave-2024-01-01T10-00-00.000Z.json"),
+        SaveKind::Autosave
+    );
+    assert_eq!(
+        SaveKind::from_file_name("checkpoint-2024-01-01T10-00-00.000Z.json"),
+        SaveKind::Checkpoint
+    );
+    assert_eq!(
+        serde_json::to_value(SaveKind::Checkpoint).unwrap(),
+        json!("checkpoint")
+    );
+}
+
+#[test]
+fn test_autosave_config_defaults_and_validation() {
+    let engine = GameRuleEngine::from_json(&get_test_rules().to_string()).unwrap();
+    assert_eq!(engine.autosave.interval_seconds, 300);
+    assert_eq!(engine.autosave.keep_saves, 10);
+
+    let mut rules = get_test_rules();
+    rules["autosave"] = json!({ "interval_seconds": 0, "keep_saves": 3 });
+    let engine = GameRuleEngine::from_json(&rules.to_string()).unwrap();
+    assert_eq!(engine.autosave.interval_seconds, 0);
+    assert_eq!(engine.autosave.keep_saves, 3);
+
+    rules["autosave"] = json!({ "interval_seconds": "often", "keep_saves": 0 });
+    let report = RulesValidator::validate(&rules);
+    assert!(!report.valid);
+    for path in ["$.autosave.interval_seconds", "$.autosave.keep_saves"] {
+        assert!(
+            report.issues.iter().any(|issue| issue.path == path),
+            "未找到路径 {} 的问题: {:?}",
+            path,
+            report.issues
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_autosaves_are_pruned_while_manual_saves_and_checkpoints_are_kept() {
+    let game_id = "test_autosave_retention";
+    let _ = std::fs::remove_dir_all(format!("game_states/{}", game_id));
+
+    let mut rules = get_test_rules();
+    rules["autosave"] = json!({ "interval_seconds": 0, "keep_saves": 2 });
+    let manager = create_manager();
+    let game_state = manager
+        .create_game_state(game_id, rules, Some(7))
+        .await
+        .unwrap();
+
+    let manual = manager.save_game_state_to_disk(game_id).await.unwrap();
+    let checkpoint =
+        GlobalGameStateManager::save_checkpoint(game_id, &game_state.read().await.clone()).unwrap();
+    let mut autosaves = Vec::new();
+    for _ in 0..4 {
+        // 存档文件名精确到毫秒，间隔保存避免重名
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        autosaves.push(manager.autosave(game_id).await.unwrap());
+    }
+    assert_eq!(SaveKind::from_file_name(&manual), SaveKind::Manual);
+    assert!(checkpoint.starts_with("checkpoint-"));
+    assert!(autosaves.iter().all(|name| name.starts_with("autosave-")));
+
+    let save_files = manager.list_save_files(game_id).await.unwrap();
+    let names: Vec<&str> = save_files
+        .iter()
+        .map(|save| save.file_name.as_str())
+        .collect();
+    assert_eq!(save_files.len(), 4);
+    assert!(names.contains(&manual.as_str()));
+    assert!(names.contains(&checkpoint.as_str()));
+    // 只保留最近的两个自动存档
+    assert!(names.contains(&autosaves[3].as_str()));
+    assert!(names.contains(&autosaves[2].as_str()));
+    assert!(save_files.iter().all(|save| save.created_at.is_some()));
+    assert_eq!(
+        save_files
+            .iter()
+            .filter(|save| save.kind == SaveKind::Autosave)
+            .count(),
+        2
+    );
+
+    // 自动存档可以直接用于恢复游戏
+    manager.remove_game_state(game_id);
+    manager
+        .load_game_state_from_disk_with_name(game_id, &autosaves[3])
+        .await
+        .unwrap();
+    assert!(
+        manager
+            .get_game_state(game_id)
+            .await
+            .unwrap()
+            .read()
+            .await
+            .save_time
+            .is_some()
+    );
+
+    // 关闭前保存内存中的全部游戏
+    assert_eq!(manager.save_all_game_states().await, 1);
+    assert_eq!(manager.prune_autosaves(game_id, 0).await.unwrap(), 2);
+    assert_eq!(manager.list_save_files(game_id).await.unwrap().len(), 2);
+
+    std::fs::remove_dir_all(format!("game_states/{}", game_id)).unwrap();
+}
+
+/// 测试：回退等待进行中的操作完成，回退期间拒绝新的操作，回退结束后恢复
+#[tokio::test]
+async fn test_rewind_excludes_operations() {
+    let manager = create_manager();
+    let game_id = "test_rewind_gate";
+
+    let operation = manager.begin_operation(game_id).unwrap();
+    assert!(manager.begin_operation(game_id).is_some());
+    // 其他游戏不受影响
+    let other = manager.begin_operation("test_rewind_gate_other").unwrap();
+
+    let rewind = tokio::spawn({
+        let manager = manager.clone();
+        async move { manager.begin_rewind(game_id).await }
+    });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(!rewind.is_finished());
+    // 回退等待期间同样拒绝新的操作
+    assert!(manager.begin_operation(game_id).is_none());
+
+    drop(operation);
+    let rewinding = rewind.await.unwrap();
+    assert!(manager.begin_operation(game_id).is_none());
+    assert!(manager.begin_operation("test_rewind_gate_other").is_some());
+
+    drop(rewinding);
+    drop(other);
+    assert!(manager.begin_operation(game_id).is_some());
+}
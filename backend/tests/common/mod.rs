@@ -0,0 +1,118 @@
+//! 集成测试共用工具
+//! 提供基础规则配置（各测试只覆盖需要的字段）以及构造游戏状态与测试玩家的方法
+
+#![allow(dead_code)]
+
+use royale_arena_backend::websocket::models::{GameState, Place, Player};
+use serde_json::{Value as JsonValue, json};
+
+/// 基础规则配置：两个地点、无物品、击杀者缴获遗物
+fn base_rules() -> JsonValue {
+    json!({
+      "map": {
+        "places": ["位置1", "位置2"],
+        "safe_places": []
+      },
+      "player": {
+        "max_life": 100,
+        "max_strength": 100,
+        "daily_life_recovery": 0,
+        "daily_strength_recovery": 0,
+        "search_cooldown": 0,
+        "max_backpack_items": 5,
+        "unarmed_damage": 10
+      },
+      "action_costs": {
+        "move": 5,
+        "search": 10,
+        "pick": 5,
+        "attack": 15,
+        "equip": 5,
+        "use": 5,
+        "throw": 5,
+        "deliver": 10
+      },
+      "rest_mode": {
+        "life_recovery": 0,
+        "strength_recovery": 0,
+        "max_moves": 1
+      },
+      "death_item_disposition": "killer_takes_loot",
+      "teammate_behavior": 0,
+      "display_names": {},
+      "items_config": {
+        "rarity_levels": [],
+        "items": {}
+      }
+    })
+}
+
+/// 将`overrides`递归合并到`target`：对象按字段合并，其他值直接替换
+fn merge(target: &mut JsonValue, overrides: JsonValue) {
+    match (target, overrides) {
+        (JsonValue::Object(target), JsonValue::Object(overrides)) => {
+            for (key, value) in overrides {
+                match target.get_mut(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        target.insert(key, value);
+                    }
+                }
+            }
+        }
+        (target, overrides) => *target = overrides,
+    }
+}
+
+/// 在基础规则配置上覆盖指定字段
+pub fn test_rules(overrides: JsonValue) -> JsonValue {
+    let mut rules = base_rules();
+    merge(&mut rules, overrides);
+    rules
+}
+
+/// 仅包含普通稀有度的稀有度配置
+pub fn common_rarity_levels() -> JsonValue {
+    json!([
+        {"internal_name": "common", "display_name": "普通", "prefix": "[普]", "is_airdropped": true}
+    ])
+}
+
+/// 创建游戏状态并添加规则中的全部地点
+pub fn create_game_state(game_id: &str, rules: JsonValue) -> GameState {
+    let mut game_state = GameState::new(game_id.to_string(), rules);
+    for place_name in game_state.rule_engine.map_config.places.clone() {
+        game_state
+            .places
+            .insert(place_name.clone(), Place::new(place_name));
+    }
+    game_state
+}
+
+/// 添加位于指定地点的测试玩家（地点为空时玩家尚未出生），返回玩家以便调整属性
+pub fn add_test_player<'a>(
+    game_state: &'a mut GameState,
+    player_id: &str,
+    player_name: &str,
+    location: &str,
+) -> &'a mut Player {
+    let mut player = Player::new(
+        player_id.to_string(),
+        player_name.to_string(),
+        0,
+        &game_state.rule_engine,
+    );
+    player.location = location.to_string();
+
+    if !location.is_empty() {
+        game_state
+            .places
+            .entry(location.to_string())
+            .or_insert_with(|| Place::new(location.to_string()))
+            .players
+            .push(player_id.to_string());
+    }
+
+    game_state.players.insert(player_id.to_string(), player);
+    game_state.players.get_mut(player_id).unwrap()
+}
@@ -120,7 +120,6 @@ fn add_test_player(
     let mut player = Player::new(
         player_id.to_string(),
         player_name.to_string(),
-        "password".to_string(),
         1,
         &game_state.rule_engine,
     );
@@ -156,6 +155,11 @@ fn empty_action_params() -> ActionParams {
         target_item_name: None,
         message: None,
         shop_buy_items: None,
+        item_ids: None,
+        coins: None,
+        request_item_ids: None,
+        request_coins: None,
+        trade_id: None,
     }
 }
 
@@ -239,7 +243,6 @@ fn test_player_initial_coins_is_zero() {
     let player = Player::new(
         "test_player_1".to_string(),
         "测试玩家".to_string(),
-        "password".to_string(),
         1,
         &rule_engine,
     );
@@ -257,7 +260,6 @@ fn test_director_set_player_coins() {
     let mut player = Player::new(
         player_id.to_string(),
         "玩家C".to_string(),
-        "password".to_string(),
         1,
         &game_state.rule_engine,
     );
@@ -317,7 +319,6 @@ fn test_director_move_player() {
     let mut player = Player::new(
         player_id.to_string(),
         "玩家D".to_string(),
-        "password".to_string(),
         1,
         &game_state.rule_engine,
     );
@@ -354,7 +355,6 @@ fn test_director_move_player_same_location() {
     let mut player = Player::new(
         player_id.to_string(),
         "玩家E".to_string(),
-        "password".to_string(),
         1,
         &game_state.rule_engine,
     );
@@ -383,7 +383,6 @@ fn test_director_move_player_nonexistent_location() {
     let mut player = Player::new(
         player_id.to_string(),
         "玩家F".to_string(),
-        "password".to_string(),
         1,
         &game_state.rule_engine,
     );
@@ -429,7 +428,6 @@ fn test_director_move_player_to_destroyed_location() {
     let mut player = Player::new(
         player_id.to_string(),
         "玩家G".to_string(),
-        "password".to_string(),
         1,
         &game_state.rule_engine,
     );
@@ -498,7 +496,6 @@ fn test_multiple_players_independent_coins() {
     let mut player1 = Player::new(
         player1_id.to_string(),
         "玩家1".to_string(),
-        "password1".to_string(),
         1,
         &game_state.rule_engine,
     );
@@ -507,7 +504,6 @@ fn test_multiple_players_independent_coins() {
     let mut player2 = Player::new(
         player2_id.to_string(),
         "玩家2".to_string(),
-        "password2".to_string(),
         1,
         &game_state.rule_engine,
     );
@@ -976,13 +972,14 @@ fn test_legacy_save_deserialization_defaults_for_coins_shop_and_quantity() {
     let mut player_value = serde_json::to_value(Player::new(
         "legacy_player".to_string(),
         "旧玩家".to_string(),
-        "password".to_string(),
         1,
         &GameRuleEngine::from_json(&serde_json::to_string(&rules_json).expect("规则序列化应成功"))
             .expect("规则解析应成功"),
     ))
     .expect("玩家序列化应成功");
     player_value.as_object_mut().unwrap().remove("coins");
+    // 旧存档中的玩家还带有明文密码字段，读取时应被忽略
+    player_value["password"] = json!("password");
 
     let game_state_without_shop: GameState = serde_json::from_value(json!({
         "game_id": "legacy-game-1",
@@ -1002,6 +999,12 @@ fn test_legacy_save_deserialization_defaults_for_coins_shop_and_quantity() {
 
     assert!(game_state_without_shop.shop.is_empty());
     assert_eq!(game_state_without_shop.players["legacy_player"].coins, 0);
+    assert!(
+        serde_json::to_value(&game_state_without_shop.players["legacy_player"])
+            .unwrap()
+            .get("password")
+            .is_none()
+    );
 
     let game_state_with_legacy_listing: GameState = serde_json::from_value(json!({
         "game_id": "legacy-game-2",
@@ -1,18 +1,23 @@
+mod common;
+
 #[cfg(test)]
 mod director_integration_tests {
+    use crate::common::test_rules;
     use royale_arena_backend::admin::service::AdminService;
     use royale_arena_backend::auth::AuthService;
     use royale_arena_backend::auth::JwtManager;
     use royale_arena_backend::director::{
         BatchAddPlayersRequest, BatchDeletePlayersRequest, CreatePlayerRequest,
-        DirectorEditGameRequest, DirectorService,
+        DirectorEditGameRequest, DirectorError, DirectorService, ResetPlayerPasswordRequest,
     };
     use royale_arena_backend::game::GameLogService;
     use royale_arena_backend::game::GameService;
+    use royale_arena_backend::game::credentials::GameCredentials;
     use royale_arena_backend::game::global_game_state_manager::GlobalGameStateManager;
-    use royale_arena_backend::game::models::GameAuthenticationRole;
+    use royale_arena_backend::game::models::{GameAuthenticationRole, GameSessionRole};
     use royale_arena_backend::routes::AppState;
     use royale_arena_backend::rule_template::service::RuleTemplateService;
+    use royale_arena_backend::websocket::game_phase_scheduler::GamePhaseScheduler;
     use royale_arena_backend::websocket::global_connection_manager::GlobalConnectionManager;
     use sqlx::MySqlPool;
     use uuid::Uuid;
@@ -28,10 +33,10 @@ mod director_integration_tests {
             .execute(&pool)
             .await?;
 
-        let director_service = DirectorService::new(pool.clone());
+        let director_service = DirectorService::new(pool.clone(), 4);
 
         // 创建最小的AppState用于测试
-        let game_service = GameService::new(pool.clone());
+        let game_service = GameService::new(pool.clone(), 4);
         let app_state = AppState {
             auth_service: AuthService::new(pool.clone(), JwtManager::new("test_secret_key", 24)),
             admin_service: AdminService::new(pool.clone(), 10),
@@ -41,57 +46,53 @@ mod director_integration_tests {
             game_state_manager: GlobalGameStateManager::new(pool.clone()),
             rule_template_service: RuleTemplateService::new(pool.clone()),
             global_connection_manager: GlobalConnectionManager::new(),
+            game_phase_scheduler: GamePhaseScheduler::new(),
         };
 
         // 创建测试数据：游戏
         let game_id = Uuid::new_v4().to_string();
         let director_password = "test123";
         sqlx::query(
-            "INSERT INTO games (id, name, description, director_password, max_players, status, rules_config) 
-             VALUES (?, ?, ?, ?, ?, ?, ?)"
+            "INSERT INTO games (id, name, description, max_players, status, rules_config) 
+             VALUES (?, ?, ?, ?, ?, ?, ?)",
         )
         .bind(&game_id)
         .bind("测试游戏")
         .bind("用于导演接口集成测试")
-        .bind(director_password)
+        .bind(GameCredentials::hash_password(director_password, 4)?)
         .bind(100)
         .bind("waiting")
         .bind(r#"{"test": true}"#)
         .execute(&pool)
         .await?;
 
-        // 测试1.1: 验证导演密码 - 正确密码
-        let result = director_service
-            .verify_director_password(&game_id, director_password)
-            .await;
-        assert!(result.is_ok(), "正确的导演密码验证应该成功");
-
-        // 测试1.2: 验证导演密码 - 错误密码
-        let result = director_service
-            .verify_director_password(&game_id, "wrong_password")
-            .await;
-        assert!(result.is_err(), "错误的导演密码验证应该失败");
+        // 测试1.1: 签发并校验导演的游戏会话令牌
+        let (director_token, expires_in) = app_state
+            .auth_service
+            .issue_game_token(&game_id, GameSessionRole::Director, &game_id)
+            .await?;
+        assert_eq!(expires_in, 120 * 60);
+        let claims = app_state
+            .auth_service
+            .validate_game_token(&director_token)
+            .await?;
+        assert_eq!(claims.role, GameSessionRole::Director);
+        assert_eq!(claims.game_id, game_id);
 
-        // 测试1.3: 验证导演密码 - 不存在的游戏
+        // 测试1.2: 不存在的游戏不能签发令牌
         let fake_game_id = Uuid::new_v4().to_string();
-        let result = director_service
-            .verify_director_password(&fake_game_id, director_password)
+        let result = app_state
+            .auth_service
+            .issue_game_token(&fake_game_id, GameSessionRole::Director, &fake_game_id)
             .await;
-        assert!(result.is_err(), "不存在的游戏验证应该失败");
-
-        // 测试2.1: 批量添加演员时的密码验证 - 错误的导演密码
-        let add_request_wrong_password = BatchAddPlayersRequest {
-            players: vec![CreatePlayerRequest {
-                player_name: "测试玩家".to_string(),
-                password: "abc123".to_string(),
-                team_id: Some(1),
-            }],
-        };
+        assert!(result.is_err(), "不存在的游戏不应签发令牌");
 
-        let result = director_service
-            .batch_add_players(&game_id, "wrong_password", add_request_wrong_password)
+        // 测试1.3: 伪造的令牌校验失败
+        let result = app_state
+            .auth_service
+            .validate_game_token("not-a-token")
             .await;
-        assert!(result.is_err(), "错误的导演密码应该导致添加操作失败");
+        assert!(result.is_err(), "无效的令牌校验应该失败");
 
         // 测试2.2: 批量添加演员 - 成功添加
         let add_request = BatchAddPlayersRequest {
@@ -110,7 +111,7 @@ mod director_integration_tests {
         };
 
         let result = director_service
-            .batch_add_players(&game_id, director_password, add_request)
+            .batch_add_players(&game_id, add_request)
             .await?;
 
         assert_eq!(result.success.len(), 2, "应该成功添加2个演员");
@@ -137,7 +138,7 @@ mod director_integration_tests {
         };
 
         let result = director_service
-            .batch_add_players(&game_id, director_password, duplicate_request)
+            .batch_add_players(&game_id, duplicate_request)
             .await?;
 
         assert_eq!(result.success.len(), 1, "应该成功添加1个演员");
@@ -162,7 +163,7 @@ mod director_integration_tests {
         };
 
         let result = director_service
-            .batch_add_players(&game_id, director_password, conflicting_password_request)
+            .batch_add_players(&game_id, conflicting_password_request)
             .await?;
 
         assert_eq!(result.success.len(), 0, "不应添加任何演员");
@@ -175,27 +176,28 @@ mod director_integration_tests {
             "失败原因应提示演员密码与导演密码不能相同"
         );
 
-        // 测试3.1: 获取演员列表时的密码验证 - 错误的导演密码
-        let result = director_service
-            .get_players(&game_id, "wrong_password")
-            .await;
-        assert!(result.is_err(), "错误的导演密码应该导致获取列表失败");
-
-        // 测试3.2: 获取演员列表 - 正确密码
-        let players = director_service
-            .get_players(&game_id, director_password)
-            .await?;
+        // 测试3.2: 获取演员列表
+        let players = director_service.get_players(&game_id).await?;
         assert_eq!(players.len(), 3, "应该有3个演员");
 
         // 测试3.3: 验证演员信息正确
         let player1 = players.iter().find(|p| p.name == "测试玩家1").unwrap();
-        assert_eq!(player1.password, "abc123");
+        // 演员密码以哈希存储，且不会出现在输出中
+        assert!(serde_json::to_value(player1)?.get("password").is_none());
+        let identified =
+            GameCredentials::find_actor(&pool, &game_id, "测试玩家1", "abc123").await?;
+        assert_eq!(identified.map(|actor| actor.id), Some(player1.id.clone()));
+        assert!(
+            GameCredentials::find_actor(&pool, &game_id, "测试玩家1", "abc124")
+                .await?
+                .is_none()
+        );
         assert_eq!(player1.team_id, 1);
         assert_eq!(player1.game_id, game_id);
 
         // 测试4.1: 游戏身份验证 - 导演密码正确（此时还没有演员，应该返回director）
         let auth_result = game_service
-            .authenticate_game(&game_id, director_password)
+            .authenticate_game(&game_id, None, director_password)
             .await?;
         assert_eq!(
             auth_result.role,
@@ -207,7 +209,7 @@ mod director_integration_tests {
 
         // 测试4.2: 游戏身份验证 - 导演密码错误
         let auth_result = game_service
-            .authenticate_game(&game_id, "wrong_password")
+            .authenticate_game(&game_id, None, "wrong_password")
             .await?;
         assert_eq!(
             auth_result.role,
@@ -217,7 +219,7 @@ mod director_integration_tests {
 
         // 测试4.3: 游戏身份验证 - 不存在的游戏
         let auth_result = game_service
-            .authenticate_game(&fake_game_id, director_password)
+            .authenticate_game(&fake_game_id, None, director_password)
             .await?;
         assert_eq!(
             auth_result.role,
@@ -226,7 +228,9 @@ mod director_integration_tests {
         );
 
         // 测试4.4: 游戏身份验证 - 演员密码正确（使用刚刚添加的演员密码）
-        let auth_result = game_service.authenticate_game(&game_id, "abc123").await?;
+        let auth_result = game_service
+            .authenticate_game(&game_id, Some("测试玩家1"), "abc123")
+            .await?;
         assert_eq!(
             auth_result.role,
             GameAuthenticationRole::Actor,
@@ -245,23 +249,94 @@ mod director_integration_tests {
 
         // 测试4.5: 游戏身份验证 - 演员密码错误
         let auth_result = game_service
-            .authenticate_game(&game_id, "wrong_player_password")
+            .authenticate_game(&game_id, Some("测试玩家1"), "wrong_player_password")
             .await?;
         assert_eq!(
             auth_result.role,
             GameAuthenticationRole::Invalid,
             "使用错误的演员密码应该返回invalid"
         );
+        assert_eq!(
+            auth_result.message.as_deref(),
+            Some("演员名称或密码错误"),
+            "演员验证失败应返回失败原因"
+        );
 
-        // 测试5.1: 批量删除演员时的密码验证 - 错误的导演密码
-        let delete_request = BatchDeletePlayersRequest {
-            player_ids: vec![Uuid::new_v4().to_string()],
-        };
+        // 测试4.5.1: 演员密码只对应其名称，未提供名称时按导演密码验证
+        let auth_result = game_service
+            .authenticate_game(&game_id, Some("测试玩家3"), "abc123")
+            .await?;
+        assert_eq!(
+            auth_result.role,
+            GameAuthenticationRole::Invalid,
+            "使用其他演员的名称应该返回invalid"
+        );
+        let auth_result = game_service
+            .authenticate_game(&game_id, None, "abc123")
+            .await?;
+        assert_eq!(
+            auth_result.role,
+            GameAuthenticationRole::Invalid,
+            "未提供演员名称时演员密码应该返回invalid"
+        );
+        assert!(
+            auth_result
+                .message
+                .as_deref()
+                .is_some_and(|message| message.contains("actor_name")),
+            "未提供演员名称时应提示演员登录需要提供名称"
+        );
+
+        // 测试4.6: 演员会话令牌在导演重置密码后失效
+        let (actor_token, _) = app_state
+            .auth_service
+            .issue_game_token(&game_id, GameSessionRole::Actor, &player1.id)
+            .await?;
+        let claims = app_state
+            .auth_service
+            .validate_game_token(&actor_token)
+            .await?;
+        assert_eq!(claims.actor_id(), Some(player1.id.as_str()));
 
         let result = director_service
-            .batch_delete_players(&game_id, "wrong_password", delete_request)
+            .reset_player_password(
+                &game_id,
+                &player1.id,
+                ResetPlayerPasswordRequest {
+                    password: director_password.to_string(),
+                },
+            )
             .await;
-        assert!(result.is_err(), "错误的导演密码应该导致删除操作失败");
+        assert!(result.is_err(), "重置的演员密码不能与导演密码相同");
+
+        director_service
+            .reset_player_password(
+                &game_id,
+                &player1.id,
+                ResetPlayerPasswordRequest {
+                    password: "reset1".to_string(),
+                },
+            )
+            .await?;
+        let result = app_state
+            .auth_service
+            .validate_game_token(&actor_token)
+            .await;
+        assert!(result.is_err(), "重置密码后旧的演员令牌应该失效");
+        let auth_result = game_service
+            .authenticate_game(&game_id, Some("测试玩家1"), "reset1")
+            .await?;
+        assert_eq!(auth_result.actor_id.as_deref(), Some(player1.id.as_str()));
+        let auth_result = game_service
+            .authenticate_game(&game_id, Some("测试玩家1"), "abc123")
+            .await?;
+        assert_eq!(auth_result.role, GameAuthenticationRole::Invalid);
+
+        // 导演令牌不受演员重置影响
+        app_state
+            .auth_service
+            .validate_game_token(&director_token)
+            .await?;
 
         // 测试5.2: 批量删除演员 - 成功删除
         let player_ids: Vec<String> = players.iter().take(2).map(|p| p.id.clone()).collect();
@@ -270,16 +345,14 @@ mod director_integration_tests {
         };
 
         let result = director_service
-            .batch_delete_players(&game_id, director_password, delete_request)
+            .batch_delete_players(&game_id, delete_request)
             .await?;
 
         assert_eq!(result.success.len(), 2, "应该成功删除2个演员");
         assert_eq!(result.failed.len(), 0, "不应该有失败的操作");
 
         // 测试5.3: 验证删除后的演员列表
-        let remaining_players = director_service
-            .get_players(&game_id, director_password)
-            .await?;
+        let remaining_players = director_service.get_players(&game_id).await?;
         assert_eq!(remaining_players.len(), 1, "应该剩余1个演员");
 
         // 测试5.4: 批量删除演员 - 不存在的演员ID
@@ -289,7 +362,7 @@ mod director_integration_tests {
         };
 
         let result = director_service
-            .batch_delete_players(&game_id, director_password, delete_request)
+            .batch_delete_players(&game_id, delete_request)
             .await?;
 
         assert_eq!(result.success.len(), 0, "不应该成功删除任何演员");
@@ -309,7 +382,7 @@ mod director_integration_tests {
         };
 
         let result = director_service
-            .batch_delete_players(&game_id, director_password, delete_request)
+            .batch_delete_players(&game_id, delete_request)
             .await;
 
         assert!(result.is_err(), "游戏运行中不应该允许删除演员");
@@ -347,7 +420,7 @@ mod director_integration_tests {
         };
 
         let result = director_service
-            .batch_add_players(&game_id, director_password, invalid_request)
+            .batch_add_players(&game_id, invalid_request)
             .await?;
 
         assert_eq!(result.success.len(), 0, "所有添加操作都应该失败");
@@ -370,18 +443,6 @@ mod director_integration_tests {
 
         // ========== 测试8: 导演编辑游戏功能 ==========
 
-        // 测试8.1: 使用错误的导演密码编辑游戏
-        let edit_request = DirectorEditGameRequest {
-            name: Some("新游戏名称".to_string()),
-            description: None,
-            max_players: None,
-            rules_config: None,
-        };
-        let result = director_service
-            .edit_game(&app_state, &game_id, "wrong_password", edit_request)
-            .await;
-        assert!(result.is_err(), "错误的导演密码应该导致编辑失败");
-
         // 测试8.2: 编辑游戏名称（有效输入）
         let edit_request = DirectorEditGameRequest {
             name: Some("新游戏名称".to_string()),
@@ -390,7 +451,7 @@ mod director_integration_tests {
             rules_config: None,
         };
         let result = director_service
-            .edit_game(&app_state, &game_id, director_password, edit_request)
+            .edit_game(&app_state, &game_id, edit_request)
             .await?;
         assert_eq!(result.name, "新游戏名称");
         assert_eq!(result.description, Some("用于导演接口集成测试".to_string()));
@@ -404,7 +465,7 @@ mod director_integration_tests {
             rules_config: None,
         };
         let result = director_service
-            .edit_game(&app_state, &game_id, director_password, edit_request)
+            .edit_game(&app_state, &game_id, edit_request)
             .await?;
         assert_eq!(result.description, Some("更新后的游戏描述".to_string()));
 
@@ -416,7 +477,7 @@ mod director_integration_tests {
             rules_config: None,
         };
         let result = director_service
-            .edit_game(&app_state, &game_id, director_password, edit_request)
+            .edit_game(&app_state, &game_id, edit_request)
             .await?;
         assert_eq!(result.max_players, 50);
 
@@ -428,14 +489,14 @@ mod director_integration_tests {
             rules_config: None,
         };
         let result = director_service
-            .edit_game(&app_state, &game_id, director_password, edit_request)
+            .edit_game(&app_state, &game_id, edit_request)
             .await?;
         assert_eq!(result.name, "最终游戏名称");
         assert_eq!(result.description, Some("最终描述".to_string()));
         assert_eq!(result.max_players, 200);
 
         // 测试8.6: 编辑规则配置
-        let new_rules = serde_json::json!({
+        let new_rules = test_rules(serde_json::json!({
             "map": {
                 "places": ["地点A", "地点B"],
                 "safe_places": ["地点A"]
@@ -444,7 +505,7 @@ mod director_integration_tests {
                 "max_life": 100,
                 "max_strength": 10
             }
-        });
+        }));
         let edit_request = DirectorEditGameRequest {
             name: None,
             description: None,
@@ -452,7 +513,7 @@ mod director_integration_tests {
             rules_config: Some(new_rules.clone()),
         };
         let result = director_service
-            .edit_game(&app_state, &game_id, director_password, edit_request)
+            .edit_game(&app_state, &game_id, edit_request)
             .await?;
         assert_eq!(result.rules_config, new_rules);
 
@@ -464,7 +525,7 @@ mod director_integration_tests {
             rules_config: None,
         };
         let result = director_service
-            .edit_game(&app_state, &game_id, director_password, edit_request)
+            .edit_game(&app_state, &game_id, edit_request)
             .await;
         assert!(result.is_err(), "空名称应该导致验证错误");
 
@@ -476,7 +537,7 @@ mod director_integration_tests {
             rules_config: None,
         };
         let result = director_service
-            .edit_game(&app_state, &game_id, director_password, edit_request)
+            .edit_game(&app_state, &game_id, edit_request)
             .await;
         assert!(result.is_err(), "超长名称应该导致验证错误");
 
@@ -488,7 +549,7 @@ mod director_integration_tests {
             rules_config: None,
         };
         let result = director_service
-            .edit_game(&app_state, &game_id, director_password, edit_request)
+            .edit_game(&app_state, &game_id, edit_request)
             .await;
         assert!(result.is_err(), "最大玩家数0应该导致验证错误");
 
@@ -499,7 +560,7 @@ mod director_integration_tests {
             rules_config: None,
         };
         let result = director_service
-            .edit_game(&app_state, &game_id, director_password, edit_request)
+            .edit_game(&app_state, &game_id, edit_request)
             .await;
         assert!(result.is_err(), "最大玩家数1001应该导致验证错误");
 
@@ -512,7 +573,7 @@ mod director_integration_tests {
             rules_config: None,
         };
         let result = director_service
-            .edit_game(&app_state, &fake_game_id, director_password, edit_request)
+            .edit_game(&app_state, &fake_game_id, edit_request)
             .await;
         assert!(result.is_err(), "编辑不存在的游戏应该失败");
 
@@ -524,10 +585,25 @@ mod director_integration_tests {
             rules_config: None,
         };
         let result = director_service
-            .edit_game(&app_state, &game_id, director_password, edit_request)
+            .edit_game(&app_state, &game_id, edit_request)
             .await;
         assert!(result.is_err(), "空请求应该导致验证错误");
 
+        // 测试9.1: 未运行的游戏不能回退
+        let result = director_service
+            .rewind_game(&app_state, &game_id, "2025-10-01T12-00-00.000Z.json")
+            .await;
+        assert!(
+            matches!(result, Err(DirectorError::InvalidGameStateTransition)),
+            "等待中的游戏回退应该失败"
+        );
+
+        // 测试9.2: 回退不存在的游戏
+        let result = director_service
+            .rewind_game(&app_state, &fake_game_id, "2025-10-01T12-00-00.000Z.json")
+            .await;
+        assert!(result.is_err(), "回退不存在的游戏应该失败");
+
         Ok(())
     }
 }
@@ -0,0 +1,327 @@
+//! 导演行动撤销集成测试
+//! 测试导演行动的撤销记录、按步数撤销、撤销结果的广播对象、与之后行动冲突时拒绝撤销以及撤销栈的深度限制
+
+mod common;
+
+use chrono::{Duration, Utc};
+use common::{add_test_player, common_rarity_levels, test_rules};
+use royale_arena_backend::game::models::MessageType;
+use royale_arena_backend::websocket::actions::director_action_scheduler::{
+    DirectorActionParams, DirectorActionScheduler,
+};
+use royale_arena_backend::websocket::actions::director_undo_action::{
+    DIRECTOR_UNDO_DEPTH, UndoTarget,
+};
+use royale_arena_backend::websocket::actions::player_action_scheduler::{
+    ActionParams, PlayerActionScheduler,
+};
+use royale_arena_backend::websocket::models::{ActionResults, GameState};
+use serde_json::{Value as JsonValue, json};
+
+/// 测试规则配置
+fn get_test_rules() -> JsonValue {
+    test_rules(json!({
+      "map": {"places": ["码头", "工厂"]},
+      "death_item_disposition": "drop_to_ground",
+      "items_config": {
+        "rarity_levels": common_rarity_levels(),
+        "items": {
+          "weapons": [
+            {
+              "internal_name": "stick",
+              "display_names": ["木棍", "铁棍"],
+              "rarity": "common",
+              "properties": {
+                "damage": 20,
+                "votes": 0
+              }
+            }
+          ]
+        }
+      }
+    }))
+}
+
+/// 创建包含两名位于码头的玩家的游戏状态
+fn create_game_state() -> GameState {
+    let mut game_state = common::create_game_state("test_director_undo", get_test_rules());
+    add_test_player(&mut game_state, "p1", "玩家1", "码头");
+    add_test_player(&mut game_state, "p2", "玩家2", "码头");
+    game_state
+}
+
+fn director(game_state: &mut GameState, action: &str, params: JsonValue) -> ActionResults {
+    let params = DirectorActionParams::from_json(&params).unwrap();
+    DirectorActionScheduler::dispatch(game_state, action, params)
+        .unwrap_or_else(|e| panic!("导演行动 {} 失败: {}", action, e))
+}
+
+fn undo(game_state: &mut GameState, steps: usize) -> ActionResults {
+    director(game_state, "undo", json!({ "steps": steps }))
+}
+
+fn place_players(game_state: &GameState, place_name: &str) -> Vec<String> {
+    game_state.places[place_name].players.clone()
+}
+
+fn inventory_names(game_state: &GameState, player_id: &str) -> Vec<String> {
+    game_state.players[player_id]
+        .inventory
+        .iter()
+        .map(|item| item.name.clone())
+        .collect()
+}
+
+/// 测试：撤销设置生命值与移动玩家，结果广播给受影响的玩家
+#[test]
+fn test_undo_life_and_move() {
+    let mut game_state = create_game_state();
+    director(
+        &mut game_state,
+        "life",
+        json!({"player_id": "p1", "life": 40}),
+    );
+    director(
+        &mut game_state,
+        "move_player",
+        json!({"player_id": "p1", "target_place": "工厂"}),
+    );
+    assert_eq!(game_state.director_undo.len(), 2);
+    assert_eq!(place_players(&game_state, "工厂"), vec!["p1".to_string()]);
+
+    // 先撤销最近的移动
+    let results = undo(&mut game_state, 1);
+    assert_eq!(results.results.len(), 1);
+    let result = &results.results[0];
+    assert_eq!(result.message_type, MessageType::SystemNotice);
+    assert_eq!(result.broadcast_players, vec!["p1".to_string()]);
+    assert!(result.broadcast_to_director);
+    assert!(!result.broadcast_to_all);
+    assert_eq!(result.data["action"], "move_player");
+    assert_eq!(result.data["remaining"], 1);
+    assert_eq!(game_state.players["p1"].location, "码头");
+    assert_eq!(
+        place_players(&game_state, "码头"),
+        vec!["p1".to_string(), "p2".to_string()]
+    );
+    assert!(place_players(&game_state, "工厂").is_empty());
+    assert_eq!(game_state.players["p1"].life, 40);
+
+    let results = undo(&mut game_state, 1);
+    assert_eq!(results.results[0].data["action"], "life");
+    assert_eq!(game_state.players["p1"].life, 100);
+    assert!(game_state.director_undo.is_empty());
+}
+
+/// 测试：一次撤销多步，撤销移除物品后物品回到原来的位置
+#[test]
+fn test_undo_multiple_item_actions() {
+    let mut game_state = create_game_state();
+    director(
+        &mut game_state,
+        "add_player_item",
+        json!({"player_id": "p2", "item_name": "木棍"}),
+    );
+    director(
+        &mut game_state,
+        "remove_player_item",
+        json!({"player_id": "p2", "item_name": "木棍"}),
+    );
+    assert!(inventory_names(&game_state, "p2").is_empty());
+
+    let results = undo(&mut game_state, 1);
+    assert_eq!(results.results[0].broadcast_players, vec!["p2".to_string()]);
+    assert_eq!(inventory_names(&game_state, "p2"), vec!["木棍".to_string()]);
+
+    director(&mut game_state, "weather", json!({"weather": 0.5}));
+    let results = undo(&mut game_state, 5);
+    assert_eq!(results.results.len(), 2);
+    assert_eq!(results.results[0].data["action"], "weather");
+    assert_eq!(results.results[1].data["action"], "add_player_item");
+    assert_eq!(game_state.weather, 1.0);
+    assert!(inventory_names(&game_state, "p2").is_empty());
+}
+
+/// 测试：撤销清空全场物品
+#[test]
+fn test_undo_clear_all_items() {
+    let mut game_state = create_game_state();
+    director(
+        &mut game_state,
+        "batch_airdrop",
+        json!({"airdrops": [
+            {"item_name": "木棍", "place_name": "码头"},
+            {"item_name": "铁棍", "place_name": "工厂"}
+        ]}),
+    );
+    director(
+        &mut game_state,
+        "batch_item_deletion",
+        json!({"clear_all": true}),
+    );
+    assert!(
+        game_state
+            .places
+            .values()
+            .all(|place| place.items.is_empty())
+    );
+
+    let results = undo(&mut game_state, 1);
+    let result = &results.results[0];
+    assert_eq!(result.data["action"], "batch_item_deletion");
+    // 玩家看不到地点中的物品，只通知导演
+    assert!(result.broadcast_players.is_empty());
+    assert_eq!(game_state.places["码头"].items[0].name, "木棍");
+    assert_eq!(game_state.places["工厂"].items[0].name, "铁棍");
+}
+
+/// 测试：撤销摧毁地点会恢复地点与被缩圈击杀的玩家，并通知全部玩家与观众
+#[test]
+fn test_undo_modify_place_revives_players() {
+    let mut game_state = create_game_state();
+    director(
+        &mut game_state,
+        "add_player_item",
+        json!({"player_id": "p1", "item_name": "木棍"}),
+    );
+    director(
+        &mut game_state,
+        "modify_place",
+        json!({"place_name": "码头", "is_destroyed": true}),
+    );
+    assert!(!game_state.players["p1"].is_alive);
+    assert_eq!(game_state.places["码头"].items.len(), 1);
+
+    let results = undo(&mut game_state, 1);
+    let result = &results.results[0];
+    assert_eq!(result.data["action"], "modify_place");
+    assert!(result.broadcast_to_all);
+    assert_eq!(
+        result.broadcast_players,
+        vec!["p1".to_string(), "p2".to_string()]
+    );
+    assert_eq!(
+        result.spectator_event,
+        Some(json!({"kind": "director_undo"}))
+    );
+
+    assert!(!game_state.places["码头"].is_destroyed);
+    assert!(game_state.places["码头"].items.is_empty());
+    for player_id in ["p1", "p2"] {
+        let player = &game_state.players[player_id];
+        assert!(player.is_alive);
+        assert_eq!(player.life, 100);
+        assert_eq!(player.location, "码头");
+    }
+    assert_eq!(inventory_names(&game_state, "p1"), vec!["木棍".to_string()]);
+    assert_eq!(
+        place_players(&game_state, "码头"),
+        vec!["p1".to_string(), "p2".to_string()]
+    );
+}
+
+/// 测试：之后被其他行动修改的字段不能撤销，未被修改的字段可以撤销
+#[test]
+fn test_undo_rejected_after_conflicting_change() {
+    let mut game_state = create_game_state();
+    game_state.night_start_time = Some(Utc::now() - Duration::hours(1));
+    game_state.night_end_time = Some(Utc::now() + Duration::hours(1));
+    director(
+        &mut game_state,
+        "move_player",
+        json!({"player_id": "p1", "target_place": "工厂"}),
+    );
+    director(
+        &mut game_state,
+        "life",
+        json!({"player_id": "p1", "life": 40}),
+    );
+
+    // 玩家自行移动回码头，导演移动的结果已被覆盖
+    let params = ActionParams::from_json(&json!({"target_place": "码头"})).unwrap();
+    PlayerActionScheduler::dispatch(&mut game_state, "p1", "move", params).unwrap();
+    assert_eq!(game_state.players["p1"].location, "码头");
+
+    let results = undo(&mut game_state, 2);
+    assert_eq!(results.results.len(), 2);
+
+    // 只修改了生命值的行动在移动之后仍可撤销
+    assert_eq!(results.results[0].message_type, MessageType::SystemNotice);
+    assert_eq!(results.results[0].data["action"], "life");
+    assert_eq!(game_state.players["p1"].life, 100);
+
+    let result = &results.results[1];
+    assert_eq!(result.message_type, MessageType::Info);
+    assert_eq!(result.data["action"], "move_player");
+    assert_eq!(result.data["field"], "location");
+    assert_eq!(
+        result.data["target"],
+        json!(UndoTarget::Player {
+            player_id: "p1".to_string()
+        })
+    );
+    assert_eq!(game_state.players["p1"].location, "码头");
+    assert!(place_players(&game_state, "工厂").is_empty());
+    assert_eq!(game_state.director_undo.len(), 1);
+}
+
+/// 测试：消息与结算不可撤销，未修改状态的行动不记录，撤销栈深度有限
+#[test]
+fn test_undo_history_is_bounded() {
+    let mut game_state = create_game_state();
+    director(&mut game_state, "broadcast", json!({"message": "注意"}));
+    director(&mut game_state, "vote_settlement", json!({}));
+    director(
+        &mut game_state,
+        "life",
+        json!({"player_id": "p1", "life": 100}),
+    );
+    assert!(game_state.director_undo.is_empty());
+
+    let results = undo(&mut game_state, 1);
+    assert_eq!(results.results[0].message_type, MessageType::Info);
+    assert_eq!(results.results[0].data["undone"], 0);
+
+    for index in 0..DIRECTOR_UNDO_DEPTH + 5 {
+        director(
+            &mut game_state,
+            "weather",
+            json!({"weather": index as f64 / 100.0}),
+        );
+    }
+    assert_eq!(game_state.director_undo.len(), DIRECTOR_UNDO_DEPTH);
+
+    let results = undo(&mut game_state, DIRECTOR_UNDO_DEPTH);
+    assert_eq!(results.results.len(), DIRECTOR_UNDO_DEPTH);
+    // 超出深度的最早5次行动无法撤销
+    assert_eq!(game_state.weather, 0.04);
+
+    for steps in [0, DIRECTOR_UNDO_DEPTH + 1] {
+        let params = DirectorActionParams::from_json(&json!({ "steps": steps })).unwrap();
+        assert!(DirectorActionScheduler::dispatch(&mut game_state, "undo", params).is_err());
+    }
+}
+
+/// 测试：撤销记录随存档保存
+#[test]
+fn test_undo_history_survives_save() {
+    let mut game_state = create_game_state();
+    director(
+        &mut game_state,
+        "move_player",
+        json!({"player_id": "p2", "target_place": "工厂"}),
+    );
+
+    let saved = serde_json::to_string(&game_state).unwrap();
+    let mut restored: GameState = serde_json::from_str(&saved).unwrap();
+    assert_eq!(restored.director_undo.len(), 1);
+
+    undo(&mut restored, 1);
+    assert_eq!(restored.players["p2"].location, "码头");
+
+    // 旧存档没有撤销记录
+    let mut legacy: JsonValue = serde_json::from_str(&saved).unwrap();
+    legacy.as_object_mut().unwrap().remove("director_undo");
+    let legacy: GameState = serde_json::from_value(legacy).unwrap();
+    assert!(legacy.director_undo.is_empty());
+}
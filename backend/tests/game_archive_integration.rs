@@ -0,0 +1,263 @@
+//! 游戏归档集成测试
+//! 测试归档的自描述格式与往返序列化、游戏与演员ID校验、名单与记录引用关系校验、存档文件名校验、存档与行动日志文件的原样读写以及导入失败时的文件清理
+
+use chrono::{TimeZone, Utc};
+use royale_arena_backend::game::action_journal::{ActionJournal, JournalActor, JournalEntry};
+use royale_arena_backend::game::archive::{
+    ARCHIVE_FORMAT, ARCHIVE_FORMAT_VERSION, ArchivedGame, ArchivedPlayer, ArchivedSave,
+    GameArchive, is_archivable_status, is_valid_save_file_name,
+};
+use royale_arena_backend::game::global_game_state_manager::GlobalGameStateManager;
+use royale_arena_backend::game::models::{
+    DamageRecord, GameStatus, KillRecord, MessageRecord, MessageType, SaveKind,
+};
+use serde_json::json;
+
+const GAME_ID: &str = "3f0c6a52-5d1e-4b7a-9c1f-2a8e4d6b7c90";
+const P1: &str = "8d2f1b3e-0a4c-4e6f-8b1d-5c7a9e3f2d10";
+const P2: &str = "c4e8a2f6-1b3d-4f5a-9e7c-0d2b4a6c8e20";
+const P3: &str = "e1a3c5b7-9d2f-4a6c-8e0b-3f5d7b9a1c30";
+
+fn player(id: &str, name: &str) -> ArchivedPlayer {
+    ArchivedPlayer {
+        id: id.to_string(),
+        name: name.to_string(),
+        team_id: 0,
+    }
+}
+
+/// 包含两名演员、日志、击杀、伤害记录与存档的归档
+fn create_archive() -> GameArchive {
+    let time = Utc.with_ymd_and_hms(2025, 10, 1, 12, 0, 0).unwrap();
+    let mut archive = GameArchive::new(
+        ArchivedGame {
+            id: GAME_ID.to_string(),
+            name: "归档测试".to_string(),
+            description: None,
+            max_players: 10,
+            status: GameStatus::Ended,
+            rules_config: json!({"map": {"places": ["码头"], "safe_places": []}}),
+            created_at: time,
+            updated_at: time,
+        },
+        vec![player(P1, "甲"), player(P2, "乙")],
+    );
+    archive.logs.push(MessageRecord {
+        id: "log-1".to_string(),
+        game_id: GAME_ID.to_string(),
+        message_type: MessageType::UserDirected,
+        message: "甲 攻击了 乙".to_string(),
+        player_id: Some(P1.to_string()),
+        timestamp: time,
+        visible_to_all_players: false,
+        visible_to_director: true,
+    });
+    archive.kill_records.push(KillRecord {
+        id: "kill-1".to_string(),
+        game_id: GAME_ID.to_string(),
+        killer_id: Some(P1.to_string()),
+        victim_id: P2.to_string(),
+        kill_time: time,
+        cause: "攻击".to_string(),
+        weapon: Some("木棍".to_string()),
+        location: Some("码头".to_string()),
+    });
+    archive.damage_records.push(DamageRecord {
+        id: "damage-1".to_string(),
+        game_id: GAME_ID.to_string(),
+        attacker_id: Some(P1.to_string()),
+        victim_id: P2.to_string(),
+        damage: 100,
+        cause: "攻击".to_string(),
+        weapon: Some("木棍".to_string()),
+        damage_time: time,
+    });
+    archive.saves.push(ArchivedSave {
+        file_name: "checkpoint-2025-10-01T12-00-00.000Z.json".to_string(),
+        kind: SaveKind::Checkpoint,
+        created_at: Some(time),
+        state: json!({"game_id": GAME_ID, "players": {}}),
+    });
+    archive
+}
+
+#[test]
+fn test_archive_round_trip() {
+    let archive = create_archive();
+    assert_eq!(archive.validate(), Ok(()));
+
+    let value = serde_json::to_value(&archive).unwrap();
+    assert_eq!(value["format"], ARCHIVE_FORMAT);
+    assert_eq!(value["format_version"], ARCHIVE_FORMAT_VERSION);
+    assert_eq!(value["game"]["status"], "ended");
+    assert_eq!(value["logs"][0]["type"], "UserDirected");
+    // 名单不包含密码
+    assert_eq!(
+        value["players"][0],
+        json!({"id": P1, "name": "甲", "team_id": 0})
+    );
+
+    let parsed: GameArchive = serde_json::from_value(value).unwrap();
+    assert_eq!(parsed.validate(), Ok(()));
+    assert_eq!(parsed.kill_records[0].weapon.as_deref(), Some("木棍"));
+    assert_eq!(parsed.saves[0].state, archive.saves[0].state);
+}
+
+#[test]
+fn test_archive_format_is_checked() {
+    let mut archive = create_archive();
+    archive.format = "royale-arena/rule-templates".to_string();
+    assert!(archive.validate().is_err());
+
+    let mut archive = create_archive();
+    archive.format_version = ARCHIVE_FORMAT_VERSION + 1;
+    assert!(archive.validate().is_err());
+
+    let mut archive = create_archive();
+    archive.game.name = " ".to_string();
+    assert!(archive.validate().is_err());
+}
+
+#[test]
+fn test_archive_ids_must_be_uuids() {
+    // 游戏ID用作存档目录名，不能包含路径
+    for game_id in ["../../etc", "archive-game", ""] {
+        let mut archive = create_archive();
+        archive.game.id = game_id.to_string();
+        assert!(archive.validate().is_err(), "{}", game_id);
+    }
+
+    let mut archive = create_archive();
+    archive.players[0].id = "p1".to_string();
+    assert!(archive.validate().unwrap_err().contains("UUID"));
+}
+
+#[test]
+fn test_archive_files_are_removed_on_failure() {
+    let mut archive = create_archive();
+    archive.game.id = uuid::Uuid::new_v4().to_string();
+    archive.journal.push(JournalEntry {
+        sequence: 1,
+        timestamp: Utc.with_ymd_and_hms(2025, 10, 1, 12, 0, 0).unwrap(),
+        actor: JournalActor::Director,
+        action: "weather".to_string(),
+        params: json!({"weather": 0.5}),
+        rng_seed: None,
+    });
+    let dir = format!("game_states/{}", archive.game.id);
+
+    archive.write_files().unwrap();
+    assert_eq!(
+        ActionJournal::read_entries(&archive.game.id).unwrap().len(),
+        1
+    );
+    assert!(
+        GlobalGameStateManager::read_save_file_json(&archive.game.id, &archive.saves[0].file_name)
+            .is_ok()
+    );
+
+    archive.remove_files();
+    assert!(!std::path::Path::new(&dir).exists());
+}
+
+#[test]
+fn test_archive_references_are_checked() {
+    // 击杀记录引用不在名单中的演员
+    let mut archive = create_archive();
+    archive.kill_records[0].victim_id = P3.to_string();
+    assert!(archive.validate().unwrap_err().contains(P3));
+
+    // 伤害记录属于其他游戏
+    let mut archive = create_archive();
+    archive.damage_records[0].game_id = "other-game".to_string();
+    assert!(archive.validate().is_err());
+
+    // 重复的演员与日志
+    let mut archive = create_archive();
+    archive.players.push(player(P1, "丙"));
+    assert!(archive.validate().is_err());
+
+    let mut archive = create_archive();
+    let duplicate = archive.logs[0].clone();
+    archive.logs.push(duplicate);
+    assert!(archive.validate().is_err());
+
+    // 系统日志可以没有相关玩家
+    let mut archive = create_archive();
+    archive.logs[0].player_id = None;
+    assert_eq!(archive.validate(), Ok(()));
+}
+
+#[test]
+fn test_save_file_names_are_checked() {
+    assert!(is_valid_save_file_name("2025-10-01T12-00-00.000Z.json"));
+    assert!(is_valid_save_file_name(
+        "autosave-2025-10-01T12-00-00.000Z.json"
+    ));
+    for file_name in [
+        ".json",
+        "journal.jsonl",
+        "../other-game/save.json",
+        "nested/save.json",
+        "..\\save.json",
+        ".hidden.json",
+    ] {
+        assert!(!is_valid_save_file_name(file_name), "{}", file_name);
+    }
+
+    let mut archive = create_archive();
+    archive.saves[0].file_name = "../../etc/passwd.json".to_string();
+    assert!(archive.validate().is_err());
+
+    let mut archive = create_archive();
+    let duplicate = archive.saves[0].clone();
+    archive.saves.push(duplicate);
+    assert!(archive.validate().is_err());
+}
+
+#[test]
+fn test_only_finished_games_are_archivable() {
+    assert!(is_archivable_status(&GameStatus::Ended));
+    assert!(is_archivable_status(&GameStatus::Hidden));
+    assert!(is_archivable_status(&GameStatus::Deleted));
+    assert!(!is_archivable_status(&GameStatus::Waiting));
+    assert!(!is_archivable_status(&GameStatus::Running));
+    assert!(!is_archivable_status(&GameStatus::Paused));
+}
+
+#[test]
+fn test_save_files_and_journal_are_written_verbatim() {
+    let game_id = "archive-files-test";
+    let _ = std::fs::remove_dir_all(format!("game_states/{}", game_id));
+
+    // 存档内容原样写入与读取，不按游戏状态结构解析
+    let state = json!({"future_field": [1, 2, 3], "players": {"p1": {"life": 0}}});
+    GlobalGameStateManager::write_save_file_json(game_id, "2025-10-01T12-00-00.000Z.json", &state)
+        .unwrap();
+    assert_eq!(
+        GlobalGameStateManager::read_save_file_json(game_id, "2025-10-01T12-00-00.000Z.json")
+            .unwrap(),
+        state
+    );
+
+    let entries: Vec<JournalEntry> = (1..=3)
+        .map(|sequence| JournalEntry {
+            sequence,
+            timestamp: Utc.with_ymd_and_hms(2025, 10, 1, 12, 0, 0).unwrap(),
+            actor: JournalActor::Director,
+            action: "weather".to_string(),
+            params: json!({"weather": sequence}),
+            rng_seed: None,
+        })
+        .collect();
+    ActionJournal::write_entries(game_id, &entries).unwrap();
+    let read = ActionJournal::read_entries(game_id).unwrap();
+    assert_eq!(read.len(), 3);
+    assert_eq!(read[2].params, json!({"weather": 3}));
+
+    // 截断仍保留序号不大于指定值的条目
+    ActionJournal::truncate_after(game_id, 2).unwrap();
+    assert_eq!(ActionJournal::read_entries(game_id).unwrap().len(), 2);
+
+    std::fs::remove_dir_all(format!("game_states/{}", game_id)).unwrap();
+}
@@ -0,0 +1,196 @@
+//! 昼夜自动调度集成测试
+//! 测试 game_flow 配置解析、夜晚时间窗口计算以及自动调度的阶段处理
+
+mod common;
+
+use chrono::{Duration, Local, NaiveTime, TimeZone, Utc};
+use common::{add_test_player, test_rules};
+use royale_arena_backend::game::game_rule_engine::{GameFlowConfig, GameRuleEngine};
+use royale_arena_backend::game::models::MessageType;
+use royale_arena_backend::websocket::models::GameState;
+use serde_json::json;
+
+/// 测试规则配置（指定游戏流程配置）
+fn get_test_rules_with_game_flow(game_flow: serde_json::Value) -> serde_json::Value {
+    test_rules(json!({
+      "game_flow": game_flow,
+      "player": {"daily_strength_recovery": 30}
+    }))
+}
+
+fn scheduled_game_flow(auto_settlement: bool) -> serde_json::Value {
+    json!({
+      "day_duration": 3600,
+      "night_duration": 1800,
+      "night_start_time": "22:00",
+      "auto_settlement": auto_settlement
+    })
+}
+
+/// 测试：未配置 game_flow 或未设置夜晚开始时刻时不启用自动调度
+#[test]
+fn test_game_flow_optional() {
+    let mut rules_without_flow = get_test_rules_with_game_flow(json!({}));
+    rules_without_flow
+        .as_object_mut()
+        .unwrap()
+        .remove("game_flow");
+
+    let engine = GameRuleEngine::from_json(&rules_without_flow.to_string()).unwrap();
+    assert!(!engine.game_flow.is_scheduled());
+
+    let engine = GameRuleEngine::from_json(
+        &get_test_rules_with_game_flow(json!({
+          "day_duration": 300,
+          "night_duration": 900
+        }))
+        .to_string(),
+    )
+    .unwrap();
+    assert!(!engine.game_flow.is_scheduled());
+    assert!(
+        engine
+            .game_flow
+            .next_night_window(Utc::now(), None)
+            .is_none()
+    );
+
+    let engine = GameRuleEngine::from_json(
+        &get_test_rules_with_game_flow(scheduled_game_flow(true)).to_string(),
+    )
+    .unwrap();
+    assert!(engine.game_flow.is_scheduled());
+    assert!(engine.game_flow.auto_settlement);
+}
+
+/// 测试：首个夜晚按服务器本地开始时刻锚定，之后按昼夜时长顺延
+#[test]
+fn test_next_night_window() {
+    let game_flow: GameFlowConfig = serde_json::from_value(scheduled_game_flow(false)).unwrap();
+
+    // 当天 21:00（本地时间），下一个夜晚为当天 22:00 - 22:30
+    let today = Local::now().date_naive();
+    let now = Local
+        .from_local_datetime(&today.and_time(NaiveTime::from_hms_opt(21, 0, 0).unwrap()))
+        .earliest()
+        .unwrap()
+        .with_timezone(&Utc);
+    let (start, end) = game_flow.next_night_window(now, None).unwrap();
+    assert_eq!(start, now + Duration::hours(1));
+    assert_eq!(end, start + Duration::minutes(30));
+
+    // 夜晚进行中时返回当前夜晚
+    let (current_start, _) = game_flow
+        .next_night_window(now + Duration::minutes(70), None)
+        .unwrap();
+    assert_eq!(current_start, start);
+
+    // 已有上一个夜晚时，下一个夜晚在白天时长之后开始
+    let (next_start, next_end) = game_flow.next_night_window(end, Some(end)).unwrap();
+    assert_eq!(next_start, end + Duration::hours(1));
+    assert_eq!(next_end, next_start + Duration::minutes(30));
+
+    // 错过的夜晚会被跳过
+    let (skipped_start, _) = game_flow
+        .next_night_window(end + Duration::hours(2), Some(end))
+        .unwrap();
+    assert_eq!(skipped_start, end + Duration::minutes(150));
+}
+
+/// 测试：安排夜晚时写入夜晚时间，尚未结束的夜晚窗口保持不变
+#[test]
+fn test_schedule_next_night_keeps_current_window() {
+    let mut game_state = GameState::new(
+        "test_schedule".to_string(),
+        get_test_rules_with_game_flow(scheduled_game_flow(false)),
+    );
+
+    let now = Utc::now();
+    let results = game_state.schedule_next_night(now).unwrap();
+    assert_eq!(results.results.len(), 1);
+    assert!(results.results[0].broadcast_players.is_empty());
+    assert!(results.results[0].broadcast_to_director);
+
+    let night_start = game_state.night_start_time.unwrap();
+    let night_end = game_state.night_end_time.unwrap();
+    assert!(night_end > now);
+    assert_eq!(night_end - night_start, Duration::minutes(30));
+
+    let results = game_state.schedule_next_night(now).unwrap();
+    assert!(results.results.is_empty());
+    assert_eq!(game_state.night_start_time, Some(night_start));
+
+    // 夜晚结束后按白天时长安排下一个夜晚
+    game_state.schedule_next_night(night_end).unwrap();
+    assert_eq!(
+        game_state.night_start_time,
+        Some(night_end + Duration::hours(1))
+    );
+}
+
+/// 测试：夜晚结束时按配置自动执行夜晚结算并公告白天开始
+#[test]
+fn test_scheduled_night_end_auto_settlement() {
+    let mut game_state = GameState::new(
+        "test_auto_settlement".to_string(),
+        get_test_rules_with_game_flow(scheduled_game_flow(true)),
+    );
+    add_test_player(&mut game_state, "p1", "玩家p1", "位置1");
+    game_state.players.get_mut("p1").unwrap().strength = 50;
+
+    let results = game_state.handle_scheduled_night_start();
+    assert!(results.results[0].broadcast_to_all);
+    assert_eq!(results.results[0].data["phase"], json!("night"));
+
+    let results = game_state.handle_scheduled_night_end().unwrap();
+    let last = results.results.last().unwrap();
+    assert!(last.broadcast_to_all);
+    assert_eq!(last.message_type, MessageType::SystemNotice);
+    assert_eq!(last.data["phase"], json!("day"));
+    assert_eq!(game_state.players.get("p1").unwrap().strength, 80);
+}
+
+/// 测试：导演本夜已手动结算时夜晚结束跳过自动结算，下一个夜晚开始后恢复自动结算
+#[test]
+fn test_scheduled_night_end_after_manual_settlement() {
+    let mut game_state = GameState::new(
+        "test_manual_then_scheduled".to_string(),
+        get_test_rules_with_game_flow(scheduled_game_flow(true)),
+    );
+    add_test_player(&mut game_state, "p1", "玩家p1", "位置1");
+    game_state.players.get_mut("p1").unwrap().strength = 20;
+
+    game_state.handle_scheduled_night_start();
+    game_state.handle_night_settlement(true).unwrap();
+    assert!(game_state.night_settled);
+    assert_eq!(game_state.players.get("p1").unwrap().strength, 50);
+
+    let results = game_state.handle_scheduled_night_end().unwrap();
+    assert_eq!(results.results.len(), 1);
+    assert_eq!(results.results[0].data["phase"], json!("day"));
+    assert_eq!(results.results[0].data["already_settled"], json!(true));
+    assert_eq!(game_state.players.get("p1").unwrap().strength, 50);
+
+    // 下一个夜晚开始时清除结算标记
+    game_state.handle_scheduled_night_start();
+    assert!(!game_state.night_settled);
+    game_state.handle_scheduled_night_end().unwrap();
+    assert!(game_state.night_settled);
+    assert_eq!(game_state.players.get("p1").unwrap().strength, 80);
+}
+
+/// 测试：未启用自动结算时夜晚结束仅公告白天开始
+#[test]
+fn test_scheduled_night_end_without_settlement() {
+    let mut game_state = GameState::new(
+        "test_manual_settlement".to_string(),
+        get_test_rules_with_game_flow(scheduled_game_flow(false)),
+    );
+    add_test_player(&mut game_state, "p1", "玩家p1", "位置1");
+    game_state.players.get_mut("p1").unwrap().strength = 50;
+
+    let results = game_state.handle_scheduled_night_end().unwrap();
+    assert_eq!(results.results.len(), 1);
+    assert_eq!(results.results[0].data["phase"], json!("day"));
+    assert_eq!(game_state.players.get("p1").unwrap().strength, 50);
+}
@@ -0,0 +1,126 @@
+//! 游戏随机数集成测试
+//! 测试 GameState 持有的可设置种子随机数生成器：相同种子结果一致、随存档保存以及旧存档兼容
+
+mod common;
+
+use chrono::{Duration, Utc};
+use common::{add_test_player, create_game_state, test_rules};
+use rand::Rng;
+use royale_arena_backend::game::action_journal::{ActionJournal, JournalActor, JournalEntry};
+use royale_arena_backend::game::game_rng::GameRng;
+use royale_arena_backend::websocket::models::GameState;
+use serde_json::json;
+
+/// 创建一个位置1中有多名玩家的游戏状态
+fn create_seeded_game_state(seed: u64) -> GameState {
+    let rules = test_rules(json!({
+      "map": {"places": ["位置1"]},
+      "player": {"max_strength": 1000},
+      "action_costs": {"search": 1}
+    }));
+    let mut game_state = create_game_state("test_rng", rules);
+    game_state.rng = GameRng::from_seed(seed);
+    game_state.weather = 0.5;
+
+    for index in 0..6 {
+        add_test_player(
+            &mut game_state,
+            &format!("p{}", index),
+            &format!("玩家{}", index),
+            "位置1",
+        );
+    }
+    game_state
+}
+
+/// 执行多次搜索并记录搜索结果
+fn search_outcomes(game_state: &mut GameState, times: usize) -> Vec<(String, bool)> {
+    (0..times)
+        .map(|_| {
+            game_state.handle_search_action("p0").unwrap();
+            let result = game_state
+                .players
+                .get("p0")
+                .unwrap()
+                .last_search_result
+                .clone()
+                .unwrap();
+            (result.target_id, result.is_visible)
+        })
+        .collect()
+}
+
+/// 测试：相同种子的搜索目标与天气可见性判定完全一致
+#[test]
+fn test_same_seed_same_search_outcomes() {
+    let mut first = create_seeded_game_state(42);
+    let mut second = create_seeded_game_state(42);
+
+    let first_outcomes = search_outcomes(&mut first, 20);
+    assert_eq!(first_outcomes, search_outcomes(&mut second, 20));
+    assert_eq!(first.rng, second.rng);
+    assert_eq!(first.rng.seed(), 42);
+}
+
+/// 测试：随机数状态随存档保存，读档后继续产生相同的随机序列
+#[test]
+fn test_rng_state_persisted_in_save() {
+    let mut game_state = create_seeded_game_state(7);
+    search_outcomes(&mut game_state, 3);
+
+    let serialized = serde_json::to_string(&game_state).unwrap();
+    let mut restored: GameState = serde_json::from_str(&serialized).unwrap();
+    assert_eq!(restored.rng, game_state.rng);
+
+    assert_eq!(
+        search_outcomes(&mut restored, 10),
+        search_outcomes(&mut game_state, 10)
+    );
+}
+
+/// 测试：没有随机数状态的旧存档可以正常读取
+#[test]
+fn test_legacy_save_without_rng() {
+    let mut value = serde_json::to_value(create_seeded_game_state(1)).unwrap();
+    value.as_object_mut().unwrap().remove("rng");
+
+    let mut game_state: GameState = serde_json::from_value(value).unwrap();
+    let roll: f64 = game_state.rng.random_range(0.0..1.0);
+    assert!((0.0..1.0).contains(&roll));
+}
+
+/// 测试：行动日志记录随机数状态，重放随机行动得到相同结果
+#[test]
+fn test_journal_replay_reproduces_random_outcomes() {
+    let mut game_state = create_seeded_game_state(99);
+    game_state.night_start_time = Some(Utc::now() - Duration::hours(1));
+    game_state.night_end_time = Some(Utc::now() + Duration::hours(1));
+    let snapshot = game_state.clone();
+
+    let mut entries = Vec::new();
+    let mut outcomes = Vec::new();
+    for _ in 0..5 {
+        // 模拟未记录在日志中的随机数消耗，重放仍以日志记录的随机数状态为准
+        let _: u64 = game_state.rng.random();
+
+        let entry = JournalEntry::new(
+            &mut game_state,
+            JournalActor::Player {
+                player_id: "p0".to_string(),
+            },
+            "search",
+            json!({"action": "search"}),
+        );
+        assert_eq!(entry.rng_seed, Some(game_state.rng.state()));
+        entry.apply(&mut game_state).unwrap();
+        outcomes.push(game_state.players["p0"].last_search_result.clone().unwrap());
+        entries.push(entry);
+    }
+
+    for (index, outcome) in outcomes.iter().enumerate() {
+        let replayed = ActionJournal::replay(snapshot.clone(), &entries, Some(index as u64 + 1));
+        let replayed_outcome = replayed.players["p0"].last_search_result.clone().unwrap();
+        assert_eq!(replayed_outcome.target_id, outcome.target_id);
+        assert_eq!(replayed_outcome.is_visible, outcome.is_visible);
+    }
+}
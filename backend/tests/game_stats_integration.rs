@@ -0,0 +1,300 @@
+//! 赛后统计集成测试
+//! 测试攻击产生的伤害记录与击杀武器、最终名次、击杀原因、武器使用与伤害统计，以及跨游戏排行榜汇总
+
+mod common;
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use common::{add_test_player, common_rarity_levels, test_rules};
+use royale_arena_backend::game::models::{
+    DamageRecord, KillRecord, NewDamageRecord, NewKillRecord,
+};
+use royale_arena_backend::game::stats::{GameStats, LeaderboardEntry, StatsPlayer};
+use royale_arena_backend::websocket::models::{GameState, SearchResult, SearchResultType};
+use serde_json::json;
+
+/// 测试规则配置（带溅射武器）
+fn get_test_rules() -> serde_json::Value {
+    test_rules(json!({
+      "map": {"places": ["位置1"]},
+      "player": {"max_backpack_items": 2},
+      "death_item_disposition": "vanish",
+      "items_config": {
+        "rarity_levels": common_rarity_levels(),
+        "items": {
+          "weapons": [
+            {
+              "internal_name": "aoe_weapon",
+              "display_names": ["溅射锤"],
+              "rarity": "common",
+              "properties": {
+                "damage": 60,
+                "votes": 0,
+                "aoe_damage": 5
+              }
+            }
+          ]
+        }
+      }
+    }))
+}
+
+fn set_search_result(game_state: &mut GameState, player_id: &str, target_id: &str) {
+    game_state
+        .players
+        .get_mut(player_id)
+        .unwrap()
+        .last_search_result = Some(SearchResult {
+        target_type: SearchResultType::Player,
+        target_id: target_id.to_string(),
+        target_name: target_id.to_string(),
+        is_visible: true,
+    });
+}
+
+fn time(minute: i64) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2025, 10, 20, 12, 0, 0).unwrap() + Duration::minutes(minute)
+}
+
+fn stats_player(game_id: &str, id: &str, name: &str) -> StatsPlayer {
+    StatsPlayer {
+        id: id.to_string(),
+        game_id: game_id.to_string(),
+        name: name.to_string(),
+        team_id: 0,
+    }
+}
+
+fn kill_record(
+    game_id: &str,
+    killer_id: Option<&str>,
+    victim_id: &str,
+    minute: i64,
+    cause: &str,
+    weapon: Option<&str>,
+) -> KillRecord {
+    KillRecord {
+        id: format!("kill-{}-{}", victim_id, minute),
+        game_id: game_id.to_string(),
+        killer_id: killer_id.map(str::to_string),
+        victim_id: victim_id.to_string(),
+        kill_time: time(minute),
+        cause: cause.to_string(),
+        weapon: weapon.map(str::to_string),
+        location: None,
+    }
+}
+
+fn damage_record(
+    game_id: &str,
+    attacker_id: Option<&str>,
+    victim_id: &str,
+    damage: i32,
+    weapon: Option<&str>,
+) -> DamageRecord {
+    DamageRecord {
+        id: format!("damage-{}-{}", victim_id, damage),
+        game_id: game_id.to_string(),
+        attacker_id: attacker_id.map(str::to_string),
+        victim_id: victim_id.to_string(),
+        damage,
+        cause: if weapon.is_some() { "攻击" } else { "流血" }.to_string(),
+        weapon: weapon.map(str::to_string),
+        damage_time: time(0),
+    }
+}
+
+#[test]
+fn test_attack_records_damage_and_kill_weapon() {
+    let mut game_state = GameState::new("test_stats_attack".to_string(), get_test_rules());
+    for player_id in ["a", "b", "c"] {
+        add_test_player(
+            &mut game_state,
+            player_id,
+            &format!("玩家{}", player_id),
+            "位置1",
+        );
+    }
+    game_state.players.get_mut("b").unwrap().life = 60;
+    let weapon = game_state
+        .rule_engine
+        .create_item_from_name("溅射锤")
+        .unwrap();
+    let weapon_name = weapon.name.clone();
+    game_state
+        .players
+        .get_mut("a")
+        .unwrap()
+        .equip_weapon(weapon);
+
+    set_search_result(&mut game_state, "a", "b");
+    let results = game_state.handle_attack_action("a").unwrap();
+
+    let damage_records: Vec<NewDamageRecord> = results
+        .results
+        .iter()
+        .flat_map(|result| NewDamageRecord::build_damage_record_params("test_stats_attack", result))
+        .collect();
+    assert_eq!(damage_records.len(), 2);
+    assert_eq!(damage_records[0].victim_id, "b");
+    assert_eq!(damage_records[0].damage, 60);
+    assert_eq!(damage_records[0].cause, "攻击");
+    assert_eq!(damage_records[1].victim_id, "c");
+    assert_eq!(damage_records[1].damage, 5);
+    assert_eq!(damage_records[1].cause, "溅射");
+    assert!(damage_records.iter().all(|record| {
+        record.attacker_id.as_deref() == Some("a") && record.weapon.as_ref() == Some(&weapon_name)
+    }));
+
+    let kill_records: Vec<NewKillRecord> = results
+        .results
+        .iter()
+        .filter_map(|result| NewKillRecord::build_kill_record_params("test_stats_attack", result))
+        .collect();
+    assert_eq!(kill_records.len(), 1);
+    assert_eq!(kill_records[0].victim_id, "b");
+    assert_eq!(kill_records[0].killer_id.as_deref(), Some("a"));
+    assert_eq!(kill_records[0].weapon.as_ref(), Some(&weapon_name));
+}
+
+#[test]
+fn test_unarmed_kill_is_recorded_as_punch() {
+    let mut game_state = GameState::new("test_stats_unarmed".to_string(), get_test_rules());
+    add_test_player(&mut game_state, "a", "玩家a", "位置1");
+    add_test_player(&mut game_state, "b", "玩家b", "位置1");
+    game_state.players.get_mut("b").unwrap().life = 10;
+
+    set_search_result(&mut game_state, "a", "b");
+    let results = game_state.handle_attack_action("a").unwrap();
+    let kill_record = results
+        .results
+        .iter()
+        .find_map(|result| NewKillRecord::build_kill_record_params("test_stats_unarmed", result))
+        .unwrap();
+    assert_eq!(kill_record.weapon.as_deref(), Some("挥拳"));
+}
+
+#[test]
+fn test_game_stats_placement_kills_and_weapons() {
+    let game_id = "game_1";
+    let players = vec![
+        stats_player(game_id, "p1", "甲"),
+        stats_player(game_id, "p2", "乙"),
+        stats_player(game_id, "p3", "丙"),
+        stats_player(game_id, "p4", "丁"),
+        stats_player(game_id, "p5", "戊"),
+    ];
+    let kill_records = vec![
+        kill_record(game_id, Some("p1"), "p2", 10, "攻击致死", Some("木棍")),
+        kill_record(game_id, None, "p3", 20, "缩圈", None),
+        kill_record(game_id, None, "p4", 20, "缩圈", None),
+        kill_record(game_id, Some("p1"), "p5", 30, "流血致死", None),
+    ];
+    let damage_records = vec![
+        damage_record(game_id, Some("p1"), "p2", 40, Some("木棍")),
+        damage_record(game_id, Some("p1"), "p2", 60, Some("木棍")),
+        damage_record(game_id, Some("p2"), "p1", 10, Some("挥拳")),
+        damage_record(game_id, Some("p1"), "p5", 15, None),
+    ];
+
+    let stats = GameStats::compute(game_id, &players, &kill_records, &damage_records);
+    assert_eq!(stats.total_players, 5);
+    assert_eq!(stats.survivors, 1);
+    assert_eq!(stats.total_kills, 4);
+    assert_eq!(stats.total_damage, 125);
+
+    // 存活者第1名，之后按死亡时间从晚到早，同时死亡的玩家名次相同
+    let placements: Vec<(&str, usize)> = stats
+        .players
+        .iter()
+        .map(|player| (player.player_id.as_str(), player.placement))
+        .collect();
+    assert_eq!(
+        placements,
+        vec![("p1", 1), ("p5", 2), ("p4", 3), ("p3", 3), ("p2", 5)]
+    );
+
+    let winner = &stats.players[0];
+    assert!(winner.is_alive);
+    assert_eq!(winner.kills, 2);
+    assert_eq!(winner.damage_dealt, 115);
+    assert_eq!(winner.damage_taken, 10);
+    let bled_out = &stats.players[1];
+    assert_eq!(bled_out.death_cause.as_deref(), Some("流血致死"));
+    assert_eq!(bled_out.killer_id.as_deref(), Some("p1"));
+
+    assert_eq!(stats.kill_causes[0].cause, "缩圈");
+    assert_eq!(stats.kill_causes[0].count, 2);
+    assert_eq!(stats.kill_causes.len(), 3);
+
+    assert_eq!(stats.weapons[0].weapon, "木棍");
+    assert_eq!(stats.weapons[0].hits, 2);
+    assert_eq!(stats.weapons[0].damage, 100);
+    assert_eq!(stats.weapons[0].kills, 1);
+    assert_eq!(stats.weapons[1].weapon, "挥拳");
+}
+
+#[test]
+fn test_revived_player_uses_last_death() {
+    let game_id = "game_revive";
+    let players = vec![
+        stats_player(game_id, "p1", "甲"),
+        stats_player(game_id, "p2", "乙"),
+    ];
+    let kill_records = vec![
+        kill_record(game_id, None, "p1", 5, "导演击杀", None),
+        kill_record(game_id, None, "p2", 10, "缩圈", None),
+        kill_record(game_id, None, "p1", 15, "缩圈", None),
+    ];
+
+    let stats = GameStats::compute(game_id, &players, &kill_records, &[]);
+    assert_eq!(stats.players[0].player_id, "p1");
+    assert_eq!(stats.players[0].placement, 1);
+    assert_eq!(stats.players[0].death_time, Some(time(15)));
+    assert_eq!(stats.survivors, 0);
+}
+
+#[test]
+fn test_leaderboard_aggregates_by_player_name() {
+    let players = vec![
+        stats_player("g1", "g1-a", "甲"),
+        stats_player("g1", "g1-b", "乙"),
+        stats_player("g2", "g2-a", "甲"),
+        stats_player("g2", "g2-b", "乙"),
+        stats_player("g2", "g2-c", "丙"),
+    ];
+    let kill_records = vec![
+        kill_record("g1", Some("g1-a"), "g1-b", 10, "攻击致死", Some("木棍")),
+        kill_record("g2", Some("g2-b"), "g2-a", 10, "攻击致死", Some("木棍")),
+        kill_record("g2", Some("g2-b"), "g2-c", 20, "攻击致死", Some("木棍")),
+        // 未包含在玩家列表中的游戏不计入排行榜
+        kill_record("g3", Some("x"), "y", 10, "攻击致死", Some("木棍")),
+    ];
+    let damage_records = vec![damage_record("g2", Some("g2-b"), "g2-a", 30, Some("木棍"))];
+
+    let games = GameStats::compute_by_game(&players, &kill_records, &damage_records);
+    assert_eq!(games.len(), 2);
+
+    let leaderboard = LeaderboardEntry::from_games(&games);
+    assert_eq!(leaderboard.len(), 3);
+
+    let second = &leaderboard[0];
+    assert_eq!(second.player_name, "乙");
+    assert_eq!(second.games_played, 2);
+    assert_eq!(second.wins, 1);
+    assert_eq!(second.kills, 2);
+    assert_eq!(second.deaths, 1);
+    assert_eq!(second.damage_dealt, 30);
+    assert_eq!(second.best_placement, 1);
+    assert_eq!(second.average_placement, 1.5);
+
+    let first = &leaderboard[1];
+    assert_eq!(first.player_name, "甲");
+    assert_eq!(first.wins, 1);
+    assert_eq!(first.kills, 1);
+    assert_eq!(first.damage_taken, 30);
+    assert_eq!(first.average_placement, 2.0);
+
+    assert_eq!(leaderboard[2].player_name, "丙");
+    assert_eq!(leaderboard[2].wins, 0);
+    assert_eq!(leaderboard[2].best_placement, 2);
+}
@@ -0,0 +1,199 @@
+//! 日志查询集成测试
+//! 测试分页游标的编码与解析、查询参数解析、游标分页语句生成与分页结果整理
+
+use axum::extract::Query;
+use axum::http::Uri;
+use chrono::{Duration, TimeZone, Utc};
+use royale_arena_backend::game::errors::GameError;
+use royale_arena_backend::game::log_query::{
+    DEFAULT_PAGE_SIZE, KillRecordQuery, LogCursor, MAX_PAGE_SIZE, MessageQuery, Pagination,
+    like_pattern,
+};
+use royale_arena_backend::game::models::MessageType;
+use sqlx::{MySql, QueryBuilder};
+
+/// 测试记录：（时间戳，ID）
+fn records(count: usize) -> Vec<LogCursor> {
+    let start = Utc.with_ymd_and_hms(2025, 10, 1, 12, 0, 0).unwrap();
+    (0..count)
+        .map(|index| {
+            // 每两条记录时间戳相同，由ID区分先后
+            LogCursor::new(
+                start + Duration::seconds((index / 2) as i64),
+                &format!("log-{index:02}"),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn test_cursor_round_trip() {
+    let timestamp =
+        Utc.with_ymd_and_hms(2025, 10, 1, 12, 0, 0).unwrap() + Duration::microseconds(123);
+    let cursor = LogCursor::new(timestamp, "0a1b_2c3d");
+
+    let encoded = cursor.encode();
+    assert_eq!(
+        encoded,
+        format!("{}_0a1b_2c3d", timestamp.timestamp_micros())
+    );
+    assert_eq!(LogCursor::decode(&encoded).unwrap(), cursor);
+}
+
+#[test]
+fn test_invalid_cursor_rejected() {
+    for value in ["", "abc", "123", "123_", "abc_log-1"] {
+        assert!(
+            matches!(LogCursor::decode(value), Err(GameError::ValidationError(_))),
+            "游标 {value:?} 应被拒绝"
+        );
+    }
+}
+
+#[test]
+fn test_message_query_deserialization() {
+    let uri: Uri = "/logs?limit=20&type=UserDirected&player_id=p1&from=2025-10-01T12:00:00Z&q=%E6%94%BB%E5%87%BB"
+        .parse()
+        .unwrap();
+    let Query(query) = Query::<MessageQuery>::try_from_uri(&uri).unwrap();
+
+    assert_eq!(query.limit, Some(20));
+    assert_eq!(query.message_type, Some(MessageType::UserDirected));
+    assert_eq!(query.player_id.as_deref(), Some("p1"));
+    assert_eq!(
+        query.from,
+        Some(Utc.with_ymd_and_hms(2025, 10, 1, 12, 0, 0).unwrap())
+    );
+    assert_eq!(query.to, None);
+    assert_eq!(query.q.as_deref(), Some("攻击"));
+
+    let uri: Uri = "/logs".parse().unwrap();
+    let Query(query) = Query::<MessageQuery>::try_from_uri(&uri).unwrap();
+    assert!(query.limit.is_none() && query.message_type.is_none() && query.before.is_none());
+}
+
+#[test]
+fn test_kill_record_query_deserialization() {
+    let uri: Uri =
+        "/kill-records?killer_id=k1&victim_id=v1&cause=%E7%BC%A9%E5%9C%88&location=A&after=1_x"
+            .parse()
+            .unwrap();
+    let Query(query) = Query::<KillRecordQuery>::try_from_uri(&uri).unwrap();
+
+    assert_eq!(query.killer_id.as_deref(), Some("k1"));
+    assert_eq!(query.victim_id.as_deref(), Some("v1"));
+    assert_eq!(query.cause.as_deref(), Some("缩圈"));
+    assert_eq!(query.location.as_deref(), Some("A"));
+    assert_eq!(query.after.as_deref(), Some("1_x"));
+}
+
+#[test]
+fn test_pagination_rejects_zero_limit_and_bad_cursor() {
+    assert!(matches!(
+        Pagination::new(Some(0), None, None),
+        Err(GameError::ValidationError(_))
+    ));
+    assert!(matches!(
+        Pagination::new(Some(MAX_PAGE_SIZE + 1), None, None),
+        Err(GameError::ValidationError(_))
+    ));
+    assert!(Pagination::new(Some(MAX_PAGE_SIZE), None, None).is_ok());
+    assert!(matches!(
+        Pagination::new(Some(10), Some("bad"), None),
+        Err(GameError::ValidationError(_))
+    ));
+}
+
+#[test]
+fn test_pagination_sql() {
+    let cursor = records(1)[0].encode();
+
+    let mut builder = QueryBuilder::<MySql>::new("SELECT * FROM game_logs WHERE game_id = ?");
+    Pagination::new(Some(10), Some(&cursor), None)
+        .unwrap()
+        .push_order(&mut builder, "timestamp");
+    assert_eq!(
+        builder.sql(),
+        "SELECT * FROM game_logs WHERE game_id = ? AND (timestamp < ? OR (timestamp = ? AND id < ?)) ORDER BY timestamp DESC, id DESC LIMIT ?"
+    );
+
+    let mut builder = QueryBuilder::<MySql>::new("SELECT * FROM kill_records WHERE game_id = ?");
+    Pagination::new(Some(10), None, Some(&cursor))
+        .unwrap()
+        .push_order(&mut builder, "kill_time");
+    assert_eq!(
+        builder.sql(),
+        "SELECT * FROM kill_records WHERE game_id = ? AND (kill_time > ? OR (kill_time = ? AND id > ?)) ORDER BY kill_time ASC, id ASC LIMIT ?"
+    );
+
+    // 未指定条数时使用默认条数返回最近的记录
+    let mut builder = QueryBuilder::<MySql>::new("SELECT * FROM game_logs WHERE game_id = ?");
+    Pagination::new(None, None, None)
+        .unwrap()
+        .push_order(&mut builder, "timestamp");
+    assert_eq!(
+        builder.sql(),
+        "SELECT * FROM game_logs WHERE game_id = ? ORDER BY timestamp DESC, id DESC LIMIT ?"
+    );
+}
+
+#[test]
+fn test_backward_page_is_returned_in_chronological_order() {
+    let all = records(5);
+
+    // 最近的2条：数据库按倒序返回并多取一条
+    let fetched: Vec<LogCursor> = all.iter().rev().take(3).cloned().collect();
+    let page = Pagination::new(Some(2), None, None)
+        .unwrap()
+        .into_page(fetched, |record| record.clone());
+
+    assert_eq!(page.items, all[3..5].to_vec());
+    assert!(page.page.has_more);
+    assert_eq!(page.page.prev_cursor, Some(all[3].encode()));
+    assert_eq!(page.page.next_cursor, Some(all[4].encode()));
+
+    // 继续向前翻页至最早的一条
+    let before = page.page.prev_cursor.unwrap();
+    let fetched: Vec<LogCursor> = all[..3].iter().rev().take(3).cloned().collect();
+    let page = Pagination::new(Some(3), Some(&before), None)
+        .unwrap()
+        .into_page(fetched, |record| record.clone());
+
+    assert_eq!(page.items, all[..3].to_vec());
+    assert!(!page.page.has_more);
+}
+
+#[test]
+fn test_forward_page_and_default_limit() {
+    let all = records(4);
+
+    let fetched: Vec<LogCursor> = all[1..4].to_vec();
+    let page = Pagination::new(Some(2), None, Some(&all[0].encode()))
+        .unwrap()
+        .into_page(fetched, |record| record.clone());
+    assert_eq!(page.items, all[1..3].to_vec());
+    assert!(page.page.has_more);
+    assert_eq!(page.page.next_cursor, Some(all[2].encode()));
+
+    // 未指定条数时最多返回默认条数
+    let many = records(DEFAULT_PAGE_SIZE + 1);
+    let fetched: Vec<LogCursor> = many.iter().rev().cloned().collect();
+    let page = Pagination::new(None, None, None)
+        .unwrap()
+        .into_page(fetched, |record| record.clone());
+    assert_eq!(page.items, many[1..].to_vec());
+    assert!(page.page.has_more);
+
+    let page = Pagination::new(Some(5), None, None)
+        .unwrap()
+        .into_page(Vec::<LogCursor>::new(), |record| record.clone());
+    assert!(page.items.is_empty());
+    assert_eq!(page.page.prev_cursor, None);
+    assert_eq!(page.page.next_cursor, None);
+}
+
+#[test]
+fn test_like_pattern_escapes_wildcards() {
+    assert_eq!(like_pattern("攻击"), "%攻击%");
+    assert_eq!(like_pattern("100%_a\\b"), "%100\\%\\_a\\\\b%");
+}
@@ -0,0 +1,143 @@
+//! 地图连接集成测试
+//! 测试地图 edges 配置：仅允许移动到相邻地点、按连接设置体力消耗、摧毁地点切断路线以及玩家视角的相邻地点
+
+mod common;
+
+use chrono::{Duration, Utc};
+use common::{add_test_player, test_rules};
+use royale_arena_backend::game::models::MessageType;
+use royale_arena_backend::websocket::actions::player_action_scheduler::{
+    ActionParams, PlayerActionScheduler,
+};
+use royale_arena_backend::websocket::broadcaster::MessageBroadcaster;
+use royale_arena_backend::websocket::models::GameState;
+use serde_json::json;
+
+/// 测试规则配置（指定地图连接）
+fn get_test_rules_with_edges(edges: serde_json::Value) -> serde_json::Value {
+    test_rules(json!({
+      "map": {
+        "places": ["码头", "工厂", "学校", "灯塔"],
+        "edges": edges
+      }
+    }))
+}
+
+/// 码头 -(3)- 工厂 -(默认)- 学校，灯塔不与任何地点相连
+fn default_edges() -> serde_json::Value {
+    json!([
+      {"from": "码头", "to": "工厂", "cost": 3},
+      {"from": "工厂", "to": "学校"}
+    ])
+}
+
+fn create_game_state(edges: serde_json::Value) -> GameState {
+    let mut game_state = common::create_game_state("test_map", get_test_rules_with_edges(edges));
+    add_test_player(&mut game_state, "p1", "玩家1", "码头");
+    game_state
+}
+
+fn move_params(target_place: &str) -> ActionParams {
+    ActionParams {
+        target_place: Some(target_place.to_string()),
+        place_name: None,
+        item_id: None,
+        slot_type: None,
+        target_player_id: None,
+        target_player_ids: None,
+        target_item_name: None,
+        message: None,
+        shop_buy_items: None,
+        item_ids: None,
+        coins: None,
+        request_item_ids: None,
+        request_coins: None,
+        trade_id: None,
+    }
+}
+
+fn location(game_state: &GameState) -> String {
+    game_state.players.get("p1").unwrap().location.clone()
+}
+
+/// 测试：只能移动到相邻地点，消耗按连接设置
+#[test]
+fn test_move_only_to_adjacent_places() {
+    let mut game_state = create_game_state(default_edges());
+
+    let results = game_state.handle_move_action("p1", "学校").unwrap();
+    assert_eq!(results.results[0].message_type, MessageType::Info);
+    assert_eq!(results.results[0].data["neighbors"], json!(["工厂"]));
+    assert_eq!(location(&game_state), "码头");
+
+    game_state.handle_move_action("p1", "工厂").unwrap();
+    assert_eq!(location(&game_state), "工厂");
+    assert_eq!(game_state.players.get("p1").unwrap().strength, 97);
+    assert!(
+        game_state.places["工厂"]
+            .players
+            .contains(&"p1".to_string())
+    );
+    assert!(game_state.places["码头"].players.is_empty());
+
+    // 未设置消耗的连接使用默认移动消耗
+    game_state.handle_move_action("p1", "学校").unwrap();
+    assert_eq!(location(&game_state), "学校");
+    assert_eq!(game_state.players.get("p1").unwrap().strength, 92);
+
+    let results = game_state.handle_move_action("p1", "灯塔").unwrap();
+    assert_eq!(results.results[0].message_type, MessageType::Info);
+    assert_eq!(location(&game_state), "学校");
+}
+
+/// 测试：被摧毁的地点切断经过它的路线
+#[test]
+fn test_destroyed_place_severs_route() {
+    let mut game_state = create_game_state(default_edges());
+    assert_eq!(game_state.place_neighbors("码头"), vec!["工厂".to_string()]);
+
+    game_state.places.get_mut("工厂").unwrap().is_destroyed = true;
+    assert!(game_state.place_neighbors("码头").is_empty());
+
+    let results = game_state.handle_move_action("p1", "工厂").unwrap();
+    assert_eq!(results.results[0].message_type, MessageType::Info);
+    assert_eq!(location(&game_state), "码头");
+}
+
+/// 测试：未配置地点连接时保持任意移动
+#[test]
+fn test_no_edges_allows_any_move() {
+    let mut game_state = create_game_state(json!([]));
+    assert_eq!(game_state.place_neighbors("码头").len(), 3);
+
+    game_state.handle_move_action("p1", "灯塔").unwrap();
+    assert_eq!(location(&game_state), "灯塔");
+    assert_eq!(game_state.players.get("p1").unwrap().strength, 95);
+}
+
+/// 测试：体力验证使用连接上的移动消耗
+#[test]
+fn test_strength_validation_uses_edge_cost() {
+    let mut game_state = create_game_state(default_edges());
+    game_state.night_start_time = Some(Utc::now() - Duration::hours(1));
+    game_state.night_end_time = Some(Utc::now() + Duration::hours(1));
+    game_state.players.get_mut("p1").unwrap().strength = 3;
+
+    PlayerActionScheduler::dispatch(&mut game_state, "p1", "move", move_params("工厂")).unwrap();
+    assert_eq!(location(&game_state), "工厂");
+    assert_eq!(game_state.players.get("p1").unwrap().strength, 0);
+}
+
+/// 测试：玩家视角的地点信息包含相邻地点
+#[test]
+fn test_player_view_exposes_neighbors() {
+    let game_state = create_game_state(default_edges());
+    let player = game_state.players.get("p1").unwrap().clone();
+    let message = MessageBroadcaster::generate_player_message(&game_state, &player, None);
+
+    let actor_places = message["game_data"]["actor_places"].as_array().unwrap();
+    let factory = actor_places.iter().find(|p| p["name"] == "工厂").unwrap();
+    assert_eq!(factory["neighbors"], json!(["码头", "学校"]));
+    let lighthouse = actor_places.iter().find(|p| p["name"] == "灯塔").unwrap();
+    assert_eq!(lighthouse["neighbors"], json!([]));
+}
@@ -0,0 +1,142 @@
+//! 监控指标集成测试
+//! 测试行动计数与耗时、处理中行动计数、连接数量统计以及Prometheus文本格式导出
+
+use royale_arena_backend::metrics::{ACTOR_DIRECTOR, ACTOR_PLAYER, Metrics};
+use royale_arena_backend::websocket::game_connection_manager::DIRECTOR_USER_ID;
+use royale_arena_backend::websocket::global_connection_manager::GlobalConnectionManager;
+use royale_arena_backend::websocket::models::ConnectionType;
+use std::time::Duration;
+
+/// 在导出文本中查找指定指标行的值
+fn sample(rendered: &str, series: &str) -> Option<f64> {
+    rendered.lines().find_map(|line| {
+        line.strip_prefix(series)
+            .and_then(|value| value.strip_prefix(' '))
+            .and_then(|value| value.parse().ok())
+    })
+}
+
+#[test]
+fn test_actions_are_counted_by_type_and_outcome() {
+    let metrics = Metrics::new();
+    metrics.record_action(ACTOR_PLAYER, "move", true, Duration::from_millis(2));
+    metrics.record_action(ACTOR_PLAYER, "move", true, Duration::from_millis(3));
+    metrics.record_action(ACTOR_PLAYER, "attack", false, Duration::from_millis(1));
+    metrics.record_action(ACTOR_DIRECTOR, "weather", true, Duration::from_millis(1));
+    metrics.observe_lock_wait(ACTOR_PLAYER, Duration::from_millis(1));
+
+    let rendered = metrics.render();
+    assert_eq!(
+        sample(
+            &rendered,
+            r#"royale_arena_actions_total{action="move",actor="player",outcome="success"}"#
+        ),
+        Some(2.0)
+    );
+    assert_eq!(
+        sample(
+            &rendered,
+            r#"royale_arena_actions_total{action="attack",actor="player",outcome="error"}"#
+        ),
+        Some(1.0)
+    );
+    assert_eq!(
+        sample(
+            &rendered,
+            r#"royale_arena_action_duration_seconds_count{actor="player"}"#
+        ),
+        Some(3.0)
+    );
+    assert_eq!(
+        sample(
+            &rendered,
+            r#"royale_arena_action_duration_seconds_count{actor="director"}"#
+        ),
+        Some(1.0)
+    );
+    assert_eq!(
+        sample(
+            &rendered,
+            r#"royale_arena_action_lock_wait_seconds_count{actor="player"}"#
+        ),
+        Some(1.0)
+    );
+    assert!(rendered.contains("# TYPE royale_arena_action_duration_seconds histogram"));
+}
+
+#[test]
+fn test_in_flight_actions_and_gauges() {
+    let metrics = Metrics::new();
+    let series = r#"royale_arena_actions_in_flight{actor="director"}"#;
+
+    let guard = metrics.action_in_flight(ACTOR_DIRECTOR);
+    let second = metrics.action_in_flight(ACTOR_DIRECTOR);
+    assert_eq!(sample(&metrics.render(), series), Some(2.0));
+    drop(guard);
+    drop(second);
+    assert_eq!(sample(&metrics.render(), series), Some(0.0));
+
+    metrics.set_active_games(3);
+    metrics.set_websocket_connections("actor", 5);
+    metrics.observe_broadcast_fanout(4);
+    metrics.observe_save_duration(Duration::from_millis(20));
+    metrics.record_db_error("game_log");
+
+    let rendered = metrics.render();
+    assert_eq!(sample(&rendered, "royale_arena_active_games"), Some(3.0));
+    assert_eq!(
+        sample(
+            &rendered,
+            r#"royale_arena_websocket_connections{role="actor"}"#
+        ),
+        Some(5.0)
+    );
+    assert_eq!(
+        sample(&rendered, "royale_arena_broadcast_fanout_sum"),
+        Some(4.0)
+    );
+    assert_eq!(
+        sample(&rendered, "royale_arena_save_duration_seconds_count"),
+        Some(1.0)
+    );
+    assert_eq!(
+        sample(
+            &rendered,
+            r#"royale_arena_db_query_errors_total{component="game_log"}"#
+        ),
+        Some(1.0)
+    );
+}
+
+#[tokio::test]
+async fn test_connection_counts_across_games() {
+    let global = GlobalConnectionManager::new();
+    let first = global.get_manager("game_1".to_string());
+    let second = global.get_manager("game_2".to_string());
+
+    let (_, _p1) = first
+        .add_connection("p1".to_string(), ConnectionType::Actor)
+        .await;
+    let (_, _p1_second_tab) = first
+        .add_connection("p1".to_string(), ConnectionType::Actor)
+        .await;
+    let (_, _director) = first
+        .add_connection(DIRECTOR_USER_ID.to_string(), ConnectionType::Director)
+        .await;
+    let (_, _p2) = second
+        .add_connection("p2".to_string(), ConnectionType::Actor)
+        .await;
+
+    let counts = global.connection_counts().await;
+    assert_eq!(counts.get(&ConnectionType::Actor), Some(&3));
+    assert_eq!(counts.get(&ConnectionType::Director), Some(&1));
+    assert_eq!(counts.get(&ConnectionType::Spectator), None);
+
+    // 广播送达数量：p1的两个连接加上导演连接
+    let recipients = vec!["p1".to_string(), "p3".to_string()];
+    assert_eq!(first.recipient_connection_count(&recipients, true).await, 3);
+    assert_eq!(
+        first.recipient_connection_count(&recipients, false).await,
+        2
+    );
+}
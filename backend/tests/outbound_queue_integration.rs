@@ -0,0 +1,215 @@
+//! 有界发送队列集成测试
+//! 测试状态推送的覆盖策略（被覆盖的广播视图保留动作结果）、队列统计、慢速客户端的强制断开以及正常关闭时的剩余消息发送
+
+mod common;
+
+use common::{add_test_player, test_rules};
+use royale_arena_backend::websocket::broadcaster::MessageBroadcaster;
+use royale_arena_backend::websocket::game_connection_manager::{
+    ConnectionMessage, GameConnectionManager, SequencedMessage,
+};
+use royale_arena_backend::websocket::models::{ActionResult, ConnectionType};
+use royale_arena_backend::websocket::outbound_queue::{
+    CloseReason, DEFAULT_OUTBOUND_QUEUE_CAPACITY, outbound_queue,
+};
+use royale_arena_backend::websocket::state_patch::{StateStream, StateSyncMode, StateUpdate};
+use serde_json::{Value as JsonValue, json};
+
+/// 模拟广播器生成的视图
+fn view(life: i32, log_message: Option<&str>) -> JsonValue {
+    json!({
+        "global_state": {},
+        "game_data": { "life": life },
+        "action_result": log_message.map(|log_message| json!({ "log_message": log_message })),
+    })
+}
+
+fn state(seq: u64, life: i32, log_message: Option<&str>) -> SequencedMessage {
+    SequencedMessage {
+        seq,
+        message: ConnectionMessage::GameState(view(life, log_message)),
+    }
+}
+
+#[tokio::test]
+async fn test_state_snapshots_are_coalesced() {
+    let (sender, receiver) = outbound_queue(3);
+
+    sender.send(state(1, 100, None)).unwrap();
+    sender.send(state(2, 90, Some("受到攻击"))).unwrap();
+    sender.send(state(3, 90, None)).unwrap();
+    sender
+        .send(SequencedMessage {
+            seq: 4,
+            message: ConnectionMessage::System(json!({ "message": "导演广播" })),
+        })
+        .unwrap();
+    // 只保留最新的状态推送，被覆盖的推送只保留动作结果，系统消息不会被覆盖
+    sender.send(state(5, 80, None)).unwrap();
+
+    let metrics = sender.metrics();
+    assert_eq!(metrics.enqueued, 5);
+    assert_eq!(metrics.coalesced, 3);
+    assert_eq!(metrics.queued, 3);
+    assert_eq!(metrics.max_queued, 3);
+
+    let mut seqs = Vec::new();
+    for _ in 0..3 {
+        let message = receiver.recv().await.unwrap();
+        if message.seq == 2 {
+            let ConnectionMessage::ActionResult(action_result) = message.message else {
+                panic!("expected action result");
+            };
+            assert_eq!(action_result, json!({ "log_message": "受到攻击" }));
+        }
+        seqs.push(message.seq);
+        receiver.record_sent(10);
+    }
+    assert_eq!(seqs, vec![2, 4, 5]);
+
+    let metrics = sender.metrics();
+    assert_eq!(metrics.queued, 0);
+    assert_eq!(metrics.sent, 3);
+    assert_eq!(metrics.sent_bytes, 30);
+}
+
+#[tokio::test]
+async fn test_broadcast_views_keep_action_results() {
+    let mut game_state = common::create_game_state("test_outbound_queue", test_rules(json!({})));
+    add_test_player(&mut game_state, "p1", "玩家1", "位置1");
+    let manager = GameConnectionManager::new(DEFAULT_OUTBOUND_QUEUE_CAPACITY);
+    let broadcaster = MessageBroadcaster::new(manager.clone());
+    let (_, rx) = manager
+        .add_connection("p1".to_string(), ConnectionType::Actor)
+        .await;
+
+    // 客户端已收到初始状态
+    let mut state_stream = StateStream::new(StateSyncMode::Snapshot);
+    let initial =
+        MessageBroadcaster::generate_player_message(&game_state, &game_state.players["p1"], None);
+    state_stream.next_update(initial);
+
+    // 广播器推送的每个视图都带有动作结果
+    for (life, log_message) in [(90, "受到攻击"), (80, "再次受到攻击"), (70, "流血")] {
+        game_state.players.get_mut("p1").unwrap().life = life;
+        let action_result = ActionResult::new_system_message(
+            json!({}),
+            vec!["p1".to_string()],
+            log_message.to_string(),
+            false,
+        );
+        broadcaster
+            .broadcast_to_players(&game_state, &["p1".to_string()], &action_result)
+            .await
+            .unwrap();
+    }
+
+    let metrics = manager.connection_metrics().await;
+    assert_eq!(metrics[0].queue.coalesced, 2);
+
+    // 被覆盖的视图只保留动作结果，随已推送的状态下发，最新视图包含完整状态
+    let mut received = Vec::new();
+    for _ in 0..3 {
+        let update = match rx.recv().await.unwrap().message {
+            ConnectionMessage::GameState(view) => state_stream.next_update(view),
+            ConnectionMessage::ActionResult(action_result) => {
+                state_stream.event_update(action_result)
+            }
+            other => panic!("unexpected message: {:?}", other),
+        };
+        let StateUpdate::Snapshot(view) = update else {
+            panic!("expected snapshot");
+        };
+        received.push((
+            view["game_data"]["player"]["life"].clone(),
+            view["action_result"]["log_message"].clone(),
+        ));
+    }
+    assert_eq!(
+        received,
+        vec![
+            (json!(100), json!("受到攻击")),
+            (json!(100), json!("再次受到攻击")),
+            (json!(70), json!("流血")),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_overflow_closes_queue() {
+    let (sender, receiver) = outbound_queue(2);
+
+    sender.send(state(1, 100, Some("搜索"))).unwrap();
+    sender.send(state(2, 90, Some("受到攻击"))).unwrap();
+    // 动作结果无法覆盖，队列已满时强制关闭并丢弃剩余消息
+    assert_eq!(
+        sender.send(state(3, 80, Some("受到攻击"))),
+        Err(CloseReason::SlowConsumer)
+    );
+    assert_eq!(
+        sender.send(state(4, 80, None)),
+        Err(CloseReason::SlowConsumer)
+    );
+
+    receiver.overflowed().await;
+    assert!(receiver.recv().await.is_none());
+    assert_eq!(receiver.close_reason(), Some(CloseReason::SlowConsumer));
+    assert_eq!(CloseReason::SlowConsumer.close_code(), 1008);
+}
+
+#[tokio::test]
+async fn test_normal_close_delivers_remaining_messages() {
+    let (sender, receiver) = outbound_queue(2);
+    sender
+        .send(SequencedMessage {
+            seq: 1,
+            message: ConnectionMessage::System(json!({ "message": "游戏已结束" })),
+        })
+        .unwrap();
+    drop(sender);
+
+    let message = receiver.recv().await.unwrap();
+    assert!(matches!(message.message, ConnectionMessage::System(_)));
+    assert!(receiver.recv().await.is_none());
+    assert_eq!(receiver.close_reason(), Some(CloseReason::Normal));
+}
+
+#[tokio::test]
+async fn test_manager_disconnects_slow_consumer() {
+    let manager = GameConnectionManager::new(2);
+    let (_, slow_rx) = manager
+        .add_connection("p1".to_string(), ConnectionType::Actor)
+        .await;
+    let (_, fast_rx) = manager
+        .add_connection("p2".to_string(), ConnectionType::Actor)
+        .await;
+
+    for index in 0..3 {
+        let log_message = format!("事件{}", index);
+        manager
+            .broadcast_to_player("p1", view(100, Some(&log_message)), None)
+            .await
+            .unwrap();
+        manager
+            .broadcast_to_player("p2", view(100, Some(&log_message)), None)
+            .await
+            .unwrap();
+        fast_rx.recv().await.unwrap();
+    }
+
+    // 慢速客户端被移除，其他连接不受影响
+    assert!(slow_rx.recv().await.is_none());
+    assert_eq!(slow_rx.close_reason(), Some(CloseReason::SlowConsumer));
+    assert!(manager.get_player_connections("p1").await.is_empty());
+
+    let metrics = manager.connection_metrics().await;
+    assert_eq!(metrics.len(), 1);
+    assert_eq!(metrics[0].user_id, "p2");
+    assert_eq!(metrics[0].queue.enqueued, 3);
+    assert_eq!(metrics[0].queue.queued, 0);
+
+    // 被断开的客户端重连后仍可补发错过的事件
+    let (seq, events) = manager.catch_up("p1", Some(0)).await;
+    assert_eq!(seq, 3);
+    assert_eq!(events.unwrap().len(), 3);
+}
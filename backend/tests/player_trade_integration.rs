@@ -0,0 +1,356 @@
+//! 玩家交易集成测试
+//! 测试交易的发起、接受、拒绝、撤回、过期，以及接受时的原子交换与背包容量校验
+
+mod common;
+
+use chrono::{Duration, Utc};
+use common::{add_test_player, common_rarity_levels, test_rules};
+use royale_arena_backend::game::models::MessageType;
+use royale_arena_backend::websocket::actions::player_action_scheduler::{
+    ActionParams, PlayerActionScheduler,
+};
+use royale_arena_backend::websocket::actions::player_trade_action::{
+    TRADE_OFFER_TIMEOUT_SECONDS, TradeOfferRequest,
+};
+use royale_arena_backend::websocket::broadcaster::MessageBroadcaster;
+use royale_arena_backend::websocket::models::GameState;
+use serde_json::json;
+
+/// 测试规则配置
+fn get_test_rules() -> serde_json::Value {
+    test_rules(json!({
+      "map": {"places": ["码头", "工厂"]},
+      "player": {"max_backpack_items": 3},
+      "items_config": {
+        "rarity_levels": common_rarity_levels(),
+        "items": {
+          "weapons": [
+            {
+              "internal_name": "test_weapon",
+              "display_names": ["测试剑"],
+              "rarity": "common",
+              "properties": {"damage": 20, "votes": 1}
+            }
+          ],
+          "consumables": [
+            {
+              "name": "[HP10]测试药水",
+              "properties": {"effect_type": "heal", "effect_value": 10}
+            }
+          ]
+        }
+      }
+    }))
+}
+
+fn add_player(game_state: &mut GameState, player_id: &str, location: &str, coins: i32) {
+    add_test_player(
+        game_state,
+        player_id,
+        &format!("玩家{}", player_id),
+        location,
+    )
+    .coins = coins;
+}
+
+/// 向玩家背包添加道具，返回道具ID
+fn give_item(game_state: &mut GameState, player_id: &str, item_name: &str) -> String {
+    let item = game_state
+        .rule_engine
+        .create_item_from_name(item_name)
+        .unwrap();
+    let item_id = item.id.clone();
+    game_state
+        .players
+        .get_mut(player_id)
+        .unwrap()
+        .inventory
+        .push(item);
+    item_id
+}
+
+/// 创建游戏状态：p1（30货币，持有测试剑）与 p2（10货币，持有测试药水）位于码头，p3 位于工厂
+fn create_game_state() -> (GameState, String, String) {
+    let mut game_state = common::create_game_state("test_trade", get_test_rules());
+    add_player(&mut game_state, "p1", "码头", 30);
+    add_player(&mut game_state, "p2", "码头", 10);
+    add_player(&mut game_state, "p3", "工厂", 0);
+    let sword = give_item(&mut game_state, "p1", "测试剑");
+    let potion = give_item(&mut game_state, "p2", "[HP10]测试药水");
+    (game_state, sword, potion)
+}
+
+fn offer(
+    game_state: &mut GameState,
+    target: &str,
+    request: TradeOfferRequest,
+) -> (MessageType, String) {
+    let results = game_state
+        .handle_trade_offer_action("p1", target, request)
+        .unwrap();
+    let trade_id = game_state
+        .trade_offers
+        .keys()
+        .max()
+        .cloned()
+        .unwrap_or_default();
+    (results.results[0].message_type.clone(), trade_id)
+}
+
+fn inventory_ids(game_state: &GameState, player_id: &str) -> Vec<String> {
+    game_state.players[player_id]
+        .inventory
+        .iter()
+        .map(|item| item.id.clone())
+        .collect()
+}
+
+/// 测试：接受交易后双方道具与货币一次性交换，并通知双方与导演
+#[test]
+fn test_offer_and_accept_exchanges_items_and_coins() {
+    let (mut game_state, sword, potion) = create_game_state();
+
+    let results = game_state
+        .handle_trade_offer_action(
+            "p1",
+            "p2",
+            TradeOfferRequest {
+                offered_item_ids: vec![sword.clone()],
+                offered_coins: 20,
+                requested_item_ids: vec![potion.clone()],
+                requested_coins: 5,
+            },
+        )
+        .unwrap();
+    assert_eq!(results.results.len(), 2);
+    assert_eq!(results.results[0].broadcast_players, vec!["p1".to_string()]);
+    assert!(results.results[0].broadcast_to_director);
+    assert_eq!(results.results[1].broadcast_players, vec!["p2".to_string()]);
+    assert_eq!(results.results[1].data["trade"]["id"], "trade-1");
+    assert_eq!(
+        results.results[1].data["offered_items"][0]["name"],
+        "测试剑"
+    );
+
+    // 发起交易不改变双方状态
+    assert_eq!(inventory_ids(&game_state, "p1"), vec![sword.clone()]);
+    assert_eq!(game_state.players["p1"].coins, 30);
+
+    let results = game_state
+        .handle_trade_accept_action("p2", "trade-1")
+        .unwrap();
+    assert_eq!(results.results.len(), 2);
+    assert_eq!(results.results[0].message_type, MessageType::SystemNotice);
+    assert!(results.results[0].broadcast_to_director);
+    assert_eq!(results.results[1].broadcast_players, vec!["p2".to_string()]);
+
+    assert_eq!(inventory_ids(&game_state, "p1"), vec![potion.clone()]);
+    assert_eq!(inventory_ids(&game_state, "p2"), vec![sword]);
+    assert_eq!(game_state.players["p1"].coins, 15);
+    assert_eq!(game_state.players["p2"].coins, 25);
+    assert!(game_state.trade_offers.is_empty());
+
+    // 同一交易不能重复接受
+    let results = game_state
+        .handle_trade_accept_action("p2", "trade-1")
+        .unwrap();
+    assert_eq!(results.results[0].message_type, MessageType::Info);
+    assert_eq!(game_state.players["p2"].coins, 25);
+}
+
+/// 测试：发起交易时校验地点、道具、货币与交易内容
+#[test]
+fn test_offer_validation() {
+    let (mut game_state, sword, potion) = create_game_state();
+
+    let cases = vec![
+        (
+            "p3",
+            TradeOfferRequest {
+                offered_item_ids: vec![sword.clone()],
+                ..Default::default()
+            },
+        ),
+        (
+            "p2",
+            TradeOfferRequest {
+                offered_coins: 31,
+                ..Default::default()
+            },
+        ),
+        (
+            "p2",
+            TradeOfferRequest {
+                offered_item_ids: vec![potion.clone()],
+                ..Default::default()
+            },
+        ),
+        (
+            "p2",
+            TradeOfferRequest {
+                requested_coins: 11,
+                ..Default::default()
+            },
+        ),
+        (
+            "p2",
+            TradeOfferRequest {
+                offered_item_ids: vec![sword.clone(), sword.clone()],
+                ..Default::default()
+            },
+        ),
+        ("p2", TradeOfferRequest::default()),
+        (
+            "p1",
+            TradeOfferRequest {
+                offered_coins: 1,
+                ..Default::default()
+            },
+        ),
+    ];
+    for (target, request) in cases {
+        let (message_type, _) = offer(&mut game_state, target, request);
+        assert_eq!(message_type, MessageType::Info);
+    }
+    assert!(game_state.trade_offers.is_empty());
+    assert_eq!(game_state.trade_sequence, 0);
+}
+
+/// 测试：接受时任何一项校验失败都不修改双方状态
+#[test]
+fn test_accept_is_atomic() {
+    let (mut game_state, sword, potion) = create_game_state();
+
+    // 接受方背包已满：2件道具换1件会超出容量
+    let extra = give_item(&mut game_state, "p1", "测试剑");
+    give_item(&mut game_state, "p2", "测试剑");
+    give_item(&mut game_state, "p2", "测试剑");
+    let (_, trade_id) = offer(
+        &mut game_state,
+        "p2",
+        TradeOfferRequest {
+            offered_item_ids: vec![sword.clone(), extra.clone()],
+            requested_item_ids: vec![potion.clone()],
+            ..Default::default()
+        },
+    );
+    let results = game_state
+        .handle_trade_accept_action("p2", &trade_id)
+        .unwrap();
+    assert_eq!(results.results[0].message_type, MessageType::Info);
+    assert_eq!(inventory_ids(&game_state, "p1"), vec![sword.clone(), extra]);
+    assert_eq!(game_state.players["p2"].inventory.len(), 3);
+    assert!(game_state.trade_offers.is_empty());
+
+    // 发起者在报价后花掉了货币
+    let (_, trade_id) = offer(
+        &mut game_state,
+        "p2",
+        TradeOfferRequest {
+            offered_coins: 30,
+            requested_item_ids: vec![potion.clone()],
+            ..Default::default()
+        },
+    );
+    game_state.players.get_mut("p1").unwrap().coins = 0;
+    game_state
+        .handle_trade_accept_action("p2", &trade_id)
+        .unwrap();
+    assert!(inventory_ids(&game_state, "p2").contains(&potion));
+    assert_eq!(game_state.players["p2"].coins, 10);
+
+    // 双方已不在同一地点
+    game_state.players.get_mut("p1").unwrap().coins = 30;
+    let (_, trade_id) = offer(
+        &mut game_state,
+        "p2",
+        TradeOfferRequest {
+            offered_coins: 5,
+            ..Default::default()
+        },
+    );
+    game_state.players.get_mut("p1").unwrap().location = "工厂".to_string();
+    game_state
+        .handle_trade_accept_action("p2", &trade_id)
+        .unwrap();
+    assert_eq!(game_state.players["p1"].coins, 30);
+    assert_eq!(game_state.players["p2"].coins, 10);
+}
+
+/// 测试：拒绝、撤回与过期
+#[test]
+fn test_decline_cancel_and_expiry() {
+    let (mut game_state, sword, _) = create_game_state();
+    let request = TradeOfferRequest {
+        offered_item_ids: vec![sword.clone()],
+        ..Default::default()
+    };
+
+    // 只有交易对象可以拒绝
+    let (_, trade_id) = offer(&mut game_state, "p2", request.clone());
+    let results = game_state
+        .handle_trade_decline_action("p1", &trade_id)
+        .unwrap();
+    assert_eq!(results.results[0].message_type, MessageType::Info);
+    let results = game_state
+        .handle_trade_decline_action("p2", &trade_id)
+        .unwrap();
+    assert_eq!(results.results.len(), 2);
+    assert_eq!(results.results[1].broadcast_players, vec!["p1".to_string()]);
+    assert!(game_state.trade_offers.is_empty());
+
+    // 只有发起者可以撤回
+    let (_, trade_id) = offer(&mut game_state, "p2", request.clone());
+    let results = game_state
+        .handle_trade_cancel_action("p2", &trade_id)
+        .unwrap();
+    assert_eq!(results.results[0].message_type, MessageType::Info);
+    game_state
+        .handle_trade_cancel_action("p1", &trade_id)
+        .unwrap();
+    assert!(game_state.trade_offers.is_empty());
+
+    // 超时后无法接受
+    game_state.action_time = Some(Utc::now());
+    let (_, trade_id) = offer(&mut game_state, "p2", request);
+    game_state.action_time = Some(Utc::now() + Duration::seconds(TRADE_OFFER_TIMEOUT_SECONDS + 1));
+    assert!(game_state.pending_trade_offers_for("p2").is_empty());
+    let results = game_state
+        .handle_trade_accept_action("p2", &trade_id)
+        .unwrap();
+    assert_eq!(results.results[0].message_type, MessageType::Info);
+    assert_eq!(inventory_ids(&game_state, "p1"), vec![sword]);
+    assert!(game_state.trade_offers.is_empty());
+}
+
+/// 测试：通过调度器发起与接受交易，玩家视角包含待处理的交易
+#[test]
+fn test_trade_through_scheduler() {
+    let (mut game_state, sword, _) = create_game_state();
+    game_state.night_start_time = Some(Utc::now() - Duration::hours(1));
+    game_state.night_end_time = Some(Utc::now() + Duration::hours(1));
+
+    let params = ActionParams::from_json(&json!({
+        "target_player_id": "p2",
+        "item_ids": [sword],
+        "request_coins": 10
+    }))
+    .unwrap();
+    PlayerActionScheduler::dispatch(&mut game_state, "p1", "trade_offer", params).unwrap();
+
+    let receiver = game_state.players["p2"].clone();
+    let message = MessageBroadcaster::generate_player_message(&game_state, &receiver, None);
+    assert_eq!(
+        message["game_data"]["trade_offers"][0]["id"],
+        json!("trade-1")
+    );
+    let outsider = game_state.players["p3"].clone();
+    let message = MessageBroadcaster::generate_player_message(&game_state, &outsider, None);
+    assert_eq!(message["game_data"]["trade_offers"], json!([]));
+
+    let params = ActionParams::from_json(&json!({"trade_id": "trade-1"})).unwrap();
+    PlayerActionScheduler::dispatch(&mut game_state, "p2", "trade_accept", params).unwrap();
+    assert_eq!(game_state.players["p1"].coins, 40);
+    assert_eq!(game_state.players["p2"].coins, 0);
+    assert_eq!(inventory_ids(&game_state, "p2").len(), 2);
+}
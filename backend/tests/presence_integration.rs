@@ -0,0 +1,171 @@
+//! 玩家在线状态集成测试
+//! 测试在线状态的上线、离线记录，导演收到的上线/离线事件，导演视图中的在线状态以及心跳相关配置
+
+mod common;
+
+use chrono::Utc;
+use common::{add_test_player, test_rules};
+use royale_arena_backend::websocket::broadcaster::MessageBroadcaster;
+use royale_arena_backend::websocket::game_connection_manager::{
+    ConnectionMessage, DIRECTOR_USER_ID, GameConnectionManager,
+};
+use royale_arena_backend::websocket::heartbeat::HeartbeatConfig;
+use royale_arena_backend::websocket::models::{ConnectionType, GameState};
+use royale_arena_backend::websocket::outbound_queue::{
+    CloseReason, DEFAULT_OUTBOUND_QUEUE_CAPACITY, OutboundReceiver,
+};
+use royale_arena_backend::websocket::presence::{PresenceStatus, PresenceTracker};
+use royale_arena_backend::websocket::replay_buffer::ReplayEventType;
+use serde_json::{Value as JsonValue, json};
+use std::time::Duration;
+
+/// 测试规则配置
+fn get_test_rules() -> JsonValue {
+    test_rules(json!({
+      "map": {"places": ["码头"]},
+      "player": {"max_backpack_items": 3},
+      "death_item_disposition": "vanish"
+    }))
+}
+
+/// 接收导演连接的下一条在线状态事件
+async fn next_presence_event(rx: &OutboundReceiver) -> JsonValue {
+    let message = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    let ConnectionMessage::Presence(event) = message.message else {
+        panic!("expected presence event");
+    };
+    event
+}
+
+/// 确认导演连接没有待接收的消息
+async fn assert_no_message(rx: &OutboundReceiver) {
+    assert!(
+        tokio::time::timeout(Duration::from_millis(50), rx.recv())
+            .await
+            .is_err()
+    );
+}
+
+#[test]
+fn test_presence_tracker_records_join_and_leave() {
+    let mut tracker = PresenceTracker::new();
+    let joined_at = Utc::now();
+
+    let event = tracker.join("p1", joined_at).unwrap();
+    assert_eq!(event.status, PresenceStatus::Join);
+    assert_eq!(event.player_id, "p1");
+    // 已在线时再次上线、从未上线时离线都不会产生事件
+    assert!(tracker.join("p1", joined_at).is_none());
+    assert!(tracker.leave("p2", joined_at).is_none());
+
+    let presence = &tracker.snapshot()["p1"];
+    assert!(presence.online);
+    assert_eq!(presence.connected_at, Some(joined_at));
+    assert_eq!(presence.last_seen, None);
+
+    let left_at = Utc::now();
+    let event = tracker.leave("p1", left_at).unwrap();
+    assert_eq!(event.status, PresenceStatus::Leave);
+    assert!(tracker.leave("p1", left_at).is_none());
+
+    let presence = &tracker.snapshot()["p1"];
+    assert!(!presence.online);
+    assert_eq!(presence.connected_at, None);
+    assert_eq!(presence.last_seen, Some(left_at));
+
+    tracker.join("p1", Utc::now());
+    tracker.leave_all(left_at);
+    assert!(!tracker.snapshot()["p1"].online);
+}
+
+#[tokio::test]
+async fn test_directors_receive_join_and_leave_events() {
+    let manager = GameConnectionManager::new(DEFAULT_OUTBOUND_QUEUE_CAPACITY);
+    let (_, director_rx) = manager
+        .add_connection(DIRECTOR_USER_ID.to_string(), ConnectionType::Director)
+        .await;
+
+    let (first, _first_rx) = manager
+        .add_connection("p1".to_string(), ConnectionType::Actor)
+        .await;
+    let event = next_presence_event(&director_rx).await;
+    assert_eq!(event["player_id"], json!("p1"));
+    assert_eq!(event["status"], json!("join"));
+
+    // 同一玩家的第二个连接不会再次上线
+    let (second, _second_rx) = manager
+        .add_connection("p1".to_string(), ConnectionType::Actor)
+        .await;
+    assert_no_message(&director_rx).await;
+
+    manager.remove_connection(&first).await;
+    assert_no_message(&director_rx).await;
+    assert!(manager.presence().await["p1"].online);
+
+    // 最后一个连接断开时离线
+    manager.remove_connection(&second).await;
+    let event = next_presence_event(&director_rx).await;
+    assert_eq!(event["player_id"], json!("p1"));
+    assert_eq!(event["status"], json!("leave"));
+
+    let presence = manager.presence().await;
+    assert!(!presence["p1"].online);
+    assert!(presence["p1"].last_seen.is_some());
+
+    // 在线状态事件保留在导演的补发缓冲区中
+    let (seq, events) = manager.catch_up(DIRECTOR_USER_ID, Some(0)).await;
+    assert_eq!(seq, 2);
+    let events = events.unwrap();
+    assert_eq!(events.len(), 2);
+    assert!(
+        events
+            .iter()
+            .all(|event| event.event_type == ReplayEventType::Presence)
+    );
+}
+
+#[tokio::test]
+async fn test_director_view_includes_presence() {
+    let manager = GameConnectionManager::new(DEFAULT_OUTBOUND_QUEUE_CAPACITY);
+    let mut game_state = GameState::new("test_presence".to_string(), get_test_rules());
+    add_test_player(&mut game_state, "p1", "玩家p1", "");
+    add_test_player(&mut game_state, "p2", "玩家p2", "");
+
+    let (_, _rx) = manager
+        .add_connection("p1".to_string(), ConnectionType::Actor)
+        .await;
+    let presence = manager.presence().await;
+    let message = MessageBroadcaster::generate_director_message(&game_state, None, &presence);
+
+    // 从未连接过的玩家显示为离线
+    assert_eq!(
+        message["game_data"]["presence"]["p1"]["online"],
+        json!(true)
+    );
+    assert_eq!(
+        message["game_data"]["presence"]["p2"],
+        json!({ "online": false, "connected_at": null, "last_seen": null })
+    );
+
+    // 断开全部连接后所有玩家离线
+    manager.disconnect_all_connections().await;
+    assert!(!manager.presence().await["p1"].online);
+}
+
+#[test]
+fn test_heartbeat_defaults_and_idle_close_code() {
+    let heartbeat = HeartbeatConfig::default();
+    assert_eq!(heartbeat.ping_interval, Duration::from_secs(30));
+    assert_eq!(heartbeat.idle_timeout, Duration::from_secs(90));
+    assert!(heartbeat.idle_timeout > heartbeat.ping_interval);
+
+    assert_eq!(CloseReason::IdleTimeout.close_code(), 1001);
+    assert!(
+        CloseReason::IdleTimeout
+            .description()
+            .starts_with("idle_timeout")
+    );
+}
@@ -0,0 +1,147 @@
+//! 断线重连事件补发集成测试
+//! 测试事件缓冲区的序号分配与淘汰，以及连接管理器按接收者补发错过的事件
+
+use royale_arena_backend::websocket::game_connection_manager::{
+    ConnectionMessage, DIRECTOR_USER_ID, GameConnectionManager,
+};
+use royale_arena_backend::websocket::models::ConnectionType;
+use royale_arena_backend::websocket::outbound_queue::DEFAULT_OUTBOUND_QUEUE_CAPACITY;
+use royale_arena_backend::websocket::replay_buffer::{ReplayBuffer, ReplayEventType};
+use serde_json::json;
+
+/// 模拟广播器生成的视图
+fn view(log_message: Option<&str>) -> serde_json::Value {
+    json!({
+        "global_state": {},
+        "game_data": {},
+        "action_result": log_message.map(|log_message| json!({ "log_message": log_message })),
+    })
+}
+
+#[test]
+fn test_replay_buffer_sequence_and_eviction() {
+    let mut buffer = ReplayBuffer::new(3);
+    assert_eq!(buffer.events_since(0), Some(vec![]));
+
+    for index in 1..=5 {
+        let seq = buffer.push(Some((
+            ReplayEventType::ActionResult,
+            json!({ "index": index }),
+        )));
+        assert_eq!(seq, index);
+    }
+    // 不需要补发的消息只占用序号
+    assert_eq!(buffer.push(None), 6);
+    assert_eq!(buffer.last_seq(), 6);
+
+    // 缓冲区保留最近3个事件（序号3-5）
+    let events = buffer.events_since(2).unwrap();
+    assert_eq!(
+        events.iter().map(|event| event.seq).collect::<Vec<_>>(),
+        vec![3, 4, 5]
+    );
+    assert_eq!(buffer.events_since(4).unwrap().len(), 1);
+    assert_eq!(buffer.events_since(6), Some(vec![]));
+
+    // 缺口超出缓冲区范围或序号来自未来（如服务器重启）时无法补发
+    assert!(buffer.events_since(1).is_none());
+    assert!(buffer.events_since(7).is_none());
+}
+
+#[test]
+fn test_replay_event_format() {
+    let mut buffer = ReplayBuffer::new(10);
+    buffer.push(Some((
+        ReplayEventType::SystemMessage,
+        json!({ "message": "导演广播" }),
+    )));
+    assert_eq!(
+        serde_json::to_value(buffer.events_since(0).unwrap()).unwrap(),
+        json!([{ "seq": 1, "type": "system_message", "data": { "message": "导演广播" } }])
+    );
+}
+
+#[tokio::test]
+async fn test_catch_up_after_reconnect() {
+    let manager = GameConnectionManager::new(DEFAULT_OUTBOUND_QUEUE_CAPACITY);
+
+    // 在线期间收到的消息带有递增序号
+    let (handle, rx) = manager
+        .add_connection("p1".to_string(), ConnectionType::Actor)
+        .await;
+    manager
+        .broadcast_to_player("p1", view(Some("搜索")), None)
+        .await
+        .unwrap();
+    let message = rx.recv().await.unwrap();
+    assert_eq!(message.seq, 1);
+    assert!(matches!(message.message, ConnectionMessage::GameState(_)));
+    manager.remove_connection(&handle).await;
+
+    // 离线期间的消息仍然分配序号并保留事件
+    manager
+        .broadcast_to_player("p1", view(Some("受到攻击")), None)
+        .await
+        .unwrap();
+    manager
+        .broadcast_to_player("p1", view(None), None)
+        .await
+        .unwrap();
+    manager
+        .broadcast_to_player("p1", view(Some("收到传音")), None)
+        .await
+        .unwrap();
+    manager
+        .broadcast_to_player("p2", view(Some("无关")), None)
+        .await
+        .unwrap();
+
+    let (seq, events) = manager.catch_up("p1", Some(1)).await;
+    assert_eq!(seq, 4);
+    let events = events.unwrap();
+    assert_eq!(
+        events
+            .iter()
+            .map(|event| (event.seq, event.data["log_message"].clone()))
+            .collect::<Vec<_>>(),
+        vec![(2, json!("受到攻击")), (4, json!("收到传音"))]
+    );
+
+    // 未提供序号时只返回当前序号
+    assert_eq!(manager.catch_up("p1", None).await, (4, None));
+    // 序号来自其他会话时无法补发
+    assert_eq!(manager.catch_up("p1", Some(10)).await, (4, None));
+    assert_eq!(manager.catch_up("p3", Some(0)).await, (0, Some(vec![])));
+    assert_eq!(manager.catch_up("p3", Some(5)).await, (0, None));
+}
+
+#[tokio::test]
+async fn test_directors_share_sequence() {
+    let manager = GameConnectionManager::new(DEFAULT_OUTBOUND_QUEUE_CAPACITY);
+    let (_, rx1) = manager
+        .add_connection(DIRECTOR_USER_ID.to_string(), ConnectionType::Director)
+        .await;
+    let (_, rx2) = manager
+        .add_connection(DIRECTOR_USER_ID.to_string(), ConnectionType::Director)
+        .await;
+
+    manager
+        .broadcast_to_directors(view(Some("天气变化")), None)
+        .await
+        .unwrap();
+    assert_eq!(rx1.recv().await.unwrap().seq, 1);
+    assert_eq!(rx2.recv().await.unwrap().seq, 1);
+
+    // 断开通知作为系统消息保留
+    manager
+        .disconnect_all_connections_with_message("游戏已暂停")
+        .await;
+    let message = rx1.recv().await.unwrap();
+    assert_eq!(message.seq, 2);
+    assert!(matches!(message.message, ConnectionMessage::System(_)));
+
+    let (_, events) = manager.catch_up(DIRECTOR_USER_ID, Some(1)).await;
+    let events = events.unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].event_type, ReplayEventType::SystemMessage);
+}
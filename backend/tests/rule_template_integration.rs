@@ -1,8 +1,17 @@
+mod common;
+
+use common::test_rules;
 use royale_arena_backend::{
     admin::{AdminService, LoginRequest},
     auth::{AuthService, JwtManager},
     config::AppConfig,
-    rule_template::{CreateRuleTemplateRequest, RuleTemplateService, UpdateRuleTemplateRequest},
+    game::{CreateGameRequest, GameService},
+    rule_template::{
+        CreateRuleTemplateRequest, RollbackRuleTemplateRequest, RuleTemplateError,
+        RuleTemplateService, UpdateRuleTemplateRequest,
+        bundle::{BUNDLE_FORMAT, ImportAction, RuleTemplateBundle},
+        diff::JsonDiffOp,
+    },
 };
 use serde_json::json;
 use sqlx::mysql::MySqlPool;
@@ -26,9 +35,14 @@ async fn test_rule_template_complete_flow(
         database_url: "test://dummy".to_string(),
         jwt_secret: "test-secret-key-for-testing-purposes-only".to_string(),
         jwt_expiration_hours: 24,
+        game_token_expiration_minutes: 120,
         bcrypt_cost: 4, // 降低成本以加快测试
         server_port: 3000,
         api_prefix: "/api".to_string(),
+        ws_outbound_queue_capacity: 256,
+        ws_ping_interval_seconds: 30,
+        ws_idle_timeout_seconds: 90,
+        archive_import_max_mb: 64,
     };
 
     // 创建服务
@@ -65,7 +79,7 @@ async fn test_rule_template_complete_flow(
     let _admin_token = login_response.token;
 
     // 测试数据
-    let test_rules_config = json!({
+    let test_rules_config = test_rules(json!({
         "game_flow": {
             "day_duration": 300,
             "night_duration": 900
@@ -94,7 +108,7 @@ async fn test_rule_template_complete_flow(
             "max_moves": 1
         },
         "teammate_behavior": 0
-    });
+    }));
 
     // 测试 1: 创建规则模版
     let create_request = CreateRuleTemplateRequest {
@@ -122,7 +136,7 @@ async fn test_rule_template_complete_flow(
         template_name: "集成测试快速模版".to_string(),
         description: Some("快速游戏模式".to_string()),
         is_active: Some(false),
-        rules_config: json!({
+        rules_config: test_rules(json!({
             "game_flow": {
                 "day_duration": 180,
                 "night_duration": 540
@@ -131,7 +145,7 @@ async fn test_rule_template_complete_flow(
                 "max_life": 80,
                 "max_strength": 80
             }
-        }),
+        })),
     };
 
     let created_template2 = rule_template_service
@@ -190,7 +204,7 @@ async fn test_rule_template_complete_flow(
     assert_eq!(search_test_templates[0].template_name, "集成测试经典模版");
 
     // 测试 7: 更新规则模版
-    let updated_rules_config = json!({
+    let updated_rules_config = test_rules(json!({
         "game_flow": {
             "day_duration": 400,
             "night_duration": 1000
@@ -209,7 +223,7 @@ async fn test_rule_template_complete_flow(
             "strength_recovery": 1200,
             "max_moves": 2
         }
-    });
+    }));
 
     let update_request = UpdateRuleTemplateRequest {
         template_name: Some("集成测试经典模版v2".to_string()),
@@ -406,7 +420,7 @@ async fn test_rule_template_complete_flow(
         template_name: "服务层测试模版".to_string(),
         description: Some("这是一个服务层测试模版".to_string()),
         is_active: Some(true),
-        rules_config: json!({
+        rules_config: test_rules(json!({
             "game_flow": {
                 "day_duration": 300,
                 "night_duration": 900
@@ -415,7 +429,7 @@ async fn test_rule_template_complete_flow(
                 "max_life": 100,
                 "max_strength": 100
             }
-        }),
+        })),
     };
 
     let service_created = rule_template_service
@@ -434,12 +448,12 @@ async fn test_rule_template_complete_flow(
         template_name: Some("更新的服务层测试模版".to_string()),
         description: Some("更新后的描述".to_string()),
         is_active: Some(false),
-        rules_config: Some(json!({
+        rules_config: Some(test_rules(json!({
             "game_flow": {
                 "day_duration": 600,
                 "night_duration": 1200
             }
-        })),
+        }))),
     };
 
     let service_updated = rule_template_service
@@ -494,3 +508,199 @@ async fn test_rule_template_complete_flow(
     println!("✅ 服务层测试全部通过！");
     Ok(())
 }
+
+#[sqlx::test(migrations = "./migrations")]
+async fn test_rule_template_revisions(pool: MySqlPool) -> Result<(), Box<dyn std::error::Error>> {
+    let rule_template_service = RuleTemplateService::new(pool.clone());
+    let game_service = GameService::new(pool.clone(), 4);
+
+    // 创建模版时记录版本1
+    let created = rule_template_service
+        .create_template(CreateRuleTemplateRequest {
+            template_name: "版本测试模版".to_string(),
+            description: None,
+            is_active: Some(true),
+            rules_config: test_rules(json!({"player": {"max_life": 100}})),
+        })
+        .await?;
+    assert_eq!(created.current_revision, 1);
+    let template_id = created.id.clone();
+
+    // 仅修改名称不产生新版本
+    let renamed = rule_template_service
+        .update_template(
+            template_id.clone(),
+            UpdateRuleTemplateRequest {
+                template_name: Some("版本测试模版v1".to_string()),
+                description: None,
+                is_active: None,
+                rules_config: None,
+            },
+        )
+        .await?;
+    assert_eq!(renamed.current_revision, 1);
+
+    // 修改规则配置产生版本2
+    let updated = rule_template_service
+        .update_template(
+            template_id.clone(),
+            UpdateRuleTemplateRequest {
+                template_name: None,
+                description: None,
+                is_active: None,
+                rules_config: Some(test_rules(json!({
+                    "player": {"max_life": 120},
+                    "spectator": {"delay_seconds": 30}
+                }))),
+            },
+        )
+        .await?;
+    assert_eq!(updated.current_revision, 2);
+
+    let revisions = rule_template_service.get_revisions(&template_id).await?;
+    assert_eq!(
+        revisions.iter().map(|r| r.revision).collect::<Vec<_>>(),
+        vec![2, 1]
+    );
+    assert_eq!(
+        revisions[1].rules_config,
+        test_rules(json!({"player": {"max_life": 100}}))
+    );
+
+    // 版本差异
+    let diff = rule_template_service
+        .diff_revisions(&template_id, 1, 2)
+        .await?;
+    assert_eq!(diff.changes.len(), 2);
+    assert_eq!(diff.changes[0].path, "$.player.max_life");
+    assert_eq!(diff.changes[0].op, JsonDiffOp::Changed);
+    assert_eq!(diff.changes[1].path, "$.spectator");
+    assert_eq!(diff.changes[1].op, JsonDiffOp::Added);
+    assert!(
+        rule_template_service
+            .diff_revisions(&template_id, 1, 99)
+            .await
+            .is_err()
+    );
+
+    // 游戏记录创建时使用的模版版本
+    game_service
+        .create_game(CreateGameRequest {
+            id: "revision_test_game".to_string(),
+            name: "版本测试游戏".to_string(),
+            description: None,
+            director_password: "password".to_string(),
+            max_players: 10,
+            rule_template_id: template_id.clone(),
+        })
+        .await?;
+    let game = game_service
+        .get_game_by_id_with_player_counts("revision_test_game")
+        .await?;
+    assert_eq!(game.template_id, Some(template_id.clone()));
+    assert_eq!(game.template_revision, Some(2));
+
+    // 回滚到版本1会以其配置创建版本3
+    let rolled_back = rule_template_service
+        .rollback_template(
+            template_id.clone(),
+            RollbackRuleTemplateRequest { revision: 1 },
+        )
+        .await?;
+    assert_eq!(rolled_back.current_revision, 3);
+    assert_eq!(
+        rolled_back.rules_config,
+        test_rules(json!({"player": {"max_life": 100}}))
+    );
+    assert_eq!(
+        rule_template_service
+            .get_revisions(&template_id)
+            .await?
+            .len(),
+        3
+    );
+
+    // 回滚到不存在的版本应返回错误
+    assert!(
+        rule_template_service
+            .rollback_template(template_id, RollbackRuleTemplateRequest { revision: 42 })
+            .await
+            .is_err()
+    );
+
+    Ok(())
+}
+
+#[sqlx::test(migrations = "./migrations")]
+async fn test_rule_template_export_import(
+    pool: MySqlPool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let rule_template_service = RuleTemplateService::new(pool.clone());
+
+    let created = rule_template_service
+        .create_template(CreateRuleTemplateRequest {
+            template_name: "导出测试模版".to_string(),
+            description: Some("导出测试".to_string()),
+            is_active: Some(true),
+            rules_config: test_rules(json!({"player": {"max_life": 100}})),
+        })
+        .await?;
+
+    // 单个导出包含格式信息、元数据与规则配置
+    let bundle = rule_template_service.export_template(&created.id).await?;
+    assert_eq!(bundle.format, BUNDLE_FORMAT);
+    assert_eq!(bundle.templates.len(), 1);
+    assert_eq!(bundle.templates[0].template_name, "导出测试模版");
+    assert_eq!(bundle.templates[0].revision, Some(1));
+    assert_eq!(
+        bundle.templates[0].rules_config,
+        test_rules(json!({"player": {"max_life": 100}}))
+    );
+
+    // 全部导出
+    let all = rule_template_service.export_all_templates().await?;
+    assert!(
+        all.templates
+            .iter()
+            .any(|entry| entry.template_name == "导出测试模版")
+    );
+
+    // 同名模版默认拒绝导入并报告冲突
+    let mut modified: RuleTemplateBundle = serde_json::from_value(serde_json::to_value(&bundle)?)?;
+    modified.templates[0].rules_config = test_rules(json!({"player": {"max_life": 120}}));
+    let mut renamed = modified.templates[0].clone();
+    renamed.template_name = "导入测试模版".to_string();
+    modified.templates.push(renamed);
+
+    match rule_template_service
+        .import_bundle(modified.clone(), false)
+        .await
+    {
+        Err(RuleTemplateError::ImportNameConflict(conflicts)) => {
+            assert_eq!(conflicts, vec!["导出测试模版".to_string()])
+        }
+        other => panic!("应报告名称冲突: {:?}", other.map(|r| r.len())),
+    }
+
+    // 开启覆盖后更新同名模版（产生新版本）并新建其余模版
+    let results = rule_template_service.import_bundle(modified, true).await?;
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].action, ImportAction::Overwritten);
+    assert_eq!(results[0].template.id, created.id);
+    assert_eq!(results[0].template.current_revision, 2);
+    assert_eq!(results[1].action, ImportAction::Created);
+    assert_eq!(
+        results[1].template.rules_config,
+        test_rules(json!({"player": {"max_life": 120}}))
+    );
+
+    // 无效的导出包整体拒绝
+    let mut invalid = bundle;
+    invalid.format_version = 99;
+    assert!(matches!(
+        rule_template_service.import_bundle(invalid, true).await,
+        Err(RuleTemplateError::RulesValidationFailed(_))
+    ));
+
+    Ok(())
+}
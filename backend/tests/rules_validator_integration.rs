@@ -0,0 +1,251 @@
+//! 规则配置校验集成测试
+//! 测试规则校验器一次性返回全部问题、JSON路径与严重程度
+
+use royale_arena_backend::game::game_rule_engine::GameRuleEngine;
+use royale_arena_backend::game::rules_validator::{
+    RuleValidationIssue, RulesValidationReport, RulesValidator, ValidationSeverity,
+};
+use serde_json::json;
+
+/// 完整且合法的规则配置
+fn get_valid_rules() -> serde_json::Value {
+    json!({
+      "map": {
+        "places": ["码头", "工厂", "学校"],
+        "safe_places": ["学校"],
+        "edges": [
+          {"from": "码头", "to": "工厂", "cost": 3},
+          {"from": "工厂", "to": "学校"}
+        ]
+      },
+      "player": {
+        "max_life": 100,
+        "max_strength": 100,
+        "daily_life_recovery": 0,
+        "daily_strength_recovery": 40,
+        "search_cooldown": 30,
+        "max_backpack_items": 5,
+        "unarmed_damage": 5
+      },
+      "action_costs": {
+        "move": 5,
+        "search": 5,
+        "pick": 0,
+        "attack": 0,
+        "equip": 0,
+        "use": 0,
+        "throw": 0,
+        "deliver": 10
+      },
+      "rest_mode": {
+        "life_recovery": 25,
+        "strength_recovery": 1000,
+        "max_moves": 1
+      },
+      "teammate_behavior": 5,
+      "game_flow": {
+        "day_duration": 300,
+        "night_duration": 900,
+        "night_start_time": "22:00"
+      },
+      "items_config": {
+        "rarity_levels": [
+          {"internal_name": "common", "display_name": "普通", "prefix": "[绿]", "is_airdropped": false},
+          {"internal_name": "legendary", "display_name": "传说", "prefix": "[橙]", "is_airdropped": true}
+        ],
+        "items": {
+          "weapons": [
+            {
+              "internal_name": "common_weapon",
+              "display_names": ["[绿]佩剑", "[绿]短刀"],
+              "rarity": "common",
+              "properties": {"damage": 10, "votes": 1}
+            },
+            {
+              "internal_name": "legendary_weapon",
+              "display_names": ["[橙]神剑"],
+              "rarity": "legendary",
+              "properties": {"damage": 50, "votes": 3}
+            }
+          ],
+          "armors": [
+            {
+              "internal_name": "common_armor",
+              "display_names": ["[绿]皮甲"],
+              "rarity": "common",
+              "properties": {"defense": 5, "votes": 1}
+            }
+          ],
+          "upgraders": [
+            {
+              "internal_name": "natural_upgrader",
+              "display_names": ["[合]自然升级器"],
+              "rarity": "legendary"
+            }
+          ]
+        },
+        "upgrade_recipes": {
+          "natural_upgrader": [
+            {"ingredients": ["common_weapon"], "result": "legendary_weapon"}
+          ]
+        }
+      }
+    })
+}
+
+fn find_issue<'a>(report: &'a RulesValidationReport, path: &str) -> &'a RuleValidationIssue {
+    report
+        .issues
+        .iter()
+        .find(|issue| issue.path == path)
+        .unwrap_or_else(|| panic!("未找到路径 {} 的问题: {:?}", path, report.issues))
+}
+
+/// 测试：合法配置没有任何问题，并且可以被规则引擎解析
+#[test]
+fn test_valid_rules_have_no_issues() {
+    let rules = get_valid_rules();
+    let report = RulesValidator::validate(&rules);
+
+    assert!(report.valid);
+    assert!(report.issues.is_empty(), "{:?}", report.issues);
+    assert!(GameRuleEngine::from_json(&rules.to_string()).is_ok());
+}
+
+/// 测试：一次性返回全部语义错误及其JSON路径
+#[test]
+fn test_reports_every_semantic_error() {
+    let mut rules = get_valid_rules();
+    rules["map"]["safe_places"] = json!(["学校", "医院"]);
+    rules["map"]["edges"][1]["to"] = json!("海滩");
+    rules["action_costs"]["search"] = json!(-5);
+    rules["items_config"]["items"]["armors"][0]["display_names"] = json!(["[绿]短刀"]);
+    rules["items_config"]["items"]["weapons"][1]["rarity"] = json!("mythic");
+    rules["items_config"]["upgrade_recipes"]["natural_upgrader"][0]["result"] =
+        json!("missing_weapon");
+    rules["items_config"]["upgrade_recipes"]["unknown_upgrader"] = json!([]);
+
+    let report = RulesValidator::validate(&rules);
+    assert!(!report.valid);
+    assert_eq!(report.errors().count(), 7, "{:?}", report.issues);
+
+    for path in [
+        "$.map.safe_places[1]",
+        "$.map.edges[1].to",
+        "$.action_costs.search",
+        "$.items_config.items.armors[0].display_names[0]",
+        "$.items_config.items.weapons[1].rarity",
+        "$.items_config.upgrade_recipes.natural_upgrader[0].result",
+        "$.items_config.upgrade_recipes.unknown_upgrader",
+    ] {
+        assert_eq!(
+            find_issue(&report, path).severity,
+            ValidationSeverity::Error,
+            "{}",
+            path
+        );
+    }
+
+    let duplicate = find_issue(&report, "$.items_config.items.armors[0].display_names[0]");
+    assert!(
+        duplicate
+            .message
+            .contains("$.items_config.items.weapons[0].display_names[1]")
+    );
+    assert!(report.summary().contains("$.map.safe_places[1]"));
+}
+
+/// 测试：字段类型错误与格式错误的物品条目定位到具体元素
+#[test]
+fn test_reports_type_errors_with_paths() {
+    let mut rules = get_valid_rules();
+    rules["player"]["max_life"] = json!("一百");
+    rules["game_flow"]["night_start_time"] = json!("25:99");
+    rules["items_config"]["items"]["weapons"][0] = json!({"internal_name": "broken_weapon"});
+    rules["spectator"] = json!({ "delay_seconds": -30 });
+
+    let report = RulesValidator::validate(&rules);
+    assert!(!report.valid);
+    assert_eq!(
+        find_issue(&report, "$.player.max_life").message,
+        "必须是整数"
+    );
+    find_issue(&report, "$.game_flow.night_start_time");
+    assert_eq!(
+        find_issue(&report, "$.spectator.delay_seconds").message,
+        "不能为负数"
+    );
+    let weapon = find_issue(&report, "$.items_config.items.weapons[0]");
+    assert!(weapon.message.contains("display_names"));
+
+    // 非对象配置直接报错
+    let report = RulesValidator::validate(&json!("not an object"));
+    assert!(!report.valid);
+    assert_eq!(report.issues[0].path, "$");
+}
+
+/// 测试：缺失的必需配置节与字段记为错误，仅未定义的规则位记为警告
+#[test]
+fn test_missing_fields_are_errors() {
+    let rules = json!({
+        "game_flow": {
+            "day_duration": 180,
+            "night_duration": 540
+        },
+        "player": {
+            "max_life": 80,
+            "max_strength": 80
+        },
+        "teammate_behavior": 32
+    });
+
+    let report = RulesValidator::validate(&rules);
+    assert!(!report.valid);
+    assert_eq!(
+        find_issue(&report, "$.map").severity,
+        ValidationSeverity::Error
+    );
+    assert_eq!(
+        find_issue(&report, "$.player.unarmed_damage").severity,
+        ValidationSeverity::Error
+    );
+    assert_eq!(
+        find_issue(&report, "$.action_costs").severity,
+        ValidationSeverity::Error
+    );
+    assert_eq!(
+        find_issue(&report, "$.teammate_behavior").severity,
+        ValidationSeverity::Warning
+    );
+}
+
+/// 测试：逐项检查通过但规则引擎无法解析的配置记为错误
+#[test]
+fn test_rule_engine_failure_is_error() {
+    let mut rules = get_valid_rules();
+    rules["player"]["max_life"] = json!(i64::from(i32::MAX) + 1);
+
+    let report = RulesValidator::validate(&rules);
+    assert!(!report.valid);
+    let issue = find_issue(&report, "$");
+    assert_eq!(issue.severity, ValidationSeverity::Error);
+    assert!(issue.message.contains("规则引擎无法解析"));
+}
+
+/// 测试：校验结果序列化为路径、严重程度与描述
+#[test]
+fn test_report_serialization() {
+    let mut rules = get_valid_rules();
+    rules["rest_mode"]["max_moves"] = json!(-1);
+
+    let report = RulesValidator::validate(&rules);
+    assert_eq!(
+        serde_json::to_value(&report).unwrap(),
+        json!({
+            "valid": false,
+            "issues": [
+                {"path": "$.rest_mode.max_moves", "severity": "error", "message": "不能为负数"}
+            ]
+        })
+    );
+}
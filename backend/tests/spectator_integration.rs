@@ -0,0 +1,199 @@
+//! 观众视角集成测试
+//! 测试观众视图的隐私过滤、公开事件、延迟推送的顺序与去重以及首次推送前的初始视图
+
+mod common;
+
+use common::{add_test_player, test_rules};
+use royale_arena_backend::websocket::broadcaster::MessageBroadcaster;
+use royale_arena_backend::websocket::game_connection_manager::{
+    ConnectionMessage, GameConnectionManager, SPECTATOR_USER_ID,
+};
+use royale_arena_backend::websocket::models::{ConnectionType, GameState};
+use royale_arena_backend::websocket::outbound_queue::DEFAULT_OUTBOUND_QUEUE_CAPACITY;
+use royale_arena_backend::websocket::spectator_feed::SpectatorSnapshot;
+use serde_json::{Value as JsonValue, json};
+use std::time::Duration;
+
+/// 测试规则配置
+fn get_test_rules() -> JsonValue {
+    test_rules(json!({
+      "map": {"places": ["码头", "工厂", "学校"]},
+      "player": {"max_backpack_items": 3},
+      "death_item_disposition": "vanish",
+      "spectator": { "delay_seconds": 30 }
+    }))
+}
+
+/// 创建游戏状态：p1、p2 位于码头，p3 位于工厂
+fn create_game_state() -> GameState {
+    let mut game_state = common::create_game_state("test_spectator", get_test_rules());
+    for (player_id, location) in [("p1", "码头"), ("p2", "码头"), ("p3", "工厂")] {
+        add_test_player(
+            &mut game_state,
+            player_id,
+            &format!("玩家{}", player_id),
+            location,
+        );
+    }
+    game_state
+}
+
+/// 模拟观众视图
+fn view(alive_players: usize, event: Option<&str>) -> JsonValue {
+    json!({
+        "global_state": {},
+        "game_data": { "alive_players": alive_players },
+        "action_result": event.map(|message| json!({ "event": { "kind": "broadcast", "message": message } })),
+    })
+}
+
+#[test]
+fn test_spectator_view_is_privacy_filtered() {
+    let mut game_state = create_game_state();
+    assert_eq!(game_state.rule_engine.spectator.delay_seconds, 30);
+
+    let death = game_state
+        .kill_player("p2", Some("p1"), Some("p1"), "攻击")
+        .unwrap();
+    let message =
+        MessageBroadcaster::generate_spectator_message(&game_state, Some(&death.results[0]));
+
+    // 地点按地图配置顺序列出，只包含存活人数
+    assert_eq!(
+        message["game_data"],
+        json!({
+            "places": [
+                { "name": "码头", "is_destroyed": false, "player_count": 1 },
+                { "name": "工厂", "is_destroyed": false, "player_count": 1 },
+                { "name": "学校", "is_destroyed": false, "player_count": 0 }
+            ],
+            "alive_players": 2,
+            "total_players": 3
+        })
+    );
+
+    // 死亡事件不暴露击杀者与地点
+    assert_eq!(
+        message["action_result"]["event"],
+        json!({ "kind": "death", "player_name": "玩家p2", "reason": "攻击" })
+    );
+
+    let text = message.to_string();
+    for hidden in [
+        "p1\"",
+        "玩家p1",
+        "inventory",
+        "rules_config",
+        "shop",
+        "password",
+    ] {
+        assert!(!text.contains(hidden), "spectator view leaks {}", hidden);
+    }
+}
+
+#[test]
+fn test_only_public_events_reach_spectators() {
+    let mut game_state = create_game_state();
+
+    let broadcast = game_state.handle_broadcast("比赛开始").unwrap();
+    let message =
+        MessageBroadcaster::generate_spectator_message(&game_state, Some(&broadcast.results[0]));
+    assert_eq!(
+        message["action_result"]["event"],
+        json!({ "kind": "broadcast", "message": "比赛开始" })
+    );
+
+    // 私信不会出现在观众视图中
+    let private = game_state
+        .handle_director_message_to_player("p1", "秘密消息")
+        .unwrap();
+    let message =
+        MessageBroadcaster::generate_spectator_message(&game_state, Some(&private.results[0]));
+    assert_eq!(message["action_result"], JsonValue::Null);
+    assert!(!message.to_string().contains("秘密消息"));
+
+    // 缩圈公开地点状态，地点中的玩家随之死亡
+    let destroyed = game_state.handle_modify_place("工厂", true).unwrap();
+    let events: Vec<JsonValue> = destroyed
+        .results
+        .iter()
+        .filter_map(|result| result.spectator_event.clone())
+        .collect();
+    assert_eq!(
+        events,
+        vec![
+            json!({ "kind": "place_status", "place": "工厂", "is_destroyed": true }),
+            json!({ "kind": "death", "player_name": "玩家p3", "reason": "缩圈" }),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_spectator_feed_is_delayed_in_order() {
+    let manager = GameConnectionManager::new(DEFAULT_OUTBOUND_QUEUE_CAPACITY);
+    let (_, rx) = manager
+        .add_connection(SPECTATOR_USER_ID.to_string(), ConnectionType::Spectator)
+        .await;
+    // 尚无任何视图入队时可以使用实时公开状态
+    assert_eq!(manager.spectator_snapshot().await, SpectatorSnapshot::Empty);
+
+    let delay = Duration::from_millis(100);
+    manager
+        .broadcast_to_spectators(view(3, Some("第一条")), delay)
+        .await;
+    // 公开状态未变化且没有事件的视图被忽略
+    manager.broadcast_to_spectators(view(3, None), delay).await;
+    manager
+        .broadcast_to_spectators(view(2, Some("第二条")), delay)
+        .await;
+
+    // 延迟期间观众收不到任何消息，新连接只能收到占位视图
+    assert!(
+        tokio::time::timeout(Duration::from_millis(10), rx.recv())
+            .await
+            .is_err()
+    );
+    assert_eq!(
+        manager.spectator_snapshot().await,
+        SpectatorSnapshot::Pending
+    );
+    let placeholder = MessageBroadcaster::generate_spectator_placeholder();
+    assert!(placeholder["game_data"].is_null());
+
+    let mut received = Vec::new();
+    for _ in 0..2 {
+        let message = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        // 同时到期的视图连续推送，未发送的视图被之后的视图覆盖时只保留事件
+        let action_result = match message.message {
+            ConnectionMessage::GameState(view) => view["action_result"].clone(),
+            ConnectionMessage::ActionResult(action_result) => action_result,
+            other => panic!("unexpected message: {:?}", other),
+        };
+        received.push((message.seq, action_result["event"]["message"].clone()));
+    }
+    assert_eq!(received, vec![(1, json!("第一条")), (2, json!("第二条"))]);
+    assert!(
+        tokio::time::timeout(Duration::from_millis(150), rx.recv())
+            .await
+            .is_err()
+    );
+
+    // 新连接的初始快照为最近一次推送的视图
+    let SpectatorSnapshot::Published(snapshot) = manager.spectator_snapshot().await else {
+        panic!("expected published snapshot");
+    };
+    assert_eq!(snapshot["game_data"]["alive_players"], json!(2));
+    assert!(snapshot.get("action_result").is_none());
+
+    // 修改观战码时断开所有观众
+    manager
+        .disconnect_spectator_connections("观战码已变更")
+        .await;
+    let message = rx.recv().await.unwrap();
+    assert!(matches!(message.message, ConnectionMessage::System(_)));
+    assert!(manager.get_spectator_connections().await.is_empty());
+    assert!(rx.recv().await.is_none());
+}
@@ -0,0 +1,215 @@
+//! 状态增量同步集成测试
+//! 测试JSON Patch差异计算、增量模式下的版本号与补丁推送以及重新同步
+
+use royale_arena_backend::websocket::state_patch::{
+    PatchOperation, StateStream, StateSyncMode, StateUpdate, diff,
+};
+use serde_json::{Value as JsonValue, json};
+
+/// 解析JSON Pointer路径
+fn pointer_tokens(path: &str) -> Vec<String> {
+    path.split('/')
+        .skip(1)
+        .map(|token| token.replace("~1", "/").replace("~0", "~"))
+        .collect()
+}
+
+/// 按顺序应用补丁操作（测试用的最小实现）
+fn apply(document: &mut JsonValue, operations: &[PatchOperation]) {
+    for operation in operations {
+        let (path, value) = match operation {
+            PatchOperation::Add { path, value } | PatchOperation::Replace { path, value } => {
+                (path, Some(value.clone()))
+            }
+            PatchOperation::Remove { path } => (path, None),
+        };
+        let mut tokens = pointer_tokens(path);
+        let Some(last) = tokens.pop() else {
+            *document = value.unwrap();
+            continue;
+        };
+        let mut target = &mut *document;
+        for token in &tokens {
+            target = match target {
+                JsonValue::Object(map) => map.get_mut(token).unwrap(),
+                JsonValue::Array(items) => &mut items[token.parse::<usize>().unwrap()],
+                _ => panic!("invalid path {}", path),
+            };
+        }
+        match (target, value) {
+            (JsonValue::Object(map), Some(value)) => {
+                map.insert(last, value);
+            }
+            (JsonValue::Object(map), None) => {
+                map.remove(&last).unwrap();
+            }
+            (JsonValue::Array(items), value) => {
+                let index = last.parse::<usize>().unwrap();
+                match (operation, value) {
+                    (PatchOperation::Add { .. }, Some(value)) => items.insert(index, value),
+                    (PatchOperation::Replace { .. }, Some(value)) => items[index] = value,
+                    _ => {
+                        items.remove(index);
+                    }
+                }
+            }
+            _ => panic!("invalid path {}", path),
+        }
+    }
+}
+
+/// 模拟广播器生成的视图
+fn view(life: i32, places: &[&str], action_result: JsonValue) -> JsonValue {
+    json!({
+        "global_state": { "weather": 1.0 },
+        "game_data": {
+            "players": { "p1": { "life": life, "location": "码头" } },
+            "places": places,
+        },
+        "action_result": action_result,
+    })
+}
+
+#[test]
+fn test_diff_roundtrip() {
+    let old = json!({
+        "players": {
+            "p1": { "life": 100, "inventory": ["a", "b", "c"] },
+            "p2": { "life": 80 }
+        },
+        "a/b": 1,
+        "m~n": [1, 2],
+        "weather": 1.0
+    });
+    let new = json!({
+        "players": {
+            "p1": { "life": 90, "inventory": ["a", "d"] },
+            "p3": { "life": 100 }
+        },
+        "a/b": 2,
+        "m~n": [1, 2, 3, 4],
+        "weather": null
+    });
+
+    let operations = diff(&old, &new);
+    let mut patched = old.clone();
+    apply(&mut patched, &operations);
+    assert_eq!(patched, new);
+
+    // 特殊字符按RFC 6901转义
+    assert!(operations.contains(&PatchOperation::Replace {
+        path: "/a~1b".to_string(),
+        value: json!(2)
+    }));
+    assert!(operations.contains(&PatchOperation::Add {
+        path: "/m~0n/2".to_string(),
+        value: json!(3)
+    }));
+    assert!(operations.contains(&PatchOperation::Remove {
+        path: "/players/p2".to_string()
+    }));
+
+    // 相同的值不产生补丁
+    assert!(diff(&new, &new).is_empty());
+}
+
+#[test]
+fn test_patch_operation_format() {
+    let operations = diff(
+        &json!({ "life": 100, "dead": true }),
+        &json!({ "life": 90 }),
+    );
+    assert_eq!(
+        serde_json::to_value(&operations).unwrap(),
+        json!([
+            { "op": "remove", "path": "/dead" },
+            { "op": "replace", "path": "/life", "value": 90 }
+        ])
+    );
+}
+
+#[test]
+fn test_delta_stream_versions_and_patches() {
+    let mut stream = StateStream::new(StateSyncMode::Delta);
+
+    // 首次推送带版本号的完整快照
+    let StateUpdate::Snapshot(mut client_state) =
+        stream.next_update(view(100, &["码头"], json!(null)))
+    else {
+        panic!("expected snapshot");
+    };
+    assert_eq!(client_state["version"], json!(1));
+    assert_eq!(client_state["action_result"], json!(null));
+
+    // 后续推送仅包含变化部分，动作结果随补丁下发且不参与比较
+    let action_result = json!({ "log_message": "受到攻击" });
+    let StateUpdate::Patch(patch) = stream.next_update(view(90, &["码头"], action_result.clone()))
+    else {
+        panic!("expected patch");
+    };
+    assert_eq!(patch["version"], json!(2));
+    assert_eq!(patch["action_result"], action_result);
+    assert_eq!(
+        patch["patch"],
+        json!([{ "op": "replace", "path": "/game_data/players/p1/life", "value": 90 }])
+    );
+
+    let operations: Vec<PatchOperation> = serde_json::from_value(patch["patch"].clone()).unwrap();
+    client_state.as_object_mut().unwrap().remove("version");
+    client_state
+        .as_object_mut()
+        .unwrap()
+        .remove("action_result");
+    apply(&mut client_state, &operations);
+
+    let StateUpdate::Patch(patch) = stream.next_update(view(90, &["码头", "工厂"], json!(null)))
+    else {
+        panic!("expected patch");
+    };
+    assert_eq!(patch["version"], json!(3));
+    let operations: Vec<PatchOperation> = serde_json::from_value(patch["patch"].clone()).unwrap();
+    apply(&mut client_state, &operations);
+
+    let mut expected = view(90, &["码头", "工厂"], json!(null));
+    expected.as_object_mut().unwrap().remove("action_result");
+    assert_eq!(client_state, expected);
+
+    // 重新同步返回当前版本的完整状态，不重复下发动作结果
+    let Some(StateUpdate::Snapshot(snapshot)) = stream.resync() else {
+        panic!("expected snapshot");
+    };
+    assert_eq!(snapshot["version"], json!(3));
+    assert_eq!(snapshot["action_result"], json!(null));
+    assert_eq!(snapshot["game_data"], expected["game_data"]);
+
+    // 重新同步不推进版本号
+    let StateUpdate::Patch(patch) = stream.next_update(view(80, &["码头", "工厂"], json!(null)))
+    else {
+        panic!("expected patch");
+    };
+    assert_eq!(patch["version"], json!(4));
+}
+
+#[test]
+fn test_snapshot_stream_keeps_full_views() {
+    let mut stream = StateStream::new(StateSyncMode::Snapshot);
+    assert!(stream.resync().is_none());
+
+    let action_result = json!({ "log_message": "搜索" });
+    let update = stream.next_update(view(100, &["码头"], action_result.clone()));
+    assert_eq!(
+        update,
+        StateUpdate::Snapshot(view(100, &["码头"], action_result))
+    );
+
+    let update = stream.next_update(view(90, &["码头"], json!(null)));
+    assert_eq!(
+        update,
+        StateUpdate::Snapshot(view(90, &["码头"], json!(null)))
+    );
+
+    assert_eq!(
+        stream.resync(),
+        Some(StateUpdate::Snapshot(view(90, &["码头"], json!(null))))
+    );
+}
@@ -0,0 +1,275 @@
+//! 队友行为规则集成测试
+//! 测试 teammate_behavior 位压缩规则：禁止队友伤害、禁止搜索到队友、观看队友状态与赠送队友物品
+
+mod common;
+
+use common::{common_rarity_levels, test_rules};
+use royale_arena_backend::game::models::MessageType;
+use royale_arena_backend::websocket::broadcaster::MessageBroadcaster;
+use royale_arena_backend::websocket::models::{
+    GameState, SearchResult, SearchResultType, SearchTarget,
+};
+use serde_json::json;
+
+/// 测试规则配置（指定队友行为规则）
+fn get_test_rules_with_teammate_behavior(mode: i32) -> serde_json::Value {
+    test_rules(json!({
+      "player": {"max_backpack_items": 2},
+      "teammate_behavior": mode,
+      "items_config": {
+        "rarity_levels": common_rarity_levels(),
+        "items": {
+          "weapons": [
+            {
+              "internal_name": "aoe_weapon",
+              "display_names": ["溅射锤"],
+              "rarity": "common",
+              "properties": {
+                "damage": 20,
+                "votes": 0,
+                "aoe_damage": 5
+              }
+            }
+          ],
+          "consumables": [
+            {
+              "name": "[HP10]测试药水",
+              "properties": {
+                "effect_type": "heal",
+                "effect_value": 10
+              }
+            }
+          ]
+        }
+      }
+    }))
+}
+
+fn add_test_player(game_state: &mut GameState, player_id: &str, team_id: u32) {
+    common::add_test_player(
+        game_state,
+        player_id,
+        &format!("玩家{}", player_id),
+        "位置1",
+    )
+    .team_id = Some(team_id);
+}
+
+fn set_search_result(game_state: &mut GameState, player_id: &str, target_id: &str) {
+    game_state
+        .players
+        .get_mut(player_id)
+        .unwrap()
+        .last_search_result = Some(SearchResult {
+        target_type: SearchResultType::Player,
+        target_id: target_id.to_string(),
+        target_name: target_id.to_string(),
+        is_visible: true,
+    });
+}
+
+fn searchable_players(game_state: &GameState, player_id: &str) -> Vec<String> {
+    game_state
+        .collect_search_targets(player_id)
+        .into_iter()
+        .filter_map(|target| match target {
+            SearchTarget::Player(id) => Some(id),
+            SearchTarget::Item(_) => None,
+        })
+        .collect()
+}
+
+/// 测试：队伍ID为0视为无队伍
+#[test]
+fn test_team_zero_is_not_teammate() {
+    let mut game_state = GameState::new(
+        "test_team_zero".to_string(),
+        get_test_rules_with_teammate_behavior(15),
+    );
+    add_test_player(&mut game_state, "a", 0);
+    add_test_player(&mut game_state, "b", 0);
+    add_test_player(&mut game_state, "c", 1);
+    add_test_player(&mut game_state, "d", 1);
+
+    assert!(!game_state.are_teammates("a", "b"));
+    assert!(game_state.are_teammates("c", "d"));
+    assert!(!game_state.are_teammates("c", "c"));
+}
+
+/// 测试：禁止队友伤害时无法攻击队友，溅射也不波及队友
+#[test]
+fn test_no_teammate_damage_blocks_attack_and_aoe() {
+    let mut game_state = GameState::new(
+        "test_no_damage".to_string(),
+        get_test_rules_with_teammate_behavior(1),
+    );
+    add_test_player(&mut game_state, "a", 1);
+    add_test_player(&mut game_state, "b", 1);
+    add_test_player(&mut game_state, "c", 2);
+
+    set_search_result(&mut game_state, "a", "b");
+    let results = game_state.handle_attack_action("a").unwrap();
+    assert_eq!(results.results.len(), 1);
+    assert_eq!(results.results[0].message_type, MessageType::Info);
+    assert_eq!(game_state.players.get("b").unwrap().life, 100);
+
+    let weapon = game_state
+        .rule_engine
+        .create_item_from_name("溅射锤")
+        .unwrap();
+    game_state
+        .players
+        .get_mut("a")
+        .unwrap()
+        .equip_weapon(weapon);
+    set_search_result(&mut game_state, "a", "c");
+    game_state.handle_attack_action("a").unwrap();
+
+    assert_eq!(game_state.players.get("c").unwrap().life, 80);
+    assert_eq!(
+        game_state.players.get("b").unwrap().life,
+        100,
+        "溅射不应波及队友"
+    );
+}
+
+/// 测试：未启用禁止队友伤害时可以攻击队友
+#[test]
+fn test_teammate_damage_allowed_without_flag() {
+    let mut game_state = GameState::new(
+        "test_damage_ok".to_string(),
+        get_test_rules_with_teammate_behavior(0),
+    );
+    add_test_player(&mut game_state, "a", 1);
+    add_test_player(&mut game_state, "b", 1);
+
+    set_search_result(&mut game_state, "a", "b");
+    game_state.handle_attack_action("a").unwrap();
+    assert_eq!(game_state.players.get("b").unwrap().life, 90);
+}
+
+/// 测试：禁止搜索到队友时搜索目标不包含队友
+#[test]
+fn test_no_teammate_search_hides_teammates() {
+    let mut game_state = GameState::new(
+        "test_no_search".to_string(),
+        get_test_rules_with_teammate_behavior(2),
+    );
+    add_test_player(&mut game_state, "a", 1);
+    add_test_player(&mut game_state, "b", 1);
+    add_test_player(&mut game_state, "c", 2);
+
+    assert_eq!(searchable_players(&game_state, "a"), vec!["c".to_string()]);
+
+    game_state.rule_engine.teammate_behavior.mode = 0;
+    let mut targets = searchable_players(&game_state, "a");
+    targets.sort();
+    assert_eq!(targets, vec!["b".to_string(), "c".to_string()]);
+}
+
+/// 测试：允许观看队友状态时玩家视角包含队友生命值、体力值与位置
+#[test]
+fn test_view_teammate_status_in_player_message() {
+    let mut game_state = GameState::new(
+        "test_view".to_string(),
+        get_test_rules_with_teammate_behavior(4),
+    );
+    add_test_player(&mut game_state, "a", 1);
+    add_test_player(&mut game_state, "b", 1);
+    add_test_player(&mut game_state, "c", 2);
+
+    let player = game_state.players.get("a").unwrap().clone();
+    let message = MessageBroadcaster::generate_player_message(&game_state, &player, None);
+    let actor_players = message["game_data"]["actor_players"].as_array().unwrap();
+
+    let teammate = actor_players.iter().find(|p| p["id"] == "b").unwrap();
+    assert_eq!(teammate["life"], json!(100));
+    assert_eq!(teammate["location"], json!("位置1"));
+
+    let enemy = actor_players.iter().find(|p| p["id"] == "c").unwrap();
+    assert!(enemy.get("life").is_none());
+
+    let myself = actor_players.iter().find(|p| p["id"] == "a").unwrap();
+    assert!(myself.get("life").is_none());
+}
+
+/// 测试：允许赠送队友物品时可转移背包道具，并检查目标背包容量
+#[test]
+fn test_gift_item_to_teammate() {
+    let mut game_state = GameState::new(
+        "test_gift".to_string(),
+        get_test_rules_with_teammate_behavior(8),
+    );
+    add_test_player(&mut game_state, "a", 1);
+    add_test_player(&mut game_state, "b", 1);
+    add_test_player(&mut game_state, "c", 2);
+
+    let potion = game_state
+        .rule_engine
+        .create_item_from_name("[HP10]测试药水")
+        .unwrap();
+    let item_id = potion.id.clone();
+    game_state
+        .players
+        .get_mut("a")
+        .unwrap()
+        .inventory
+        .push(potion);
+
+    // 不能赠送给非队友
+    let results = game_state.handle_gift_action("a", "c", &item_id).unwrap();
+    assert_eq!(results.results[0].message_type, MessageType::Info);
+    assert_eq!(game_state.players.get("a").unwrap().inventory.len(), 1);
+
+    let results = game_state.handle_gift_action("a", "b", &item_id).unwrap();
+    assert_eq!(results.results.len(), 2);
+    assert!(game_state.players.get("a").unwrap().inventory.is_empty());
+    assert_eq!(game_state.players.get("b").unwrap().inventory.len(), 1);
+
+    // 队友背包已满时拒绝赠送
+    for _ in 0..2 {
+        let extra = game_state
+            .rule_engine
+            .create_item_from_name("[HP10]测试药水")
+            .unwrap();
+        game_state
+            .players
+            .get_mut("a")
+            .unwrap()
+            .inventory
+            .push(extra);
+    }
+    let extra_id = game_state.players.get("a").unwrap().inventory[0].id.clone();
+    game_state.handle_gift_action("a", "b", &extra_id).unwrap();
+    let last_id = game_state.players.get("a").unwrap().inventory[0].id.clone();
+    let results = game_state.handle_gift_action("a", "b", &last_id).unwrap();
+    assert_eq!(results.results[0].message_type, MessageType::Info);
+    assert_eq!(game_state.players.get("b").unwrap().inventory.len(), 2);
+}
+
+/// 测试：未启用赠送规则时无法赠送
+#[test]
+fn test_gift_disabled_without_flag() {
+    let mut game_state = GameState::new(
+        "test_gift_off".to_string(),
+        get_test_rules_with_teammate_behavior(7),
+    );
+    add_test_player(&mut game_state, "a", 1);
+    add_test_player(&mut game_state, "b", 1);
+
+    let potion = game_state
+        .rule_engine
+        .create_item_from_name("[HP10]测试药水")
+        .unwrap();
+    let item_id = potion.id.clone();
+    game_state
+        .players
+        .get_mut("a")
+        .unwrap()
+        .inventory
+        .push(potion);
+
+    let results = game_state.handle_gift_action("a", "b", &item_id).unwrap();
+    assert_eq!(results.results[0].message_type, MessageType::Info);
+    assert_eq!(game_state.players.get("a").unwrap().inventory.len(), 1);
+}
@@ -1,5 +1,7 @@
+use royale_arena_backend::game::credentials::GameCredentials;
 use royale_arena_backend::game::{
-    CreateGameRequest, GameFilterType, GameListQuery, GameService, UpdateGameRequest,
+    CreateGameRequest, GameAuthenticationRole, GameFilterType, GameListQuery, GameService,
+    UpdateGameRequest,
 };
 use serde_json::json;
 use sqlx::MySqlPool;
@@ -15,7 +17,7 @@ async fn test_game_crud_operations(pool: MySqlPool) -> Result<(), Box<dyn std::e
         .execute(&pool)
         .await?;
 
-    let service = GameService::new(pool.clone());
+    let service = GameService::new(pool.clone(), 4);
 
     // 首先创建一个规则模板用于测试
     let template_id = Uuid::new_v4().to_string();
@@ -66,61 +68,58 @@ async fn test_game_crud_operations(pool: MySqlPool) -> Result<(), Box<dyn std::e
 
     // 测试3: 获取游戏列表
     let all_games_query = GameListQuery { filter: None };
-    let all_games = service.get_games(&all_games_query, false).await?;
+    let all_games = service.get_games(&all_games_query).await?;
     assert!(all_games.len() >= 1);
 
     let waiting_games_query = GameListQuery {
         filter: Some(GameFilterType::Waiting),
     };
-    let waiting_games = service.get_games(&waiting_games_query, false).await?;
+    let waiting_games = service.get_games(&waiting_games_query).await?;
     assert!(waiting_games.len() >= 1);
 
-    // 测试4: 验证游戏列表的权限控制行为
-    // 检查没有管理员权限时，游戏列表项中不包含导演密码
+    // 测试4: 游戏列表不输出导演密码
     for game in &all_games {
         assert!(
-            game.director_password.is_none(),
-            "Non-admin user should not see director password in game list"
+            serde_json::to_value(game)?
+                .get("director_password")
+                .is_none(),
+            "Game list should never expose director password"
         );
     }
 
-    // 检查有管理员权限时，游戏列表项中包含导演密码
-    let all_games_with_password = service.get_games(&all_games_query, true).await?;
-    assert!(all_games_with_password.len() >= 1);
-
-    // 验证至少有一个游戏包含导演密码
-    let game_with_password = all_games_with_password
-        .iter()
-        .find(|g| g.director_password.is_some());
-    assert!(
-        game_with_password.is_some(),
-        "Admin user should see director password in game list"
-    );
-
-    // 验证密码内容正确
-    if let Some(game) = game_with_password {
-        // 根据创建请求中的密码验证
-        assert_eq!(game.name, "updated_game_name");
-        assert_eq!(game.director_password.as_ref().unwrap(), "newpassword");
-    }
-
     // 测试5: 获取游戏详情（包含规则）
-    let game_without_rules = service
-        .get_game_by_id_with_player_counts(&updated_game.id, false)
+    let game_with_rules = service
+        .get_game_by_id_with_player_counts(&updated_game.id)
         .await?;
-    assert_eq!(game_without_rules.name, "updated_game_name");
-    assert_eq!(game_without_rules.rules_config, template_rules_config);
-
-    // 测试6: 验证权限控制行为
-    // 没有管理员权限时，不应该返回导演密码
-    assert!(game_without_rules.director_password.is_none());
+    assert_eq!(game_with_rules.name, "updated_game_name");
+    assert_eq!(game_with_rules.rules_config, template_rules_config);
 
-    // 有管理员权限时，应该返回导演密码
-    let game_with_password = service
-        .get_game_by_id_with_player_counts(&game_with_template.id, true)
-        .await?;
-    assert!(game_with_password.director_password.is_some());
-    assert_eq!(game_with_password.director_password.unwrap(), "newpassword");
+    // 测试6: 导演密码以哈希存储且不对外输出
+    assert!(
+        serde_json::to_value(&game_with_rules)?
+            .get("director_password")
+            .is_none()
+    );
+    let stored_game = service.get_game_by_id(&updated_game.id).await?;
+    assert!(GameCredentials::is_password_hash(
+        &stored_game.director_password
+    ));
+    assert!(GameCredentials::verify_password(
+        "newpassword",
+        &stored_game.director_password
+    ));
+    assert!(
+        serde_json::to_value(&stored_game)?
+            .get("director_password")
+            .is_none()
+    );
+    assert_eq!(
+        service
+            .authenticate_game(&updated_game.id, None, "newpassword")
+            .await?
+            .role,
+        GameAuthenticationRole::Director
+    );
 
     // 测试7: 验证错误情况
 
@@ -207,7 +206,7 @@ async fn test_game_crud_operations(pool: MySqlPool) -> Result<(), Box<dyn std::e
 
     // 验证游戏已删除
     let result = service
-        .get_game_by_id_with_player_counts(&game_with_template.id, false)
+        .get_game_by_id_with_player_counts(&game_with_template.id)
         .await;
     assert!(result.is_err());
 
@@ -240,7 +239,7 @@ async fn test_game_validation(pool: MySqlPool) -> Result<(), Box<dyn std::error:
     .execute(&pool)
     .await?;
 
-    let service = GameService::new(pool);
+    let service = GameService::new(pool, 4);
 
     // 测试参数验证
 
@@ -0,0 +1,179 @@
+//! 白天投票功能集成测试
+//! 测试玩家投票行动的时间限制以及导演投票结算的加权计票、平票与公示逻辑
+
+mod common;
+
+use chrono::{Duration, Utc};
+use common::{add_test_player, common_rarity_levels, test_rules};
+use royale_arena_backend::game::models::MessageType;
+use royale_arena_backend::websocket::actions::director_action_scheduler::{
+    DirectorActionParams, DirectorActionScheduler,
+};
+use royale_arena_backend::websocket::actions::player_action_scheduler::{
+    ActionParams, PlayerActionScheduler,
+};
+use royale_arena_backend::websocket::models::GameState;
+use serde_json::json;
+
+/// 测试规则配置（包含带票数的武器与防具）
+fn get_test_rules_with_votes() -> serde_json::Value {
+    test_rules(json!({
+      "items_config": {
+        "rarity_levels": common_rarity_levels(),
+        "items": {
+          "weapons": [
+            {
+              "internal_name": "vote_weapon",
+              "display_names": ["票剑"],
+              "rarity": "common",
+              "properties": {
+                "damage": 20,
+                "votes": 2
+              }
+            }
+          ],
+          "armors": [
+            {
+              "internal_name": "vote_armor",
+              "display_names": ["票甲"],
+              "rarity": "common",
+              "properties": {
+                "defense": 5,
+                "votes": 1
+              }
+            }
+          ]
+        }
+      }
+    }))
+}
+
+fn vote_params(target_player_id: &str) -> ActionParams {
+    ActionParams {
+        target_place: None,
+        place_name: None,
+        item_id: None,
+        slot_type: None,
+        target_player_id: Some(target_player_id.to_string()),
+        target_player_ids: None,
+        target_item_name: None,
+        message: None,
+        shop_buy_items: None,
+        item_ids: None,
+        coins: None,
+        request_item_ids: None,
+        request_coins: None,
+        trade_id: None,
+    }
+}
+
+fn settle_votes(game_state: &mut GameState) -> serde_json::Value {
+    let params = DirectorActionParams::from_json(&json!({})).unwrap();
+    let results = DirectorActionScheduler::dispatch(game_state, "vote_settlement", params)
+        .expect("投票结算应成功");
+    assert_eq!(results.results.len(), 1);
+    let result = &results.results[0];
+    assert!(result.broadcast_to_all);
+    assert_eq!(result.message_type, MessageType::SystemNotice);
+    result.data.clone()
+}
+
+/// 测试：夜晚行动时间内无法投票
+#[test]
+fn test_vote_rejected_during_night() {
+    let mut game_state = GameState::new("test_vote_night".to_string(), get_test_rules_with_votes());
+    add_test_player(&mut game_state, "p1", "玩家1", "位置1");
+    add_test_player(&mut game_state, "p2", "玩家2", "位置1");
+
+    game_state.night_start_time = Some(Utc::now() - Duration::hours(1));
+    game_state.night_end_time = Some(Utc::now() + Duration::hours(1));
+
+    let results =
+        PlayerActionScheduler::dispatch(&mut game_state, "p1", "vote", vote_params("p2")).unwrap();
+
+    assert_eq!(results.results[0].message_type, MessageType::Info);
+    assert!(game_state.votes.is_empty());
+}
+
+/// 测试：白天投票为匿名投票，重复投票覆盖之前的选择
+#[test]
+fn test_vote_is_anonymous_and_overwrites() {
+    let mut game_state = GameState::new("test_vote_day".to_string(), get_test_rules_with_votes());
+    add_test_player(&mut game_state, "p1", "玩家1", "位置1");
+    add_test_player(&mut game_state, "p2", "玩家2", "位置1");
+    add_test_player(&mut game_state, "p3", "玩家3", "位置1");
+
+    let results =
+        PlayerActionScheduler::dispatch(&mut game_state, "p1", "vote", vote_params("p2")).unwrap();
+    let result = &results.results[0];
+    assert_eq!(result.broadcast_players, vec!["p1".to_string()]);
+    assert!(result.broadcast_to_director);
+    assert!(!result.broadcast_to_all);
+
+    PlayerActionScheduler::dispatch(&mut game_state, "p1", "vote", vote_params("p3")).unwrap();
+    assert_eq!(game_state.votes.len(), 1);
+    assert_eq!(game_state.votes.get("p1"), Some(&"p3".to_string()));
+}
+
+/// 测试：投票结算按装备票数加权，并公示最高票玩家信息
+#[test]
+fn test_vote_settlement_weighted_winner() {
+    let mut game_state =
+        GameState::new("test_vote_weight".to_string(), get_test_rules_with_votes());
+    add_test_player(&mut game_state, "p1", "玩家1", "位置1");
+    add_test_player(&mut game_state, "p2", "玩家2", "位置1");
+    add_test_player(&mut game_state, "p3", "玩家3", "位置1");
+    add_test_player(&mut game_state, "p4", "玩家4", "位置1");
+
+    // p1 装备武器（+2）和防具（+1），总计 4 票
+    let weapon = game_state
+        .rule_engine
+        .create_item_from_name("票剑")
+        .unwrap();
+    let armor = game_state
+        .rule_engine
+        .create_item_from_name("票甲")
+        .unwrap();
+    {
+        let p1 = game_state.players.get_mut("p1").unwrap();
+        p1.equip_weapon(weapon);
+        p1.equip_armor(armor);
+    }
+    assert_eq!(game_state.players.get("p1").unwrap().get_vote_weight(), 4);
+
+    game_state.votes.insert("p1".to_string(), "p4".to_string());
+    game_state.votes.insert("p2".to_string(), "p3".to_string());
+    game_state.votes.insert("p3".to_string(), "p3".to_string());
+    game_state.votes.insert("p4".to_string(), "p3".to_string());
+
+    let data = settle_votes(&mut game_state);
+
+    assert_eq!(data["is_tie"], json!(false));
+    assert_eq!(data["max_votes"], json!(4));
+    assert_eq!(data["winner"]["id"], json!("p4"));
+    assert_eq!(data["winner"]["life"], json!(100));
+    assert!(data["winner"].get("password").is_none());
+    assert!(game_state.votes.is_empty(), "结算后应清空投票");
+}
+
+/// 测试：平票时白天无事发生，死亡玩家的选票不计入
+#[test]
+fn test_vote_settlement_tie_and_dead_voters() {
+    let mut game_state = GameState::new("test_vote_tie".to_string(), get_test_rules_with_votes());
+    add_test_player(&mut game_state, "p1", "玩家1", "位置1");
+    add_test_player(&mut game_state, "p2", "玩家2", "位置1");
+    add_test_player(&mut game_state, "p3", "玩家3", "位置1");
+
+    game_state.players.get_mut("p3").unwrap().is_alive = false;
+
+    game_state.votes.insert("p1".to_string(), "p2".to_string());
+    game_state.votes.insert("p2".to_string(), "p1".to_string());
+    game_state.votes.insert("p3".to_string(), "p1".to_string());
+
+    let data = settle_votes(&mut game_state);
+
+    assert_eq!(data["is_tie"], json!(true));
+    assert_eq!(data["ballot_count"], json!(2));
+    assert!(data.get("winner").is_none());
+    assert!(game_state.votes.is_empty());
+}
@@ -0,0 +1,177 @@
+//! WebSocket协议集成测试
+//! 测试客户端请求ID的解析与只向发起连接回传、错误消息格式以及调度器支持的行动类型
+
+mod common;
+
+use common::{add_test_player, common_rarity_levels, test_rules};
+use royale_arena_backend::websocket::actions::director_action_scheduler::{
+    DirectorActionParams, DirectorActionScheduler,
+};
+use royale_arena_backend::websocket::actions::player_action_scheduler::{
+    ActionParams, PlayerActionScheduler,
+};
+use royale_arena_backend::websocket::broadcaster::MessageBroadcaster;
+use royale_arena_backend::websocket::game_connection_manager::{
+    ConnectionMessage, GameConnectionManager, SequencedMessage,
+};
+use royale_arena_backend::websocket::models::{
+    ActionResult, ActionResults, ConnectionType, GameState, RequestOrigin, WebSocketClientMessage,
+    WebSocketError, WebSocketErrorCode,
+};
+use serde_json::json;
+
+/// 测试规则配置
+fn get_test_rules() -> serde_json::Value {
+    test_rules(json!({
+      "map": {"places": ["码头", "工厂"]},
+      "player": {"max_backpack_items": 3},
+      "items_config": {
+        "rarity_levels": common_rarity_levels(),
+        "items": {
+          "weapons": [
+            {
+              "internal_name": "test_weapon",
+              "display_names": ["测试剑"],
+              "rarity": "common",
+              "properties": {"damage": 20, "votes": 1}
+            }
+          ],
+          "consumables": [
+            {
+              "name": "[HP10]测试药水",
+              "properties": {"effect_type": "heal", "effect_value": 10}
+            }
+          ]
+        }
+      }
+    }))
+}
+
+/// 创建游戏状态：p1 位于码头
+fn create_game_state() -> GameState {
+    let mut game_state = common::create_game_state("test_protocol", get_test_rules());
+    add_test_player(&mut game_state, "p1", "玩家p1", "码头");
+    game_state
+}
+
+#[test]
+fn test_client_message_request_id() {
+    let message: WebSocketClientMessage = serde_json::from_value(json!({
+        "type": "player_action",
+        "data": { "action": "search" },
+        "request_id": "req-1"
+    }))
+    .unwrap();
+    assert_eq!(message.request_id.as_deref(), Some("req-1"));
+
+    // 未提供请求ID的旧客户端消息仍可解析
+    let message: WebSocketClientMessage = serde_json::from_value(json!({
+        "type": "director_action",
+        "data": { "action": "weather", "weather": 0.5 }
+    }))
+    .unwrap();
+    assert!(message.request_id.is_none());
+}
+
+#[test]
+fn test_error_frame_format() {
+    let error = WebSocketError::new(
+        WebSocketErrorCode::UnknownAction,
+        "Unknown action type: fly",
+    )
+    .with_request_id(Some("req-2".to_string()));
+    assert_eq!(
+        serde_json::to_value(&error).unwrap(),
+        json!({
+            "code": "unknown_action",
+            "message": "Unknown action type: fly",
+            "request_id": "req-2"
+        })
+    );
+
+    let error = WebSocketError::new(WebSocketErrorCode::InvalidMessage, "Invalid message format");
+    assert_eq!(
+        serde_json::to_value(&error).unwrap()["request_id"],
+        json!(null)
+    );
+}
+
+#[tokio::test]
+async fn test_request_id_echoed_only_to_origin() {
+    let game_state = create_game_state();
+    let manager = GameConnectionManager::new(8);
+    let (origin, origin_rx) = manager
+        .add_connection("p1".to_string(), ConnectionType::Actor)
+        .await;
+    let (_, other_rx) = manager
+        .add_connection("p1".to_string(), ConnectionType::Actor)
+        .await;
+    let (_, director_rx) = manager
+        .add_connection("director".to_string(), ConnectionType::Director)
+        .await;
+
+    let results = ActionResults {
+        results: vec![ActionResult::new_system_message(
+            json!({}),
+            vec!["p1".to_string()],
+            "a".to_string(),
+            true,
+        )],
+    }
+    .with_request_origin(Some(RequestOrigin {
+        connection_id: origin.id.clone(),
+        request_id: "req-3".to_string(),
+    }));
+    let result = &results.results[0];
+    assert_eq!(result.to_client_response()["request_id"], json!(null));
+
+    let broadcaster = MessageBroadcaster::new(manager.clone());
+    broadcaster
+        .broadcast_to_players(&game_state, &["p1".to_string()], result)
+        .await
+        .unwrap();
+    broadcaster
+        .broadcast_to_directors(&game_state, result)
+        .await
+        .unwrap();
+
+    let request_id = |message: SequencedMessage| match message.message {
+        ConnectionMessage::GameState(view) => view["action_result"]["request_id"].clone(),
+        other => panic!("unexpected message: {:?}", other),
+    };
+    assert_eq!(request_id(origin_rx.recv().await.unwrap()), json!("req-3"));
+    assert_eq!(request_id(other_rx.recv().await.unwrap()), json!(null));
+    assert_eq!(request_id(director_rx.recv().await.unwrap()), json!(null));
+
+    let (_, events) = manager.catch_up("p1", Some(0)).await;
+    assert_eq!(events.unwrap()[0].data["request_id"], json!(null));
+}
+
+#[test]
+fn test_scheduler_supported_actions() {
+    // 所有声明支持的行动类型都能被调度器识别
+    for action in PlayerActionScheduler::SUPPORTED_ACTIONS {
+        let mut game_state = create_game_state();
+        let params = ActionParams::from_json(&json!({})).unwrap();
+        if let Err(e) = PlayerActionScheduler::dispatch(&mut game_state, "p1", action, params) {
+            assert!(!e.starts_with("Unknown action type"), "{}: {}", action, e);
+        }
+    }
+    for action in DirectorActionScheduler::SUPPORTED_ACTIONS {
+        let mut game_state = create_game_state();
+        let params = DirectorActionParams::from_json(&json!({})).unwrap();
+        if let Err(e) = DirectorActionScheduler::dispatch(&mut game_state, action, params) {
+            assert!(
+                !e.starts_with("Unknown director action type"),
+                "{}: {}",
+                action,
+                e
+            );
+        }
+    }
+
+    assert!(PlayerActionScheduler::supports("search"));
+    assert!(!PlayerActionScheduler::supports("fly"));
+    assert!(DirectorActionScheduler::supports("weather"));
+    assert!(!DirectorActionScheduler::supports("search"));
+}
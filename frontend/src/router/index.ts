@@ -40,14 +40,6 @@ const routes = [
       title: '导演控制台'
     }
   },
-  {
-    path: '/game/:id/director/:password',
-    name: 'DirectorMainWithPassword',
-    component: DirectorMain,
-    meta: {
-      title: '导演控制台'
-    }
-  },
   {
     path: '/game/:id/actor',
     name: 'ActorMain',
@@ -56,14 +48,6 @@ const routes = [
       title: '演员'
     }
   },
-  {
-    path: '/game/:id/actor/:password',
-    name: 'ActorMainWithPassword',
-    component: ActorMain,
-    meta: {
-      title: '夜晚行动'
-    }
-  },
   
   // 管理员登录路由
   {
@@ -1,7 +1,8 @@
 import { ElMessage } from 'element-plus'
 import apiClient from './client'
 import { API_ENDPOINTS } from './config'
-import type { GameAuthenticationResponse } from '@/types/game'
+import { saveGameSession } from './gameSession'
+import type { GameAuthenticationResponse, GameLoginRequest } from '@/types/game'
 
 export interface AuthResult {
   success: boolean
@@ -12,12 +13,17 @@ export interface AuthResult {
 }
 
 /**
- * 统一的游戏认证函数
+ * 统一的游戏认证函数，验证成功时保存服务器签发的游戏会话令牌
  * @param gameId 游戏ID
  * @param password 密码
+ * @param actorName 演员名称，演员登录时必填，导演登录时留空
  * @returns 认证结果
  */
-export async function authenticateGame(gameId: string, password: string): Promise<AuthResult> {
+export async function authenticateGame(
+  gameId: string,
+  password: string,
+  actorName?: string
+): Promise<AuthResult> {
   if (!password.trim()) {
     return {
       success: false,
@@ -26,18 +32,44 @@ export async function authenticateGame(gameId: string, password: string): Promis
   }
 
   try {
-    // 使用认证接口进行权限验证
-    const response = await apiClient.get<GameAuthenticationResponse>(
+    // 使用认证接口进行权限验证，密码放在请求体中，不出现在URL里
+    const request: GameLoginRequest = { password }
+    const trimmedActorName = actorName?.trim()
+    if (trimmedActorName) {
+      request.actor_name = trimmedActorName
+    }
+    const response = await apiClient.post<GameAuthenticationResponse>(
       API_ENDPOINTS.GAME_AUTH(gameId),
-      { params: { password } }
+      request
     )
 
     const {
       role,
       actor_id: actorIdRaw,
-      actor_name: actorNameRaw
+      actor_name: actorNameRaw,
+      token,
+      expires_in: expiresIn,
+      message
     } = response.data
 
+    if (role === 'director' || role === 'actor') {
+      if (!token || !expiresIn) {
+        return {
+          success: false,
+          errorMessage: '未获取到登录令牌，请稍后重试'
+        }
+      }
+
+      // 保存游戏会话令牌，之后的接口请求与WebSocket连接都使用该令牌
+      saveGameSession(gameId, {
+        role,
+        token,
+        actorId: actorIdRaw ?? undefined,
+        actorName: actorNameRaw ?? undefined,
+        expiresAt: Date.now() + expiresIn * 1000
+      })
+    }
+
     if (role === 'director') {
       return {
         success: true,
@@ -54,7 +86,7 @@ export async function authenticateGame(gameId: string, password: string): Promis
       return {
         success: false,
         role: 'invalid',
-        errorMessage: '密码错误'
+        errorMessage: message || '密码错误'
       }
     }
     
@@ -75,13 +107,11 @@ export async function authenticateGame(gameId: string, password: string): Promis
  * 处理认证结果并执行相应的路由跳转
  * @param authResult 认证结果
  * @param gameId 游戏ID
- * @param password 密码
  * @param router Vue Router实例
  */
 export function handleAuthResult(
   authResult: AuthResult, 
   gameId: string, 
-  password: string, 
   router: any
 ): boolean {
   if (!authResult.success) {
@@ -94,13 +124,13 @@ export function handleAuthResult(
   if (authResult.role === 'director') {
     // 如果是导演身份，跳转到导演页面
     ElMessage.success('成功以导演身份进入控制台')
-    router.push(`/game/${gameId}/director/${encodeURIComponent(password)}`)
+    router.push(`/game/${gameId}/director`)
     return true
   } else if (authResult.role === 'actor') {
     // 如果是演员身份，跳转到演员页面
     const actorName = authResult.actorName?.trim() || '演员'
     ElMessage.success(`成功以演员身份登录：${actorName}`)
-    router.push(`/game/${gameId}/actor/${encodeURIComponent(password)}`)
+    router.push(`/game/${gameId}/actor`)
     return true
   }
   
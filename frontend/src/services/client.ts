@@ -1,5 +1,6 @@
 import axios from 'axios'
 import { API_CONFIG } from './config'
+import { clearGameSession, getGameSession, resolveGameSessionTarget } from './gameSession'
 
 const apiClient = axios.create({
   baseURL: API_CONFIG.BASE_URL,
@@ -19,6 +20,15 @@ apiClient.interceptors.request.use(config => {
       config.headers.Authorization = `Bearer ${token}`
     }
   }
+
+  // 导演与玩家接口携带游戏会话令牌
+  const sessionTarget = resolveGameSessionTarget(config.url)
+  if (sessionTarget) {
+    const session = getGameSession(sessionTarget.gameId, sessionTarget.role)
+    if (session) {
+      config.headers.Authorization = `Bearer ${session.token}`
+    }
+  }
   
   // 记录所有API请求
   console.log('API Request:', {
@@ -36,7 +46,11 @@ apiClient.interceptors.request.use(config => {
 apiClient.interceptors.response.use(
   response => response,
   error => {
-    if (error.response?.status === 401) {
+    // 游戏会话令牌过期或已被吊销，清除后需要重新登录
+    const sessionTarget = resolveGameSessionTarget(error.config?.url)
+    if (error.response?.status === 401 && sessionTarget) {
+      clearGameSession(sessionTarget.gameId, sessionTarget.role)
+    } else if (error.response?.status === 401) {
       // 清除过期token
       localStorage.removeItem('admin_token')
       localStorage.removeItem('admin_user')
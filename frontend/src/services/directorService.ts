@@ -17,12 +17,10 @@ export class DirectorService {
    * 获取演员列表
    */
   async getPlayers(
-    gameId: string, 
-    password: string
+    gameId: string
   ): Promise<DirectorAuthResponse> {
     const response = await apiClient.get(
-      API_ENDPOINTS.DIRECTOR_PLAYERS(gameId),
-      { params: { password } }
+      API_ENDPOINTS.DIRECTOR_PLAYERS(gameId)
     )
     return response.data
   }
@@ -32,7 +30,6 @@ export class DirectorService {
    */
   async batchAddPlayers(
     gameId: string,
-    password: string,
     players: AddPlayerItem[]
   ): Promise<ApiResponse<BatchOperationResponse<PlayerInfo>>> {
     const request: BatchAddPlayersRequest = {
@@ -45,8 +42,7 @@ export class DirectorService {
     
     const response = await apiClient.post(
       API_ENDPOINTS.DIRECTOR_BATCH_ADD(gameId),
-      request,
-      { params: { password } }
+      request
     )
     return response.data
   }
@@ -56,7 +52,6 @@ export class DirectorService {
    */
   async batchDeletePlayers(
     gameId: string,
-    password: string,
     playerIds: string[]
   ): Promise<ApiResponse<BatchOperationResponse<DeleteSuccessInfo>>> {
     const request: BatchDeletePlayersRequest = {
@@ -66,32 +61,20 @@ export class DirectorService {
     const response = await apiClient.delete(
       API_ENDPOINTS.DIRECTOR_BATCH_DELETE(gameId),
       { 
-        data: request,
-        params: { password }
+        data: request
       }
     )
     return response.data
   }
 
-  /**
-   * URI参数解析 - 从路径中提取密码
-   */
-  parsePasswordFromURI(path: string): string | null {
-    // 匹配 /game/{gameId}/{password}
-    const match = path.match(/\/game\/([^/]+)\/([^/]+)$/)
-    return match ? decodeURIComponent(match[2]) : null
-  }
-
   /**
    * 手动存盘游戏
    */
   async manualSaveGame(
-    gameId: string,
-    password: string
+    gameId: string
   ): Promise<any> {
     const response = await apiClient.post(
-      API_ENDPOINTS.DIRECTOR_SAVE_GAME(gameId),
-      { password }
+      API_ENDPOINTS.DIRECTOR_SAVE_GAME(gameId)
     )
     return response.data
   }
@@ -100,12 +83,10 @@ export class DirectorService {
    * 获取存档文件列表
    */
   async listSaveFiles(
-    gameId: string,
-    password: string
+    gameId: string
   ): Promise<any> {
     const response = await apiClient.get(
-      API_ENDPOINTS.DIRECTOR_LIST_SAVES(gameId),
-      { params: { password } }
+      API_ENDPOINTS.DIRECTOR_LIST_SAVES(gameId)
     )
     return response.data
   }
@@ -115,12 +96,10 @@ export class DirectorService {
    */
   async updateGameStatus(
     gameId: string,
-    password: string,
     status: string,
     saveFileName?: string
   ): Promise<any> {
     const requestData: any = {
-      password,
       status
     }
     
@@ -140,7 +119,6 @@ export class DirectorService {
    */
   async editGame(
     gameId: string,
-    password: string,
     data: {
       name?: string
       description?: string
@@ -150,8 +128,7 @@ export class DirectorService {
   ): Promise<any> {
     const response = await apiClient.put(
       API_ENDPOINTS.DIRECTOR_EDIT_GAME(gameId),
-      data,
-      { params: { password } }
+      data
     )
     return response.data
   }
@@ -281,15 +258,14 @@ export class DirectorService {
   }
 
   /**
-   * 检查新用户名和密码与现有用户是否重复
+   * 检查新用户名与现有用户是否重复
+   * 演员密码以哈希存储、不会返回给前端，演员登录时以名称区分，因此只检查名称
    */
   checkDuplicatesWithExistingUsers(
     newUsernames: string[],
-    newPasswords: string[],
-    existingPlayers: Array<{ name: string; password: string }>
+    existingPlayers: Array<{ name: string }>
   ): { isValid: boolean; errorMessage?: string } {
     const existingUsernames = existingPlayers.map(p => p.name)
-    const existingPasswords = existingPlayers.map(p => p.password)
     
     // 检查新用户名与现有用户名是否重复
     const duplicateUsernames = newUsernames.filter(username => existingUsernames.includes(username))
@@ -300,33 +276,6 @@ export class DirectorService {
       }
     }
     
-    // 检查新密码与现有密码是否重复
-    const duplicatePasswords = newPasswords.filter(password => existingPasswords.includes(password))
-    if (duplicatePasswords.length > 0) {
-      return {
-        isValid: false,
-        errorMessage: `密码与现有用户重复：${duplicatePasswords.join(', ')}`
-      }
-    }
-    
-    // 检查新用户名与现有密码是否重复
-    const usernamePasswordConflicts = newUsernames.filter(username => existingPasswords.includes(username))
-    if (usernamePasswordConflicts.length > 0) {
-      return {
-        isValid: false,
-        errorMessage: `用户名与现有密码重复：${usernamePasswordConflicts.join(', ')}`
-      }
-    }
-    
-    // 检查新密码与现有用户名是否重复
-    const passwordUsernameConflicts = newPasswords.filter(password => existingUsernames.includes(password))
-    if (passwordUsernameConflicts.length > 0) {
-      return {
-        isValid: false,
-        errorMessage: `密码与现有用户名重复：${passwordUsernameConflicts.join(', ')}`
-      }
-    }
-    
     return { isValid: true }
   }
   validatePlayerPassword(password: string): { isValid: boolean; message?: string } {
@@ -51,10 +51,9 @@ export const gameService = {
   // 获取导演消息记录
   async getDirectorMessages(
     gameId: string,
-    password: string,
     limit?: number
   ): Promise<ApiResponse<MessageRecord[]>> {
-    const params: Record<string, unknown> = { password }
+    const params: Record<string, unknown> = {}
     if (typeof limit === 'number') {
       params.limit = limit
     }
@@ -66,10 +65,8 @@ export const gameService = {
   },
 
   // 获取导演击杀记录
-  async getDirectorKillRecords(gameId: string, password: string): Promise<ApiResponse<KillRecord[]>> {
-    const response = await apiClient.get(API_ENDPOINTS.DIRECTOR_KILL_RECORDS(gameId), { 
-      params: { password } 
-    })
+  async getDirectorKillRecords(gameId: string): Promise<ApiResponse<KillRecord[]>> {
+    const response = await apiClient.get(API_ENDPOINTS.DIRECTOR_KILL_RECORDS(gameId))
     return response.data
   },
 
@@ -77,21 +74,22 @@ export const gameService = {
   async getPlayerMessages(
     gameId: string,
     playerId: string,
-    password: string,
     limit?: number
   ): Promise<ApiResponse<MessageRecord[]>> {
-    const payload: { password: string; limit?: number } = { password }
+    const params: Record<string, unknown> = {}
     if (typeof limit === 'number') {
-      payload.limit = limit
+      params.limit = limit
     }
 
-    const response = await apiClient.post(API_ENDPOINTS.PLAYER_MESSAGES(gameId, playerId), payload)
+    const response = await apiClient.get(API_ENDPOINTS.PLAYER_MESSAGES(gameId, playerId), {
+      params
+    })
     return response.data
   },
 
   // 获取玩家击杀记录
-  async getPlayerKillRecords(gameId: string, playerId: string, password: string): Promise<ApiResponse<KillRecord[]>> {
-    const response = await apiClient.post(API_ENDPOINTS.PLAYER_KILL_RECORDS(gameId, playerId), { password })
+  async getPlayerKillRecords(gameId: string, playerId: string): Promise<ApiResponse<KillRecord[]>> {
+    const response = await apiClient.get(API_ENDPOINTS.PLAYER_KILL_RECORDS(gameId, playerId))
     return response.data
   },
 
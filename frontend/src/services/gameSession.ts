@@ -0,0 +1,81 @@
+// 游戏会话令牌存储
+// 游戏身份验证成功后保存服务器签发的令牌，导演接口、玩家接口与WebSocket连接都使用该令牌，密码只在登录时发送一次
+
+export type GameSessionRole = 'director' | 'actor'
+
+export interface GameSession {
+  role: GameSessionRole
+  token: string
+  actorId?: string
+  actorName?: string
+  // 令牌过期时间（毫秒时间戳）
+  expiresAt: number
+}
+
+const storageKey = (gameId: string, role: GameSessionRole) => `game_session_${gameId}_${role}`
+
+/**
+ * 保存游戏会话
+ * @param gameId 游戏ID
+ * @param session 游戏会话
+ */
+export function saveGameSession(gameId: string, session: GameSession): void {
+  localStorage.setItem(storageKey(gameId, session.role), JSON.stringify(session))
+}
+
+/**
+ * 获取未过期的游戏会话，过期或格式无效时清除
+ * @param gameId 游戏ID
+ * @param role 会话角色
+ */
+export function getGameSession(gameId: string, role: GameSessionRole): GameSession | null {
+  const saved = localStorage.getItem(storageKey(gameId, role))
+  if (!saved) {
+    return null
+  }
+
+  try {
+    const session = JSON.parse(saved) as GameSession
+    if (session && session.token && session.expiresAt > Date.now()) {
+      return session
+    }
+  } catch (err) {
+    console.warn('清除无效的游戏会话:', err)
+  }
+
+  clearGameSession(gameId, role)
+  return null
+}
+
+/**
+ * 清除游戏会话
+ * @param gameId 游戏ID
+ * @param role 会话角色
+ */
+export function clearGameSession(gameId: string, role: GameSessionRole): void {
+  localStorage.removeItem(storageKey(gameId, role))
+}
+
+/**
+ * 根据接口地址判断所需的游戏会话：玩家接口使用演员令牌，其余游戏接口使用导演令牌
+ * 登录与观战验证接口不需要令牌，返回null
+ * @param url 接口地址
+ */
+export function resolveGameSessionTarget(
+  url: string | undefined
+): { gameId: string; role: GameSessionRole } | null {
+  const match = url?.match(/^\/game\/([^/]+)\/(.+)$/)
+  if (!match) {
+    return null
+  }
+
+  const [, gameId, rest] = match
+  if (rest === 'auth' || rest === 'spectate') {
+    return null
+  }
+
+  return {
+    gameId: decodeURIComponent(gameId),
+    role: rest.startsWith('player/') ? 'actor' : 'director'
+  }
+}
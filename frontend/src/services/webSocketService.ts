@@ -1,6 +1,7 @@
 import { ElMessage } from 'element-plus'
 import type { DirectorGameState } from '@/types/gameStateTypes'
 import { API_CONFIG } from './config'
+import { getGameSession, type GameSessionRole } from './gameSession'
 
 // WebSocket连接状态常量
 export const WebSocketStatus = {
@@ -29,11 +30,10 @@ export class WebSocketService {
   private status: WebSocketStatus = WebSocketStatus.DISCONNECTED
   private listeners: Array<(event: WebSocketEvent) => void> = []
   private gameId: string = ''
-  private password: string = ''
-  private userType: string = '' // 默认为导演
+  private userType: GameSessionRole = 'director'
 
   // 连接到WebSocket服务器
-  connect(gameId: string, password: string, userType: string, timeout: number = API_CONFIG.TIMEOUT): Promise<void> {
+  connect(gameId: string, userType: GameSessionRole, timeout: number = API_CONFIG.TIMEOUT): Promise<void> {
     return new Promise((resolve, reject) => {
       // 如果已经连接，先断开
       if (this.ws) {
@@ -41,11 +41,10 @@ export class WebSocketService {
       }
 
       this.gameId = gameId
-      this.password = password
       this.userType = userType // 保存用户类型
 
       try {
-        this.url = this.buildWebSocketUrl(gameId, password, userType)
+        this.url = this.buildWebSocketUrl(gameId, userType)
       } catch (error) {
         console.error('构建WebSocket连接地址失败:', error)
         this.setStatus(WebSocketStatus.ERROR)
@@ -185,7 +184,7 @@ export class WebSocketService {
       ElMessage.warning(`连接断开，正在尝试重连 (${this.reconnectAttempts}/${this.maxReconnectAttempts})...`)
 
       setTimeout(() => {
-        this.connect(this.gameId, this.password, this.userType).catch(() => {
+        this.connect(this.gameId, this.userType).catch(() => {
           // 重连失败，继续尝试
           this.attemptReconnect()
         })
@@ -289,14 +288,19 @@ export class WebSocketService {
     })
   }
 
-  private buildWebSocketUrl(gameId: string, password: string, userType: string): string {
+  // 使用登录时保存的游戏会话令牌建立连接，令牌携带身份信息
+  private buildWebSocketUrl(gameId: string, userType: GameSessionRole): string {
+    const session = getGameSession(gameId, userType)
+    if (!session) {
+      throw new Error('登录已过期，请重新登录')
+    }
+
     const resolvedBase = new URL(API_CONFIG.BASE_URL, window.location.origin)
     const wsUrl = new URL(resolvedBase.toString())
     wsUrl.protocol = resolvedBase.protocol === 'https:' ? 'wss:' : 'ws:'
     wsUrl.pathname = `${resolvedBase.pathname.replace(/\/+$/, '')}/ws/${encodeURIComponent(gameId)}`
     wsUrl.search = ''
-    wsUrl.searchParams.set('user_type', userType)
-    wsUrl.searchParams.set('password', password)
+    wsUrl.searchParams.set('token', session.token)
     return wsUrl.toString()
   }
 }
@@ -17,6 +17,7 @@ import type {
   ShopBuyItem
 } from '@/types/gameStateTypes'
 import { webSocketService, type WebSocketEvent } from '@/services/webSocketService'
+import type { GameSessionRole } from '@/services/gameSession'
 
 function isDirectorState(state: DirectorGameState | ActorGameState | null): state is DirectorGameState {
   const data = (state as DirectorGameState | ActorGameState | null)?.game_data as Record<string, unknown> | undefined
@@ -100,7 +101,7 @@ export const useGameStateStore = defineStore('gameState', () => {
   })
 
   // 操作
-  const connect = async (gameId: string, password: string, userType: string) => {
+  const connect = async (gameId: string, userType: GameSessionRole) => {
     connecting.value = true
     error.value = null
     
@@ -109,7 +110,7 @@ export const useGameStateStore = defineStore('gameState', () => {
       webSocketService.addEventListener(handleWebSocketEvent)
       
       // 连接到WebSocket，传递用户类型参数
-      await webSocketService.connect(gameId, password, userType)
+      await webSocketService.connect(gameId, userType)
       connected.value = true
     } catch (err) {
       console.error('连接WebSocket失败:', err)
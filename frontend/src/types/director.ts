@@ -4,7 +4,6 @@
 export interface PlayerInfo {
   id: string
   name: string
-  password: string
   game_id: string
   team_id: number
 }
@@ -68,13 +67,9 @@ export interface BatchPasteData {
 
 // 导演控制台状态
 export interface DirectorConsoleState {
-  // URI参数相关
-  passwordFromURI: string | null
-  autoAuthenticated: boolean
-  
   // 认证相关
+  autoAuthenticated: boolean
   isAuthenticated: boolean
-  directorPassword: string
   authLoading: boolean
   authError: string | null
   
@@ -91,6 +91,8 @@ export interface GameListQuery {
 
 // 游戏登录请求
 export interface GameLoginRequest {
+  // 演员名称，演员登录时必填，省略时按导演身份验证
+  actor_name?: string
   password: string
 }
 
@@ -106,6 +108,12 @@ export interface GameAuthenticationResponse {
   role: 'actor' | 'director' | 'invalid'
   actor_id?: string | null
   actor_name?: string | null
+  // 游戏会话令牌，验证失败时不返回
+  token?: string
+  // 令牌有效期（秒）
+  expires_in?: number
+  // 验证失败原因
+  message?: string
 }
 
 // API响应基础类型
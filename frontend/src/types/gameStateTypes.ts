@@ -36,7 +36,6 @@ export interface Item {
 export interface Player {
   id: string;
   name: string;
-  password: string;
   location: string;
   life: number;
   strength: number;
@@ -16,21 +16,14 @@ type ManualSaveResult = {
 
 /**
  * Shared manual save logic for director-facing views.
- * Handles credential validation, API invocation, and user feedback.
+ * Handles API invocation and user feedback; the director session token is attached by the API client.
  */
 export function useManualSaveGame(
-  gameId: Ref<string | undefined | null>,
-  directorPassword: Ref<string | undefined | null>
+  gameId: Ref<string | undefined | null>
 ) {
   const saving = ref(false)
 
   const manualSave = async (options?: ManualSaveOptions): Promise<ManualSaveResult> => {
-    if (!directorPassword.value) {
-      const errorMessage = '缺少导演密码'
-      ElMessage.error(errorMessage)
-      return { success: false, errorMessage }
-    }
-
     if (!gameId.value) {
       const errorMessage = '未找到有效的游戏信息'
       ElMessage.error(errorMessage)
@@ -40,10 +33,7 @@ export function useManualSaveGame(
     saving.value = true
 
     try {
-      const response = await directorService.manualSaveGame(
-        gameId.value,
-        directorPassword.value
-      )
+      const response = await directorService.manualSaveGame(gameId.value)
 
       if (!response?.success) {
         throw new Error(response?.message || '存盘失败')
@@ -59,7 +49,7 @@ export function useManualSaveGame(
       return { success: true, saveFileName }
     } catch (error: any) {
       const errorMessage = error?.response?.status === 401
-        ? '导演密码错误'
+        ? '导演登录已过期，请重新登录'
         : (error?.message || '存盘失败')
       ElMessage.error(errorMessage)
       return { success: false, errorMessage }